@@ -0,0 +1,643 @@
+// Remote API - HTTP server for external dashboards and scripts
+//
+// Serves the sources, universes and listener statistics as JSON, mirrors the
+// source and expected device commands of the app and streams live updates over
+// a WebSocket at /ws (see stream.rs). Requests that change anything (every
+// method but GET) need the `Authorization: Bearer <token>` header with the token
+// from the settings, generated on first use. Built with the "api" feature;
+// without it the server reports that it is unavailable.
+
+use crate::labels::UniverseLabelsHandle;
+use crate::network::{
+    DmxStoreHandle, EventBusHandle, EventBusStats, ListenerCounters, ListenerStateHandle,
+    NetworkSource, ProtocolBandwidth, SourceManagerHandle, SourceStatus,
+};
+use crate::persistence::{load_json, save_json};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::warn;
+
+#[cfg(feature = "api")]
+use crate::network::{
+    group_devices, send_artpoll, ExpectedDevice, ExpectedDeviceStatus, FrameLength, ListenerEvent,
+    NetworkDevice, SourceEvent, SourceUniverseStats,
+};
+#[cfg(feature = "api")]
+use axum::{
+    extract::{Path, Query, Request, State},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Json, Router,
+};
+#[cfg(feature = "api")]
+use tracing::{error, info};
+
+/// Default port of the remote API
+pub const DEFAULT_API_PORT: u16 = 8420;
+
+/// Remote API server settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiConfig {
+    pub enabled: bool,
+    /// 127.0.0.1 serves this machine only, 0.0.0.0 every interface
+    pub bind: Ipv4Addr,
+    pub port: u16,
+    /// Bearer token for requests other than GET, generated when not set
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: Ipv4Addr::LOCALHOST,
+            port: DEFAULT_API_PORT,
+            token: None,
+        }
+    }
+}
+
+/// A random bearer token
+fn generate_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Remote API server status for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiStatus {
+    pub available: bool, // Built with the "api" feature
+    pub running: bool,
+    pub config: ApiConfig,
+    pub address: Option<String>,
+    pub error: Option<String>, // Why the server last failed to start
+}
+
+/// Listener and source totals served at /stats
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiStats {
+    pub sources: usize,
+    pub active_sources: usize,
+    pub universes: usize,
+    pub listeners: Vec<ListenerCounters>,
+    pub protocol_bandwidth: Vec<ProtocolBandwidth>,
+    pub event_bus: EventBusStats,
+}
+
+/// Universe listing entry served at /universes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniverseSummary {
+    pub universe: u16,
+    pub label: Option<String>,
+    pub length: usize,
+    pub sources: Vec<String>, // Source IDs sending the universe
+}
+
+/// Universe contents served at /universes/{n}
+#[cfg(feature = "api")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniverseData {
+    pub universe: u16,
+    pub label: Option<String>,
+    pub data: Vec<u8>,
+    pub sources: Vec<String>,
+    pub frame_lengths: Vec<FrameLength>,
+}
+
+/// Handles the remote API reads and controls
+#[derive(Clone)]
+pub struct ApiContext {
+    pub source_manager: SourceManagerHandle,
+    pub dmx_store: DmxStoreHandle,
+    pub listener_state: ListenerStateHandle,
+    pub event_tx: EventBusHandle,
+    pub universe_labels: UniverseLabelsHandle,
+}
+
+impl ApiContext {
+    pub fn get_stats(&self) -> ApiStats {
+        let sources = self.source_manager.get_all_sources(true);
+        ApiStats {
+            active_sources: sources
+                .iter()
+                .filter(|s| s.status == SourceStatus::Active)
+                .count(),
+            sources: sources.len(),
+            universes: self.dmx_store.get_all().len(),
+            listeners: self.listener_state.get_counters(),
+            protocol_bandwidth: self.source_manager.get_protocol_bandwidth(),
+            event_bus: self.event_tx.get_stats(),
+        }
+    }
+
+    pub fn get_universes(&self) -> Vec<UniverseSummary> {
+        let sources = self.source_manager.get_all_sources(true);
+        let mut universes: Vec<UniverseSummary> = self
+            .dmx_store
+            .get_all()
+            .into_iter()
+            .map(|(universe, data)| UniverseSummary {
+                universe,
+                label: self.universe_labels.get(universe),
+                length: data.len(),
+                sources: senders(&sources, universe),
+            })
+            .collect();
+        universes.sort_by_key(|u| u.universe);
+        universes
+    }
+}
+
+/// IDs of the sources sending a universe, leaving out offline ones
+fn senders(sources: &[NetworkSource], universe: u16) -> Vec<String> {
+    sources
+        .iter()
+        .filter(|s| s.universes.contains(&universe) && s.status != SourceStatus::Offline)
+        .map(|s| s.id.clone())
+        .collect()
+}
+
+#[cfg(feature = "api")]
+struct RunningServer {
+    address: SocketAddr,
+    shutdown: tokio::sync::oneshot::Sender<()>,
+}
+
+/// Starts and stops the remote API server
+pub struct ApiServer {
+    config: RwLock<ApiConfig>,
+    config_path: RwLock<Option<PathBuf>>,
+    #[cfg(feature = "api")]
+    running: Mutex<Option<RunningServer>>,
+    error: Mutex<Option<String>>,
+}
+
+impl ApiServer {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(ApiConfig::default()),
+            config_path: RwLock::new(None),
+            #[cfg(feature = "api")]
+            running: Mutex::new(None),
+            error: Mutex::new(None),
+        }
+    }
+
+    /// Load the saved settings, generating and saving a token when they have
+    /// none; the caller starts the server when enabled
+    pub fn load_config(&self, path: PathBuf) {
+        let mut config: ApiConfig = load_json(&path);
+        if config.token.as_deref().is_none_or(str::is_empty) {
+            config.token = Some(generate_token());
+            if let Err(e) = save_json(&path, &config) {
+                warn!("[API] Failed to save the generated token: {}", e);
+            }
+        }
+        *self.config.write() = config;
+        *self.config_path.write() = Some(path);
+    }
+
+    pub fn get_config(&self) -> ApiConfig {
+        self.config.read().clone()
+    }
+
+    /// Save new settings and start, restart or stop the server to match. Without
+    /// a token the current one is kept, or a new one generated.
+    pub async fn set_config(
+        &self,
+        context: ApiContext,
+        mut config: ApiConfig,
+    ) -> Result<(), String> {
+        if config.port == 0 {
+            return Err("API port must be between 1 and 65535".to_string());
+        }
+        if config.token.as_deref().is_none_or(str::is_empty) {
+            config.token = Some(self.get_config().token.unwrap_or_else(generate_token));
+        }
+        self.stop();
+        *self.config.write() = config.clone();
+        if let Some(path) = self.config_path.read().as_ref() {
+            save_json(path, &config)?;
+        }
+        if config.enabled {
+            self.start(context).await?;
+        }
+        Ok(())
+    }
+
+    pub fn get_status(&self) -> ApiStatus {
+        #[cfg(feature = "api")]
+        let address = self.running.lock().as_ref().map(|r| r.address.to_string());
+        #[cfg(not(feature = "api"))]
+        let address = None;
+        ApiStatus {
+            available: cfg!(feature = "api"),
+            running: address.is_some(),
+            config: self.get_config(),
+            address,
+            error: self.error.lock().clone(),
+        }
+    }
+
+    /// Start serving on the configured address. Needs to run inside the tokio runtime.
+    #[cfg(feature = "api")]
+    pub async fn start(&self, context: ApiContext) -> Result<SocketAddr, String> {
+        let mut config = self.get_config();
+        let token = config.token.get_or_insert_with(generate_token).clone();
+        *self.config.write() = config.clone();
+        let addr = SocketAddr::from((config.bind, config.port));
+        let result = bind_and_serve(context, addr, token).await;
+        *self.error.lock() = result.as_ref().err().cloned();
+        let server = result?;
+        let address = server.address;
+        if let Some(previous) = self.running.lock().replace(server) {
+            let _ = previous.shutdown.send(());
+        }
+        info!("[API] Serving on http://{}", address);
+        Ok(address)
+    }
+
+    #[cfg(not(feature = "api"))]
+    pub async fn start(&self, _context: ApiContext) -> Result<SocketAddr, String> {
+        let message = "Built without remote API support (enable the \"api\" feature)".to_string();
+        *self.error.lock() = Some(message.clone());
+        Err(message)
+    }
+
+    pub fn stop(&self) {
+        #[cfg(feature = "api")]
+        if let Some(server) = self.running.lock().take() {
+            let _ = server.shutdown.send(());
+            info!("[API] Stopped serving on http://{}", server.address);
+        }
+    }
+}
+
+impl Default for ApiServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type ApiServerHandle = Arc<ApiServer>;
+
+#[cfg(feature = "api")]
+async fn bind_and_serve(
+    context: ApiContext,
+    addr: SocketAddr,
+    token: String,
+) -> Result<RunningServer, String> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Failed to bind API server to {}: {}", addr, e))?;
+    let address = listener.local_addr().unwrap_or(addr);
+    let (shutdown, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+    tokio::spawn(async move {
+        let serve = axum::serve(listener, router(context, token)).with_graceful_shutdown(async {
+            let _ = shutdown_rx.await;
+        });
+        if let Err(e) = serve.await {
+            error!("[API] Server error: {}", e);
+        }
+    });
+
+    Ok(RunningServer { address, shutdown })
+}
+
+#[cfg(feature = "api")]
+fn router(context: ApiContext, token: String) -> Router {
+    Router::new()
+        .route("/sources", get(get_sources).delete(clear_sources))
+        .route("/sources/{id}", axum::routing::delete(remove_source))
+        .route("/sources/{id}/universes", get(get_source_universe_stats))
+        .route("/sources/{id}/events", get(get_source_events))
+        .route("/sources/{id}/alias", put(set_source_alias))
+        .route("/sources/{id}/note", put(set_source_note))
+        .route("/sources/{id}/pinned", put(set_source_pinned))
+        .route("/sources/{id}/hidden", put(set_source_hidden))
+        .route("/devices", get(get_devices))
+        .route(
+            "/expected-devices",
+            get(get_expected_devices).post(add_expected_device),
+        )
+        .route(
+            "/expected-devices/{id}",
+            axum::routing::delete(remove_expected_device),
+        )
+        .route("/universes", get(get_universes))
+        .route("/universes/{universe}", get(get_universe))
+        .route("/stats", get(get_stats))
+        .route("/artpoll", post(send_artnet_poll))
+        .route("/ws", get(crate::stream::websocket))
+        .route_layer(axum::middleware::from_fn_with_state(
+            Arc::<str>::from(token),
+            require_token,
+        ))
+        .with_state(context)
+}
+
+/// Let GET requests through, others only with the bearer token
+#[cfg(feature = "api")]
+async fn require_token(State(token): State<Arc<str>>, request: Request, next: Next) -> Response {
+    if request.method() == Method::GET {
+        return next.run(request).await;
+    }
+    let given = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match given {
+        Some(given) if tokens_match(given.trim(), &token) => next.run(request).await,
+        _ => ApiError::Unauthorized.into_response(),
+    }
+}
+
+/// Compare tokens in time independent of where they differ
+#[cfg(feature = "api")]
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Error response, `{"error": "..."}`
+#[cfg(feature = "api")]
+enum ApiError {
+    NotFound(String),
+    BadRequest(String),
+    Unauthorized,
+    Internal(String),
+}
+
+#[cfg(feature = "api")]
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                "Missing or wrong API token".to_string(),
+            ),
+            ApiError::Internal(message) => (StatusCode::INTERNAL_SERVER_ERROR, message),
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+/// 404 unless the source is known
+#[cfg(feature = "api")]
+fn require_source(ctx: &ApiContext, id: &str) -> Result<(), ApiError> {
+    if ctx.source_manager.contains_source(id) {
+        Ok(())
+    } else {
+        Err(ApiError::NotFound(format!("Source {} not found", id)))
+    }
+}
+
+#[cfg(feature = "api")]
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// Controls answer 204 No Content on success
+#[cfg(feature = "api")]
+type EmptyResult = Result<StatusCode, ApiError>;
+
+#[cfg(feature = "api")]
+#[derive(Deserialize)]
+struct HiddenQuery {
+    #[serde(default)]
+    include_hidden: bool,
+}
+
+#[cfg(feature = "api")]
+#[derive(Deserialize)]
+struct PadQuery {
+    #[serde(default)]
+    pad: bool,
+}
+
+#[cfg(feature = "api")]
+#[derive(Deserialize)]
+struct AliasBody {
+    alias: Option<String>,
+}
+
+#[cfg(feature = "api")]
+#[derive(Deserialize)]
+struct NoteBody {
+    note: Option<String>,
+}
+
+#[cfg(feature = "api")]
+#[derive(Deserialize)]
+struct PinnedBody {
+    pinned: bool,
+}
+
+#[cfg(feature = "api")]
+#[derive(Deserialize)]
+struct HiddenBody {
+    hidden: bool,
+}
+
+#[cfg(feature = "api")]
+async fn get_sources(
+    State(ctx): State<ApiContext>,
+    Query(query): Query<HiddenQuery>,
+) -> Json<Vec<NetworkSource>> {
+    Json(ctx.source_manager.get_all_sources(query.include_hidden))
+}
+
+#[cfg(feature = "api")]
+async fn get_devices(
+    State(ctx): State<ApiContext>,
+    Query(query): Query<HiddenQuery>,
+) -> Json<Vec<NetworkDevice>> {
+    Json(group_devices(
+        ctx.source_manager.get_all_sources(query.include_hidden),
+        ctx.source_manager.get_arp_table(),
+    ))
+}
+
+#[cfg(feature = "api")]
+async fn get_source_universe_stats(
+    State(ctx): State<ApiContext>,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<SourceUniverseStats>> {
+    ctx.source_manager
+        .get_universe_stats(&id)
+        .map(Json)
+        .map_err(ApiError::NotFound)
+}
+
+#[cfg(feature = "api")]
+async fn get_source_events(
+    State(ctx): State<ApiContext>,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<SourceEvent>> {
+    ctx.source_manager
+        .get_events(&id)
+        .map(Json)
+        .map_err(ApiError::NotFound)
+}
+
+#[cfg(feature = "api")]
+async fn set_source_alias(
+    State(ctx): State<ApiContext>,
+    Path(id): Path<String>,
+    Json(body): Json<AliasBody>,
+) -> EmptyResult {
+    require_source(&ctx, &id)?;
+    ctx.source_manager
+        .set_alias(&id, body.alias)
+        .map_err(ApiError::Internal)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(feature = "api")]
+async fn set_source_note(
+    State(ctx): State<ApiContext>,
+    Path(id): Path<String>,
+    Json(body): Json<NoteBody>,
+) -> EmptyResult {
+    require_source(&ctx, &id)?;
+    ctx.source_manager
+        .set_note(&id, body.note)
+        .map_err(ApiError::Internal)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(feature = "api")]
+async fn set_source_pinned(
+    State(ctx): State<ApiContext>,
+    Path(id): Path<String>,
+    Json(body): Json<PinnedBody>,
+) -> EmptyResult {
+    require_source(&ctx, &id)?;
+    ctx.source_manager
+        .set_pinned(&id, body.pinned)
+        .map_err(ApiError::Internal)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(feature = "api")]
+async fn set_source_hidden(
+    State(ctx): State<ApiContext>,
+    Path(id): Path<String>,
+    Json(body): Json<HiddenBody>,
+) -> EmptyResult {
+    require_source(&ctx, &id)?;
+    ctx.source_manager
+        .set_hidden(&id, body.hidden)
+        .map_err(ApiError::Internal)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(feature = "api")]
+async fn remove_source(State(ctx): State<ApiContext>, Path(id): Path<String>) -> EmptyResult {
+    require_source(&ctx, &id)?;
+    ctx.source_manager
+        .remove_source(&id)
+        .map_err(ApiError::Internal)?;
+    let _ = ctx.event_tx.send(ListenerEvent::SourcesUpdated);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(feature = "api")]
+async fn clear_sources(State(ctx): State<ApiContext>) -> EmptyResult {
+    ctx.source_manager
+        .clear_sources()
+        .map_err(ApiError::Internal)?;
+    ctx.listener_state.poll_latency.clear();
+    ctx.listener_state.rdm_devices.clear();
+    ctx.listener_state.timecode.clear();
+    let _ = ctx.event_tx.send(ListenerEvent::SourcesUpdated);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(feature = "api")]
+async fn get_expected_devices(State(ctx): State<ApiContext>) -> Json<Vec<ExpectedDeviceStatus>> {
+    Json(ctx.source_manager.get_expected_devices())
+}
+
+#[cfg(feature = "api")]
+async fn add_expected_device(
+    State(ctx): State<ApiContext>,
+    Json(device): Json<ExpectedDevice>,
+) -> ApiResult<ExpectedDevice> {
+    ctx.source_manager
+        .add_expected_device(device)
+        .map(Json)
+        .map_err(ApiError::BadRequest)
+}
+
+#[cfg(feature = "api")]
+async fn remove_expected_device(
+    State(ctx): State<ApiContext>,
+    Path(id): Path<String>,
+) -> EmptyResult {
+    if !ctx
+        .source_manager
+        .get_expected_device_list()
+        .iter()
+        .any(|device| device.id == id)
+    {
+        return Err(ApiError::NotFound(format!(
+            "Expected device {} not found",
+            id
+        )));
+    }
+    ctx.source_manager
+        .remove_expected_device(&id)
+        .map_err(ApiError::Internal)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(feature = "api")]
+async fn get_universes(State(ctx): State<ApiContext>) -> Json<Vec<UniverseSummary>> {
+    Json(ctx.get_universes())
+}
+
+#[cfg(feature = "api")]
+async fn get_universe(
+    State(ctx): State<ApiContext>,
+    Path(universe): Path<u16>,
+    Query(query): Query<PadQuery>,
+) -> ApiResult<UniverseData> {
+    let data = if query.pad {
+        ctx.dmx_store.get_padded(universe)
+    } else {
+        ctx.dmx_store.get(universe)
+    };
+    let data =
+        data.ok_or_else(|| ApiError::NotFound(format!("Universe {} not found", universe)))?;
+    Ok(Json(UniverseData {
+        universe,
+        label: ctx.universe_labels.get(universe),
+        data,
+        sources: senders(&ctx.source_manager.get_all_sources(true), universe),
+        frame_lengths: ctx.dmx_store.get_frame_lengths(Some(universe)),
+    }))
+}
+
+#[cfg(feature = "api")]
+async fn get_stats(State(ctx): State<ApiContext>) -> Json<ApiStats> {
+    Json(ctx.get_stats())
+}
+
+#[cfg(feature = "api")]
+async fn send_artnet_poll(State(ctx): State<ApiContext>) -> EmptyResult {
+    send_artpoll(&ctx.listener_state).map_err(ApiError::Internal)?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,2410 @@
+// LXMonitor app - Tauri commands, window handling and startup
+
+use crate::api::{ApiConfig, ApiContext, ApiServer, ApiServerHandle, ApiStatus};
+use crate::autosave::{run_autosave, Autosave, AutosaveContext, AutosaveHandle, AutosaveStatus};
+use crate::deep_link::{open_deep_link, DEEP_LINK_SCHEME};
+use crate::events::{
+    is_universe_window, universe_window_label, DmxCoalescer, DmxSubscriptions,
+    DmxSubscriptionsHandle, EventConfig, EventSettings, EventSettingsHandle,
+};
+use crate::export::ExportFormat;
+use crate::labels::{UniverseLabels, UniverseLabelsHandle};
+use crate::logging::{LogLevel, LogLine, Logger, LoggerHandle};
+use crate::metrics::{AppMetrics, AppMetricsHandle, AppMetricsReport};
+use crate::mqtt::{MqttConfig, MqttContext, MqttPublisher, MqttPublisherHandle, MqttStatus};
+use crate::notifications::{
+    Notification, NotificationConfig, Notifications, NotificationsHandle, SilenceDetector,
+};
+use crate::profiles::{ProfileStore, ProfileStoreHandle, ProfileSummary, VenueProfile};
+use crate::report::{run_alert_log, AlertLog, AlertLogHandle};
+use crate::scripting::{
+    ScriptHost, ScriptHostHandle, ScriptingConfig, ScriptingContext, ScriptingStatus,
+};
+use crate::session::SessionComparison;
+use crate::snapshots::{
+    run_snapshots, SnapshotConfig, SnapshotScheduler, SnapshotSchedulerHandle, SnapshotStatus,
+};
+
+use crate::network::{
+    arp_scan_blocking,
+    assess_network_health,
+    build_topology,
+    // Sniffer mode
+    capture_setup_hint,
+    capture_support,
+    create_source_manager,
+    group_devices,
+    is_capture_available,
+    kernel_drops,
+    list_capture_interfaces,
+    ping,
+    run_anomaly_detector,
+    run_frame_phase,
+    run_numbering_check,
+    run_output,
+    run_stress_test_blocking,
+    select_capture_interface,
+    send_artpoll,
+    spawn_protocol_listeners,
+    start_listeners,
+    start_park_output,
+    start_router,
+    start_sniffer_blocking,
+    start_storm_monitor,
+    traceroute,
+    validate_capture_filter,
+    validate_capture_recording,
+    AlternateFrame,
+    ArpMapping,
+    ArpScanResult,
+    CaptureInterface,
+    CaptureRecording,
+    CaptureSupport,
+    ChannelAge,
+    DecoderInfo,
+    DeviceBandwidth,
+    DmxStore,
+    DmxStoreHandle,
+    EventBus,
+    EventBusConfig,
+    EventBusHandle,
+    EventBusStats,
+    EventReceiver,
+    ExpectedDevice,
+    ExpectedDeviceStatus,
+    FrameLength,
+    FramePhase,
+    FramePhaseAnalyzer,
+    FramePhaseAnalyzerHandle,
+    InspectedPacket,
+    InterfaceCapture,
+    ListenerBinding,
+    ListenerCounters,
+    ListenerEvent,
+    ListenerState,
+    ListenerStateHandle,
+    NetworkDevice,
+    NetworkHealth,
+    NetworkSource,
+    NetworkTopology,
+    NodeEmulator,
+    NodeEmulatorConfig,
+    NodeEmulatorHandle,
+    NodeEmulatorStatus,
+    NodePollLatency,
+    NumberingCheck,
+    NumberingCheckHandle,
+    NumberingMismatch,
+    OutputConfig,
+    OutputStatus,
+    PacketFilter,
+    ParkManager,
+    ParkManagerHandle,
+    ParkedChannel,
+    PingResult,
+    Protocol,
+    ProtocolBandwidth,
+    RdmDevice,
+    RdmDeviceConfig,
+    RdmResponder,
+    RdmResponderHandle,
+    Route,
+    RouteConfig,
+    RoutingManager,
+    RoutingManagerHandle,
+    SequenceGap,
+    SequenceGapFilter,
+    SnifferState,
+    SnifferStateHandle,
+    SnifferStatus,
+    SourceEvent,
+    SourceManagerHandle,
+    SourceThresholds,
+    SourceUniverseStats,
+    StormConfig,
+    StormStatus,
+    StressTestConfig,
+    StressTestState,
+    StressTestStateHandle,
+    StressTestStatus,
+    SwitchNeighbor,
+    TimecodeConfig,
+    TimecodeStatus,
+    TraceRoute,
+    TrafficSummary,
+    TransmitManager,
+    TransmitManagerHandle,
+    UniverseConflict,
+    UniverseRange,
+    VirtualRdmDevice,
+    ARTNET_PORT,
+    CAPTURE_LIBRARY,
+    DEFAULT_TOPOLOGY_PREFIX_LEN,
+    SACN_PORT,
+};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::{
+    AppHandle, Emitter, Manager, RunEvent, State, WebviewUrl, WebviewWindowBuilder, WindowEvent,
+};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+/// Application state
+pub struct AppState {
+    pub(crate) source_manager: SourceManagerHandle,
+    dmx_store: DmxStoreHandle,
+    event_tx: EventBusHandle,
+    pub(crate) is_listening: Mutex<bool>,
+    pub(crate) listener_state: ListenerStateHandle,
+    pub(crate) sniffer_state: SnifferStateHandle,
+    pub(crate) universe_labels: UniverseLabelsHandle,
+    profiles: ProfileStoreHandle,
+    event_settings: EventSettingsHandle,
+    dmx_subscriptions: DmxSubscriptionsHandle,
+    metrics: AppMetricsHandle,
+    transmit_manager: TransmitManagerHandle,
+    park_manager: ParkManagerHandle,
+    routing_manager: RoutingManagerHandle,
+    stress_test: StressTestStateHandle,
+    node_emulator: NodeEmulatorHandle,
+    rdm_responder: RdmResponderHandle,
+    api_server: ApiServerHandle,
+    mqtt: MqttPublisherHandle,
+    logger: LoggerHandle,
+    pub(crate) notifications: NotificationsHandle,
+    pub(crate) alert_log: AlertLogHandle,
+    frame_phase: FramePhaseAnalyzerHandle,
+    numbering: NumberingCheckHandle,
+    snapshots: SnapshotSchedulerHandle,
+    scripting: ScriptHostHandle,
+    autosave: AutosaveHandle,
+}
+
+impl AppState {
+    /// Handles the remote API serves from
+    fn api_context(&self) -> ApiContext {
+        ApiContext {
+            source_manager: self.source_manager.clone(),
+            dmx_store: self.dmx_store.clone(),
+            listener_state: self.listener_state.clone(),
+            event_tx: self.event_tx.clone(),
+            universe_labels: self.universe_labels.clone(),
+        }
+    }
+
+    /// Handles the MQTT publisher reads from
+    fn mqtt_context(&self) -> MqttContext {
+        MqttContext {
+            source_manager: self.source_manager.clone(),
+            event_tx: self.event_tx.clone(),
+            universe_labels: self.universe_labels.clone(),
+        }
+    }
+
+    /// Handles scripts read from and act on
+    fn scripting_context(&self) -> ScriptingContext {
+        ScriptingContext {
+            source_manager: self.source_manager.clone(),
+            event_tx: self.event_tx.clone(),
+            universe_labels: self.universe_labels.clone(),
+        }
+    }
+
+    /// Handles the session state is saved from
+    fn autosave_context(&self) -> AutosaveContext {
+        AutosaveContext {
+            source_manager: self.source_manager.clone(),
+            alert_log: self.alert_log.clone(),
+            routing_manager: self.routing_manager.clone(),
+            park_manager: self.park_manager.clone(),
+        }
+    }
+}
+
+/// Get all discovered sources (hidden sources only when requested)
+#[tauri::command]
+async fn get_sources(
+    state: State<'_, AppState>,
+    include_hidden: Option<bool>,
+) -> Result<Vec<NetworkSource>, String> {
+    Ok(state
+        .source_manager
+        .get_all_sources(include_hidden.unwrap_or(false)))
+}
+
+/// Get sources grouped into physical devices (sources sharing an IP or MAC)
+#[tauri::command]
+async fn get_devices(
+    state: State<'_, AppState>,
+    include_hidden: Option<bool>,
+) -> Result<Vec<NetworkDevice>, String> {
+    Ok(group_devices(
+        state
+            .source_manager
+            .get_all_sources(include_hidden.unwrap_or(false)),
+        state.source_manager.get_arp_table(),
+    ))
+}
+
+/// Get per-universe FPS and packet loss for a source
+#[tauri::command]
+async fn get_source_universe_stats(
+    state: State<'_, AppState>,
+    source_id: String,
+) -> Result<Vec<SourceUniverseStats>, String> {
+    state.source_manager.get_universe_stats(&source_id)
+}
+
+/// Get the event history of a source (online/stale transitions, renames, ...)
+#[tauri::command]
+async fn get_source_events(
+    state: State<'_, AppState>,
+    source_id: String,
+) -> Result<Vec<SourceEvent>, String> {
+    state.source_manager.get_events(&source_id)
+}
+
+/// Relative frame timing of each pair of senders of a universe (all universes
+/// when none is given): clock drift, phase and when their frames next line up
+#[tauri::command]
+async fn get_frame_phase(
+    state: State<'_, AppState>,
+    universe: Option<u16>,
+) -> Result<Vec<FramePhase>, String> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    Ok(state.frame_phase.get_phases(universe, now))
+}
+
+/// Universes sent by more than one source, with every sender, its protocol and
+/// priority, and how receivers will resolve them
+#[tauri::command]
+async fn get_universe_conflicts(
+    state: State<'_, AppState>,
+) -> Result<Vec<UniverseConflict>, String> {
+    Ok(state.source_manager.get_universe_conflicts())
+}
+
+/// Art-Net and sACN universes one apart that carry the same levels, a sign the
+/// sender and a gateway number universes differently
+#[tauri::command]
+async fn get_numbering_mismatches(
+    state: State<'_, AppState>,
+) -> Result<Vec<NumberingMismatch>, String> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    Ok(state.numbering.get_mismatches(now))
+}
+
+/// Ping a source with ICMP echo requests (4 by default, one a second) to check
+/// whether a silent node is reachable
+#[tauri::command]
+async fn ping_source(
+    state: State<'_, AppState>,
+    source_id: String,
+    count: Option<u32>,
+) -> Result<PingResult, String> {
+    let ip = state.source_manager.get_source_ip(&source_id)?;
+    tokio::task::spawn_blocking(move || ping(ip, count.unwrap_or(4)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Trace the route to a source, to see whether its traffic crosses routers
+#[tauri::command]
+async fn trace_source(state: State<'_, AppState>, source_id: String) -> Result<TraceRoute, String> {
+    let ip = state.source_manager.get_source_ip(&source_id)?;
+    tokio::task::spawn_blocking(move || traceroute(ip))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Health score of the network and each sender, from loss, jitter, frame rate,
+/// shared universes and conflicts
+#[tauri::command]
+async fn get_network_health(state: State<'_, AppState>) -> Result<NetworkHealth, String> {
+    Ok(assess_network_health(
+        &state.source_manager.get_all_sources(false),
+    ))
+}
+
+/// Logged sequence gaps matching the filter (newest `count`, 500 by default,
+/// oldest first), to line up a visible glitch with actual packet loss
+#[tauri::command]
+async fn get_sequence_gaps(
+    state: State<'_, AppState>,
+    filter: Option<SequenceGapFilter>,
+    count: Option<usize>,
+) -> Result<Vec<SequenceGap>, String> {
+    Ok(state
+        .source_manager
+        .get_sequence_gaps(&filter.unwrap_or_default(), count))
+}
+
+/// Clear the sequence gap log
+#[tauri::command]
+async fn clear_sequence_gaps(state: State<'_, AppState>) -> Result<(), String> {
+    state.source_manager.clear_sequence_gaps();
+    Ok(())
+}
+
+/// Set or clear the user alias of a source
+#[tauri::command]
+async fn set_source_alias(
+    state: State<'_, AppState>,
+    source_id: String,
+    alias: Option<String>,
+) -> Result<(), String> {
+    state.source_manager.set_alias(&source_id, alias)
+}
+
+/// Set or clear the user note of a source
+#[tauri::command]
+async fn set_source_note(
+    state: State<'_, AppState>,
+    source_id: String,
+    note: Option<String>,
+) -> Result<(), String> {
+    state.source_manager.set_note(&source_id, note)
+}
+
+/// Pin or unpin a source
+#[tauri::command]
+async fn set_source_pinned(
+    state: State<'_, AppState>,
+    source_id: String,
+    pinned: bool,
+) -> Result<(), String> {
+    state.source_manager.set_pinned(&source_id, pinned)
+}
+
+/// Hide or unhide a source
+#[tauri::command]
+async fn set_source_hidden(
+    state: State<'_, AppState>,
+    source_id: String,
+    hidden: bool,
+) -> Result<(), String> {
+    state.source_manager.set_hidden(&source_id, hidden)
+}
+
+/// Remove a source (it reappears if it is still transmitting)
+#[tauri::command]
+async fn remove_source(state: State<'_, AppState>, source_id: String) -> Result<(), String> {
+    state.source_manager.remove_source(&source_id)?;
+    let _ = state.event_tx.send(ListenerEvent::SourcesUpdated);
+    Ok(())
+}
+
+/// Remove all sources except pinned ones
+#[tauri::command]
+async fn clear_sources(state: State<'_, AppState>) -> Result<(), String> {
+    state.source_manager.clear_sources()?;
+    state.listener_state.poll_latency.clear();
+    state.listener_state.rdm_devices.clear();
+    state.listener_state.timecode.clear();
+    let _ = state.event_tx.send(ListenerEvent::SourcesUpdated);
+    Ok(())
+}
+
+/// Get the source status and FPS warning thresholds
+#[tauri::command]
+async fn get_source_thresholds(state: State<'_, AppState>) -> Result<SourceThresholds, String> {
+    Ok(state.source_manager.get_thresholds())
+}
+
+/// Set the source status and FPS warning thresholds
+#[tauri::command]
+async fn set_source_thresholds(
+    state: State<'_, AppState>,
+    thresholds: SourceThresholds,
+) -> Result<(), String> {
+    state.source_manager.set_thresholds(thresholds)
+}
+
+/// Get the expected devices with their present/missing state
+#[tauri::command]
+async fn get_expected_devices(
+    state: State<'_, AppState>,
+) -> Result<Vec<ExpectedDeviceStatus>, String> {
+    Ok(state.source_manager.get_expected_devices())
+}
+
+/// Add a device that must be present (matched by IP, MAC and/or name)
+#[tauri::command]
+async fn add_expected_device(
+    state: State<'_, AppState>,
+    device: ExpectedDevice,
+) -> Result<ExpectedDevice, String> {
+    state.source_manager.add_expected_device(device)
+}
+
+/// Remove an expected device
+#[tauri::command]
+async fn remove_expected_device(
+    state: State<'_, AppState>,
+    device_id: String,
+) -> Result<(), String> {
+    state.source_manager.remove_expected_device(&device_id)
+}
+
+/// Append expected devices from a JSON or CSV file, returns the number added
+#[tauri::command]
+async fn import_expected_devices(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<usize, String> {
+    state
+        .source_manager
+        .import_expected_devices(std::path::Path::new(&path))
+}
+
+/// Write the full source table, hidden sources included, to a CSV or JSON file
+#[tauri::command]
+async fn export_sources(
+    state: State<'_, AppState>,
+    path: String,
+    format: ExportFormat,
+) -> Result<(), String> {
+    let mut sources = state.source_manager.get_all_sources(true);
+    sources.sort_by(|a, b| a.ip.cmp(&b.ip).then(a.id.cmp(&b.id)));
+    crate::export::export_sources(&sources, std::path::Path::new(&path), format)
+}
+
+/// Write an HTML network health report covering the last `window_minutes`, or
+/// everything kept since the monitor started when omitted
+#[tauri::command]
+async fn generate_report(
+    state: State<'_, AppState>,
+    path: String,
+    window_minutes: Option<u64>,
+) -> Result<(), String> {
+    let since = window_minutes.map(|minutes| {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        now.saturating_sub(minutes * 60_000)
+    });
+    crate::report::write_report(&state, std::path::Path::new(&path), since)
+}
+
+/// Get the snapshot schedule, where snapshots go and the latest outcome
+#[tauri::command]
+async fn get_snapshot_status(state: State<'_, AppState>) -> Result<SnapshotStatus, String> {
+    Ok(state.snapshots.get_status())
+}
+
+/// Save the snapshot schedule and retention limits
+#[tauri::command]
+async fn set_snapshot_config(
+    state: State<'_, AppState>,
+    config: SnapshotConfig,
+) -> Result<SnapshotStatus, String> {
+    state.snapshots.set_config(config)?;
+    Ok(state.snapshots.get_status())
+}
+
+/// Write a snapshot of the sources and universe statistics now, returning its path
+#[tauri::command]
+async fn take_snapshot(state: State<'_, AppState>) -> Result<String, String> {
+    state
+        .snapshots
+        .take_snapshot(&state.source_manager, &state.universe_labels)
+        .map(|path| path.display().to_string())
+}
+
+/// When the session state was last autosaved and whether it was restored after
+/// a crash
+#[tauri::command]
+async fn get_autosave_status(state: State<'_, AppState>) -> Result<AutosaveStatus, String> {
+    Ok(state.autosave.get_status())
+}
+
+/// Restart the routes and parked channels a crashed run left pending
+#[tauri::command]
+async fn confirm_pending_restore(state: State<'_, AppState>) -> Result<(), String> {
+    state
+        .autosave
+        .confirm_pending_restore(&state.autosave_context())
+}
+
+/// Drop the routes and parked channels a crashed run left pending
+#[tauri::command]
+async fn discard_pending_restore(state: State<'_, AppState>) -> Result<(), String> {
+    state.autosave.discard_pending_restore();
+    Ok(())
+}
+
+/// Compare the current sources with a saved session (a JSON source export)
+/// and report devices that disappeared, appeared or changed
+#[tauri::command]
+async fn compare_session(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<SessionComparison, String> {
+    crate::session::compare_session(
+        &state.source_manager.get_all_sources(true),
+        std::path::Path::new(&path),
+    )
+}
+
+/// Get total bytes/sec and packets/sec per protocol
+#[tauri::command]
+async fn get_protocol_bandwidth(
+    state: State<'_, AppState>,
+) -> Result<Vec<ProtocolBandwidth>, String> {
+    Ok(state.source_manager.get_protocol_bandwidth())
+}
+
+/// Get sources grouped by subnet (default /24) and shared link MAC for the network map
+#[tauri::command]
+async fn get_topology(
+    state: State<'_, AppState>,
+    prefix_len: Option<u8>,
+) -> Result<NetworkTopology, String> {
+    build_topology(
+        &state.source_manager.get_all_sources(true),
+        prefix_len.unwrap_or(DEFAULT_TOPOLOGY_PREFIX_LEN),
+    )
+}
+
+/// Get DMX data for a specific universe, optionally padded to 512 slots
+#[tauri::command]
+async fn get_dmx_data(
+    state: State<'_, AppState>,
+    universe: u16,
+    pad: Option<bool>,
+) -> Result<Option<Vec<u8>>, String> {
+    if pad.unwrap_or(false) {
+        Ok(state.dmx_store.get_padded(universe))
+    } else {
+        Ok(state.dmx_store.get(universe))
+    }
+}
+
+/// Get received frame lengths per universe/source (all universes if none given)
+#[tauri::command]
+async fn get_frame_lengths(
+    state: State<'_, AppState>,
+    universe: Option<u16>,
+) -> Result<Vec<FrameLength>, String> {
+    Ok(state.dmx_store.get_frame_lengths(universe))
+}
+
+/// Get when each channel in a universe last changed value
+#[tauri::command]
+async fn get_channel_ages(
+    state: State<'_, AppState>,
+    universe: u16,
+) -> Result<Option<Vec<ChannelAge>>, String> {
+    Ok(state.dmx_store.get_channel_ages(universe))
+}
+
+/// Get frames received with non-zero start codes (RDM, text, SIP, per-address priority)
+#[tauri::command]
+async fn get_alternate_frames(
+    state: State<'_, AppState>,
+    universe: Option<u16>,
+    start_code: Option<u8>,
+) -> Result<Vec<AlternateFrame>, String> {
+    Ok(state.dmx_store.get_alternate_frames(universe, start_code))
+}
+
+/// Get DMX data for all universes
+#[tauri::command]
+async fn get_all_dmx_data(
+    state: State<'_, AppState>,
+) -> Result<std::collections::HashMap<u16, Vec<u8>>, String> {
+    Ok(state.dmx_store.get_all())
+}
+
+/// Network interface info
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkInterface {
+    pub name: String,
+    pub ip: String,
+    pub is_loopback: bool,
+}
+
+/// Get available network interfaces
+#[tauri::command]
+async fn get_network_interfaces() -> Result<Vec<NetworkInterface>, String> {
+    let mut interfaces = Vec::new();
+
+    // Add "all interfaces" option
+    interfaces.push(NetworkInterface {
+        name: "All Interfaces".to_string(),
+        ip: "0.0.0.0".to_string(),
+        is_loopback: false,
+    });
+
+    // Get local interfaces
+    if let Ok(local_ip) = local_ip_address::local_ip() {
+        interfaces.push(NetworkInterface {
+            name: format!("Primary ({})", local_ip),
+            ip: local_ip.to_string(),
+            is_loopback: false,
+        });
+    }
+
+    // Try to get all interfaces
+    if let Ok(ifaces) = local_ip_address::list_afinet_netifas() {
+        for (name, ip) in ifaces {
+            if let std::net::IpAddr::V4(ipv4) = ip {
+                if ipv4 != Ipv4Addr::LOCALHOST
+                    && !interfaces.iter().any(|i| i.ip == ipv4.to_string())
+                {
+                    interfaces.push(NetworkInterface {
+                        name,
+                        ip: ipv4.to_string(),
+                        is_loopback: ipv4.is_loopback(),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(interfaces)
+}
+
+/// Listener status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenerStatus {
+    pub is_listening: bool,
+    pub artnet_enabled: bool,
+    pub sacn_enabled: bool,
+    pub auto_poll_enabled: bool,
+    pub artnet_active: bool,
+    pub sacn_active: bool,
+    pub bind_interfaces: Vec<String>,   // Configured interfaces
+    pub artnet_interfaces: Vec<String>, // Interfaces with a running Art-Net listener
+    pub sacn_interfaces: Vec<String>,   // Interfaces with a running sACN listener
+    pub sacn_source_specific: bool,
+    pub receive_buffer_size: Option<usize>, // Configured, None for the OS default
+    pub artnet_receive_buffer: Option<usize>, // Granted by the OS
+    pub sacn_receive_buffer: Option<usize>,
+    pub artnet_kernel_drops: Option<u64>, // Full receive buffer drops, Linux only
+    pub sacn_kernel_drops: Option<u64>,
+    pub bindings: Vec<ListenerBinding>, // Port binding per listener, incl. port-in-use failures
+    pub counters: Vec<ListenerCounters>, // Receive, parse failure and drop counts per listener
+    pub decoders: Vec<DecoderInfo>,     // Registered decoders of other protocols
+}
+
+/// Get listener status
+#[tauri::command]
+async fn get_listener_status(state: State<'_, AppState>) -> Result<ListenerStatus, String> {
+    let is_listening = *state.is_listening.lock();
+    let to_strings = |ips: &[Ipv4Addr]| ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>();
+    let artnet_interfaces = to_strings(&state.listener_state.artnet_running.lock());
+    let sacn_interfaces = to_strings(&state.listener_state.sacn_running.lock());
+    Ok(ListenerStatus {
+        is_listening,
+        artnet_enabled: state.listener_state.protocol_enabled(Protocol::ArtNet),
+        sacn_enabled: state.listener_state.protocol_enabled(Protocol::Sacn),
+        auto_poll_enabled: state.listener_state.auto_poll_enabled(),
+        artnet_active: !artnet_interfaces.is_empty(),
+        sacn_active: !sacn_interfaces.is_empty(),
+        bind_interfaces: to_strings(&state.listener_state.bind_addrs()),
+        artnet_interfaces,
+        sacn_interfaces,
+        sacn_source_specific: state.listener_state.sacn_source_specific(),
+        receive_buffer_size: state.listener_state.receive_buffer_size(),
+        artnet_receive_buffer: *state.listener_state.artnet_receive_buffer.lock(),
+        sacn_receive_buffer: *state.listener_state.sacn_receive_buffer.lock(),
+        artnet_kernel_drops: kernel_drops(ARTNET_PORT),
+        sacn_kernel_drops: kernel_drops(SACN_PORT),
+        bindings: state.listener_state.bindings.lock().clone(),
+        counters: state.listener_state.get_counters(),
+        decoders: state.listener_state.decoders.get_status(),
+    })
+}
+
+/// Listen on other interfaces (IPs from get_network_interfaces, or just 0.0.0.0
+/// for all) and restart the listeners on them. The choice is remembered.
+#[tauri::command]
+async fn set_bind_interfaces(state: State<'_, AppState>, ips: Vec<String>) -> Result<(), String> {
+    let bind_addresses = ips
+        .iter()
+        .map(|ip| {
+            ip.trim()
+                .parse::<Ipv4Addr>()
+                .map_err(|_| format!("Invalid IPv4 address: {}", ip))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    state.listener_state.set_bind_addresses(bind_addresses)?;
+    restart_listeners(&state).await;
+    Ok(())
+}
+
+/// Enable or disable the Art-Net or sACN listeners. The choice is remembered.
+#[tauri::command]
+pub(crate) async fn set_listener_enabled(
+    state: State<'_, AppState>,
+    protocol: Protocol,
+    enabled: bool,
+) -> Result<(), String> {
+    let was_running = state.listener_state.is_running(protocol);
+    state
+        .listener_state
+        .set_protocol_enabled(protocol, enabled)
+        .await?;
+    if enabled && !was_running {
+        spawn_protocol_listeners(
+            state.source_manager.clone(),
+            state.dmx_store.clone(),
+            state.event_tx.clone(),
+            state.node_emulator.clone(),
+            state.rdm_responder.clone(),
+            state.listener_state.clone(),
+            &[protocol],
+        );
+    }
+    Ok(())
+}
+
+/// Enable or disable the periodic ArtPoll broadcast. The choice is remembered.
+#[tauri::command]
+async fn set_auto_poll_enabled(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    state.listener_state.set_auto_poll_enabled(enabled)
+}
+
+/// Set the socket receive buffer size in bytes for both listeners (None for the
+/// OS default) and restart them. Raise it when kernel drops show up.
+#[tauri::command]
+async fn set_receive_buffer_size(
+    state: State<'_, AppState>,
+    size: Option<usize>,
+) -> Result<(), String> {
+    state.listener_state.set_receive_buffer_size(size)?;
+    restart_listeners(&state).await;
+    Ok(())
+}
+
+/// Stop the running protocol listeners and start them with the current configuration
+async fn restart_listeners(state: &AppState) {
+    state.listener_state.stop_listeners().await;
+    spawn_protocol_listeners(
+        state.source_manager.clone(),
+        state.dmx_store.clone(),
+        state.event_tx.clone(),
+        state.node_emulator.clone(),
+        state.rdm_responder.clone(),
+        state.listener_state.clone(),
+        &[Protocol::ArtNet, Protocol::Sacn, Protocol::Other],
+    );
+}
+
+/// Get the sACN universe ranges whose multicast groups are always joined
+#[tauri::command]
+async fn get_sacn_universe_ranges(
+    state: State<'_, AppState>,
+) -> Result<Vec<UniverseRange>, String> {
+    Ok(state.listener_state.sacn_universe_ranges())
+}
+
+/// Always join the sACN multicast groups of universes start..=end
+#[tauri::command]
+async fn add_sacn_universe_range(
+    state: State<'_, AppState>,
+    start: u16,
+    end: u16,
+) -> Result<(), String> {
+    state
+        .listener_state
+        .add_sacn_universe_range(UniverseRange { start, end })
+}
+
+/// Stop joining a universe range (groups with active traffic stay joined)
+#[tauri::command]
+async fn remove_sacn_universe_range(
+    state: State<'_, AppState>,
+    start: u16,
+    end: u16,
+) -> Result<(), String> {
+    state
+        .listener_state
+        .remove_sacn_universe_range(UniverseRange { start, end })
+}
+
+/// Join sACN groups source-specifically (IGMPv3 SSM) for the known sources of
+/// each universe, or any-source as usual
+#[tauri::command]
+async fn set_sacn_source_specific(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    state.listener_state.set_sacn_source_specific(enabled)
+}
+
+// ============================================================================
+// Sniffer Mode Commands
+// ============================================================================
+
+/// Check whether packet capture works on this machine, with setup instructions
+/// for the platform when it doesn't
+#[tauri::command]
+async fn get_capture_support() -> Result<CaptureSupport, String> {
+    Ok(capture_support())
+}
+
+/// Get available capture interfaces
+#[tauri::command]
+async fn get_capture_interfaces() -> Result<Vec<CaptureInterface>, String> {
+    Ok(list_capture_interfaces())
+}
+
+/// Get sniffer status
+#[tauri::command]
+async fn get_sniffer_status(state: State<'_, AppState>) -> Result<SnifferStatus, String> {
+    Ok(state.sniffer_state.get_status())
+}
+
+/// Enable or disable sniffer mode. `interfaces` captures on several interfaces
+/// at once (e.g. a mirror port and the local NIC), `interface` on one. An
+/// optional BPF expression (e.g. "vlan 20", "net 10.0.0.0/16") narrows the
+/// capture beyond the Art-Net and sACN ports.
+#[tauri::command]
+pub(crate) async fn set_sniffer_mode(
+    state: State<'_, AppState>,
+    enabled: bool,
+    interface: Option<String>,
+    interfaces: Option<Vec<String>>,
+    filter: Option<String>,
+    recording: Option<CaptureRecording>,
+    all_traffic: Option<bool>,
+) -> Result<(), String> {
+    if enabled {
+        // Check if the capture library is available
+        if !is_capture_available() {
+            return Err(format!(
+                "{} is not available. {}",
+                CAPTURE_LIBRARY,
+                capture_setup_hint()
+            ));
+        }
+
+        // Get interface names
+        let mut interface_names: Vec<String> = Vec::new();
+        for name in interfaces.unwrap_or_default().into_iter().chain(interface) {
+            if !interface_names.contains(&name) {
+                interface_names.push(name);
+            }
+        }
+        if interface_names.is_empty() {
+            // Use the interface carrying lighting traffic, sampling takes a moment
+            let name = tokio::task::spawn_blocking(select_capture_interface)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or("No capture interfaces available")?;
+            interface_names.push(name);
+        }
+
+        // Check if already running
+        if state.sniffer_state.is_running() {
+            return Err("Sniffer is already running".to_string());
+        }
+
+        let filter = filter
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty());
+        if let Some(filter) = &filter {
+            validate_capture_filter(filter)?;
+        }
+        if let Some(recording) = &recording {
+            validate_capture_recording(recording)?;
+        }
+
+        // Start a capture thread per interface
+        let captures: Vec<Arc<InterfaceCapture>> = interface_names
+            .into_iter()
+            .map(|name| Arc::new(InterfaceCapture::new(name)))
+            .collect();
+        *state.sniffer_state.captures.lock() = captures.clone();
+        *state.sniffer_state.stop_flag.lock() = false;
+        *state.sniffer_state.user_filter.lock() = filter;
+        *state.sniffer_state.recording.lock() = recording;
+        *state.sniffer_state.triggered.lock() = None;
+        *state.sniffer_state.all_traffic.lock() = all_traffic.unwrap_or(false);
+        state.sniffer_state.inspector.clear();
+        state.sniffer_state.bandwidth.clear();
+        state.sniffer_state.storm.reset();
+        state.sniffer_state.switches.clear();
+
+        for capture in captures {
+            let sm = state.source_manager.clone();
+            let ds = state.dmx_store.clone();
+            let tx = state.event_tx.clone();
+            let ss = state.sniffer_state.clone();
+
+            let thread_capture = capture.clone();
+            let thread = std::thread::spawn(move || {
+                start_sniffer_blocking(thread_capture, sm, ds, tx, ss);
+            });
+            *capture.thread.lock() = Some(thread);
+        }
+
+        Ok(())
+    } else {
+        // Stop sniffer and wait for the capture threads to exit, they check
+        // the flag every 100ms
+        *state.sniffer_state.stop_flag.lock() = true;
+        let threads: Vec<_> = state
+            .sniffer_state
+            .captures
+            .lock()
+            .iter()
+            .filter_map(|c| c.thread.lock().take())
+            .collect();
+        let joined =
+            tokio::task::spawn_blocking(move || threads.into_iter().all(|t| t.join().is_ok()));
+        match tokio::time::timeout(std::time::Duration::from_secs(5), joined).await {
+            Ok(Ok(true)) => Ok(()),
+            Ok(_) => Err("A capture thread failed while stopping".to_string()),
+            Err(_) => Err("Sniffer did not stop within 5 seconds".to_string()),
+        }
+    }
+}
+
+/// Most recent captured frames matching the filter, with hex dumps and decode
+/// trees (newest `count`, 100 by default, oldest first)
+#[tauri::command]
+async fn get_captured_packets(
+    state: State<'_, AppState>,
+    filter: Option<PacketFilter>,
+    count: Option<usize>,
+) -> Result<Vec<InspectedPacket>, String> {
+    Ok(state
+        .sniffer_state
+        .inspector
+        .query(&filter.unwrap_or_default(), count))
+}
+
+/// Traffic per device captured by the sniffer, busiest first. Non-lighting
+/// protocols are included when the sniffer captures all traffic.
+#[tauri::command]
+async fn get_capture_bandwidth(state: State<'_, AppState>) -> Result<Vec<DeviceBandwidth>, String> {
+    Ok(state.sniffer_state.bandwidth.get_devices())
+}
+
+/// Captured traffic by protocol and category, with the share that isn't
+/// lighting. Needs the sniffer to capture all traffic.
+#[tauri::command]
+async fn get_capture_traffic_summary(state: State<'_, AppState>) -> Result<TrafficSummary, String> {
+    Ok(state.sniffer_state.bandwidth.get_summary())
+}
+
+/// IP to MAC mappings learned from ARP captured by the sniffer
+#[tauri::command]
+async fn get_arp_table(state: State<'_, AppState>) -> Result<Vec<ArpMapping>, String> {
+    Ok(state.source_manager.get_arp_table())
+}
+
+/// Sweep a subnet (CIDR, /20 or smaller) with ARP for every device on it,
+/// including ones that never send Art-Net or sACN. Needs the sniffer feature.
+#[tauri::command]
+async fn arp_scan(
+    state: State<'_, AppState>,
+    subnet: String,
+    interface: Option<String>,
+) -> Result<ArpScanResult, String> {
+    let source_manager = state.source_manager.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        arp_scan_blocking(&subnet, interface.as_deref(), &source_manager)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let _ = state.event_tx.send(ListenerEvent::SourcesUpdated);
+    Ok(result)
+}
+
+/// Switches and ports announced with LLDP or CDP on the captured links
+#[tauri::command]
+async fn get_switch_neighbors(state: State<'_, AppState>) -> Result<Vec<SwitchNeighbor>, String> {
+    Ok(state.sniffer_state.switches.get_all())
+}
+
+/// Broadcast and multicast rates seen by the sniffer, with the storm limits
+#[tauri::command]
+async fn get_broadcast_storm_status(state: State<'_, AppState>) -> Result<StormStatus, String> {
+    Ok(state.sniffer_state.storm.get_status())
+}
+
+/// Set the packets-per-second limits above which a broadcast-storm-alert is sent
+#[tauri::command]
+async fn set_broadcast_storm_limits(
+    state: State<'_, AppState>,
+    config: StormConfig,
+) -> Result<(), String> {
+    state.sniffer_state.storm.set_config(config)
+}
+
+/// Empty the captured frame buffer
+#[tauri::command]
+async fn clear_captured_packets(state: State<'_, AppState>) -> Result<(), String> {
+    state.sniffer_state.inspector.clear();
+    Ok(())
+}
+
+// ============================================================================
+// Universe Label Commands
+// ============================================================================
+
+/// Get all universe labels
+#[tauri::command]
+async fn get_universe_labels(
+    state: State<'_, AppState>,
+) -> Result<std::collections::BTreeMap<u16, String>, String> {
+    Ok(state.universe_labels.get_all())
+}
+
+/// Set (or clear, with an empty/missing label) the label for a universe
+#[tauri::command]
+async fn set_universe_label(
+    state: State<'_, AppState>,
+    universe: u16,
+    label: Option<String>,
+) -> Result<(), String> {
+    state.universe_labels.set(universe, label)
+}
+
+// ============================================================================
+// Venue Profile Commands
+// ============================================================================
+
+/// List the saved venue profiles
+#[tauri::command]
+async fn get_profiles(state: State<'_, AppState>) -> Result<Vec<ProfileSummary>, String> {
+    Ok(state.profiles.list())
+}
+
+/// Save the current interfaces, sACN universe ranges, expected devices, universe
+/// labels and alert limits as a named profile, replacing one of the same name
+#[tauri::command]
+async fn save_profile(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    state.profiles.save_profile(VenueProfile {
+        name,
+        saved_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+        bind_addresses: state.listener_state.bind_addrs(),
+        sacn_universe_ranges: state.listener_state.sacn_universe_ranges(),
+        expected_devices: state.source_manager.get_expected_device_list(),
+        universe_labels: state.universe_labels.get_all(),
+        source_thresholds: state.source_manager.get_thresholds(),
+        storm_limits: state.sniffer_state.storm.get_status().config,
+    })
+}
+
+/// Switch to a saved profile, restarting the listeners when its interfaces
+/// differ. Returns the settings that couldn't be applied on this machine (e.g.
+/// interfaces it doesn't have), which keep their current values.
+#[tauri::command]
+async fn load_profile(state: State<'_, AppState>, name: String) -> Result<Vec<String>, String> {
+    let profile = state.profiles.get(&name)?;
+    let bind_addresses = state.listener_state.bind_addrs();
+
+    let mut skipped = Vec::new();
+    let mut apply = |setting: &str, result: Result<(), String>| {
+        if let Err(e) = result {
+            skipped.push(format!("{}: {}", setting, e));
+        }
+    };
+    apply(
+        "Interfaces",
+        state
+            .listener_state
+            .set_bind_addresses(profile.bind_addresses),
+    );
+    apply(
+        "sACN universe ranges",
+        state
+            .listener_state
+            .set_sacn_universe_ranges(profile.sacn_universe_ranges),
+    );
+    apply(
+        "Expected devices",
+        state
+            .source_manager
+            .set_expected_devices(profile.expected_devices),
+    );
+    apply(
+        "Universe labels",
+        state.universe_labels.set_all(profile.universe_labels),
+    );
+    apply(
+        "Source thresholds",
+        state
+            .source_manager
+            .set_thresholds(profile.source_thresholds),
+    );
+    apply(
+        "Storm limits",
+        state.sniffer_state.storm.set_config(profile.storm_limits),
+    );
+    state.profiles.set_active(&profile.name)?;
+
+    if state.listener_state.bind_addrs() != bind_addresses {
+        restart_listeners(&state).await;
+    }
+    Ok(skipped)
+}
+
+/// Delete a saved profile, the current settings are left as they are
+#[tauri::command]
+async fn delete_profile(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    state.profiles.delete(&name)
+}
+
+// ============================================================================
+// Frontend Event Commands
+// ============================================================================
+
+/// Get the frontend event settings
+#[tauri::command]
+async fn get_event_config(state: State<'_, AppState>) -> Result<EventConfig, String> {
+    Ok(state.event_settings.get_config())
+}
+
+/// Set how often coalesced DMX updates are sent to the frontend, in Hz
+#[tauri::command]
+async fn set_dmx_event_rate(state: State<'_, AppState>, hz: f32) -> Result<(), String> {
+    state.event_settings.set_dmx_flush_hz(hz)
+}
+
+/// Set whether DMX frames identical to the universe's previous frame are left
+/// out of the frontend events. Source statistics still count every frame.
+#[tauri::command]
+async fn set_suppress_identical_frames(
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    state.event_settings.set_suppress_identical_frames(enabled)
+}
+
+/// Get the monitor's own CPU and memory usage and pipeline throughput. Rates
+/// cover the time since the previous call.
+#[tauri::command]
+async fn get_app_metrics(state: State<'_, AppState>) -> Result<AppMetricsReport, String> {
+    Ok(state.metrics.report(&state.listener_state, &state.event_tx))
+}
+
+/// Get the event bus configuration and the events lost by lagging consumers
+#[tauri::command]
+async fn get_event_bus_stats(state: State<'_, AppState>) -> Result<EventBusStats, String> {
+    Ok(state.event_tx.get_stats())
+}
+
+/// Get the most recent log lines (500 unless given) at a level or more severe
+#[tauri::command]
+async fn get_recent_logs(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+    level: Option<LogLevel>,
+) -> Result<Vec<LogLine>, String> {
+    Ok(state
+        .logger
+        .get_recent(limit.unwrap_or(500), level.unwrap_or(LogLevel::Trace)))
+}
+
+/// Set the event channel capacity and backpressure policy. Takes effect
+/// immediately; events still queued on the old channels are dropped.
+#[tauri::command]
+async fn set_event_bus_config(
+    state: State<'_, AppState>,
+    config: EventBusConfig,
+) -> Result<(), String> {
+    state.event_tx.set_config(config)
+}
+
+/// Send this window the dmx-N frames of a universe
+#[tauri::command]
+async fn subscribe_universe(
+    window: tauri::WebviewWindow,
+    state: State<'_, AppState>,
+    universe: u16,
+) -> Result<(), String> {
+    state.dmx_subscriptions.subscribe(universe, window.label());
+    Ok(())
+}
+
+/// Stop sending this window the dmx-N frames of a universe
+#[tauri::command]
+async fn unsubscribe_universe(
+    window: tauri::WebviewWindow,
+    state: State<'_, AppState>,
+    universe: u16,
+) -> Result<(), String> {
+    state
+        .dmx_subscriptions
+        .unsubscribe(universe, window.label());
+    Ok(())
+}
+
+/// Get the universes this window is subscribed to
+#[tauri::command]
+async fn get_subscribed_universes(
+    window: tauri::WebviewWindow,
+    state: State<'_, AppState>,
+) -> Result<Vec<u16>, String> {
+    Ok(state.dmx_subscriptions.get_universes(window.label()))
+}
+
+/// Open a window showing one universe, or focus it if it is already open. The
+/// window is subscribed to the universe's dmx-N frames and loads the frontend
+/// with ?universe=N. Returns the window label.
+#[tauri::command]
+async fn open_universe_window(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    universe: u16,
+) -> Result<String, String> {
+    show_universe_window(&app, &state, universe)
+}
+
+pub(crate) fn show_universe_window(
+    app: &AppHandle,
+    state: &AppState,
+    universe: u16,
+) -> Result<String, String> {
+    let label = universe_window_label(universe);
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.unminimize();
+        window.show().map_err(|e| e.to_string())?;
+        window.set_focus().map_err(|e| e.to_string())?;
+        return Ok(label);
+    }
+
+    let title = match state.universe_labels.get(universe) {
+        Some(name) => format!("Universe {} ({}) - LXMonitor", universe, name),
+        None => format!("Universe {} - LXMonitor", universe),
+    };
+    let url = WebviewUrl::App(format!("index.html?universe={}", universe).into());
+    // Subscribe first so the window doesn't miss the frames sent while it loads
+    state.dmx_subscriptions.subscribe(universe, &label);
+    if let Err(e) = WebviewWindowBuilder::new(app, label.as_str(), url)
+        .title(title)
+        .inner_size(720.0, 640.0)
+        .min_inner_size(400.0, 300.0)
+        .build()
+    {
+        state.dmx_subscriptions.remove_window(&label);
+        return Err(format!("Failed to open universe window: {}", e));
+    }
+    Ok(label)
+}
+
+// ============================================================================
+// Output Commands
+// ============================================================================
+
+/// Start transmitting a test pattern over Art-Net or sACN, returning the new output's id
+#[tauri::command]
+async fn start_output(state: State<'_, AppState>, config: OutputConfig) -> Result<u32, String> {
+    let output = state.transmit_manager.add_output(config)?;
+    let id = output.id;
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = run_output(output.clone()).await {
+            error!("[Transmit] Output {} error: {}", output.id, e);
+            output.record_error(e.to_string());
+            *output.running.lock() = false;
+        }
+    });
+
+    Ok(id)
+}
+
+/// Stop a running output
+#[tauri::command]
+async fn stop_output(state: State<'_, AppState>, id: u32) -> Result<(), String> {
+    state.transmit_manager.stop_output(id)
+}
+
+/// Stop all running outputs
+#[tauri::command]
+async fn stop_all_outputs(state: State<'_, AppState>) -> Result<(), String> {
+    state.transmit_manager.stop_all();
+    Ok(())
+}
+
+/// Get all outputs and their statistics
+#[tauri::command]
+async fn get_outputs(state: State<'_, AppState>) -> Result<Vec<OutputStatus>, String> {
+    Ok(state.transmit_manager.get_outputs())
+}
+
+/// Start a traffic stress test
+#[tauri::command]
+async fn start_stress_test(
+    state: State<'_, AppState>,
+    config: StressTestConfig,
+) -> Result<(), String> {
+    config.validate()?;
+
+    let stress = state.stress_test.clone();
+    {
+        // Checked and set under one lock so two starts can't both pass
+        let mut running = stress.running.lock();
+        if *running {
+            return Err("A stress test is already running".to_string());
+        }
+        *running = true;
+    }
+
+    // Reset state and start the generator on its own thread
+    *stress.stop_flag.lock() = false;
+    *stress.config.lock() = Some(config.clone());
+    *stress.packets_sent.lock() = 0;
+    *stress.errors.lock() = 0;
+    *stress.last_error.lock() = None;
+    *stress.achieved_pps.lock() = 0.0;
+    *stress.elapsed_ms.lock() = 0;
+
+    std::thread::spawn(move || {
+        run_stress_test_blocking(config, stress);
+    });
+
+    Ok(())
+}
+
+/// Stop the running stress test
+#[tauri::command]
+async fn stop_stress_test(state: State<'_, AppState>) -> Result<(), String> {
+    *state.stress_test.stop_flag.lock() = true;
+    Ok(())
+}
+
+/// Get stress test progress, achieved rate and errors
+#[tauri::command]
+async fn get_stress_test_status(state: State<'_, AppState>) -> Result<StressTestStatus, String> {
+    Ok(state.stress_test.get_status())
+}
+
+// ============================================================================
+// Channel Park Commands
+// ============================================================================
+
+/// Park a channel at a fixed value (transmitted over sACN on top of live data)
+#[tauri::command]
+async fn park_channel(
+    state: State<'_, AppState>,
+    universe: u16,
+    channel: u16,
+    value: u8,
+) -> Result<(), String> {
+    state.park_manager.park(universe, channel, value)
+}
+
+/// Release a parked channel
+#[tauri::command]
+async fn unpark_channel(
+    state: State<'_, AppState>,
+    universe: u16,
+    channel: u16,
+) -> Result<(), String> {
+    state.park_manager.unpark(universe, channel);
+    Ok(())
+}
+
+/// Release all parked channels
+#[tauri::command]
+async fn unpark_all(state: State<'_, AppState>) -> Result<(), String> {
+    state.park_manager.unpark_all();
+    Ok(())
+}
+
+/// Get all parked channels
+#[tauri::command]
+async fn get_parked_channels(state: State<'_, AppState>) -> Result<Vec<ParkedChannel>, String> {
+    Ok(state.park_manager.get_parked())
+}
+
+// ============================================================================
+// Routing Commands
+// ============================================================================
+
+/// Add a route retransmitting/remapping received universes, returning its id
+#[tauri::command]
+async fn add_route(state: State<'_, AppState>, config: RouteConfig) -> Result<u32, String> {
+    state.routing_manager.add_route(config)
+}
+
+/// Replace the configuration of an existing route
+#[tauri::command]
+async fn update_route(
+    state: State<'_, AppState>,
+    id: u32,
+    config: RouteConfig,
+) -> Result<(), String> {
+    state.routing_manager.update_route(id, config)
+}
+
+/// Remove a route
+#[tauri::command]
+async fn remove_route(state: State<'_, AppState>, id: u32) -> Result<(), String> {
+    state.routing_manager.remove_route(id)
+}
+
+/// Get all routes with their statistics
+#[tauri::command]
+async fn get_routes(state: State<'_, AppState>) -> Result<Vec<Route>, String> {
+    Ok(state.routing_manager.get_routes())
+}
+
+/// Reset the statistics of all routes
+#[tauri::command]
+async fn reset_route_stats(state: State<'_, AppState>) -> Result<(), String> {
+    state.routing_manager.reset_stats();
+    Ok(())
+}
+
+// ============================================================================
+// Node Emulator Commands
+// ============================================================================
+
+/// Start answering ArtPoll as an emulated node
+#[tauri::command]
+async fn start_node_emulator(
+    state: State<'_, AppState>,
+    config: NodeEmulatorConfig,
+) -> Result<(), String> {
+    state
+        .node_emulator
+        .start(config, state.rdm_responder.reply_count())
+}
+
+/// Stop the emulated node
+#[tauri::command]
+async fn stop_node_emulator(state: State<'_, AppState>) -> Result<(), String> {
+    state.node_emulator.stop();
+    Ok(())
+}
+
+/// Get the emulated node's configuration and the traffic sent to it
+#[tauri::command]
+async fn get_node_emulator_status(
+    state: State<'_, AppState>,
+) -> Result<NodeEmulatorStatus, String> {
+    Ok(state.node_emulator.get_status())
+}
+
+// ============================================================================
+// RDM Device Commands
+// ============================================================================
+
+/// Get the RDM devices an Art-Net node lists in its ToD, by universe and UID
+#[tauri::command]
+async fn get_rdm_devices(
+    state: State<'_, AppState>,
+    source_id: String,
+) -> Result<Vec<RdmDevice>, String> {
+    state.source_manager.get_source_ip(&source_id)?;
+    Ok(state.listener_state.rdm_devices.get_devices(&source_id))
+}
+
+/// Read device info, software version, DMX start address and label of an RDM
+/// device behind an Art-Net node with RDM GETs
+#[tauri::command]
+async fn read_rdm_device(
+    state: State<'_, AppState>,
+    source_id: String,
+    uid: String,
+) -> Result<RdmDevice, String> {
+    require_artnet_listener(&state)?;
+    state
+        .listener_state
+        .rdm_devices
+        .read_device(&source_id, &uid)
+        .await
+}
+
+/// Ask an Art-Net node for the ToD of its universes and read every RDM device
+/// it lists, to audit fixture addresses
+#[tauri::command]
+async fn audit_rdm_devices(
+    state: State<'_, AppState>,
+    source_id: String,
+) -> Result<Vec<RdmDevice>, String> {
+    require_artnet_listener(&state)?;
+    let ip = state.source_manager.get_source_ip(&source_id)?;
+    let universes = state
+        .source_manager
+        .get_all_sources(true)
+        .into_iter()
+        .find(|s| s.id == source_id)
+        .map(|s| s.universes)
+        .unwrap_or_default();
+    state
+        .listener_state
+        .rdm_devices
+        .audit_node(ip, &source_id, &universes)
+        .await
+}
+
+/// Collect the status messages (lamp failure, overtemperature) and queued
+/// messages of an RDM device
+#[tauri::command]
+async fn collect_rdm_status(
+    state: State<'_, AppState>,
+    source_id: String,
+    uid: String,
+) -> Result<RdmDevice, String> {
+    require_artnet_listener(&state)?;
+    state
+        .listener_state
+        .rdm_devices
+        .collect_status(&source_id, &uid)
+        .await
+}
+
+/// Switch identify mode of an RDM device on or off, confirmed with a GET
+#[tauri::command]
+async fn identify_rdm_device(
+    state: State<'_, AppState>,
+    source_id: String,
+    uid: String,
+    identify: bool,
+) -> Result<RdmDevice, String> {
+    require_artnet_listener(&state)?;
+    state
+        .listener_state
+        .rdm_devices
+        .set_identify(&source_id, &uid, identify)
+        .await
+}
+
+/// Set the DMX start address of an RDM device, confirmed with a GET
+#[tauri::command]
+async fn set_rdm_dmx_address(
+    state: State<'_, AppState>,
+    source_id: String,
+    uid: String,
+    address: u16,
+) -> Result<RdmDevice, String> {
+    require_artnet_listener(&state)?;
+    state
+        .listener_state
+        .rdm_devices
+        .set_dmx_address(&source_id, &uid, address)
+        .await
+}
+
+/// Set the personality of an RDM device, confirmed with a GET
+#[tauri::command]
+async fn set_rdm_personality(
+    state: State<'_, AppState>,
+    source_id: String,
+    uid: String,
+    personality: u8,
+) -> Result<RdmDevice, String> {
+    require_artnet_listener(&state)?;
+    state
+        .listener_state
+        .rdm_devices
+        .set_personality(&source_id, &uid, personality)
+        .await
+}
+
+/// RDM responses arrive through the Art-Net listener
+fn require_artnet_listener(state: &AppState) -> Result<(), String> {
+    if state.listener_state.is_running(Protocol::ArtNet) {
+        Ok(())
+    } else {
+        Err("The Art-Net listener isn't running, RDM responses can't be received".to_string())
+    }
+}
+
+// ============================================================================
+// RDM Responder Commands
+// ============================================================================
+
+/// Add a virtual RDM device answering ArtTodRequest/ArtRdm on a universe
+#[tauri::command]
+async fn add_rdm_device(
+    state: State<'_, AppState>,
+    config: RdmDeviceConfig,
+) -> Result<VirtualRdmDevice, String> {
+    state
+        .rdm_responder
+        .add_device(config, state.node_emulator.reply_count())
+}
+
+/// Remove a virtual RDM device
+#[tauri::command]
+async fn remove_rdm_device(state: State<'_, AppState>, uid: String) -> Result<(), String> {
+    state.rdm_responder.remove_device(&uid)
+}
+
+/// Get all virtual RDM devices and their current state
+#[tauri::command]
+async fn get_virtual_rdm_devices(
+    state: State<'_, AppState>,
+) -> Result<Vec<VirtualRdmDevice>, String> {
+    Ok(state.rdm_responder.get_devices())
+}
+
+// ============================================================================
+// Network Discovery Commands
+// ============================================================================
+
+/// Send an ArtPoll packet to discover Art-Net devices
+#[tauri::command]
+async fn send_artnet_poll(state: State<'_, AppState>) -> Result<(), String> {
+    send_artpoll(&state.listener_state)
+}
+
+/// ArtPoll reply delay per Art-Net node, slowest first, with nodes that reply
+/// slowly or inconsistently flagged
+#[tauri::command]
+async fn get_artpoll_latency(state: State<'_, AppState>) -> Result<Vec<NodePollLatency>, String> {
+    Ok(state.listener_state.poll_latency.get_latencies())
+}
+
+// ============================================================================
+// Timecode Commands
+// ============================================================================
+
+/// Timecode feeds with the offset and drift of each pair and the limits
+#[tauri::command]
+async fn get_timecode_status(state: State<'_, AppState>) -> Result<TimecodeStatus, String> {
+    Ok(state.listener_state.timecode.get_status())
+}
+
+/// Set the offset and drift above which a timecode-drift alert is sent
+#[tauri::command]
+async fn set_timecode_limits(
+    state: State<'_, AppState>,
+    config: TimecodeConfig,
+) -> Result<(), String> {
+    state.listener_state.timecode.set_config(config)
+}
+
+// ============================================================================
+// Remote API Commands
+// ============================================================================
+
+/// Get the remote API settings and the address it is serving on
+#[tauri::command]
+async fn get_api_status(state: State<'_, AppState>) -> Result<ApiStatus, String> {
+    Ok(state.api_server.get_status())
+}
+
+/// Save the remote API settings and start, restart or stop the server to match
+#[tauri::command]
+async fn set_api_config(
+    state: State<'_, AppState>,
+    config: ApiConfig,
+) -> Result<ApiStatus, String> {
+    state
+        .api_server
+        .set_config(state.api_context(), config)
+        .await?;
+    Ok(state.api_server.get_status())
+}
+
+// ============================================================================
+// MQTT Commands
+// ============================================================================
+
+/// Get the MQTT publisher settings, connection state and message counts
+#[tauri::command]
+async fn get_mqtt_status(state: State<'_, AppState>) -> Result<MqttStatus, String> {
+    Ok(state.mqtt.get_status())
+}
+
+/// Save the MQTT settings and connect, reconnect or disconnect to match
+#[tauri::command]
+async fn set_mqtt_config(
+    state: State<'_, AppState>,
+    config: MqttConfig,
+) -> Result<MqttStatus, String> {
+    state.mqtt.set_config(state.mqtt_context(), config)?;
+    Ok(state.mqtt.get_status())
+}
+
+// ============================================================================
+// Scripting Commands
+// ============================================================================
+
+/// Get the scripting settings and the loaded scripts with their errors
+#[tauri::command]
+async fn get_scripting_status(state: State<'_, AppState>) -> Result<ScriptingStatus, String> {
+    Ok(state.scripting.get_status())
+}
+
+/// Save the scripting settings and start, restart or stop scripts to match
+#[tauri::command]
+async fn set_scripting_config(
+    state: State<'_, AppState>,
+    config: ScriptingConfig,
+) -> Result<ScriptingStatus, String> {
+    state
+        .scripting
+        .set_config(state.scripting_context(), config)?;
+    Ok(state.scripting.get_status())
+}
+
+/// Load the scripts again, picking up edited, added and removed files
+#[tauri::command]
+async fn reload_scripts(state: State<'_, AppState>) -> Result<ScriptingStatus, String> {
+    state.scripting.reload(state.scripting_context())?;
+    Ok(state.scripting.get_status())
+}
+
+// ============================================================================
+// Notification Commands
+// ============================================================================
+
+/// Get the desktop notification settings
+#[tauri::command]
+async fn get_notification_config(state: State<'_, AppState>) -> Result<NotificationConfig, String> {
+    Ok(state.notifications.get_config())
+}
+
+/// Set which critical alerts raise a desktop notification
+#[tauri::command]
+async fn set_notification_config(
+    state: State<'_, AppState>,
+    config: NotificationConfig,
+) -> Result<(), String> {
+    state.notifications.set_config(config)
+}
+
+/// Bring the main window to the front
+pub(crate) fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Show an OS notification, which is seen even with the window minimized
+fn show_notification(app_handle: &AppHandle, notification: Notification) {
+    let shown = app_handle
+        .notification()
+        .builder()
+        .title(notification.title)
+        .body(notification.body)
+        .show();
+    if let Err(e) = shown {
+        warn!("[Notifications] Failed to show notification: {}", e);
+    }
+}
+
+
+
+/// Start the network event forwarder to send events to the frontend. DMX updates
+/// are coalesced per universe and flushed at the configured rate, frames only to
+/// the windows subscribed to the universe. The network health score is sent
+/// when it changes. Critical alerts and the network going silent also raise
+/// desktop notifications.
+#[allow(clippy::too_many_arguments)]
+fn start_event_forwarder(
+    app_handle: AppHandle,
+    mut event_rx: EventReceiver,
+    source_manager: SourceManagerHandle,
+    universe_labels: UniverseLabelsHandle,
+    event_settings: EventSettingsHandle,
+    dmx_subscriptions: DmxSubscriptionsHandle,
+    metrics: AppMetricsHandle,
+    notifications: NotificationsHandle,
+) {
+
+    tauri::async_runtime::spawn(async move {
+        let mut dmx = DmxCoalescer::new();
+        let mut silence = SilenceDetector::new();
+        let mut health: Option<NetworkHealth> = None;
+        let mut flush_interval = event_settings.dmx_flush_interval();
+        let mut flush = tokio::time::interval(flush_interval);
+        flush.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                result = event_rx.recv() => match result {
+                    Ok(ListenerEvent::SourcesUpdated) => {
+                        let sources = source_manager.get_all_sources(false);
+                        let assessed = assess_network_health(&sources);
+                        let _ = app_handle.emit("sources-updated", sources);
+                        metrics.record_events(1);
+                        if health.as_ref() != Some(&assessed) {
+                            let _ = app_handle.emit("network-health", &assessed);
+                            metrics.record_events(1);
+                            health = Some(assessed);
+                        }
+                    }
+                    Ok(ListenerEvent::ExpectedDeviceAlert(alert)) => {
+                        if let Some(notification) = notifications.for_expected_device(&alert) {
+                            show_notification(&app_handle, notification);
+                        }
+                        let _ = app_handle.emit("expected-device-alert", alert);
+                        metrics.record_events(1);
+                    }
+                    Ok(ListenerEvent::StormAlert(alert)) => {
+                        let _ = app_handle.emit("broadcast-storm-alert", alert);
+                        metrics.record_events(1);
+                    }
+                    Ok(ListenerEvent::UniverseAnomaly(alert)) => {
+                        let _ = app_handle.emit("universe-anomaly", alert);
+                        metrics.record_events(1);
+                    }
+                    Ok(ListenerEvent::PriorityChanged(change)) => {
+                        let _ = app_handle.emit("priority-changed", change);
+                        metrics.record_events(1);
+                    }
+                    Ok(ListenerEvent::TodChanged(change)) => {
+                        let _ = app_handle.emit("rdm-tod-changed", change);
+                        metrics.record_events(1);
+                    }
+                    Ok(ListenerEvent::RdmStatus(status)) => {
+                        let _ = app_handle.emit("rdm-status-message", status);
+                        metrics.record_events(1);
+                    }
+                    Ok(ListenerEvent::TimecodeDrift(alert)) => {
+                        let _ = app_handle.emit("timecode-drift", alert);
+                        metrics.record_events(1);
+                    }
+                    Ok(ListenerEvent::Sniffer(event)) => {
+                        let _ = app_handle.emit("sniffer-event", event);
+                        metrics.record_events(1);
+                    }
+                    Ok(ListenerEvent::DmxData(data)) => {
+                        if silence.dmx_received(Instant::now()) {
+                            info!("[Events] DMX received again");
+                            if let Some(notification) =
+                                notifications.for_silence(false, notifications.silent_after())
+                            {
+                                show_notification(&app_handle, notification);
+                            }
+                        }
+                        if data.changed || !event_settings.suppress_identical_frames() {
+                            dmx.push(data);
+                        } else {
+                            dmx.count(data);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("[Events] Event forwarder lagged {} messages", n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => {
+                        break;
+                    }
+                },
+                _ = flush.tick() => {
+                    let silent_after = notifications.silent_after();
+                    if silence.check(Instant::now(), silent_after) {
+                        warn!("[Events] No DMX received for {} seconds", silent_after.as_secs());
+                        if let Some(notification) = notifications.for_silence(true, silent_after) {
+                            show_notification(&app_handle, notification);
+                        }
+                    }
+
+                    let updates = dmx.take();
+                    if !updates.is_empty() {
+                        // Newest data per universe, then one event describing them all
+                        let mut batch = Vec::with_capacity(updates.len());
+                        for (mut update, data) in updates {
+                            if let Some(data) = data {
+                                let event = format!("dmx-{}", update.universe);
+                                for window in dmx_subscriptions.subscribers(update.universe) {
+                                    let _ = app_handle.emit_to(window, &event, &data);
+                                    metrics.record_events(1);
+                                }
+                            }
+                            update.label = universe_labels.get(update.universe);
+                            batch.push(update);
+                        }
+                        // Universe windows only get the frames of their own universe
+                        for (window, _) in app_handle.webview_windows() {
+                            if !is_universe_window(&window) {
+                                let _ = app_handle.emit_to(window, "dmx-batch", &batch);
+                                metrics.record_events(1);
+                            }
+                        }
+                    }
+
+                    // Pick up a changed rate
+                    let interval = event_settings.dmx_flush_interval();
+                    if interval != flush_interval {
+                        flush_interval = interval;
+                        flush = tokio::time::interval(interval);
+                        flush.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    // Log to the console until setup adds the log file
+    let logger = Logger::init();
+
+    // Create shared state
+    let source_manager = create_source_manager();
+    let dmx_store = Arc::new(DmxStore::new());
+    // Listener event bus, its configuration is loaded in setup
+    let event_tx = Arc::new(EventBus::new());
+
+    // Create sniffer state
+    let sniffer_state = Arc::new(SnifferState::new());
+
+    // Universe labels are loaded from disk once the app data dir is known (in setup)
+    let universe_labels = Arc::new(UniverseLabels::new());
+
+    // Venue profiles, loaded in setup
+    let profiles = Arc::new(ProfileStore::new());
+
+    // Frontend event settings, also loaded in setup
+    let event_settings = Arc::new(EventSettings::new());
+    let dmx_subscriptions = Arc::new(DmxSubscriptions::new());
+
+    // Self-monitoring counters, fed by the event forwarder
+    let metrics = Arc::new(AppMetrics::new());
+
+    // Create transmit manager for test outputs and park manager for held channels
+    let transmit_manager = Arc::new(TransmitManager::new());
+    let park_manager = Arc::new(ParkManager::new());
+
+    // Create routing manager for protocol bridging
+    let routing_manager = Arc::new(RoutingManager::new());
+
+    // Create stress test state
+    let stress_test = Arc::new(StressTestState::new());
+
+    // Create node emulator (silent until started)
+    let node_emulator = Arc::new(NodeEmulator::new());
+
+    // Create RDM responder emulator (silent until a virtual device is added)
+    let rdm_responder = Arc::new(RdmResponder::new());
+
+    // Listener configuration is loaded from disk in setup, before listeners start
+    let listener_state = Arc::new(ListenerState::new());
+    listener_state.ignore_sacn_cid(park_manager.cid());
+
+    // Remote API server, started in setup when enabled
+    let api_server = Arc::new(ApiServer::new());
+
+    // MQTT publisher, connected in setup when enabled
+    let mqtt = Arc::new(MqttPublisher::new());
+
+    // Desktop notification settings, loaded in setup
+    let notifications = Arc::new(Notifications::new());
+
+    // Alerts kept for health reports
+    let alert_log = Arc::new(AlertLog::new());
+
+    // Frame timing of redundant senders
+    let frame_phase = Arc::new(FramePhaseAnalyzer::new());
+
+    // Art-Net and sACN universes compared for numbering mismatches
+    let numbering = Arc::new(NumberingCheck::new());
+
+    // Scheduled snapshots, configured in setup
+    let snapshots = Arc::new(SnapshotScheduler::new());
+
+    // User scripts, started in setup when enabled
+    let scripting = Arc::new(ScriptHost::new());
+
+    // Session state autosave, restored in setup after a crash
+    let autosave = Arc::new(Autosave::new());
+
+    let app_state = AppState {
+        source_manager: source_manager.clone(),
+        dmx_store: dmx_store.clone(),
+        event_tx: event_tx.clone(),
+        is_listening: Mutex::new(true),
+        listener_state: listener_state.clone(),
+        sniffer_state: sniffer_state.clone(),
+        universe_labels: universe_labels.clone(),
+        profiles: profiles.clone(),
+        event_settings: event_settings.clone(),
+        dmx_subscriptions: dmx_subscriptions.clone(),
+        metrics: metrics.clone(),
+        transmit_manager,
+        park_manager: park_manager.clone(),
+        routing_manager: routing_manager.clone(),
+        stress_test,
+        node_emulator: node_emulator.clone(),
+        rdm_responder: rdm_responder.clone(),
+        api_server: api_server.clone(),
+        mqtt: mqtt.clone(),
+        logger: logger.clone(),
+        notifications: notifications.clone(),
+        alert_log: alert_log.clone(),
+        frame_phase: frame_phase.clone(),
+        numbering: numbering.clone(),
+        snapshots: snapshots.clone(),
+        scripting: scripting.clone(),
+        autosave: autosave.clone(),
+    };
+
+    // Closed windows are dropped from the DMX subscriptions
+    let window_subscriptions = dmx_subscriptions.clone();
+
+    tauri::Builder::default()
+        // Registered first: a second instance hands its arguments, including
+        // any lxmonitor:// link, to this one and exits
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            let scheme = format!("{}://", DEEP_LINK_SCHEME);
+            if !argv.iter().any(|arg| arg.starts_with(&scheme)) {
+                show_main_window(app);
+            }
+        }))
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .manage(app_state)
+        .invoke_handler(tauri::generate_handler![
+            get_sources,
+            get_devices,
+            get_source_universe_stats,
+            get_source_events,
+            get_network_health,
+            get_universe_conflicts,
+            get_numbering_mismatches,
+            ping_source,
+            trace_source,
+            get_frame_phase,
+            get_sequence_gaps,
+            clear_sequence_gaps,
+            set_source_alias,
+            set_source_note,
+            set_source_pinned,
+            set_source_hidden,
+            remove_source,
+            clear_sources,
+            get_source_thresholds,
+            set_source_thresholds,
+            get_expected_devices,
+            add_expected_device,
+            remove_expected_device,
+            import_expected_devices,
+            export_sources,
+            generate_report,
+            get_snapshot_status,
+            set_snapshot_config,
+            take_snapshot,
+            get_autosave_status,
+            confirm_pending_restore,
+            discard_pending_restore,
+            compare_session,
+            get_protocol_bandwidth,
+            get_topology,
+            get_dmx_data,
+            get_all_dmx_data,
+            get_frame_lengths,
+            get_alternate_frames,
+            get_channel_ages,
+            get_network_interfaces,
+            get_listener_status,
+            set_bind_interfaces,
+            get_sacn_universe_ranges,
+            add_sacn_universe_range,
+            remove_sacn_universe_range,
+            set_sacn_source_specific,
+            set_receive_buffer_size,
+            set_listener_enabled,
+            set_auto_poll_enabled,
+            // Sniffer commands
+            get_capture_support,
+            get_capture_interfaces,
+            get_sniffer_status,
+            set_sniffer_mode,
+            get_captured_packets,
+            clear_captured_packets,
+            get_capture_bandwidth,
+            get_capture_traffic_summary,
+            get_arp_table,
+            arp_scan,
+            get_switch_neighbors,
+            get_broadcast_storm_status,
+            set_broadcast_storm_limits,
+            // Universe label commands
+            get_universe_labels,
+            set_universe_label,
+            // Venue profile commands
+            get_profiles,
+            save_profile,
+            load_profile,
+            delete_profile,
+            // Frontend event commands
+            get_event_config,
+            set_dmx_event_rate,
+            set_suppress_identical_frames,
+            get_app_metrics,
+            get_event_bus_stats,
+            get_recent_logs,
+            set_event_bus_config,
+            subscribe_universe,
+            unsubscribe_universe,
+            get_subscribed_universes,
+            open_universe_window,
+            // Output commands
+            start_output,
+            stop_output,
+            stop_all_outputs,
+            get_outputs,
+            start_stress_test,
+            stop_stress_test,
+            get_stress_test_status,
+            // Channel park commands
+            park_channel,
+            unpark_channel,
+            unpark_all,
+            get_parked_channels,
+            // Routing commands
+            add_route,
+            update_route,
+            remove_route,
+            get_routes,
+            reset_route_stats,
+            // Node emulator commands
+            start_node_emulator,
+            stop_node_emulator,
+            get_node_emulator_status,
+            // RDM device commands
+            get_rdm_devices,
+            read_rdm_device,
+            audit_rdm_devices,
+            collect_rdm_status,
+            identify_rdm_device,
+            set_rdm_dmx_address,
+            set_rdm_personality,
+            // RDM responder commands
+            add_rdm_device,
+            remove_rdm_device,
+            get_virtual_rdm_devices,
+            // Discovery commands
+            send_artnet_poll,
+            get_artpoll_latency,
+            // Timecode commands
+            get_timecode_status,
+            set_timecode_limits,
+            // Remote API commands
+            get_api_status,
+            set_api_config,
+            // MQTT commands
+            get_mqtt_status,
+            set_mqtt_config,
+            // Scripting commands
+            get_scripting_status,
+            set_scripting_config,
+            reload_scripts,
+            // Notification commands
+            get_notification_config,
+            set_notification_config,
+        ])
+        .on_window_event(move |window, event| {
+            if let WindowEvent::Destroyed = event {
+                window_subscriptions.remove_window(window.label());
+            }
+        })
+        .setup(move |app| {
+            let app_handle = app.handle().clone();
+
+            let autosave_context = AutosaveContext {
+                source_manager: source_manager.clone(),
+                alert_log: alert_log.clone(),
+                routing_manager: routing_manager.clone(),
+                park_manager: park_manager.clone(),
+            };
+
+            // Load persisted data from the app data directory
+            match app.path().app_data_dir() {
+                Ok(data_dir) => {
+                    logger.set_log_dir(data_dir.join("logs"));
+                    event_tx.load_config(data_dir.join("event_bus.json"));
+                    universe_labels.load(data_dir.join("universe_labels.json"));
+                    profiles.load(data_dir.join("profiles.json"));
+                    event_settings.load(data_dir.join("event_config.json"));
+                    listener_state.load_config(data_dir.join("listener_config.json"));
+                    listener_state
+                        .timecode
+                        .load_config(data_dir.join("timecode_limits.json"));
+                    source_manager.load_thresholds(data_dir.join("source_thresholds.json"));
+                    source_manager.load_oui_file(&data_dir.join("oui.txt"));
+                    source_manager.load_known_sources(data_dir.join("known_sources.json"));
+                    source_manager.load_expected_devices(data_dir.join("expected_devices.json"));
+                    sniffer_state
+                        .storm
+                        .load_config(data_dir.join("storm_limits.json"));
+                    api_server.load_config(data_dir.join("api_server.json"));
+                    mqtt.load_config(data_dir.join("mqtt.json"));
+                    notifications.load(data_dir.join("notifications.json"));
+                    snapshots
+                        .load_config(data_dir.join("snapshots.json"), data_dir.join("snapshots"));
+                    scripting
+                        .load_config(data_dir.join("scripting.json"), data_dir.join("scripts"));
+                    // After the device database, restored sources merge into it
+                    autosave.load(data_dir.join("autosave.json"), &autosave_context);
+                }
+                Err(e) => error!("[Persistence] Failed to resolve app data directory: {}", e),
+            }
+
+            // Start event forwarder
+            start_event_forwarder(
+                app_handle,
+                event_tx.subscribe(),
+                source_manager.clone(),
+                universe_labels.clone(),
+                event_settings.clone(),
+                dmx_subscriptions.clone(),
+                metrics.clone(),
+                notifications.clone(),
+            );
+
+            // Open lxmonitor:// links, the one the app was launched with and
+            // those arriving while it runs
+            #[cfg(any(windows, target_os = "linux"))]
+            if let Err(e) = app.deep_link().register_all() {
+                warn!(
+                    "[Deep link] Failed to register the {} scheme: {}",
+                    DEEP_LINK_SCHEME, e
+                );
+            }
+            let link_handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    open_deep_link(&link_handle, url.as_str());
+                }
+            });
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                for url in urls {
+                    open_deep_link(app.handle(), url.as_str());
+                }
+            }
+
+            // Tray icon showing network health
+            if let Err(e) = crate::tray::create_tray(app.handle(), event_tx.subscribe()) {
+                warn!("[Tray] Failed to create tray icon: {}", e);
+            }
+
+            // Start park output (idle until a channel is parked)
+            let parks = park_manager.clone();
+            let ds = dmx_store.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = start_park_output(parks, ds).await {
+                    error!("[Park] Output error: {}", e);
+                }
+            });
+
+            // Start broadcast storm monitor (quiet until the sniffer captures)
+            let ss = sniffer_state.clone();
+            let tx = event_tx.clone();
+            tauri::async_runtime::spawn(start_storm_monitor(ss, tx));
+
+            // Keep alerts for health reports
+            tauri::async_runtime::spawn(run_alert_log(alert_log.clone(), event_tx.subscribe()));
+
+            // Watch universes for blackouts and stops while their sender is alive
+            tauri::async_runtime::spawn(run_anomaly_detector(
+                source_manager.clone(),
+                event_tx.clone(),
+                event_tx.subscribe(),
+            ));
+
+            // Time the frames of universes with more than one sender
+            tauri::async_runtime::spawn(run_frame_phase(frame_phase.clone(), event_tx.subscribe()));
+
+            // Compare Art-Net and sACN universes for numbering mismatches
+            tauri::async_runtime::spawn(run_numbering_check(
+                numbering.clone(),
+                event_tx.subscribe(),
+            ));
+
+            // Take scheduled snapshots (idle until enabled)
+            tauri::async_runtime::spawn(run_snapshots(
+                snapshots.clone(),
+                source_manager.clone(),
+                universe_labels.clone(),
+            ));
+
+            // Autosave the session state for crash recovery
+            tauri::async_runtime::spawn(run_autosave(autosave.clone(), autosave_context));
+
+            // Start router (idle until a route is added)
+            let routing = routing_manager.clone();
+            let ls = listener_state.clone();
+            let router_rx = event_tx.subscribe();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = start_router(routing, ls, router_rx).await {
+                    error!("[Routing] Router error: {}", e);
+                }
+            });
+
+            // Start network listeners, the remote API, MQTT and scripts, from a
+            // task so they start on the runtime
+            let api_context = ApiContext {
+                source_manager: source_manager.clone(),
+                dmx_store: dmx_store.clone(),
+                listener_state: listener_state.clone(),
+                event_tx: event_tx.clone(),
+                universe_labels: universe_labels.clone(),
+            };
+            let mqtt_context = MqttContext {
+                source_manager: source_manager.clone(),
+                event_tx: event_tx.clone(),
+                universe_labels: universe_labels.clone(),
+            };
+            let scripting_context = ScriptingContext {
+                source_manager: source_manager.clone(),
+                event_tx: event_tx.clone(),
+                universe_labels: universe_labels.clone(),
+            };
+            tauri::async_runtime::spawn(async move {
+                start_listeners(
+                    source_manager,
+                    dmx_store,
+                    event_tx,
+                    node_emulator,
+                    rdm_responder,
+                    listener_state,
+                );
+                if api_server.get_config().enabled {
+                    if let Err(e) = api_server.start(api_context).await {
+                        warn!("[API] {}", e);
+                    }
+                }
+                if mqtt.get_config().enabled {
+                    if let Err(e) = mqtt.start(mqtt_context) {
+                        warn!("[MQTT] {}", e);
+                    }
+                }
+                if scripting.get_config().enabled {
+                    if let Err(e) = scripting.start(scripting_context) {
+                        warn!("[Scripts] {}", e);
+                    }
+                }
+            });
+
+            info!("LXMonitor started - listening for Art-Net and sACN traffic");
+
+            Ok(())
+        })
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                // The device database is otherwise only saved periodically
+                let state = app.state::<AppState>();
+                if let Err(e) = state.source_manager.save_known_sources() {
+                    warn!("[Sources] Failed to save device database: {}", e);
+                }
+                if let Err(e) = state.autosave.save(&state.autosave_context(), true) {
+                    warn!("[Autosave] {}", e);
+                }
+            }
+        });
+}
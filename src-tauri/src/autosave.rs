@@ -0,0 +1,220 @@
+// Autosave - Session state that survives a crash
+//
+// Every 30 seconds the sources with their event history, the alert log, the
+// routes and the parked channels are written to autosave.json, and once more on
+// a clean exit, marked as such. When the app starts and finds an autosave
+// without that mark, the previous run crashed or was killed mid-show, and its
+// state is restored: sources come back with their history (offline until heard
+// again) and alerts rejoin the log. Routes and parked channels put DMX on the
+// wire, so they are held as a pending restore until the user confirms it.
+// Labels and settings are saved whenever they change and need no restoring.
+// Test pattern outputs are deliberately not restarted.
+
+use crate::network::{
+    ParkManagerHandle, ParkedChannel, RouteConfig, RoutingManagerHandle, SourceHistory,
+    SourceManagerHandle,
+};
+use crate::persistence::{load_json, save_json};
+use crate::report::{AlertLogHandle, LoggedAlert};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// How often the session state is written
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Contents of the autosave file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    pub saved_at: u64,    // Unix timestamp ms, 0 when nothing was saved
+    pub clean_exit: bool, // Written on exit, the state needs no restoring
+    pub sources: Vec<SourceHistory>,
+    pub alerts: Vec<LoggedAlert>,
+    pub routes: Vec<RouteConfig>,
+    pub parked: Vec<ParkedChannel>,
+}
+
+/// Routes and parked channels of a crashed run, waiting for the user to restart them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PendingRestore {
+    pub routes: Vec<RouteConfig>,
+    pub parked: Vec<ParkedChannel>,
+}
+
+impl PendingRestore {
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty() && self.parked.is_empty()
+    }
+}
+
+/// Autosave state for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutosaveStatus {
+    pub last_saved: Option<u64>,    // Unix timestamp ms
+    pub restored_from: Option<u64>, // Save time of the state restored at startup
+    pub last_error: Option<String>,
+    /// Needs confirm_pending_restore or discard_pending_restore, None when empty
+    pub pending_restore: Option<PendingRestore>,
+}
+
+/// Handles the session state is read from and restored into
+#[derive(Clone)]
+pub struct AutosaveContext {
+    pub source_manager: SourceManagerHandle,
+    pub alert_log: AlertLogHandle,
+    pub routing_manager: RoutingManagerHandle,
+    pub park_manager: ParkManagerHandle,
+}
+
+pub struct Autosave {
+    path: RwLock<Option<PathBuf>>,
+    last_saved: Mutex<Option<u64>>,
+    restored_from: Mutex<Option<u64>>,
+    last_error: Mutex<Option<String>>,
+    pending: Mutex<PendingRestore>,
+}
+
+impl Autosave {
+    pub fn new() -> Self {
+        Self {
+            path: RwLock::new(None),
+            last_saved: Mutex::new(None),
+            restored_from: Mutex::new(None),
+            last_error: Mutex::new(None),
+            pending: Mutex::new(PendingRestore::default()),
+        }
+    }
+
+    /// Save to this file from now on, first restoring what it holds if the last
+    /// run didn't exit cleanly. Routes and parked channels are only held as a
+    /// pending restore. Call after the device database is loaded.
+    pub fn load(&self, path: PathBuf, context: &AutosaveContext) {
+        let state: SessionState = load_json(&path);
+        *self.path.write() = Some(path);
+        if state.saved_at == 0 || state.clean_exit {
+            return;
+        }
+
+        info!(
+            "[Autosave] Last run did not exit cleanly, restoring {} sources and {} alerts, {} routes and {} parked channels wait for confirmation",
+            state.sources.len(),
+            state.alerts.len(),
+            state.routes.len(),
+            state.parked.len()
+        );
+        context.source_manager.restore_histories(state.sources);
+        context.alert_log.restore(state.alerts);
+        *self.pending.lock() = PendingRestore {
+            routes: state.routes,
+            parked: state.parked,
+        };
+        *self.restored_from.lock() = Some(state.saved_at);
+    }
+
+    /// Restart the routes and parked channels of the crashed run
+    pub fn confirm_pending_restore(&self, context: &AutosaveContext) -> Result<(), String> {
+        let pending = std::mem::take(&mut *self.pending.lock());
+        if pending.is_empty() {
+            return Err("Nothing is waiting to be restored".to_string());
+        }
+        info!(
+            "[Autosave] Restoring {} routes and {} parked channels",
+            pending.routes.len(),
+            pending.parked.len()
+        );
+        for route in pending.routes {
+            if let Err(e) = context.routing_manager.add_route(route) {
+                warn!("[Autosave] Failed to restore route: {}", e);
+            }
+        }
+        for parked in pending.parked {
+            if let Err(e) = context
+                .park_manager
+                .park(parked.universe, parked.channel, parked.value)
+            {
+                warn!("[Autosave] Failed to restore parked channel: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the routes and parked channels of the crashed run
+    pub fn discard_pending_restore(&self) {
+        *self.pending.lock() = PendingRestore::default();
+    }
+
+    /// Write the current session state. `clean_exit` marks it as not needing
+    /// to be restored.
+    pub fn save(&self, context: &AutosaveContext, clean_exit: bool) -> Result<(), String> {
+        let Some(path) = self.path.read().clone() else {
+            return Ok(());
+        };
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let mut sources = context.source_manager.get_histories();
+        sources.sort_by(|a, b| a.source.id.cmp(&b.source.id));
+        let mut routes: Vec<RouteConfig> = context
+            .routing_manager
+            .get_routes()
+            .into_iter()
+            .map(|route| route.config)
+            .collect();
+        let mut parked = context.park_manager.get_parked();
+        // Kept until answered, in case this run crashes too
+        let pending = self.pending.lock().clone();
+        routes.extend(pending.routes);
+        parked.extend(pending.parked);
+        let state = SessionState {
+            saved_at: now,
+            clean_exit,
+            sources,
+            alerts: context.alert_log.since(0),
+            routes,
+            parked,
+        };
+
+        let result = save_json(&path, &state);
+        if result.is_ok() {
+            *self.last_saved.lock() = Some(now);
+        }
+        *self.last_error.lock() = result.as_ref().err().cloned();
+        result
+    }
+
+    pub fn get_status(&self) -> AutosaveStatus {
+        AutosaveStatus {
+            last_saved: *self.last_saved.lock(),
+            restored_from: *self.restored_from.lock(),
+            last_error: self.last_error.lock().clone(),
+            pending_restore: Some(self.pending.lock().clone()).filter(|p| !p.is_empty()),
+        }
+    }
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type AutosaveHandle = Arc<Autosave>;
+
+/// Save the session state periodically. Runs for the life of the app.
+pub async fn run_autosave(autosave: AutosaveHandle, context: AutosaveContext) {
+    let mut interval = tokio::time::interval(AUTOSAVE_INTERVAL);
+    // The first tick fires immediately, the state was just loaded
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        if let Err(e) = autosave.save(&context, false) {
+            warn!("[Autosave] {}", e);
+        }
+    }
+}
@@ -0,0 +1,316 @@
+// LXMonitor CLI - Headless monitoring for machines without a screen
+//
+// Runs the same listeners as the app and prints the source table every few
+// seconds to stdout, logging alerts and diagnostics to stderr and the log
+// directory as they happen. Settings, expected
+// devices and the device database are read from the app's data directory, so
+// a rig set up in the app is monitored the same way on a rack server.
+
+use lxmonitor_lib::api::{ApiConfig, ApiContext, ApiServer};
+use lxmonitor_lib::labels::UniverseLabels;
+use lxmonitor_lib::logging::Logger;
+use lxmonitor_lib::mqtt::{MqttConfig, MqttContext, MqttPublisher, DEFAULT_MQTT_PORT};
+use lxmonitor_lib::network::{
+    create_source_manager, start_listeners, DmxStore, EventBus, ListenerState,
+    NetworkSource, NodeEmulator, RdmResponder, SourceManagerHandle,
+};
+use lxmonitor_lib::persistence::load_json;
+
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+const USAGE: &str = "Usage: lxmonitor-cli [options]
+
+Options:
+  --data-dir <path>   Settings and device database directory (default: the app's)
+  --interval <secs>   Seconds between source tables (default: 5)
+  --json              Print each source table as one line of JSON, for logging
+  --all               Include hidden sources
+  --api <[ip:]port>   Serve the remote API (default address 127.0.0.1)
+  --mqtt <host[:port]> Publish to an MQTT broker (default: the app's MQTT settings)
+  -h, --help          Show this help";
+
+/// App identifier, names the app data directory
+const APP_IDENTIFIER: &str = "com.lxmonitor.app";
+
+struct Options {
+    data_dir: Option<PathBuf>,
+    interval: Duration,
+    json: bool,
+    include_hidden: bool,
+    api: Option<SocketAddrV4>,
+    mqtt: Option<(String, u16)>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        data_dir: app_data_dir(),
+        interval: Duration::from_secs(5),
+        json: false,
+        include_hidden: false,
+        api: None,
+        mqtt: None,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--data-dir" => options.data_dir = Some(PathBuf::from(value()?)),
+            "--interval" => {
+                let value = value()?;
+                let secs: u64 = value
+                    .parse()
+                    .ok()
+                    .filter(|secs| *secs > 0)
+                    .ok_or(format!("Invalid interval: {}", value))?;
+                options.interval = Duration::from_secs(secs);
+            }
+            "--json" => options.json = true,
+            "--all" => options.include_hidden = true,
+            "--api" => {
+                let value = value()?;
+                let addr = match value.parse::<u16>() {
+                    Ok(port) => Ok(SocketAddrV4::new(Ipv4Addr::LOCALHOST, port)),
+                    Err(_) => value.parse(),
+                };
+                options.api = Some(addr.map_err(|_| format!("Invalid API address: {}", value))?);
+            }
+            "--mqtt" => {
+                let value = value()?;
+                options.mqtt = Some(match value.rsplit_once(':') {
+                    Some((host, port)) => {
+                        let port = port
+                            .parse()
+                            .map_err(|_| format!("Invalid MQTT port: {}", port))?;
+                        (host.to_string(), port)
+                    }
+                    None => (value, DEFAULT_MQTT_PORT),
+                });
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => return Err(format!("Unknown option: {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+/// The data directory the app uses on this platform
+fn app_data_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        PathBuf::from(std::env::var_os("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(std::env::var_os("HOME")?).join("Library/Application Support")
+    } else {
+        match std::env::var_os("XDG_DATA_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".local/share"),
+        }
+    };
+    Some(base.join(APP_IDENTIFIER))
+}
+
+#[tokio::main]
+async fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+    let logger = Logger::init();
+
+    let source_manager = create_source_manager();
+    let dmx_store = Arc::new(DmxStore::new());
+    let event_tx = Arc::new(EventBus::new());
+    let listener_state = Arc::new(ListenerState::new());
+    let node_emulator = Arc::new(NodeEmulator::new());
+    let rdm_responder = Arc::new(RdmResponder::new());
+    let universe_labels = Arc::new(UniverseLabels::new());
+    let mqtt = MqttPublisher::new();
+
+    match &options.data_dir {
+        Some(data_dir) => {
+            logger.set_log_dir(data_dir.join("logs"));
+            info!("Using settings from {}", data_dir.display());
+            event_tx.load_config(data_dir.join("event_bus.json"));
+            listener_state.load_config(data_dir.join("listener_config.json"));
+            universe_labels.load(data_dir.join("universe_labels.json"));
+            if options.mqtt.is_none() {
+                mqtt.load_config(data_dir.join("mqtt.json"));
+            }
+            source_manager.load_thresholds(data_dir.join("source_thresholds.json"));
+            source_manager.load_oui_file(&data_dir.join("oui.txt"));
+            source_manager.load_known_sources(data_dir.join("known_sources.json"));
+            source_manager.load_expected_devices(data_dir.join("expected_devices.json"));
+        }
+        None => warn!("No data directory, running with default settings"),
+    }
+
+    let mqtt_context = MqttContext {
+        source_manager: source_manager.clone(),
+        event_tx: event_tx.clone(),
+        universe_labels: universe_labels.clone(),
+    };
+    let api_context = ApiContext {
+        source_manager: source_manager.clone(),
+        dmx_store: dmx_store.clone(),
+        listener_state: listener_state.clone(),
+        event_tx: event_tx.clone(),
+        universe_labels,
+    };
+    start_listeners(
+        source_manager.clone(),
+        dmx_store,
+        event_tx,
+        node_emulator,
+        rdm_responder,
+        listener_state,
+    );
+    info!("LXMonitor CLI started - listening for Art-Net and sACN traffic");
+
+    let api_server = ApiServer::new();
+    if let Some(addr) = options.api {
+        // The app's token, so scripts work against either
+        let token = options
+            .data_dir
+            .as_ref()
+            .and_then(|dir| load_json::<ApiConfig>(&dir.join("api_server.json")).token);
+        let config = ApiConfig {
+            enabled: true,
+            bind: *addr.ip(),
+            port: addr.port(),
+            token,
+        };
+        if let Err(e) = api_server.set_config(api_context, config).await {
+            error!("[API] {}", e);
+            std::process::exit(1);
+        }
+        info!(
+            "[API] Requests other than GET need \"Authorization: Bearer {}\"",
+            api_server.get_config().token.unwrap_or_default()
+        );
+    }
+
+    // A broker given on the command line is not saved, its settings were never
+    // loaded from a file
+    let mqtt_started = match options.mqtt.clone() {
+        Some((host, port)) => {
+            let config = MqttConfig {
+                enabled: true,
+                host,
+                port,
+                ..MqttConfig::default()
+            };
+            mqtt.set_config(mqtt_context, config)
+        }
+        None if mqtt.get_config().enabled => mqtt.start(mqtt_context),
+        None => Ok(()),
+    };
+    if let Err(e) = mqtt_started {
+        error!("[MQTT] {}", e);
+        std::process::exit(1);
+    }
+
+    let mut interval = tokio::time::interval(options.interval);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => print_sources(&source_manager, &options),
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    api_server.stop();
+    mqtt.stop();
+    if let Err(e) = source_manager.save_known_sources() {
+        warn!("[Sources] Failed to save device database: {}", e);
+    }
+}
+
+fn print_sources(source_manager: &SourceManagerHandle, options: &Options) {
+    let mut sources = source_manager.get_all_sources(options.include_hidden);
+    sources.sort_by(|a, b| a.ip.cmp(&b.ip).then(a.id.cmp(&b.id)));
+
+    if options.json {
+        match serde_json::to_string(&sources) {
+            Ok(json) => println!("{}", json),
+            Err(e) => error!("Failed to serialize sources: {}", e),
+        }
+        return;
+    }
+
+    println!(
+        "\n{:<28} {:<16} {:<7} {:<8} {:>6} {:>7}  {:<20} Warnings",
+        "Name", "IP", "Proto", "Status", "FPS", "Loss %", "Universes"
+    );
+    for source in &sources {
+        println!(
+            "{:<28} {:<16} {:<7} {:<8} {:>6.1} {:>7.2}  {:<20} {}",
+            truncate(source.alias.as_deref().unwrap_or(&source.name), 28),
+            source.ip,
+            enum_name(&source.protocol),
+            enum_name(&source.status),
+            source.fps,
+            source.packet_loss_percent,
+            truncate(&join_universes(&source.universes), 20),
+            warnings(source).join(", ")
+        );
+    }
+    println!("{} sources", sources.len());
+}
+
+/// Diagnostics worth a look, in the words of the app's source table
+fn warnings(source: &NetworkSource) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(fps) = &source.fps_warning {
+        warnings.push(format!("{} fps", fps));
+    }
+    if !source.duplicate_universes.is_empty() {
+        warnings.push(format!(
+            "duplicate {}",
+            join_universes(&source.duplicate_universes)
+        ));
+    }
+    if !source.priority_conflicts.is_empty() {
+        warnings.push(format!(
+            "priority conflict {}",
+            join_universes(&source.priority_conflicts)
+        ));
+    }
+    if source.duplicate_cid {
+        warnings.push("duplicate CID".to_string());
+    }
+    if source.router_hops.is_some_and(|hops| hops > 0) {
+        warnings.push("routed".to_string());
+    }
+    warnings
+}
+
+fn join_universes(universes: &[u16]) -> String {
+    universes
+        .iter()
+        .map(|u| u.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    match text.char_indices().nth(width) {
+        Some((end, _)) => format!("{}…", &text[..end - 1]),
+        None => text.to_string(),
+    }
+}
+
+/// Serialized name of a unit enum variant ("artnet", "active", ...)
+fn enum_name<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
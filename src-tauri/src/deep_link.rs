@@ -0,0 +1,85 @@
+// Deep links - lxmonitor:// URLs that open a view
+//
+// Links embedded in show documentation and incident reports bring the app up
+// at the view they name:
+//
+//   lxmonitor://universe/12                the universe window of universe 12
+//   lxmonitor://source/artnet-10.0.1.5     the main window, with a deep-link
+//                                          event telling it to select the source
+//
+// A second instance started by a link hands it to the running one and exits.
+
+use crate::app::AppState;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+pub const DEEP_LINK_SCHEME: &str = "lxmonitor";
+
+/// View named by a deep link
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DeepLink {
+    Universe { universe: u16 },
+    Source { id: String },
+}
+
+/// Parse a lxmonitor:// URL. A query or fragment is ignored.
+pub fn parse_deep_link(url: &str) -> Result<DeepLink, String> {
+    let rest = url
+        .split_once("://")
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case(DEEP_LINK_SCHEME))
+        .map(|(_, rest)| rest)
+        .ok_or_else(|| format!("Not a {}:// link: {}", DEEP_LINK_SCHEME, url))?;
+    let path = rest.split(['?', '#']).next().unwrap_or_default();
+    let path = path.trim_end_matches('/');
+
+    match path.split_once('/') {
+        Some(("universe", universe)) => universe
+            .parse()
+            .map(|universe| DeepLink::Universe { universe })
+            .map_err(|_| format!("Invalid universe in link: {}", url)),
+        Some(("source", id)) if !id.is_empty() => Ok(DeepLink::Source { id: id.to_string() }),
+        _ => Err(format!("Unknown link: {}", url)),
+    }
+}
+
+/// Open the view a link names
+pub fn open_deep_link(app: &AppHandle, url: &str) {
+    let link = match parse_deep_link(url) {
+        Ok(link) => link,
+        Err(e) => {
+            warn!("[Deep link] {}", e);
+            return;
+        }
+    };
+    info!("[Deep link] Opening {}", url);
+
+    match link {
+        DeepLink::Universe { universe } => {
+            let app = app.clone();
+            // Windows are built from a task, never from the callback delivering
+            // the link
+            tauri::async_runtime::spawn(async move {
+                let state = app.state::<AppState>();
+                if let Err(e) = crate::app::show_universe_window(&app, &state, universe) {
+                    warn!("[Deep link] {}", e);
+                }
+            });
+        }
+        DeepLink::Source { ref id } => {
+            let state = app.state::<AppState>();
+            if !state
+                .source_manager
+                .get_all_sources(true)
+                .iter()
+                .any(|s| &s.id == id)
+            {
+                warn!("[Deep link] Source {} has not been seen yet", id);
+            }
+            crate::app::show_main_window(app);
+            let _ = app.emit_to("main", "deep-link", &link);
+        }
+    }
+}
@@ -0,0 +1,276 @@
+// Frontend events - Coalesces DMX updates before they are emitted to the webview
+//
+// Emitting one event per received packet overwhelms the webview at dozens of
+// universes. DMX updates are collected per universe instead and flushed at a
+// configurable rate, each flush sending the newest frame of every universe that
+// changed plus one dmx-batch event describing them all. Universes that only
+// received identical frames are still in dmx-batch with their frame count, so a
+// static look doesn't read as a stopped sender. Per-universe dmx-N
+// frames only go to the windows subscribed to that universe. Universe windows
+// (labelled universe-N) show a single universe and get no dmx-batch events.
+
+use crate::network::{DmxData, Protocol, DMX_UNIVERSE_SIZE};
+use crate::persistence::{load_json, save_json};
+
+use bytes::Bytes;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Label prefix of the windows showing a single universe
+const UNIVERSE_WINDOW_PREFIX: &str = "universe-";
+
+/// Accepted DMX flush rates in Hz
+const MIN_DMX_FLUSH_HZ: f32 = 1.0;
+const MAX_DMX_FLUSH_HZ: f32 = 120.0;
+
+/// Frontend event configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventConfig {
+    /// How often coalesced DMX updates are sent to the frontend
+    pub dmx_flush_hz: f32,
+    /// Leave out frames identical to the universe's previous frame
+    pub suppress_identical_frames: bool,
+}
+
+impl Default for EventConfig {
+    fn default() -> Self {
+        Self {
+            dmx_flush_hz: 30.0,
+            suppress_identical_frames: true,
+        }
+    }
+}
+
+/// Persistent frontend event settings
+pub struct EventSettings {
+    config: RwLock<EventConfig>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl EventSettings {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(EventConfig::default()),
+            path: RwLock::new(None),
+        }
+    }
+
+    /// Load the settings from disk and persist future changes to the same file
+    pub fn load(&self, path: PathBuf) {
+        *self.config.write() = load_json(&path);
+        *self.path.write() = Some(path);
+    }
+
+    pub fn get_config(&self) -> EventConfig {
+        self.config.read().clone()
+    }
+
+    pub fn set_dmx_flush_hz(&self, hz: f32) -> Result<(), String> {
+        if !(MIN_DMX_FLUSH_HZ..=MAX_DMX_FLUSH_HZ).contains(&hz) {
+            return Err(format!(
+                "DMX event rate must be between {} and {} Hz",
+                MIN_DMX_FLUSH_HZ, MAX_DMX_FLUSH_HZ
+            ));
+        }
+        self.config.write().dmx_flush_hz = hz;
+        self.save()
+    }
+
+    pub fn suppress_identical_frames(&self) -> bool {
+        self.config.read().suppress_identical_frames
+    }
+
+    pub fn set_suppress_identical_frames(&self, enabled: bool) -> Result<(), String> {
+        self.config.write().suppress_identical_frames = enabled;
+        self.save()
+    }
+
+    /// Time between DMX flushes, falling back to the default for a bad saved rate
+    pub fn dmx_flush_interval(&self) -> Duration {
+        let hz = self.config.read().dmx_flush_hz;
+        let hz = if (MIN_DMX_FLUSH_HZ..=MAX_DMX_FLUSH_HZ).contains(&hz) {
+            hz
+        } else {
+            EventConfig::default().dmx_flush_hz
+        };
+        Duration::from_secs_f32(1.0 / hz)
+    }
+
+    fn save(&self) -> Result<(), String> {
+        match self.path.read().as_ref() {
+            Some(path) => save_json(path, &*self.config.read()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for EventSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type EventSettingsHandle = Arc<EventSettings>;
+
+/// Label of the window showing one universe
+pub fn universe_window_label(universe: u16) -> String {
+    format!("{}{}", UNIVERSE_WINDOW_PREFIX, universe)
+}
+
+pub fn is_universe_window(label: &str) -> bool {
+    label.starts_with(UNIVERSE_WINDOW_PREFIX)
+}
+
+/// Windows subscribed to the dmx-N events of each universe
+pub struct DmxSubscriptions {
+    windows: RwLock<HashMap<u16, BTreeSet<String>>>,
+}
+
+impl DmxSubscriptions {
+    pub fn new() -> Self {
+        Self {
+            windows: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn subscribe(&self, universe: u16, window: &str) {
+        self.windows
+            .write()
+            .entry(universe)
+            .or_default()
+            .insert(window.to_string());
+    }
+
+    pub fn unsubscribe(&self, universe: u16, window: &str) {
+        let mut windows = self.windows.write();
+        if let Some(subscribed) = windows.get_mut(&universe) {
+            subscribed.remove(window);
+            if subscribed.is_empty() {
+                windows.remove(&universe);
+            }
+        }
+    }
+
+    /// Drop every subscription of a closed window
+    pub fn remove_window(&self, window: &str) {
+        self.windows.write().retain(|_, subscribed| {
+            subscribed.remove(window);
+            !subscribed.is_empty()
+        });
+    }
+
+    /// Universes a window is subscribed to
+    pub fn get_universes(&self, window: &str) -> Vec<u16> {
+        let mut universes: Vec<u16> = self
+            .windows
+            .read()
+            .iter()
+            .filter(|(_, subscribed)| subscribed.contains(window))
+            .map(|(universe, _)| *universe)
+            .collect();
+        universes.sort();
+        universes
+    }
+
+    /// Windows that want the frames of a universe
+    pub fn subscribers(&self, universe: u16) -> Vec<String> {
+        self.windows
+            .read()
+            .get(&universe)
+            .map(|subscribed| subscribed.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for DmxSubscriptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type DmxSubscriptionsHandle = Arc<DmxSubscriptions>;
+
+/// Newest DMX update of a universe since the previous flush
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DmxUpdate {
+    pub universe: u16,
+    pub label: Option<String>,
+    pub protocol: Protocol,
+    pub source_ip: String,
+    pub interface: Option<String>,
+    pub timestamp: u64,
+    pub length: usize,
+    pub short_frame: bool,
+    pub frames: u32,   // Frames with new data since the previous flush
+    pub received: u32, // All frames received since the previous flush, identical ones included
+}
+
+/// DMX updates waiting for the next flush
+pub struct DmxCoalescer {
+    pending: HashMap<u16, (DmxUpdate, Option<Bytes>)>,
+}
+
+impl DmxCoalescer {
+    pub fn new() -> Self {
+        Self {
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Keep the newest frame of the universe, counting the ones it replaces.
+    /// The label is left for the flush to fill in.
+    pub fn push(&mut self, data: DmxData) {
+        self.record(data, true);
+    }
+
+    /// Count a frame without keeping its data, for frames identical to the
+    /// universe's previous one
+    pub fn count(&mut self, data: DmxData) {
+        self.record(data, false);
+    }
+
+    fn record(&mut self, data: DmxData, keep: bool) {
+        let previous = self.pending.remove(&data.universe);
+        let (frames, received) = previous
+            .as_ref()
+            .map_or((0, 0), |(update, _)| (update.frames, update.received));
+        let update = DmxUpdate {
+            universe: data.universe,
+            label: None,
+            protocol: data.protocol,
+            source_ip: data.source_ip.to_string(),
+            interface: data.interface.map(|i| i.to_string()),
+            timestamp: data.timestamp,
+            length: data.data.len(),
+            short_frame: data.data.len() < DMX_UNIVERSE_SIZE,
+            frames: frames + u32::from(keep),
+            received: received + 1,
+        };
+        let frame = if keep {
+            Some(data.data)
+        } else {
+            previous.and_then(|(_, frame)| frame)
+        };
+        self.pending.insert(data.universe, (update, frame));
+    }
+
+    /// Take the pending updates with their newest frame data, if any was
+    /// kept, ordered by universe
+    pub fn take(&mut self) -> Vec<(DmxUpdate, Option<Bytes>)> {
+        let mut updates: Vec<_> = self.pending.drain().map(|(_, pending)| pending).collect();
+        updates.sort_by_key(|(update, _)| update.universe);
+        updates
+    }
+}
+
+impl Default for DmxCoalescer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
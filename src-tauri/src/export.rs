@@ -0,0 +1,143 @@
+// Export - Source report files for venue IT and support tickets
+
+use crate::network::NetworkSource;
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Report file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+const CSV_HEADER: &[&str] = &[
+    "id",
+    "name",
+    "alias",
+    "ip",
+    "protocol",
+    "status",
+    "direction",
+    "universes",
+    "fps",
+    "packet_count",
+    "packet_loss_percent",
+    "latency_jitter_ms",
+    "fps_warning",
+    "duplicate_universes",
+    "priority_conflicts",
+    "duplicate_cid",
+    "artnet_short_name",
+    "artnet_long_name",
+    "mac_address",
+    "mac_vendor",
+    "link_mac",
+    "vlan",
+    "ttl",
+    "router_hops",
+    "switch_name",
+    "switch_port",
+    "sacn_cid",
+    "sacn_priority",
+    "first_seen",
+    "last_seen",
+    "note",
+];
+
+/// Write the source table to a file
+pub fn export_sources(
+    sources: &[NetworkSource],
+    path: &Path,
+    format: ExportFormat,
+) -> Result<(), String> {
+    let contents = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(sources)
+            .map_err(|e| format!("Failed to serialize sources: {}", e))?,
+        ExportFormat::Csv => sources_to_csv(sources),
+    };
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn sources_to_csv(sources: &[NetworkSource]) -> String {
+    let mut csv = CSV_HEADER.join(",");
+    csv.push_str("\r\n");
+
+    for source in sources {
+        let row = [
+            source.id.clone(),
+            source.name.clone(),
+            source.alias.clone().unwrap_or_default(),
+            source.ip.clone(),
+            enum_name(&source.protocol),
+            enum_name(&source.status),
+            enum_name(&source.direction),
+            join_universes(&source.universes),
+            format!("{:.1}", source.fps),
+            source.packet_count.to_string(),
+            format!("{:.2}", source.packet_loss_percent),
+            format!("{:.2}", source.latency_jitter_ms),
+            source.fps_warning.clone().unwrap_or_default(),
+            join_universes(&source.duplicate_universes),
+            join_universes(&source.priority_conflicts),
+            source.duplicate_cid.to_string(),
+            source.artnet_short_name.clone().unwrap_or_default(),
+            source.artnet_long_name.clone().unwrap_or_default(),
+            source.mac_address.clone().unwrap_or_default(),
+            source.mac_vendor.clone().unwrap_or_default(),
+            source.link_mac.clone().unwrap_or_default(),
+            match (source.outer_vlan, source.vlan) {
+                (Some(outer), Some(vlan)) => format!("{}.{}", outer, vlan),
+                (None, Some(vlan)) => vlan.to_string(),
+                _ => String::new(),
+            },
+            source.ttl.map(|t| t.to_string()).unwrap_or_default(),
+            source
+                .router_hops
+                .map(|h| h.to_string())
+                .unwrap_or_default(),
+            source.switch_name.clone().unwrap_or_default(),
+            source.switch_port.clone().unwrap_or_default(),
+            source.sacn_cid.clone().unwrap_or_default(),
+            source
+                .sacn_priority
+                .map(|p| p.to_string())
+                .unwrap_or_default(),
+            source.first_seen.to_string(),
+            source.last_seen.to_string(),
+            source.note.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Universe list as space separated numbers, so it stays one CSV column
+fn join_universes(universes: &[u16]) -> String {
+    universes
+        .iter()
+        .map(|u| u.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Serialized name of a unit enum variant ("artnet", "active", ...)
+pub fn enum_name<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
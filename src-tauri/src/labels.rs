@@ -0,0 +1,78 @@
+// Universe Labels - User-assigned names for universes ("FOH Truss", "Pixel Wall")
+
+use crate::persistence::{load_json, save_json};
+
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Persistent universe label store
+pub struct UniverseLabels {
+    labels: RwLock<BTreeMap<u16, String>>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl UniverseLabels {
+    pub fn new() -> Self {
+        Self {
+            labels: RwLock::new(BTreeMap::new()),
+            path: RwLock::new(None),
+        }
+    }
+
+    /// Load labels from disk and persist future changes to the same file
+    pub fn load(&self, path: PathBuf) {
+        *self.labels.write() = load_json(&path);
+        *self.path.write() = Some(path);
+    }
+
+    pub fn get(&self, universe: u16) -> Option<String> {
+        self.labels.read().get(&universe).cloned()
+    }
+
+    pub fn get_all(&self) -> BTreeMap<u16, String> {
+        self.labels.read().clone()
+    }
+
+    /// Set or clear (None / empty string) the label for a universe
+    pub fn set(&self, universe: u16, label: Option<String>) -> Result<(), String> {
+        {
+            let mut labels = self.labels.write();
+            match label.map(|l| l.trim().to_string()) {
+                Some(l) if !l.is_empty() => {
+                    labels.insert(universe, l);
+                }
+                _ => {
+                    labels.remove(&universe);
+                }
+            }
+        }
+        self.save()
+    }
+
+    /// Replace all labels, e.g. from a venue profile
+    pub fn set_all(&self, labels: BTreeMap<u16, String>) -> Result<(), String> {
+        *self.labels.write() = labels
+            .into_iter()
+            .map(|(universe, label)| (universe, label.trim().to_string()))
+            .filter(|(_, label)| !label.is_empty())
+            .collect();
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        match self.path.read().as_ref() {
+            Some(path) => save_json(path, &*self.labels.read()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for UniverseLabels {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type UniverseLabelsHandle = Arc<UniverseLabels>;
@@ -0,0 +1,255 @@
+// Logging - Leveled log output to the console, rotated files and the UI
+//
+// Everything logs through `tracing`. Lines go to stderr, to lxmonitor.log in
+// the log directory once it is known (rotated at 5 MB, keeping 5 old files as
+// lxmonitor.1.log to lxmonitor.5.log) and to a buffer of recent lines the
+// frontend reads. The level defaults to info and can be set with the
+// LXMONITOR_LOG environment variable (error, warn, info, debug or trace).
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+/// Environment variable setting the log level
+pub const LOG_LEVEL_ENV: &str = "LXMONITOR_LOG";
+
+const LOG_FILE_NAME: &str = "lxmonitor";
+const MAX_LOG_FILE_BYTES: u64 = 5 * 1024 * 1024;
+const KEPT_LOG_FILES: usize = 5;
+
+/// Lines kept for the frontend
+const RECENT_LOG_LINES: usize = 2000;
+
+/// Our crates; dependencies only log warnings and errors
+const LOG_TARGETS: [&str; 3] = ["lxmonitor", "lxmonitor_lib", "lxmonitor_cli"];
+
+/// Log level, most severe first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<&Level> for LogLevel {
+    fn from(level: &Level) -> Self {
+        match *level {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warn,
+            Level::INFO => LogLevel::Info,
+            Level::DEBUG => LogLevel::Debug,
+            Level::TRACE => LogLevel::Trace,
+        }
+    }
+}
+
+/// A recent log line for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLine {
+    pub timestamp: u64, // Unix timestamp ms
+    pub level: LogLevel,
+    pub message: String,
+}
+
+/// Log file that starts over in a new file when it grows too large
+struct RotatingFile {
+    dir: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(dir: PathBuf) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_file_path(&dir, 0))?;
+        let size = file.metadata()?.len();
+        Ok(Self { dir, file, size })
+    }
+
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        if self.size > 0 && self.size + buf.len() as u64 > MAX_LOG_FILE_BYTES {
+            self.rotate()?;
+        }
+        self.file.write_all(buf)?;
+        self.size += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Shift lxmonitor.log to lxmonitor.1.log and so on, dropping the oldest
+    fn rotate(&mut self) -> std::io::Result<()> {
+        for index in (0..KEPT_LOG_FILES).rev() {
+            let from = log_file_path(&self.dir, index);
+            if from.exists() {
+                std::fs::rename(&from, log_file_path(&self.dir, index + 1))?;
+            }
+        }
+        self.file = File::create(log_file_path(&self.dir, 0))?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn log_file_path(dir: &Path, index: usize) -> PathBuf {
+    match index {
+        0 => dir.join(format!("{}.log", LOG_FILE_NAME)),
+        _ => dir.join(format!("{}.{}.log", LOG_FILE_NAME, index)),
+    }
+}
+
+/// Log file and recent lines shared with the tracing subscriber
+pub struct Logger {
+    file: Mutex<Option<RotatingFile>>,
+    recent: Mutex<VecDeque<LogLine>>,
+}
+
+impl Logger {
+    /// Install the global tracing subscriber. Call once, before anything logs.
+    pub fn init() -> LoggerHandle {
+        let logger = Arc::new(Logger {
+            file: Mutex::new(None),
+            recent: Mutex::new(VecDeque::with_capacity(RECENT_LOG_LINES)),
+        });
+
+        let level = std::env::var(LOG_LEVEL_ENV)
+            .ok()
+            .and_then(|level| level.parse::<LevelFilter>().ok())
+            .unwrap_or(LevelFilter::INFO);
+        let filter = LOG_TARGETS.iter().fold(
+            Targets::new().with_default(LevelFilter::WARN),
+            |targets, target| targets.with_target(*target, level),
+        );
+
+        let subscriber = tracing_subscriber::registry()
+            .with(filter)
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_target(false)
+                    .with_ansi(std::io::stderr().is_terminal())
+                    .with_writer(std::io::stderr),
+            )
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_target(false)
+                    .with_ansi(false)
+                    .with_writer(LogFileWriter(logger.clone())),
+            )
+            .with(RecentLines(logger.clone()));
+        if tracing::subscriber::set_global_default(subscriber).is_err() {
+            eprintln!("[Logging] A logger is already installed");
+        }
+        logger
+    }
+
+    /// Start writing log files to a directory
+    pub fn set_log_dir(&self, dir: PathBuf) {
+        match RotatingFile::open(dir.clone()) {
+            Ok(file) => {
+                *self.file.lock() = Some(file);
+                tracing::info!("[Logging] Writing to {}", log_file_path(&dir, 0).display());
+            }
+            Err(e) => tracing::error!(
+                "[Logging] Failed to open log file in {}: {}",
+                dir.display(),
+                e
+            ),
+        }
+    }
+
+    /// Most recent lines at `level` or more severe, oldest first
+    pub fn get_recent(&self, limit: usize, level: LogLevel) -> Vec<LogLine> {
+        let recent = self.recent.lock();
+        let mut lines: Vec<LogLine> = recent
+            .iter()
+            .rev()
+            .filter(|line| line.level <= level)
+            .take(limit)
+            .cloned()
+            .collect();
+        lines.reverse();
+        lines
+    }
+
+    fn push_recent(&self, line: LogLine) {
+        let mut recent = self.recent.lock();
+        if recent.len() == RECENT_LOG_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+}
+
+pub type LoggerHandle = Arc<Logger>;
+
+/// Formatted lines for the log file, dropped until the log directory is set
+struct LogFileWriter(LoggerHandle);
+
+impl<'a> MakeWriter<'a> for LogFileWriter {
+    type Writer = LogFileLine<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogFileLine(&self.0)
+    }
+}
+
+struct LogFileLine<'a>(&'a Logger);
+
+impl Write for LogFileLine<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Some(file) = self.0.file.lock().as_mut() {
+            // A full disk must not take down logging, the console still works
+            let _ = file.write(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Keeps recent events for the frontend
+struct RecentLines(LoggerHandle);
+
+impl<S: Subscriber> Layer<S> for RecentLines {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut message = MessageVisitor(String::new());
+        event.record(&mut message);
+        self.0.push_recent(LogLine {
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            level: event.metadata().level().into(),
+            message: message.0,
+        });
+    }
+}
+
+/// Collects the message of an event, followed by any other fields
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        use std::fmt::Write;
+        if field.name() == "message" {
+            let _ = write!(self.0, "{:?}", value);
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// App metrics - LXMonitor's own resource usage and pipeline throughput
+//
+// Rates are measured between successive reports, so a client polling once a
+// second sees per-second figures. CPU time and memory are read from /proc and
+// are only reported on Linux.
+
+use crate::network::{EventBus, ListenerState};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Resource usage and throughput of the monitor itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppMetricsReport {
+    pub uptime_secs: u64,
+    pub cpu_percent: Option<f32>, // Of one core, since the previous report
+    pub memory_bytes: Option<u64>, // Resident set size
+    pub packets_per_second: f32,  // Datagrams received by all listeners
+    pub events_per_second: f32,   // Events emitted to the frontend
+    pub parser_queue_depth: u64,  // Datagrams waiting for a parser worker
+    pub dmx_events_queued: usize,
+    pub control_events_queued: usize,
+}
+
+/// Counter values at the time of a report
+#[derive(Clone, Copy)]
+struct Sample {
+    at: Instant,
+    packets: u64,
+    events: u64,
+    cpu_time: Option<Duration>,
+}
+
+pub struct AppMetrics {
+    started: Instant,
+    events_emitted: AtomicU64,
+    previous: Mutex<Sample>,
+}
+
+impl AppMetrics {
+    pub fn new() -> Self {
+        let started = Instant::now();
+        Self {
+            started,
+            events_emitted: AtomicU64::new(0),
+            previous: Mutex::new(Sample {
+                at: started,
+                packets: 0,
+                events: 0,
+                cpu_time: Some(Duration::ZERO),
+            }),
+        }
+    }
+
+    /// Count events emitted to the frontend
+    pub fn record_events(&self, count: u64) {
+        self.events_emitted.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn report(&self, listener_state: &ListenerState, event_bus: &EventBus) -> AppMetricsReport {
+        let counters = listener_state.get_counters();
+        let sample = Sample {
+            at: Instant::now(),
+            packets: counters.iter().map(|c| c.packets_received).sum(),
+            events: self.events_emitted.load(Ordering::Relaxed),
+            cpu_time: process_cpu_time(),
+        };
+        let previous = std::mem::replace(&mut *self.previous.lock(), sample);
+
+        let elapsed = sample.at.duration_since(previous.at).as_secs_f32();
+        let rate = |now: u64, before: u64| {
+            if elapsed > 0.0 {
+                now.saturating_sub(before) as f32 / elapsed
+            } else {
+                0.0
+            }
+        };
+        let cpu_percent = match (sample.cpu_time, previous.cpu_time) {
+            (Some(now), Some(before)) if elapsed > 0.0 => {
+                Some(now.saturating_sub(before).as_secs_f32() / elapsed * 100.0)
+            }
+            _ => None,
+        };
+        let (dmx_events_queued, control_events_queued) = event_bus.queued();
+
+        AppMetricsReport {
+            uptime_secs: self.started.elapsed().as_secs(),
+            cpu_percent,
+            memory_bytes: resident_memory(),
+            packets_per_second: rate(sample.packets, previous.packets),
+            events_per_second: rate(sample.events, previous.events),
+            parser_queue_depth: counters.iter().map(|c| c.queue_depth).sum(),
+            dmx_events_queued,
+            control_events_queued,
+        }
+    }
+}
+
+impl Default for AppMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type AppMetricsHandle = Arc<AppMetrics>;
+
+/// User plus system CPU time of this process, from /proc/self/stat
+fn process_cpu_time() -> Option<Duration> {
+    // Clock ticks per second of the stat fields, 100 on all common Linux builds
+    const TICKS_PER_SECOND: u64 = 100;
+
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // Fields after the parenthesised command name, starting with the state (field 3)
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime = fields.get(11)?.parse::<u64>().ok()?;
+    let stime = fields.get(12)?.parse::<u64>().ok()?;
+    Some(Duration::from_millis(
+        (utime + stime) * 1000 / TICKS_PER_SECOND,
+    ))
+}
+
+/// Resident set size of this process, from /proc/self/status
+fn resident_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib = line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    Some(kib * 1024)
+}
@@ -0,0 +1,477 @@
+// MQTT - Publishes source status, alerts and universe stats to a broker
+//
+// For building and show control systems that already speak MQTT. Topics, with
+// the default prefixes:
+//
+//   lxmonitor/sources/{id}         source status (retained), cleared when removed
+//   lxmonitor/alerts/expected      expected device alerts
+//   lxmonitor/alerts/storm         broadcast storm alerts
+//   lxmonitor/alerts/universe      universe blackout and stop alerts
+//   lxmonitor/universes/{n}        universe stats every stats interval
+//
+// Built with the "mqtt" feature; without it the publisher reports that it is
+// unavailable.
+
+use crate::labels::UniverseLabelsHandle;
+use crate::network::{EventBusHandle, Protocol, SourceManagerHandle, SourceStatus};
+use crate::persistence::{load_json, save_json};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+#[cfg(feature = "mqtt")]
+use crate::network::{ListenerEvent, NetworkSource};
+#[cfg(feature = "mqtt")]
+use rumqttc::{AsyncClient, Event, EventLoop, Incoming, MqttOptions, QoS};
+#[cfg(feature = "mqtt")]
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "mqtt")]
+use std::time::Duration;
+#[cfg(feature = "mqtt")]
+use tokio::sync::broadcast::error::RecvError;
+#[cfg(feature = "mqtt")]
+use tracing::{info, warn};
+
+/// Default MQTT broker port
+pub const DEFAULT_MQTT_PORT: u16 = 1883;
+
+/// Longest accepted stats interval
+const MAX_STATS_INTERVAL_SECS: u32 = 3600;
+
+/// Wait before reconnecting to an unreachable broker
+#[cfg(feature = "mqtt")]
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// MQTT publisher settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub username: Option<String>,
+    /// Never sent to the frontend. In new settings None keeps the saved
+    /// password and an empty string removes it.
+    pub password: Option<String>,
+    pub source_topic: String,     // Prefix of the per-source status topics
+    pub alert_topic: String,      // Prefix of the alert topics
+    pub universe_topic: String,   // Prefix of the per-universe stats topics
+    pub stats_interval_secs: u32, // 0 publishes no universe stats
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: DEFAULT_MQTT_PORT,
+            client_id: "lxmonitor".to_string(),
+            username: None,
+            password: None,
+            source_topic: "lxmonitor/sources".to_string(),
+            alert_topic: "lxmonitor/alerts".to_string(),
+            universe_topic: "lxmonitor/universes".to_string(),
+            stats_interval_secs: 10,
+        }
+    }
+}
+
+impl MqttConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.host.trim().is_empty() {
+            return Err("MQTT broker host must not be empty".to_string());
+        }
+        if self.port == 0 {
+            return Err("MQTT port must be between 1 and 65535".to_string());
+        }
+        if self.client_id.trim().is_empty() {
+            return Err("MQTT client ID must not be empty".to_string());
+        }
+        for topic in [&self.source_topic, &self.alert_topic, &self.universe_topic] {
+            if topic.trim_matches('/').is_empty() || topic.contains(['+', '#']) {
+                return Err(format!("Invalid MQTT topic prefix: \"{}\"", topic));
+            }
+        }
+        if self.stats_interval_secs > MAX_STATS_INTERVAL_SECS {
+            return Err(format!(
+                "Stats interval must be at most {} seconds",
+                MAX_STATS_INTERVAL_SECS
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// MQTT publisher status for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MqttStatus {
+    pub available: bool, // Built with the "mqtt" feature
+    pub running: bool,
+    pub connected: bool,
+    pub config: MqttConfig, // Without the password
+    pub has_password: bool,
+    pub published: u64,
+    pub dropped: u64, // Messages lost because the broker connection was backed up
+    pub error: Option<String>, // Last connection error
+}
+
+/// Status of one source, published retained to {source_topic}/{id}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceStatusMessage {
+    pub id: String,
+    pub name: String,
+    pub ip: String,
+    pub protocol: Protocol,
+    pub status: SourceStatus,
+    pub universes: Vec<u16>,
+    pub fps: f32,
+    pub packet_loss_percent: f32,
+    pub timestamp: u64,
+}
+
+/// Stats of one universe across its senders, published to {universe_topic}/{n}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniverseStatsMessage {
+    pub universe: u16,
+    pub label: Option<String>,
+    pub sources: Vec<String>,
+    pub fps: f32,                 // Highest sender rate
+    pub packet_loss_percent: f32, // Worst sender loss
+    pub packet_count: u64,
+    pub timestamp: u64,
+}
+
+/// Handles the publisher reads from
+#[derive(Clone)]
+pub struct MqttContext {
+    pub source_manager: SourceManagerHandle,
+    pub event_tx: EventBusHandle,
+    pub universe_labels: UniverseLabelsHandle,
+}
+
+#[cfg(feature = "mqtt")]
+struct RunningPublisher {
+    client: AsyncClient,
+    tasks: Vec<tokio::task::AbortHandle>,
+}
+
+/// Starts and stops the MQTT publisher
+pub struct MqttPublisher {
+    config: RwLock<MqttConfig>,
+    config_path: RwLock<Option<PathBuf>>,
+    #[cfg(feature = "mqtt")]
+    running: Mutex<Option<RunningPublisher>>,
+    connected: Arc<AtomicBool>,
+    published: Arc<AtomicU64>,
+    dropped: Arc<AtomicU64>,
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl MqttPublisher {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(MqttConfig::default()),
+            config_path: RwLock::new(None),
+            #[cfg(feature = "mqtt")]
+            running: Mutex::new(None),
+            connected: Arc::new(AtomicBool::new(false)),
+            published: Arc::new(AtomicU64::new(0)),
+            dropped: Arc::new(AtomicU64::new(0)),
+            error: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Load the saved settings; the caller starts the publisher when enabled
+    pub fn load_config(&self, path: PathBuf) {
+        *self.config.write() = load_json(&path);
+        *self.config_path.write() = Some(path);
+    }
+
+    pub fn get_config(&self) -> MqttConfig {
+        self.config.read().clone()
+    }
+
+    /// Save new settings and start, restart or stop the publisher to match
+    pub fn set_config(&self, context: MqttContext, mut config: MqttConfig) -> Result<(), String> {
+        config.validate()?;
+        match config.password.as_deref() {
+            None => config.password = self.get_config().password,
+            Some("") => config.password = None,
+            Some(_) => {}
+        }
+        self.stop();
+        *self.config.write() = config.clone();
+        if let Some(path) = self.config_path.read().as_ref() {
+            save_json(path, &config)?;
+        }
+        if config.enabled {
+            self.start(context)?;
+        }
+        Ok(())
+    }
+
+    pub fn get_status(&self) -> MqttStatus {
+        #[cfg(feature = "mqtt")]
+        let running = self.running.lock().is_some();
+        #[cfg(not(feature = "mqtt"))]
+        let running = false;
+        let mut config = self.get_config();
+        let has_password = config.password.take().is_some();
+        MqttStatus {
+            available: cfg!(feature = "mqtt"),
+            running,
+            connected: running && self.connected.load(Ordering::Relaxed),
+            config,
+            has_password,
+            published: self.published.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            error: self.error.lock().clone(),
+        }
+    }
+
+    /// Connect to the configured broker and publish until stopped. The
+    /// connection is retried in the background. Needs to run inside the tokio runtime.
+    #[cfg(feature = "mqtt")]
+    pub fn start(&self, context: MqttContext) -> Result<(), String> {
+        let config = self.get_config();
+        config.validate()?;
+        self.stop();
+
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if let Some(username) = &config.username {
+            options.set_credentials(username, config.password.as_deref().unwrap_or(""));
+        }
+        let (client, eventloop) = AsyncClient::new(options, 100);
+
+        self.connected.store(false, Ordering::Relaxed);
+        *self.error.lock() = None;
+        let connection = tokio::spawn(drive_connection(
+            eventloop,
+            self.connected.clone(),
+            self.error.clone(),
+        ));
+        let publisher = tokio::spawn(publish_events(
+            Publisher {
+                client: client.clone(),
+                config: config.clone(),
+                published: self.published.clone(),
+                dropped: self.dropped.clone(),
+            },
+            context,
+        ));
+
+        *self.running.lock() = Some(RunningPublisher {
+            client,
+            tasks: vec![connection.abort_handle(), publisher.abort_handle()],
+        });
+        info!(
+            "[MQTT] Publishing to {}:{} as {}",
+            config.host, config.port, config.client_id
+        );
+        Ok(())
+    }
+
+    #[cfg(not(feature = "mqtt"))]
+    pub fn start(&self, _context: MqttContext) -> Result<(), String> {
+        let message = "Built without MQTT support (enable the \"mqtt\" feature)".to_string();
+        *self.error.lock() = Some(message.clone());
+        Err(message)
+    }
+
+    pub fn stop(&self) {
+        #[cfg(feature = "mqtt")]
+        if let Some(running) = self.running.lock().take() {
+            let _ = running.client.try_disconnect();
+            for task in running.tasks {
+                task.abort();
+            }
+            self.connected.store(false, Ordering::Relaxed);
+            info!("[MQTT] Stopped publishing");
+        }
+    }
+}
+
+impl Default for MqttPublisher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type MqttPublisherHandle = Arc<MqttPublisher>;
+
+/// Poll the broker connection, which also sends the queued publishes
+#[cfg(feature = "mqtt")]
+async fn drive_connection(
+    mut eventloop: EventLoop,
+    connected: Arc<AtomicBool>,
+    error: Arc<Mutex<Option<String>>>,
+) {
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(Incoming::ConnAck(_))) => {
+                connected.store(true, Ordering::Relaxed);
+                *error.lock() = None;
+                info!("[MQTT] Connected to broker");
+            }
+            Ok(_) => {}
+            Err(e) => {
+                if connected.swap(false, Ordering::Relaxed) {
+                    warn!("[MQTT] Connection lost: {}", e);
+                }
+                *error.lock() = Some(e.to_string());
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "mqtt")]
+struct Publisher {
+    client: AsyncClient,
+    config: MqttConfig,
+    published: Arc<AtomicU64>,
+    dropped: Arc<AtomicU64>,
+}
+
+#[cfg(feature = "mqtt")]
+impl Publisher {
+    /// Queue a message without waiting, dropping it if the queue is full
+    fn publish(&self, topic: String, qos: QoS, retain: bool, payload: Vec<u8>) {
+        match self.client.try_publish(topic, qos, retain, payload) {
+            Ok(()) => self.published.fetch_add(1, Ordering::Relaxed),
+            Err(_) => self.dropped.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    fn publish_json<T: Serialize>(&self, topic: String, qos: QoS, retain: bool, value: &T) {
+        match serde_json::to_vec(value) {
+            Ok(payload) => self.publish(topic, qos, retain, payload),
+            Err(e) => warn!("[MQTT] Failed to serialize message for {}: {}", topic, e),
+        }
+    }
+
+    fn topic(prefix: &str, name: impl std::fmt::Display) -> String {
+        format!("{}/{}", prefix.trim_end_matches('/'), name)
+    }
+
+    /// Publish the sources whose status changed since the last call and clear
+    /// the retained status of removed ones
+    fn publish_sources(&self, sources: &[NetworkSource], last: &mut HashMap<String, SourceStatus>) {
+        let now = now_ms();
+        let mut current = HashMap::with_capacity(sources.len());
+        for source in sources {
+            current.insert(source.id.clone(), source.status);
+            if last.get(&source.id) == Some(&source.status) {
+                continue;
+            }
+            let message = SourceStatusMessage {
+                id: source.id.clone(),
+                name: source.alias.clone().unwrap_or_else(|| source.name.clone()),
+                ip: source.ip.clone(),
+                protocol: source.protocol,
+                status: source.status,
+                universes: source.universes.clone(),
+                fps: source.fps,
+                packet_loss_percent: source.packet_loss_percent,
+                timestamp: now,
+            };
+            let topic = Self::topic(&self.config.source_topic, &source.id);
+            self.publish_json(topic, QoS::AtLeastOnce, true, &message);
+        }
+        for id in last.keys().filter(|id| !current.contains_key(*id)) {
+            let topic = Self::topic(&self.config.source_topic, id);
+            self.publish(topic, QoS::AtLeastOnce, true, Vec::new());
+        }
+        *last = current;
+    }
+
+    fn publish_universe_stats(&self, context: &MqttContext) {
+        let now = now_ms();
+        let mut universes: BTreeMap<u16, UniverseStatsMessage> = BTreeMap::new();
+        for source in context.source_manager.get_all_sources(true) {
+            if source.status == SourceStatus::Offline {
+                continue;
+            }
+            let Ok(stats) = context.source_manager.get_universe_stats(&source.id) else {
+                continue;
+            };
+            for stat in stats {
+                let entry =
+                    universes
+                        .entry(stat.universe)
+                        .or_insert_with(|| UniverseStatsMessage {
+                            universe: stat.universe,
+                            label: context.universe_labels.get(stat.universe),
+                            sources: Vec::new(),
+                            fps: 0.0,
+                            packet_loss_percent: 0.0,
+                            packet_count: 0,
+                            timestamp: now,
+                        });
+                entry.sources.push(source.id.clone());
+                entry.fps = entry.fps.max(stat.fps);
+                entry.packet_loss_percent = entry.packet_loss_percent.max(stat.packet_loss_percent);
+                entry.packet_count += stat.packet_count;
+            }
+        }
+        for (universe, message) in universes {
+            let topic = Self::topic(&self.config.universe_topic, universe);
+            self.publish_json(topic, QoS::AtMostOnce, false, &message);
+        }
+    }
+}
+
+#[cfg(feature = "mqtt")]
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Publish status changes and alerts as they happen and universe stats on an interval
+#[cfg(feature = "mqtt")]
+async fn publish_events(publisher: Publisher, context: MqttContext) {
+    let mut events = context.event_tx.subscribe();
+    let mut last_status = HashMap::new();
+    publisher.publish_sources(
+        &context.source_manager.get_all_sources(true),
+        &mut last_status,
+    );
+
+    // Universe stats are off with an interval of 0
+    let stats_secs = publisher.config.stats_interval_secs;
+    let mut stats = tokio::time::interval(Duration::from_secs(stats_secs.max(1) as u64));
+    stats.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(ListenerEvent::SourcesUpdated) => {
+                    let sources = context.source_manager.get_all_sources(true);
+                    publisher.publish_sources(&sources, &mut last_status);
+                }
+                Ok(ListenerEvent::ExpectedDeviceAlert(alert)) => {
+                    let topic = Publisher::topic(&publisher.config.alert_topic, "expected");
+                    publisher.publish_json(topic, QoS::AtLeastOnce, false, &alert);
+                }
+                Ok(ListenerEvent::StormAlert(alert)) => {
+                    let topic = Publisher::topic(&publisher.config.alert_topic, "storm");
+                    publisher.publish_json(topic, QoS::AtLeastOnce, false, &alert);
+                }
+                Ok(ListenerEvent::UniverseAnomaly(alert)) => {
+                    let topic = Publisher::topic(&publisher.config.alert_topic, "universe");
+                    publisher.publish_json(topic, QoS::AtLeastOnce, false, &alert);
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            },
+            _ = stats.tick(), if stats_secs > 0 => publisher.publish_universe_stats(&context),
+        }
+    }
+}
@@ -0,0 +1,239 @@
+// Universe anomalies - A universe going dark while its sender looks fine
+//
+// Two failures a console's own screen doesn't show: a universe that drops to
+// all zero from one frame to the next, and a universe that stops arriving while
+// its sender is still heard on other universes. Each raises an alert with the
+// last frame before it happened. A fade to black ends on low levels and is not
+// flagged, nor is a sender disappearing altogether (the source goes stale).
+
+use crate::network::bus::{EventBusHandle, EventReceiver};
+use crate::network::listener::{DmxData, ListenerEvent};
+use crate::network::source::{NetworkSource, Protocol, SourceManagerHandle, SourceStatus};
+
+use bytes::Bytes;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+
+/// A frame going to all zero counts as sudden when the frame before had a
+/// channel at least this high
+const SUDDEN_BLACKOUT_LEVEL: u8 = 64;
+
+/// Time without frames after which a universe has stopped, like the E1.31
+/// network data loss timeout
+const STALL_TIMEOUT_MS: u64 = 2500;
+
+/// Streams not heard for this long are forgotten
+const FORGET_AFTER_MS: u64 = 10 * 60_000;
+
+/// How often stopped universes are looked for
+const STALL_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UniverseAnomalyKind {
+    /// Dropped to all zero from one frame to the next
+    Blackout,
+    /// Stopped arriving while the sender is still active
+    Stopped,
+}
+
+/// Alert raised when a universe suddenly goes dark
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniverseAnomaly {
+    pub universe: u16,
+    pub kind: UniverseAnomalyKind,
+    pub protocol: Protocol,
+    pub source_ip: String,
+    pub source_id: Option<String>,
+    pub source_name: Option<String>,
+    pub previous_frame: Vec<u8>, // Last frame before the anomaly
+    pub last_frame_at: u64,      // Unix timestamp ms of that frame
+    pub message: String,
+    pub timestamp: u64,
+}
+
+/// Last frame of one universe from one sender
+struct Stream {
+    frame: Bytes,
+    at: u64,
+    dark: bool,    // All zero since a blackout was reported
+    stopped: bool, // Reported or gone, until frames arrive again
+}
+
+/// Watches every universe stream for blackouts and stops
+struct AnomalyDetector {
+    streams: Mutex<HashMap<(u16, Protocol, IpAddr), Stream>>,
+}
+
+impl AnomalyDetector {
+    fn new() -> Self {
+        Self {
+            streams: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record a frame, returning the frame before it when it is a sudden blackout
+    fn record(&self, data: &DmxData) -> Option<(Bytes, u64)> {
+        let mut streams = self.streams.lock();
+        let key = (data.universe, data.protocol, data.source_ip);
+        let all_zero = data.data.iter().all(|level| *level == 0);
+        let Some(stream) = streams.get_mut(&key) else {
+            streams.insert(
+                key,
+                Stream {
+                    frame: data.data.clone(),
+                    at: data.timestamp,
+                    dark: all_zero,
+                    stopped: false,
+                },
+            );
+            return None;
+        };
+
+        let sudden = all_zero
+            && !stream.dark
+            && !stream.stopped
+            && stream
+                .frame
+                .iter()
+                .any(|level| *level >= SUDDEN_BLACKOUT_LEVEL);
+        let previous = sudden.then(|| (stream.frame.clone(), stream.at));
+        stream.frame = data.data.clone();
+        stream.at = data.timestamp;
+        stream.dark = all_zero;
+        stream.stopped = false;
+        previous
+    }
+
+    /// Streams that stopped since the last check, with their last frame.
+    /// `is_active` tells whether the sender is still heard.
+    fn check_stopped(
+        &self,
+        now: u64,
+        is_active: impl Fn(Protocol, IpAddr) -> bool,
+    ) -> Vec<((u16, Protocol, IpAddr), Bytes, u64)> {
+        let mut streams = self.streams.lock();
+        streams.retain(|_, stream| now.saturating_sub(stream.at) < FORGET_AFTER_MS);
+        let mut stopped = Vec::new();
+        for (key, stream) in streams.iter_mut() {
+            if stream.stopped || now.saturating_sub(stream.at) < STALL_TIMEOUT_MS {
+                continue;
+            }
+            stream.stopped = true;
+            // A sender that went away entirely is reported as a stale source
+            if is_active(key.1, key.2) {
+                stopped.push((*key, stream.frame.clone(), stream.at));
+            }
+        }
+        stopped
+    }
+}
+
+/// Watch the DMX on the event bus and send an alert for each anomaly, until the
+/// bus closes
+pub async fn run_anomaly_detector(
+    source_manager: SourceManagerHandle,
+    event_tx: EventBusHandle,
+    mut events: EventReceiver,
+) {
+    let detector = AnomalyDetector::new();
+    let mut check = tokio::time::interval(STALL_CHECK_INTERVAL);
+    check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        let anomalies = tokio::select! {
+            event = events.recv() => match event {
+                Ok(ListenerEvent::DmxData(data)) => match detector.record(&data) {
+                    Some((frame, at)) => vec![anomaly(
+                        UniverseAnomalyKind::Blackout,
+                        (data.universe, data.protocol, data.source_ip),
+                        frame,
+                        at,
+                        &source_manager.get_all_sources(true),
+                    )],
+                    None => continue,
+                },
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+            _ = check.tick() => {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64;
+                let sources = source_manager.get_all_sources(true);
+                detector
+                    .check_stopped(now, |protocol, ip| {
+                        find_source(&sources, protocol, ip)
+                            .is_some_and(|s| s.status == SourceStatus::Active)
+                    })
+                    .into_iter()
+                    .map(|(key, frame, at)| {
+                        anomaly(UniverseAnomalyKind::Stopped, key, frame, at, &sources)
+                    })
+                    .collect()
+            }
+        };
+        for anomaly in anomalies {
+            warn!("[Anomaly] {}", anomaly.message);
+            let _ = event_tx.send(ListenerEvent::UniverseAnomaly(Box::new(anomaly)));
+        }
+    }
+}
+
+fn find_source(
+    sources: &[NetworkSource],
+    protocol: Protocol,
+    ip: IpAddr,
+) -> Option<&NetworkSource> {
+    let ip = ip.to_string();
+    sources
+        .iter()
+        .find(|s| s.protocol == protocol && s.ip == ip)
+}
+
+fn anomaly(
+    kind: UniverseAnomalyKind,
+    (universe, protocol, ip): (u16, Protocol, IpAddr),
+    previous_frame: Bytes,
+    last_frame_at: u64,
+    sources: &[NetworkSource],
+) -> UniverseAnomaly {
+    let source = find_source(sources, protocol, ip);
+    let source_name = source.map(|s| s.alias.clone().unwrap_or_else(|| s.name.clone()));
+    let sender = match &source_name {
+        Some(name) if !name.is_empty() => format!("{} ({})", name, ip),
+        _ => ip.to_string(),
+    };
+    let message = match kind {
+        UniverseAnomalyKind::Blackout => {
+            format!(
+                "Universe {} from {} suddenly went to zero",
+                universe, sender
+            )
+        }
+        UniverseAnomalyKind::Stopped => format!(
+            "Universe {} from {} stopped while the source is still active",
+            universe, sender
+        ),
+    };
+    UniverseAnomaly {
+        universe,
+        kind,
+        protocol,
+        source_ip: ip.to_string(),
+        source_id: source.map(|s| s.id.clone()),
+        source_name,
+        previous_frame: previous_frame.to_vec(),
+        last_frame_at,
+        message,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+    }
+}
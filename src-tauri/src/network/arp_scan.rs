@@ -0,0 +1,214 @@
+// ARP scan - Sweep a subnet for every device on it, lighting protocol or not
+//
+// Switches, fixtures and gateways that never send Art-Net or sACN only show
+// up when asked. A UDP datagram to each address of the subnet makes the
+// operating system ask for it with ARP, and the answers are captured.
+// Addresses the OS already has cached aren't asked that way, so once its own
+// requests have shown this machine's MAC, the addresses that stayed quiet are
+// asked again with ARP requests sent through the capture. Answers go into the
+// source manager's ARP table, which fills in source MACs and lists the silent
+// devices in get_devices.
+//
+// Capturing needs the sniffer feature.
+
+use crate::network::source::{ArpMapping, SourceManagerHandle};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "sniffer")]
+use crate::network::sniffer::{arp_sender, capture_setup_hint};
+
+#[cfg(feature = "sniffer")]
+use pcap::{Capture, Device, Linktype};
+#[cfg(feature = "sniffer")]
+use std::collections::HashMap;
+#[cfg(feature = "sniffer")]
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+#[cfg(feature = "sniffer")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "sniffer")]
+use tracing::info;
+
+/// Smallest prefix scanned, 4094 addresses
+#[cfg(feature = "sniffer")]
+const MIN_SCAN_PREFIX: u8 = 20;
+
+/// Pause between probes, so a sweep doesn't flood the network
+#[cfg(feature = "sniffer")]
+const PROBE_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Time left for the last answers after the last probe
+#[cfg(feature = "sniffer")]
+const REPLY_WAIT: Duration = Duration::from_secs(2);
+
+/// Discard protocol port, the triggering datagrams go here
+#[cfg(feature = "sniffer")]
+const DISCARD_PORT: u16 = 9;
+
+/// Devices that answered an ARP scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArpScanResult {
+    pub subnet: String,
+    pub interface: String,
+    pub addresses_probed: u32,
+    pub hosts: Vec<ArpMapping>, // By IP
+    pub duration_ms: u64,
+}
+
+/// Scan a subnet in CIDR notation (/20 or smaller) on the interface with an
+/// address in it, or on the given interface
+#[cfg(feature = "sniffer")]
+pub fn arp_scan_blocking(
+    subnet: &str,
+    interface: Option<&str>,
+    source_manager: &SourceManagerHandle,
+) -> Result<ArpScanResult, String> {
+    let started = Instant::now();
+    let (network, prefix) = parse_subnet(subnet)?;
+    let mask = u32::MAX << (32 - prefix);
+    let in_subnet = |ip: Ipv4Addr| u32::from(ip) & mask == u32::from(network);
+
+    let devices =
+        Device::list().map_err(|e| format!("Failed to list capture interfaces: {}", e))?;
+    let (device, local_ip) = devices
+        .into_iter()
+        .filter(|d| interface.is_none_or(|name| d.name == name))
+        .find_map(|d| {
+            let ip = d.addresses.iter().find_map(|a| match a.addr {
+                IpAddr::V4(ip) if in_subnet(ip) => Some(ip),
+                _ => None,
+            })?;
+            Some((d, ip))
+        })
+        .ok_or_else(|| format!("No capture interface has an address in {}", subnet))?;
+    let name = device.name.clone();
+
+    let mut cap = Capture::from_device(device)
+        .and_then(|c| c.snaplen(128).timeout(100).immediate_mode(true).open())
+        .map_err(|e| format!("Failed to open {}: {}. {}", name, e, capture_setup_hint()))?;
+    cap.filter("arp", true)
+        .map_err(|e| format!("Failed to set the ARP filter: {}", e))?;
+    let linktype = cap.get_datalink();
+
+    let size = 1u32 << (32 - prefix);
+    let targets: Vec<Ipv4Addr> = (1..size - 1)
+        .map(|i| Ipv4Addr::from(u32::from(network) + i))
+        .filter(|ip| *ip != local_ip)
+        .collect();
+    let socket = UdpSocket::bind((local_ip, 0))
+        .map_err(|e| format!("Failed to bind to {}: {}", local_ip, e))?;
+    let trigger = {
+        let targets = targets.clone();
+        std::thread::spawn(move || {
+            for ip in targets {
+                // Fails for addresses the OS couldn't resolve, which is expected
+                let _ = socket.send_to(&[0], (ip, DISCARD_PORT));
+                std::thread::sleep(PROBE_INTERVAL);
+            }
+        })
+    };
+
+    let mut answered: HashMap<Ipv4Addr, [u8; 6]> = HashMap::new();
+    let mut own_mac: Option<[u8; 6]> = None;
+    let mut deadline: Option<Instant> = None;
+    loop {
+        if deadline.is_none() && trigger.is_finished() {
+            // Ask the addresses that stayed quiet directly, the OS may have them cached
+            if let Some(mac) = own_mac.filter(|_| linktype == Linktype::ETHERNET) {
+                for ip in targets.iter().filter(|ip| !answered.contains_key(ip)) {
+                    let _ = cap.sendpacket(arp_request(mac, local_ip, *ip));
+                    std::thread::sleep(PROBE_INTERVAL);
+                }
+            }
+            deadline = Some(Instant::now() + REPLY_WAIT);
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            break;
+        }
+        match cap.next_packet() {
+            Ok(packet) => match arp_sender(linktype, packet.data) {
+                Some((mac, ip)) if ip == local_ip => {
+                    own_mac.get_or_insert(mac);
+                }
+                Some((mac, ip)) if in_subnet(ip) => {
+                    answered.insert(ip, mac);
+                }
+                _ => {}
+            },
+            Err(pcap::Error::TimeoutExpired) => {}
+            Err(e) => return Err(format!("Capture on {} failed: {}", name, e)),
+        }
+    }
+
+    for (ip, mac) in &answered {
+        source_manager.record_arp(IpAddr::V4(*ip), mac);
+    }
+    let hosts: Vec<ArpMapping> = source_manager
+        .get_arp_table()
+        .into_iter()
+        .filter(|m| {
+            m.ip.parse::<Ipv4Addr>()
+                .is_ok_and(|ip| answered.contains_key(&ip))
+        })
+        .collect();
+    info!(
+        "[ARP scan] {} devices answered in {} on {}",
+        hosts.len(),
+        subnet,
+        name
+    );
+    Ok(ArpScanResult {
+        subnet: format!("{}/{}", network, prefix),
+        interface: name,
+        addresses_probed: targets.len() as u32,
+        hosts,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Network address and prefix length of a subnet in CIDR notation
+#[cfg(feature = "sniffer")]
+fn parse_subnet(subnet: &str) -> Result<(Ipv4Addr, u8), String> {
+    let (ip, prefix) = subnet.trim().split_once('/').ok_or_else(|| {
+        format!(
+            "Subnet \"{}\" must be in CIDR notation, e.g. 10.0.0.0/24",
+            subnet
+        )
+    })?;
+    let ip: Ipv4Addr = ip
+        .parse()
+        .map_err(|_| format!("Invalid subnet address \"{}\"", ip))?;
+    let prefix: u8 = prefix
+        .parse()
+        .ok()
+        .filter(|p| (MIN_SCAN_PREFIX..=30).contains(p))
+        .ok_or_else(|| format!("Subnet prefix must be /{} to /30", MIN_SCAN_PREFIX))?;
+    let mask = u32::MAX << (32 - prefix);
+    Ok((Ipv4Addr::from(u32::from(ip) & mask), prefix))
+}
+
+/// Broadcast Ethernet frame asking who has an IPv4 address
+#[cfg(feature = "sniffer")]
+fn arp_request(own_mac: [u8; 6], own_ip: Ipv4Addr, target: Ipv4Addr) -> Vec<u8> {
+    // Broadcast, from us, ARP EtherType
+    let mut frame = Vec::with_capacity(42);
+    frame.extend_from_slice(&[0xff; 6]);
+    frame.extend_from_slice(&own_mac);
+    frame.extend_from_slice(&[0x08, 0x06]);
+    // Ethernet hardware, IPv4 protocol addresses, 6 and 4 bytes long, request
+    frame.extend_from_slice(&[0, 1, 0x08, 0x00, 6, 4, 0, 1]);
+    frame.extend_from_slice(&own_mac);
+    frame.extend_from_slice(&own_ip.octets());
+    frame.extend_from_slice(&[0; 6]);
+    frame.extend_from_slice(&target.octets());
+    frame
+}
+
+#[cfg(not(feature = "sniffer"))]
+pub fn arp_scan_blocking(
+    _subnet: &str,
+    _interface: Option<&str>,
+    _source_manager: &SourceManagerHandle,
+) -> Result<ArpScanResult, String> {
+    Err("Sniffer feature not compiled. Rebuild with --features sniffer".to_string())
+}
@@ -0,0 +1,233 @@
+//! Interface a received datagram arrived on
+//!
+//! A socket bound to an interface's unicast address gets no broadcasts or
+//! multicast on Linux and macOS, so there the listeners bind the wildcard address
+//! and read the arrival interface from IP_PKTINFO. Windows does deliver them to a
+//! socket bound to an interface address, so it keeps one socket per interface.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+
+/// Least time between two lookups of the interface indexes
+const INDEX_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A listener socket: the address it binds and the selected interfaces it
+/// receives for (just 0.0.0.0 for all)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListenTarget {
+    pub bind_addr: Ipv4Addr,
+    pub interfaces: Vec<Ipv4Addr>,
+}
+
+/// Sockets to open for the selected interfaces
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub fn listen_targets(interfaces: &[Ipv4Addr]) -> Vec<ListenTarget> {
+    vec![ListenTarget {
+        bind_addr: Ipv4Addr::UNSPECIFIED,
+        interfaces: interfaces.to_vec(),
+    }]
+}
+
+/// Sockets to open for the selected interfaces
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn listen_targets(interfaces: &[Ipv4Addr]) -> Vec<ListenTarget> {
+    interfaces
+        .iter()
+        .map(|interface| ListenTarget {
+            bind_addr: *interface,
+            interfaces: vec![*interface],
+        })
+        .collect()
+}
+
+/// Have the OS report the arrival interface of each datagram on the socket
+pub fn enable_arrival_interface(socket: &UdpSocket) -> std::io::Result<()> {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    pktinfo::enable(socket)?;
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    let _ = socket;
+    Ok(())
+}
+
+/// Receive a datagram along with the index of the interface it arrived on, when
+/// the OS reports it
+pub async fn recv_with_interface(
+    socket: &UdpSocket,
+    buf: &mut [u8],
+) -> std::io::Result<(usize, SocketAddr, Option<u32>)> {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        socket
+            .async_io(tokio::io::Interest::READABLE, || pktinfo::recv(socket, buf))
+            .await
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let (len, src) = socket.recv_from(buf).await?;
+        Ok((len, src, None))
+    }
+}
+
+/// Decides per datagram whether it arrived on a selected interface, and which
+/// interface to tag it with
+pub struct ArrivalFilter {
+    target: ListenTarget,
+    /// Interface index to the selected address on that interface
+    indexes: HashMap<u32, Ipv4Addr>,
+    last_refresh: Option<Instant>,
+}
+
+impl ArrivalFilter {
+    pub fn new(target: &ListenTarget) -> Self {
+        let mut filter = Self {
+            target: target.clone(),
+            indexes: HashMap::new(),
+            last_refresh: None,
+        };
+        filter.refresh();
+        filter
+    }
+
+    /// None to drop the datagram, otherwise its interface tag (None when
+    /// listening on all interfaces)
+    pub fn accept(&mut self, if_index: Option<u32>) -> Option<Option<Ipv4Addr>> {
+        if self.listens_on_all() {
+            return Some(None);
+        }
+        // A socket bound to an interface address only gets that interface's traffic
+        if !self.target.bind_addr.is_unspecified() {
+            return Some(Some(self.target.bind_addr));
+        }
+        let Some(if_index) = if_index else {
+            return Some(None);
+        };
+        if !self.indexes.contains_key(&if_index)
+            && self
+                .last_refresh
+                .is_none_or(|at| at.elapsed() >= INDEX_REFRESH_INTERVAL)
+        {
+            // The interface may have come up or been renumbered since
+            self.refresh();
+        }
+        self.indexes.get(&if_index).map(|ip| Some(*ip))
+    }
+
+    fn listens_on_all(&self) -> bool {
+        self.target.interfaces.iter().any(|ip| ip.is_unspecified())
+    }
+
+    fn refresh(&mut self) {
+        self.last_refresh = Some(Instant::now());
+        if self.listens_on_all() || !self.target.bind_addr.is_unspecified() {
+            return;
+        }
+        let Ok(ifaces) = local_ip_address::list_afinet_netifas() else {
+            return;
+        };
+        self.indexes = ifaces
+            .iter()
+            .filter_map(|(name, addr)| match addr {
+                IpAddr::V4(ip) if self.target.interfaces.contains(ip) => {
+                    Some((interface_index(name)?, *ip))
+                }
+                _ => None,
+            })
+            .collect();
+    }
+}
+
+/// Index of a named interface. Linux lists extra addresses under labels like
+/// "eth0:1", which share the index of "eth0".
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn interface_index(name: &str) -> Option<u32> {
+    let name = name.split(':').next()?;
+    let name = std::ffi::CString::new(name).ok()?;
+    // SAFETY: name is a valid NUL-terminated string for the duration of the call
+    let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+    (index != 0).then_some(index)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn interface_index(_name: &str) -> Option<u32> {
+    None
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod pktinfo {
+    use std::io;
+    use std::mem;
+    use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+    use std::os::fd::AsRawFd;
+
+    pub(super) fn enable(socket: &impl AsRawFd) -> io::Result<()> {
+        let on: libc::c_int = 1;
+        // SAFETY: the option value is a c_int that lives across the call
+        let result = unsafe {
+            libc::setsockopt(
+                socket.as_raw_fd(),
+                libc::IPPROTO_IP,
+                libc::IP_PKTINFO,
+                (&on as *const libc::c_int).cast(),
+                mem::size_of::<libc::c_int>() as libc::socklen_t,
+            )
+        };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    /// One non-blocking recvmsg, taking the interface index from the IP_PKTINFO
+    /// control message
+    pub(super) fn recv(
+        socket: &impl AsRawFd,
+        buf: &mut [u8],
+    ) -> io::Result<(usize, SocketAddr, Option<u32>)> {
+        // SAFETY: all-zero is a valid value for these plain C structs
+        let mut addr: libc::sockaddr_in = unsafe { mem::zeroed() };
+        let mut iov = libc::iovec {
+            iov_base: buf.as_mut_ptr().cast(),
+            iov_len: buf.len(),
+        };
+        // u64 elements keep the control buffer aligned for cmsghdr
+        let mut control = [0u64; 16];
+        // SAFETY: as above
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_name = (&mut addr as *mut libc::sockaddr_in).cast();
+        msg.msg_namelen = mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = mem::size_of_val(&control) as _;
+
+        // SAFETY: msg points at buffers that outlive the call
+        let len = unsafe { libc::recvmsg(socket.as_raw_fd(), &mut msg, 0) };
+        if len < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut if_index = None;
+        // SAFETY: the kernel filled msg_control with msg_controllen bytes of
+        // control messages, walked with the CMSG macros
+        unsafe {
+            let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+            while !cmsg.is_null() {
+                if (*cmsg).cmsg_level == libc::IPPROTO_IP && (*cmsg).cmsg_type == libc::IP_PKTINFO {
+                    let info =
+                        std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const libc::in_pktinfo);
+                    if_index = Some(info.ipi_ifindex as u32);
+                }
+                cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+            }
+        }
+
+        let src = SocketAddrV4::new(
+            Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)),
+            u16::from_be(addr.sin_port),
+        );
+        Ok((len as usize, SocketAddr::V4(src), if_index))
+    }
+}
@@ -1,6 +1,9 @@
 // Art-Net Protocol Implementation
 // Art-Net 4 Protocol: https://art-net.org.uk/
 
+use crate::network::timecode::{Timecode, TimecodeRate};
+
+use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
@@ -20,6 +23,7 @@ pub enum ArtNetOpCode {
     OpTodControl = 0x8200,
     OpRdm = 0x8300,
     OpRdmSub = 0x8400,
+    OpTimeCode = 0x9700,
     OpIpProg = 0xf800,
     OpIpProgReply = 0xf900,
     Unknown = 0xFFFF,
@@ -40,6 +44,7 @@ impl From<u16> for ArtNetOpCode {
             0x8200 => ArtNetOpCode::OpTodControl,
             0x8300 => ArtNetOpCode::OpRdm,
             0x8400 => ArtNetOpCode::OpRdmSub,
+            0x9700 => ArtNetOpCode::OpTimeCode,
             0xf800 => ArtNetOpCode::OpIpProg,
             0xf900 => ArtNetOpCode::OpIpProgReply,
             _ => ArtNetOpCode::Unknown,
@@ -116,7 +121,48 @@ pub struct ArtDmx {
     pub physical: u8,
     pub universe: u16, // 15-bit universe (net:subnet:universe)
     pub length: u16,
-    pub data: Vec<u8>,
+    pub data: Bytes, // Slice of the received datagram
+}
+
+/// Parsed ArtNzs packet (DMX512 data with a non-zero start code)
+#[derive(Debug, Clone)]
+pub struct ArtNzs {
+    pub start_code: u8,
+    pub universe: u16, // 15-bit universe (net:subnet:universe)
+    pub data: Bytes,
+}
+
+/// Parsed ArtTodRequest or ArtTodControl - universes a controller wants the ToD for
+#[derive(Debug, Clone)]
+pub struct ArtTodRequest {
+    pub universes: Vec<u16>,
+}
+
+/// Parsed ArtTodData packet - RDM UIDs a node found on one of its ports
+#[derive(Debug, Clone)]
+pub struct ArtTodData {
+    pub port: u8, // 1-4
+    pub bind_index: u8,
+    pub universe: u16, // 15-bit universe (net:subnet:universe)
+    pub full: bool,    // TodFull, false for TodNak (the node has no ToD to send)
+    pub uid_total: u16,
+    pub block: u8, // Packets with more than 200 UIDs are split in blocks from 0
+    pub uids: Vec<[u8; 6]>,
+}
+
+/// Parsed ArtRdm packet
+#[derive(Debug, Clone)]
+pub struct ArtRdm {
+    pub universe: u16, // 15-bit universe (net:subnet:universe)
+    pub command: u8,
+    pub rdm_packet: Vec<u8>, // RDM message without the start code
+}
+
+/// Parsed ArtTimeCode packet - a console or media server's show clock
+#[derive(Debug, Clone)]
+pub struct ArtTimeCode {
+    pub stream_id: u8, // 0 for the master stream
+    pub timecode: Timecode,
 }
 
 /// Result of parsing an Art-Net packet
@@ -125,11 +171,17 @@ pub enum ArtNetPacket {
     Poll,
     PollReply(ArtPollReply),
     Dmx(ArtDmx),
+    Nzs(ArtNzs),
+    TodRequest(ArtTodRequest),
+    TodData(ArtTodData),
+    Rdm(ArtRdm),
+    TimeCode(ArtTimeCode),
     Other(ArtNetOpCode),
 }
 
-/// Parse an Art-Net packet from raw bytes
-pub fn parse_artnet_packet(data: &[u8], _source: SocketAddr) -> Option<ArtNetPacket> {
+/// Parse an Art-Net packet from raw bytes. DMX data is sliced out of the
+/// datagram buffer, not copied.
+pub fn parse_artnet_packet(data: &Bytes, _source: SocketAddr) -> Option<ArtNetPacket> {
     // Minimum packet size check
     if data.len() < 12 {
         return None;
@@ -148,6 +200,12 @@ pub fn parse_artnet_packet(data: &[u8], _source: SocketAddr) -> Option<ArtNetPac
         ArtNetOpCode::OpPoll => Some(ArtNetPacket::Poll),
         ArtNetOpCode::OpPollReply => parse_poll_reply(data),
         ArtNetOpCode::OpDmx => parse_dmx(data),
+        ArtNetOpCode::OpNzs => parse_nzs(data),
+        ArtNetOpCode::OpTodRequest => parse_tod_request(data),
+        ArtNetOpCode::OpTodControl => parse_tod_control(data),
+        ArtNetOpCode::OpTodData => parse_tod_data(data),
+        ArtNetOpCode::OpRdm => parse_rdm(data),
+        ArtNetOpCode::OpTimeCode => parse_timecode(data),
         other => Some(ArtNetPacket::Other(other)),
     }
 }
@@ -241,7 +299,7 @@ fn parse_poll_reply(data: &[u8]) -> Option<ArtNetPacket> {
 }
 
 /// Parse ArtDmx packet
-fn parse_dmx(data: &[u8]) -> Option<ArtNetPacket> {
+fn parse_dmx(data: &Bytes) -> Option<ArtNetPacket> {
     if data.len() < 18 {
         return None;
     }
@@ -269,7 +327,7 @@ fn parse_dmx(data: &[u8]) -> Option<ArtNetPacket> {
         return None;
     }
 
-    let dmx_data = data[18..dmx_end].to_vec();
+    let dmx_data = data.slice(18..dmx_end);
 
     Some(ArtNetPacket::Dmx(ArtDmx {
         sequence,
@@ -280,6 +338,135 @@ fn parse_dmx(data: &[u8]) -> Option<ArtNetPacket> {
     }))
 }
 
+/// Parse ArtNzs packet
+fn parse_nzs(data: &Bytes) -> Option<ArtNetPacket> {
+    if data.len() < 18 {
+        return None;
+    }
+
+    // Sequence (byte 12) - not tracked for alternate start code data
+
+    // Start code (byte 13) - a zero start code must be sent as ArtDmx instead
+    let start_code = data[13];
+    if start_code == 0 {
+        return None;
+    }
+
+    // Universe (bytes 14-15) - SubUni in low byte, Net in high byte
+    let universe = ((data[15] as u16) << 8) | (data[14] as u16);
+
+    // Length (bytes 16-17, big-endian)
+    let length = u16::from_be_bytes([data[16], data[17]]);
+
+    // Data starts at byte 18
+    let data_end = 18 + (length as usize).min(512);
+    if data.len() < data_end {
+        return None;
+    }
+
+    Some(ArtNetPacket::Nzs(ArtNzs {
+        start_code,
+        universe,
+        data: data.slice(18..data_end),
+    }))
+}
+
+/// Parse ArtTodRequest packet
+fn parse_tod_request(data: &[u8]) -> Option<ArtNetPacket> {
+    if data.len() < 24 {
+        return None;
+    }
+
+    // Net (byte 21), Command (byte 22, 0x00 = TodFull), AdCount (byte 23)
+    let net = (data[21] & 0x7F) as u16;
+    let count = (data[23] as usize).min(32);
+
+    // Address list (bytes 24+) - low byte of each requested universe
+    let universes = data[24..]
+        .iter()
+        .take(count)
+        .map(|&address| (net << 8) | address as u16)
+        .collect();
+
+    Some(ArtNetPacket::TodRequest(ArtTodRequest { universes }))
+}
+
+/// Parse ArtTodControl packet - every command is answered with a fresh ToD,
+/// so it is reported as a request for that universe
+fn parse_tod_control(data: &[u8]) -> Option<ArtNetPacket> {
+    if data.len() < 24 {
+        return None;
+    }
+
+    // Net (byte 21), Command (byte 22), Address (byte 23)
+    let universe = ((data[21] as u16 & 0x7F) << 8) | data[23] as u16;
+
+    Some(ArtNetPacket::TodRequest(ArtTodRequest {
+        universes: vec![universe],
+    }))
+}
+
+/// Parse ArtTodData packet
+fn parse_tod_data(data: &[u8]) -> Option<ArtNetPacket> {
+    if data.len() < 28 {
+        return None;
+    }
+
+    // Port (byte 13), BindIndex (byte 20), Net (byte 21),
+    // CommandResponse (byte 22, 0x00 = TodFull), Address (byte 23)
+    let universe = ((data[21] as u16 & 0x7F) << 8) | data[23] as u16;
+
+    // UidTotal (bytes 24-25, high byte first), BlockCount (byte 26), UidCount (byte 27)
+    let uid_count = data[27] as usize;
+    let uids = data[28..]
+        .chunks_exact(6)
+        .take(uid_count)
+        .map(|uid| [uid[0], uid[1], uid[2], uid[3], uid[4], uid[5]])
+        .collect();
+
+    Some(ArtNetPacket::TodData(ArtTodData {
+        port: data[13],
+        bind_index: data[20],
+        universe,
+        full: data[22] == 0x00,
+        uid_total: u16::from_be_bytes([data[24], data[25]]),
+        block: data[26],
+        uids,
+    }))
+}
+
+/// Parse ArtTimeCode packet
+fn parse_timecode(data: &[u8]) -> Option<ArtNetPacket> {
+    if data.len() < 19 {
+        return None;
+    }
+
+    // StreamId (byte 13), Frames, Seconds, Minutes, Hours (bytes 14-17), Type (byte 18)
+    let rate = TimecodeRate::from_code(data[18])?;
+    let timecode = Timecode::new(data[17], data[16], data[15], data[14], rate)?;
+
+    Some(ArtNetPacket::TimeCode(ArtTimeCode {
+        stream_id: data[13],
+        timecode,
+    }))
+}
+
+/// Parse ArtRdm packet
+fn parse_rdm(data: &[u8]) -> Option<ArtNetPacket> {
+    if data.len() < 24 {
+        return None;
+    }
+
+    // Net (byte 21), Command (byte 22, 0x00 = ArProcess), Address (byte 23)
+    let universe = ((data[21] as u16 & 0x7F) << 8) | data[23] as u16;
+
+    Some(ArtNetPacket::Rdm(ArtRdm {
+        universe,
+        command: data[22],
+        rdm_packet: data[24..].to_vec(),
+    }))
+}
+
 /// Extract null-terminated string from bytes
 fn extract_string(data: &[u8]) -> String {
     let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
@@ -316,3 +503,209 @@ pub fn create_artpoll_packet() -> Vec<u8> {
 
     packet
 }
+
+/// Create an ArtDmx packet carrying DMX data for a 15-bit universe
+pub fn create_artdmx_packet(universe: u16, sequence: u8, data: &[u8]) -> Vec<u8> {
+    // Length must be even and in the range 2-512
+    let length = data.len().clamp(2, 512);
+    let length = length + (length & 1);
+
+    let mut packet = Vec::with_capacity(18 + length);
+
+    // Art-Net header
+    packet.extend_from_slice(ARTNET_HEADER);
+
+    // OpCode (little-endian) - OpDmx = 0x5000
+    packet.push(0x00);
+    packet.push(0x50);
+
+    // Protocol version (high byte first) - version 14
+    packet.push(0x00);
+    packet.push(0x0E);
+
+    // Sequence (0 disables sequencing on receivers)
+    packet.push(sequence);
+
+    // Physical port
+    packet.push(0x00);
+
+    // SubUni (low byte) and Net (high byte)
+    packet.push((universe & 0xFF) as u8);
+    packet.push(((universe >> 8) & 0x7F) as u8);
+
+    // Length (big-endian)
+    packet.extend_from_slice(&(length as u16).to_be_bytes());
+
+    // DMX data, zero-padded to the even length
+    let copy_len = data.len().min(length);
+    packet.extend_from_slice(&data[..copy_len]);
+    packet.resize(18 + length, 0);
+
+    packet
+}
+
+/// Create an ArtPollReply packet describing a node
+pub fn create_artpollreply_packet(reply: &ArtPollReply) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(239);
+
+    // Art-Net header
+    packet.extend_from_slice(ARTNET_HEADER);
+
+    // OpCode (little-endian) - OpPollReply = 0x2100
+    packet.push(0x00);
+    packet.push(0x21);
+
+    // IP address and port (little-endian)
+    packet.extend_from_slice(&reply.ip_address);
+    packet.extend_from_slice(&reply.port.to_le_bytes());
+
+    // Firmware version (high byte first), Net/Sub switch
+    packet.extend_from_slice(&reply.version_info.to_be_bytes());
+    packet.push(reply.net_switch);
+    packet.push(reply.sub_switch);
+
+    // OEM (high byte first), UBEA version, Status1, ESTA manufacturer (low byte first)
+    packet.extend_from_slice(&reply.oem.to_be_bytes());
+    packet.push(reply.ubea_version);
+    packet.push(reply.status1);
+    packet.extend_from_slice(&reply.esta_manufacturer.to_le_bytes());
+
+    // Short name (18), long name (64) and node report (64), null terminated
+    push_fixed_string(&mut packet, &reply.short_name, 18);
+    push_fixed_string(&mut packet, &reply.long_name, 64);
+    push_fixed_string(&mut packet, &reply.node_report, 64);
+
+    // NumPorts (high byte first), port types, GoodInput, GoodOutput, SwIn, SwOut
+    packet.extend_from_slice(&reply.num_ports.to_be_bytes());
+    packet.extend_from_slice(&reply.port_types);
+    packet.extend_from_slice(&reply.good_input);
+    packet.extend_from_slice(&reply.good_output);
+    packet.extend_from_slice(&reply.sw_in);
+    packet.extend_from_slice(&reply.sw_out);
+
+    // AcnPriority, SwMacro, SwRemote, 3 spare bytes
+    packet.extend_from_slice(&[0; 6]);
+
+    // Style, MAC address, bind IP, bind index, Status2
+    packet.push(reply.style);
+    packet.extend_from_slice(&reply.mac_address);
+    packet.extend_from_slice(&reply.bind_ip);
+    packet.push(reply.bind_index);
+    packet.push(reply.status2);
+
+    // GoodOutputB, Status3, default responder UID, user, refresh rate and filler
+    packet.resize(239, 0);
+
+    packet
+}
+
+/// Create an ArtTodData packet listing RDM UIDs discovered on a universe
+pub fn create_arttoddata_packet(
+    universe: u16,
+    bind_index: u8,
+    uid_total: u16,
+    block: u8,
+    uids: &[[u8; 6]],
+) -> Vec<u8> {
+    let count = uids.len().min(200);
+    let mut packet = Vec::with_capacity(28 + count * 6);
+
+    // Art-Net header
+    packet.extend_from_slice(ARTNET_HEADER);
+
+    // OpCode (little-endian) - OpTodData = 0x8100
+    packet.push(0x00);
+    packet.push(0x81);
+
+    // Protocol version (high byte first) - version 14
+    packet.push(0x00);
+    packet.push(0x0E);
+
+    // RdmVer (RDM Standard V1.0), Port (1-4), 6 spare bytes, BindIndex
+    packet.push(0x01);
+    packet.push(0x01);
+    packet.extend_from_slice(&[0; 6]);
+    packet.push(bind_index);
+
+    // Net, CommandResponse (0x00 = TodFull), Address
+    packet.push(((universe >> 8) & 0x7F) as u8);
+    packet.push(0x00);
+    packet.push((universe & 0xFF) as u8);
+
+    // UidTotal (high byte first), BlockCount, UidCount, ToD
+    packet.extend_from_slice(&uid_total.to_be_bytes());
+    packet.push(block);
+    packet.push(count as u8);
+    for uid in &uids[..count] {
+        packet.extend_from_slice(uid);
+    }
+
+    packet
+}
+
+/// Create an ArtTodRequest packet asking for the ToD of universes sharing a Net
+pub fn create_arttodrequest_packet(net: u8, addresses: &[u8]) -> Vec<u8> {
+    let count = addresses.len().min(32);
+    let mut packet = Vec::with_capacity(56);
+
+    // Art-Net header
+    packet.extend_from_slice(ARTNET_HEADER);
+
+    // OpCode (little-endian) - OpTodRequest = 0x8000
+    packet.push(0x00);
+    packet.push(0x80);
+
+    // Protocol version (high byte first) - version 14
+    packet.push(0x00);
+    packet.push(0x0E);
+
+    // 2 filler bytes, 7 spare bytes
+    packet.extend_from_slice(&[0; 9]);
+
+    // Net, Command (0x00 = TodFull), AdCount, Address list (32 bytes)
+    packet.push(net & 0x7F);
+    packet.push(0x00);
+    packet.push(count as u8);
+    packet.extend_from_slice(&addresses[..count]);
+    packet.resize(56, 0);
+
+    packet
+}
+
+/// Create an ArtRdm packet carrying an RDM message (without start code)
+pub fn create_artrdm_packet(universe: u16, rdm_packet: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(24 + rdm_packet.len());
+
+    // Art-Net header
+    packet.extend_from_slice(ARTNET_HEADER);
+
+    // OpCode (little-endian) - OpRdm = 0x8300
+    packet.push(0x00);
+    packet.push(0x83);
+
+    // Protocol version (high byte first) - version 14
+    packet.push(0x00);
+    packet.push(0x0E);
+
+    // RdmVer (RDM Standard V1.0), filler, 7 spare bytes
+    packet.push(0x01);
+    packet.push(0x00);
+    packet.extend_from_slice(&[0; 7]);
+
+    // Net, Command (0x00 = ArProcess), Address
+    packet.push(((universe >> 8) & 0x7F) as u8);
+    packet.push(0x00);
+    packet.push((universe & 0xFF) as u8);
+
+    packet.extend_from_slice(rdm_packet);
+
+    packet
+}
+
+/// Append a string as a fixed-size null-terminated field
+fn push_fixed_string(packet: &mut Vec<u8>, value: &str, size: usize) {
+    let bytes = value.as_bytes();
+    let len = bytes.len().min(size - 1);
+    packet.extend_from_slice(&bytes[..len]);
+    packet.resize(packet.len() + size - len, 0);
+}
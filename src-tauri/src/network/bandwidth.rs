@@ -0,0 +1,308 @@
+// Capture bandwidth - Traffic per device seen by the sniffer
+//
+// Every captured frame, lighting or not, is counted against the MAC and IP it
+// came from and classed by protocol, so users can find what is loading the
+// lighting switch. The protocols are grouped into categories to show how much
+// of a supposedly dedicated lighting network carries other traffic. Non-lighting traffic is only seen when the sniffer runs
+// with all traffic captured instead of the Art-Net/sACN port filter.
+
+use crate::network::source::mac_to_string;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Instant;
+
+/// How often byte and packet counts are turned into rates
+const RATE_WINDOW: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// What a protocol is used for, to tell lighting from the traffic that
+/// shouldn't be on a dedicated lighting network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrafficCategory {
+    Lighting,       // Art-Net, sACN
+    Discovery,      // mDNS, SSDP, LLMNR, WS-Discovery
+    NetBios,        // NetBIOS and SMB file sharing
+    Video,          // NDI, RTSP, RTP and MPEG-TS streams
+    Infrastructure, // ARP, DHCP, DNS, PTP and the like
+    Unknown,
+}
+
+impl TrafficCategory {
+    pub fn of(protocol: &str) -> Self {
+        match protocol {
+            "Art-Net" | "sACN" => Self::Lighting,
+            "mDNS" | "SSDP" | "LLMNR" | "WS-Discovery" => Self::Discovery,
+            "NetBIOS" | "SMB" => Self::NetBios,
+            "NDI" | "RTSP" | "RTP" | "MPEG-TS" => Self::Video,
+            "ARP" | "DHCP" | "DNS" | "NTP" | "PTP" | "SNMP" | "ICMP" | "ICMPv6" | "IGMP"
+            | "LLDP" | "802.3 LLC" => Self::Infrastructure,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Traffic of one protocol across all devices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolSummary {
+    pub protocol: String,
+    pub category: TrafficCategory,
+    pub bytes: u64,
+    pub packets: u64,
+    pub devices: usize,     // Devices that sent it
+    pub share_percent: f32, // Of all captured bytes
+}
+
+/// Captured bytes and packets of one category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryTraffic {
+    pub category: TrafficCategory,
+    pub bytes: u64,
+    pub packets: u64,
+    pub share_percent: f32,
+}
+
+/// Captured traffic by category and protocol, showing how much of the
+/// network is taken by traffic other than lighting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrafficSummary {
+    pub total_bytes: u64,
+    pub total_packets: u64,
+    pub non_lighting_percent: f32, // Share of bytes that aren't Art-Net or sACN
+    pub categories: Vec<CategoryTraffic>, // Busiest first
+    pub protocols: Vec<ProtocolSummary>, // Busiest first
+}
+
+/// Traffic of one protocol from a device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolTraffic {
+    pub protocol: String, // e.g. "sACN", "mDNS", "TCP", "ARP"
+    pub bytes: u64,
+    pub packets: u64,
+}
+
+/// Traffic captured from one device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceBandwidth {
+    pub mac: Option<String>, // None when the link layer carries no sender MAC
+    pub ip: Option<String>,  // None for non-IP frames (ARP, LLDP, ...)
+    pub bytes_per_second: f32,
+    pub packets_per_second: f32,
+    pub total_bytes: u64,
+    pub total_packets: u64,
+    pub lighting_bytes: u64, // Art-Net and sACN share of total_bytes
+    pub protocols: Vec<ProtocolTraffic>, // Busiest first
+    pub last_seen_secs: f32, // Seconds since the last frame
+}
+
+struct DeviceCounter {
+    total_bytes: u64,
+    total_packets: u64,
+    lighting_bytes: u64,
+    window_bytes: u64,
+    window_packets: u64,
+    bytes_per_second: f32,
+    packets_per_second: f32,
+    protocols: HashMap<String, (u64, u64)>, // Bytes and packets
+    last_seen: Instant,
+}
+
+type DeviceKey = (Option<[u8; 6]>, Option<IpAddr>);
+
+/// Bytes and packets per sending device since the sniffer started
+pub struct BandwidthTable {
+    devices: Mutex<HashMap<DeviceKey, DeviceCounter>>,
+    window_start: Mutex<Instant>,
+}
+
+impl BandwidthTable {
+    pub fn new() -> Self {
+        Self {
+            devices: Mutex::new(HashMap::new()),
+            window_start: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Count a captured frame against its sender
+    #[cfg(feature = "sniffer")]
+    pub fn record(
+        &self,
+        mac: Option<[u8; 6]>,
+        ip: Option<IpAddr>,
+        protocol: &str,
+        bytes: usize,
+        lighting: bool,
+    ) {
+        let bytes = bytes as u64;
+        let mut devices = self.devices.lock();
+        let counter = devices.entry((mac, ip)).or_insert_with(|| DeviceCounter {
+            total_bytes: 0,
+            total_packets: 0,
+            lighting_bytes: 0,
+            window_bytes: 0,
+            window_packets: 0,
+            bytes_per_second: 0.0,
+            packets_per_second: 0.0,
+            protocols: HashMap::new(),
+            last_seen: Instant::now(),
+        });
+        counter.total_bytes += bytes;
+        counter.total_packets += 1;
+        counter.window_bytes += bytes;
+        counter.window_packets += 1;
+        if lighting {
+            counter.lighting_bytes += bytes;
+        }
+        counter.last_seen = Instant::now();
+        match counter.protocols.get_mut(protocol) {
+            Some((protocol_bytes, packets)) => {
+                *protocol_bytes += bytes;
+                *packets += 1;
+            }
+            None => {
+                counter.protocols.insert(protocol.to_string(), (bytes, 1));
+            }
+        }
+    }
+
+    /// Turn the counts of the last window into rates once the window has passed
+    pub fn roll_if_due(&self) {
+        let mut window_start = self.window_start.lock();
+        let elapsed = window_start.elapsed();
+        if elapsed < RATE_WINDOW {
+            return;
+        }
+        let secs = elapsed.as_secs_f32();
+        for counter in self.devices.lock().values_mut() {
+            counter.bytes_per_second = counter.window_bytes as f32 / secs;
+            counter.packets_per_second = counter.window_packets as f32 / secs;
+            counter.window_bytes = 0;
+            counter.window_packets = 0;
+        }
+        *window_start = Instant::now();
+    }
+
+    pub fn clear(&self) {
+        self.devices.lock().clear();
+        *self.window_start.lock() = Instant::now();
+    }
+
+    /// Devices ordered by current byte rate, busiest first. Rolling here too
+    /// lets the rates fall to zero once capture stops.
+    pub fn get_devices(&self) -> Vec<DeviceBandwidth> {
+        self.roll_if_due();
+        let mut devices: Vec<DeviceBandwidth> = self
+            .devices
+            .lock()
+            .iter()
+            .map(|((mac, ip), counter)| {
+                let mut protocols: Vec<ProtocolTraffic> = counter
+                    .protocols
+                    .iter()
+                    .map(|(protocol, (bytes, packets))| ProtocolTraffic {
+                        protocol: protocol.clone(),
+                        bytes: *bytes,
+                        packets: *packets,
+                    })
+                    .collect();
+                protocols.sort_by_key(|p| std::cmp::Reverse(p.bytes));
+                DeviceBandwidth {
+                    mac: mac.as_ref().map(mac_to_string),
+                    ip: ip.map(|ip| ip.to_string()),
+                    bytes_per_second: counter.bytes_per_second,
+                    packets_per_second: counter.packets_per_second,
+                    total_bytes: counter.total_bytes,
+                    total_packets: counter.total_packets,
+                    lighting_bytes: counter.lighting_bytes,
+                    protocols,
+                    last_seen_secs: counter.last_seen.elapsed().as_secs_f32(),
+                }
+            })
+            .collect();
+        devices.sort_by(|a, b| {
+            b.bytes_per_second
+                .total_cmp(&a.bytes_per_second)
+                .then(b.total_bytes.cmp(&a.total_bytes))
+        });
+        devices
+    }
+
+    /// Totals per protocol and category over all devices since the sniffer started
+    pub fn get_summary(&self) -> TrafficSummary {
+        let mut protocols: HashMap<&str, ProtocolSummary> = HashMap::new();
+        let devices = self.devices.lock();
+        for counter in devices.values() {
+            for (protocol, (bytes, packets)) in &counter.protocols {
+                let summary =
+                    protocols
+                        .entry(protocol.as_str())
+                        .or_insert_with(|| ProtocolSummary {
+                            protocol: protocol.clone(),
+                            category: TrafficCategory::of(protocol),
+                            bytes: 0,
+                            packets: 0,
+                            devices: 0,
+                            share_percent: 0.0,
+                        });
+                summary.bytes += bytes;
+                summary.packets += packets;
+                summary.devices += 1;
+            }
+        }
+
+        let total_bytes: u64 = protocols.values().map(|p| p.bytes).sum();
+        let total_packets: u64 = protocols.values().map(|p| p.packets).sum();
+        let share = |bytes: u64| {
+            if total_bytes > 0 {
+                bytes as f32 / total_bytes as f32 * 100.0
+            } else {
+                0.0
+            }
+        };
+
+        let mut categories: HashMap<TrafficCategory, CategoryTraffic> = HashMap::new();
+        for summary in protocols.values() {
+            let category = categories
+                .entry(summary.category)
+                .or_insert_with(|| CategoryTraffic {
+                    category: summary.category,
+                    bytes: 0,
+                    packets: 0,
+                    share_percent: 0.0,
+                });
+            category.bytes += summary.bytes;
+            category.packets += summary.packets;
+        }
+        let mut categories: Vec<CategoryTraffic> = categories.into_values().collect();
+        for category in &mut categories {
+            category.share_percent = share(category.bytes);
+        }
+        categories.sort_by_key(|c| std::cmp::Reverse(c.bytes));
+
+        let mut protocols: Vec<ProtocolSummary> = protocols.into_values().collect();
+        for protocol in &mut protocols {
+            protocol.share_percent = share(protocol.bytes);
+        }
+        protocols.sort_by_key(|p| std::cmp::Reverse(p.bytes));
+
+        let lighting_bytes = categories
+            .iter()
+            .find(|c| c.category == TrafficCategory::Lighting)
+            .map_or(0, |c| c.bytes);
+        TrafficSummary {
+            total_bytes,
+            total_packets,
+            non_lighting_percent: share(total_bytes - lighting_bytes),
+            categories,
+            protocols,
+        }
+    }
+}
+
+impl Default for BandwidthTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,218 @@
+// Event bus - Broadcast channels carrying listener events to the forwarder and router
+//
+// A consumer that falls behind loses the oldest events of its channel. DMX events
+// vastly outnumber everything else, so with the separate policy they get their
+// own channel: falling behind on DMX then drops DMX frames only, never source
+// updates or alerts.
+
+use crate::network::listener::ListenerEvent;
+use crate::persistence::{load_json, save_json};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// Accepted channel capacities
+const MIN_EVENT_CAPACITY: usize = 16;
+const MAX_EVENT_CAPACITY: usize = 100_000;
+
+/// What a slow consumer loses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackpressurePolicy {
+    /// One channel for all events, any event may be dropped
+    Shared,
+    /// DMX on its own channel, only the oldest DMX events are dropped
+    Separate,
+}
+
+/// Event bus configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventBusConfig {
+    pub capacity: usize, // Events buffered per channel
+    pub policy: BackpressurePolicy,
+}
+
+impl Default for EventBusConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1000,
+            policy: BackpressurePolicy::Separate,
+        }
+    }
+}
+
+/// Event bus configuration with the events lost by lagging consumers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventBusStats {
+    pub config: EventBusConfig,
+    /// Lost from the DMX channel, or from the single channel when shared
+    pub dmx_lagged: u64,
+    /// Lost from the control channel (source updates and alerts)
+    pub control_lagged: u64,
+    pub receivers: usize,
+}
+
+struct Channels {
+    dmx: broadcast::Sender<ListenerEvent>,
+    control: broadcast::Sender<ListenerEvent>, // Same channel as dmx when shared
+}
+
+impl Channels {
+    fn new(config: &EventBusConfig) -> Self {
+        let (dmx, _) = broadcast::channel(config.capacity);
+        let control = match config.policy {
+            BackpressurePolicy::Shared => dmx.clone(),
+            BackpressurePolicy::Separate => broadcast::channel(config.capacity).0,
+        };
+        Self { dmx, control }
+    }
+
+    fn is_shared(&self) -> bool {
+        self.dmx.same_channel(&self.control)
+    }
+}
+
+/// Listener event channels. Reconfiguring replaces the channels, receivers
+/// move to the new ones by themselves.
+pub struct EventBus {
+    config: RwLock<EventBusConfig>,
+    config_path: RwLock<Option<PathBuf>>,
+    channels: RwLock<Channels>,
+    dmx_lagged: AtomicU64,
+    control_lagged: AtomicU64,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let config = EventBusConfig::default();
+        Self {
+            channels: RwLock::new(Channels::new(&config)),
+            config: RwLock::new(config),
+            config_path: RwLock::new(None),
+            dmx_lagged: AtomicU64::new(0),
+            control_lagged: AtomicU64::new(0),
+        }
+    }
+
+    /// Load the configuration from disk and persist future changes to the same file
+    pub fn load_config(&self, path: PathBuf) {
+        let config: EventBusConfig = load_json(&path);
+        *self.config_path.write() = Some(path);
+        if let Err(e) = self.apply(config) {
+            warn!("[Events] Ignoring saved event bus configuration: {}", e);
+        }
+    }
+
+    pub fn set_config(&self, config: EventBusConfig) -> Result<(), String> {
+        self.apply(config)?;
+        match self.config_path.read().as_ref() {
+            Some(path) => save_json(path, &*self.config.read()),
+            None => Ok(()),
+        }
+    }
+
+    fn apply(&self, config: EventBusConfig) -> Result<(), String> {
+        if !(MIN_EVENT_CAPACITY..=MAX_EVENT_CAPACITY).contains(&config.capacity) {
+            return Err(format!(
+                "Event capacity must be between {} and {}",
+                MIN_EVENT_CAPACITY, MAX_EVENT_CAPACITY
+            ));
+        }
+        *self.channels.write() = Channels::new(&config);
+        *self.config.write() = config;
+        Ok(())
+    }
+
+    /// Send an event to every receiver, on the channel of its class
+    pub fn send(
+        &self,
+        event: ListenerEvent,
+    ) -> Result<usize, broadcast::error::SendError<ListenerEvent>> {
+        let channels = self.channels.read();
+        match event {
+            ListenerEvent::DmxData(_) => channels.dmx.send(event),
+            _ => channels.control.send(event),
+        }
+    }
+
+    pub fn subscribe(self: &Arc<Self>) -> EventReceiver {
+        let channels = self.channels.read();
+        EventReceiver {
+            bus: self.clone(),
+            dmx: channels.dmx.subscribe(),
+            control: (!channels.is_shared()).then(|| channels.control.subscribe()),
+        }
+    }
+
+    /// Events waiting for the slowest receiver on the DMX and control channels.
+    /// With the shared policy everything is counted as DMX.
+    pub fn queued(&self) -> (usize, usize) {
+        let channels = self.channels.read();
+        if channels.is_shared() {
+            (channels.dmx.len(), 0)
+        } else {
+            (channels.dmx.len(), channels.control.len())
+        }
+    }
+
+    pub fn get_stats(&self) -> EventBusStats {
+        EventBusStats {
+            config: self.config.read().clone(),
+            dmx_lagged: self.dmx_lagged.load(Ordering::Relaxed),
+            control_lagged: self.control_lagged.load(Ordering::Relaxed),
+            receivers: self.channels.read().dmx.receiver_count(),
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type EventBusHandle = Arc<EventBus>;
+
+/// Receiving end of the event bus
+pub struct EventReceiver {
+    bus: EventBusHandle,
+    dmx: broadcast::Receiver<ListenerEvent>,
+    control: Option<broadcast::Receiver<ListenerEvent>>, // None when shared
+}
+
+impl EventReceiver {
+    /// Wait for the next event, control events first. Lost events are counted
+    /// and reported as Lagged; a reconfigured bus is resubscribed transparently.
+    pub async fn recv(&mut self) -> Result<ListenerEvent, broadcast::error::RecvError> {
+        loop {
+            let (result, is_dmx) = match &mut self.control {
+                Some(control) => tokio::select! {
+                    biased;
+                    result = control.recv() => (result, false),
+                    result = self.dmx.recv() => (result, true),
+                },
+                None => (self.dmx.recv().await, true),
+            };
+            match result {
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    let lagged = if is_dmx {
+                        &self.bus.dmx_lagged
+                    } else {
+                        &self.bus.control_lagged
+                    };
+                    lagged.fetch_add(n, Ordering::Relaxed);
+                    return Err(broadcast::error::RecvError::Lagged(n));
+                }
+                // The bus replaced its channels
+                Err(broadcast::error::RecvError::Closed) => *self = self.bus.subscribe(),
+                result => return result,
+            }
+        }
+    }
+}
@@ -0,0 +1,514 @@
+// Protocol Decoders - Plugin API for protocols beyond Art-Net and sACN
+//
+// Support for another protocol (KiNET, Pathport, proprietary formats) is added
+// by implementing ProtocolDecoder and registering it before the listeners start:
+//
+//   listener_state.decoders.register(Arc::new(KinetDecoder))?;
+//
+// Each decoder gets a listener per port, covering the configured interfaces and
+// joining its multicast groups on each, which restarts and stops with the Art-Net and sACN
+// listeners. Datagrams go through the same parser worker pool and counters.
+// What decode() returns is recorded like built-in traffic: the sender appears
+// as a source with protocol "other" and the decoder's name, and universe levels
+// reach the DMX store and the event bus, so universe views, recording, routing
+// and alerts see them. Timecode a decoder finds, e.g. MIDI timecode, is
+// compared with the other timecode feeds.
+
+use crate::network::arrival::{
+    enable_arrival_interface, recv_with_interface, ArrivalFilter, ListenTarget,
+};
+use crate::network::artnet::ARTNET_PORT;
+use crate::network::bus::EventBusHandle;
+use crate::network::listener::{
+    bind_listener_socket, Datagram, DmxData, DmxStoreHandle, ListenerEvent, ListenerStateHandle,
+    PacketCounters, ParserPool, LISTENER_STOP_CHECK_INTERVAL,
+};
+use crate::network::sacn::SACN_PORT;
+use crate::network::source::{Protocol, SourceDirection, SourceManagerHandle};
+use crate::network::timecode::Timecode;
+
+use bytes::Bytes;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::net::UdpSocket;
+use tracing::{info, warn};
+
+/// A protocol the listeners can receive in addition to Art-Net and sACN
+pub trait ProtocolDecoder: Send + Sync {
+    /// Short lowercase name, e.g. "kinet". Prefixes the ids of its sources.
+    fn name(&self) -> &str;
+
+    /// UDP ports to listen on
+    fn ports(&self) -> Vec<u16>;
+
+    /// Multicast groups to join on each interface
+    fn multicast_groups(&self) -> Vec<Ipv4Addr> {
+        Vec::new()
+    }
+
+    /// Decode a received datagram, None when it is not a packet of this
+    /// protocol (counted as a parse failure). Called from several parser
+    /// workers at once, datagrams of one sender always from the same worker.
+    fn decode(&self, data: &Bytes, src: SocketAddr) -> Option<DecodedPacket>;
+}
+
+/// What a decoder found in a datagram
+#[derive(Debug, Clone, Default)]
+pub struct DecodedPacket {
+    /// Name the sender announces, if the packet carries one
+    pub source_name: Option<String>,
+    /// Universes the sender advertises without carrying their data, e.g. in a
+    /// discovery reply
+    pub universes: Vec<u16>,
+    /// Universe levels carried by the packet
+    pub dmx: Vec<DecodedDmx>,
+    /// Timecode carried by the packet, see Timecode::from_mtc_full_frame and
+    /// MtcQuarterFrames for MIDI timecode
+    pub timecode: Option<Timecode>,
+}
+
+/// Levels of one universe
+#[derive(Debug, Clone)]
+pub struct DecodedDmx {
+    pub universe: u16,
+    pub data: Bytes, // Up to 512 channel levels, without a start code
+    /// Packet sequence number for loss tracking, None if the protocol has none
+    pub sequence: Option<u8>,
+}
+
+/// A registered decoder for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecoderInfo {
+    pub name: String,
+    pub ports: Vec<u16>,
+    pub multicast_groups: Vec<String>,
+    pub listening: Vec<String>, // Addresses with a running listener
+}
+
+/// Registered protocol decoders and their running listeners
+pub struct DecoderRegistry {
+    decoders: RwLock<Vec<Arc<dyn ProtocolDecoder>>>,
+    /// Addresses with a running listener, per decoder name
+    running: Mutex<Vec<(String, SocketAddrV4)>>,
+}
+
+impl DecoderRegistry {
+    pub fn new() -> Self {
+        Self {
+            decoders: RwLock::new(Vec::new()),
+            running: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Add a decoder. Its listeners start with the next (re)start of the
+    /// listeners.
+    pub fn register(&self, decoder: Arc<dyn ProtocolDecoder>) -> Result<(), String> {
+        let name = decoder.name();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            return Err(format!(
+                "Invalid decoder name \"{}\" (use lowercase letters, digits and _)",
+                name
+            ));
+        }
+        // Source ids of the built-in protocols start with these
+        if name == "artnet" || name == "sacn" {
+            return Err(format!("Decoder name \"{}\" is reserved", name));
+        }
+        let ports = decoder.ports();
+        if ports.is_empty() {
+            return Err(format!("Decoder {} has no ports", name));
+        }
+        if let Some(port) = ports
+            .iter()
+            .find(|p| **p == ARTNET_PORT || **p == SACN_PORT)
+        {
+            return Err(format!(
+                "Decoder {} can't use port {}, it belongs to Art-Net or sACN",
+                name, port
+            ));
+        }
+
+        let mut decoders = self.decoders.write();
+        if decoders.iter().any(|d| d.name() == name) {
+            return Err(format!("Decoder {} is already registered", name));
+        }
+        info!("[Decoder] Registered {} on ports {:?}", name, ports);
+        decoders.push(decoder);
+        Ok(())
+    }
+
+    pub fn decoders(&self) -> Vec<Arc<dyn ProtocolDecoder>> {
+        self.decoders.read().clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.decoders.read().is_empty()
+    }
+
+    /// Whether a decoder listener is running on any interface
+    pub fn is_running(&self) -> bool {
+        !self.running.lock().is_empty()
+    }
+
+    pub fn get_status(&self) -> Vec<DecoderInfo> {
+        let running = self.running.lock();
+        self.decoders
+            .read()
+            .iter()
+            .map(|decoder| DecoderInfo {
+                name: decoder.name().to_string(),
+                ports: decoder.ports(),
+                multicast_groups: decoder
+                    .multicast_groups()
+                    .iter()
+                    .map(|group| group.to_string())
+                    .collect(),
+                listening: running
+                    .iter()
+                    .filter(|(name, _)| name == decoder.name())
+                    .map(|(_, addr)| addr.to_string())
+                    .collect(),
+            })
+            .collect()
+    }
+
+    fn mark_running(&self, name: &str, addr: SocketAddrV4, is_running: bool) {
+        let mut running = self.running.lock();
+        running.retain(|(n, a)| n != name || *a != addr);
+        if is_running {
+            running.push((name.to_string(), addr));
+        }
+    }
+}
+
+impl Default for DecoderRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Everything a decoder parser worker needs to handle a datagram
+struct DecoderHandler {
+    decoder: Arc<dyn ProtocolDecoder>,
+    source_manager: SourceManagerHandle,
+    dmx_store: DmxStoreHandle,
+    event_tx: EventBusHandle,
+    listener_state: ListenerStateHandle,
+    counters: Arc<PacketCounters>,
+}
+
+impl DecoderHandler {
+    fn handle(
+        &self,
+        Datagram {
+            data,
+            src,
+            interface,
+        }: Datagram,
+    ) {
+        let Some(packet) = self.decoder.decode(&data, src) else {
+            self.counters.parse_failures.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+        let name = self.decoder.name();
+        let source_name = packet.source_name.as_deref().unwrap_or_default();
+        let source_manager = &self.source_manager;
+        source_manager.record_traffic(src.ip(), Protocol::Other, data.len(), interface);
+
+        if !packet.universes.is_empty() || packet.dmx.is_empty() {
+            source_manager.update_decoded_source(
+                src.ip(),
+                name,
+                source_name,
+                &packet.universes,
+                SourceDirection::Unknown,
+                None,
+            );
+            let _ = self.event_tx.send(ListenerEvent::SourcesUpdated);
+        }
+
+        if let Some(timecode) = packet.timecode {
+            let source_id = format!("{}-{}", name, src.ip());
+            for alert in self.listener_state.timecode.record(&source_id, 0, timecode) {
+                warn!("[Timecode] {}", alert.message);
+                let _ = self.event_tx.send(ListenerEvent::TimecodeDrift(alert));
+            }
+        }
+
+        for dmx in packet.dmx {
+            source_manager.update_decoded_source(
+                src.ip(),
+                name,
+                source_name,
+                &[dmx.universe],
+                SourceDirection::Sending,
+                dmx.sequence,
+            );
+            let changed = self.dmx_store.update(dmx.universe, src.ip(), &dmx.data);
+            let _ = self.event_tx.send(ListenerEvent::DmxData(DmxData {
+                universe: dmx.universe,
+                data: dmx.data,
+                protocol: Protocol::Other,
+                source_ip: src.ip(),
+                source_port: src.port(),
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64,
+                interface,
+                changed,
+            }));
+        }
+    }
+}
+
+/// Start the listener of a decoder on one port
+pub async fn start_decoder_listener(
+    decoder: Arc<dyn ProtocolDecoder>,
+    source_manager: SourceManagerHandle,
+    dmx_store: DmxStoreHandle,
+    event_tx: EventBusHandle,
+    listener_state: ListenerStateHandle,
+    target: ListenTarget,
+    port: u16,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let generation = listener_state.generation();
+    let name = decoder.name().to_string();
+    let addr = SocketAddr::new(IpAddr::V4(target.bind_addr), port);
+    let groups = decoder.multicast_groups();
+
+    // Multicast ports stay shared like sACN's
+    let socket = bind_listener_socket(&listener_state, Protocol::Other, addr, !groups.is_empty())?;
+    for interface in &target.interfaces {
+        for group in &groups {
+            if let Err(e) = socket.join_multicast_v4(group, interface) {
+                warn!(
+                    "[Decoder] {} failed to join {} on {}: {}",
+                    name, group, interface, e
+                );
+            }
+        }
+    }
+    let socket = UdpSocket::from_std(socket.into())?;
+    enable_arrival_interface(&socket)?;
+
+    info!(
+        "[Decoder] {} listening on {} for {:?}",
+        name, addr, target.interfaces
+    );
+    let running_addrs: Vec<SocketAddrV4> = target
+        .interfaces
+        .iter()
+        .map(|interface| SocketAddrV4::new(*interface, port))
+        .collect();
+    for running_addr in &running_addrs {
+        listener_state
+            .decoders
+            .mark_running(&name, *running_addr, true);
+    }
+    let counters = listener_state
+        .listener_counters(Protocol::Other, SocketAddrV4::new(target.bind_addr, port));
+
+    let handler = Arc::new(DecoderHandler {
+        decoder,
+        source_manager,
+        dmx_store,
+        event_tx,
+        listener_state: listener_state.clone(),
+        counters: counters.clone(),
+    });
+    let parsers = ParserPool::spawn(counters.clone(), move |datagram| {
+        let handler = handler.clone();
+        async move { handler.handle(datagram) }
+    });
+
+    let mut buf = vec![0u8; 1500];
+    let mut arrival = ArrivalFilter::new(&target);
+
+    while !listener_state.should_stop(generation, Protocol::Other) {
+        // Time out regularly so a stop request is noticed on a quiet network
+        let Ok(result) = tokio::time::timeout(
+            LISTENER_STOP_CHECK_INTERVAL,
+            recv_with_interface(&socket, &mut buf),
+        )
+        .await
+        else {
+            continue;
+        };
+        match result {
+            Ok((len, src, if_index)) => {
+                // Traffic of interfaces that aren't selected
+                let Some(interface) = arrival.accept(if_index) else {
+                    continue;
+                };
+                counters.record_packet(len);
+                let datagram = Datagram {
+                    data: Bytes::copy_from_slice(&buf[..len]),
+                    src,
+                    interface,
+                };
+                if !parsers.dispatch(datagram) {
+                    counters.queue_drops.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            Err(e) => {
+                if e.kind() != std::io::ErrorKind::WouldBlock {
+                    counters.receive_errors.fetch_add(1, Ordering::Relaxed);
+                    warn!("[Decoder] {} receive error: {}", name, e);
+                }
+            }
+        }
+    }
+
+    info!("[Decoder] {} stopped listening on {}", name, addr);
+    for running_addr in &running_addrs {
+        listener_state
+            .decoders
+            .mark_running(&name, *running_addr, false);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::bus::EventBus;
+    use crate::network::listener::{DmxStore, ListenerState};
+    use crate::network::source::SourceManager;
+
+    /// Minimal protocol: "TEST", a big-endian universe, then the levels
+    struct TestDecoder {
+        name: &'static str,
+        ports: Vec<u16>,
+    }
+
+    impl TestDecoder {
+        fn new(name: &'static str, ports: Vec<u16>) -> Arc<Self> {
+            Arc::new(Self { name, ports })
+        }
+    }
+
+    impl ProtocolDecoder for TestDecoder {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn ports(&self) -> Vec<u16> {
+            self.ports.clone()
+        }
+
+        fn decode(&self, data: &Bytes, _src: SocketAddr) -> Option<DecodedPacket> {
+            let [b'T', b'E', b'S', b'T', high, low, ..] = data[..] else {
+                return None;
+            };
+            Some(DecodedPacket {
+                dmx: vec![DecodedDmx {
+                    universe: u16::from_be_bytes([high, low]),
+                    data: data.slice(6..),
+                    sequence: None,
+                }],
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn register_accepts_a_valid_decoder() {
+        let registry = DecoderRegistry::new();
+        assert!(registry
+            .register(TestDecoder::new("test", vec![6038]))
+            .is_ok());
+        assert_eq!(registry.decoders().len(), 1);
+    }
+
+    #[test]
+    fn register_rejects_reserved_and_invalid_names() {
+        let registry = DecoderRegistry::new();
+        for name in ["artnet", "sacn", "", "Test", "te-st"] {
+            assert!(
+                registry
+                    .register(TestDecoder::new(name, vec![6038]))
+                    .is_err(),
+                "{:?} was accepted",
+                name
+            );
+        }
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn register_rejects_artnet_and_sacn_ports() {
+        let registry = DecoderRegistry::new();
+        for port in [ARTNET_PORT, SACN_PORT] {
+            assert!(registry
+                .register(TestDecoder::new("test", vec![6038, port]))
+                .is_err());
+        }
+        assert!(registry
+            .register(TestDecoder::new("test", Vec::new()))
+            .is_err());
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn register_rejects_duplicate_names() {
+        let registry = DecoderRegistry::new();
+        registry
+            .register(TestDecoder::new("test", vec![6038]))
+            .unwrap();
+        assert!(registry
+            .register(TestDecoder::new("test", vec![6039]))
+            .is_err());
+        assert_eq!(registry.decoders().len(), 1);
+    }
+
+    fn test_handler() -> DecoderHandler {
+        DecoderHandler {
+            decoder: TestDecoder::new("test", vec![6038]),
+            source_manager: Arc::new(SourceManager::new()),
+            dmx_store: Arc::new(DmxStore::new()),
+            event_tx: Arc::new(EventBus::new()),
+            listener_state: Arc::new(ListenerState::new()),
+            counters: Arc::new(PacketCounters::default()),
+        }
+    }
+
+    #[test]
+    fn handle_records_levels_and_source() {
+        let handler = test_handler();
+        let src: SocketAddr = "10.0.0.5:6038".parse().unwrap();
+        handler.handle(Datagram {
+            data: Bytes::from_static(b"TEST\x00\x07\xff\x80\x00"),
+            src,
+            interface: None,
+        });
+
+        assert_eq!(handler.dmx_store.get(7), Some(vec![0xFF, 0x80, 0x00]));
+        let sources = handler.source_manager.get_all_sources(true);
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].id, "test-10.0.0.5");
+        assert_eq!(sources[0].protocol, Protocol::Other);
+        assert!(sources[0].universes.contains(&7));
+        assert_eq!(handler.counters.parse_failures.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn handle_counts_undecodable_datagrams() {
+        let handler = test_handler();
+        handler.handle(Datagram {
+            data: Bytes::from_static(b"NOPE"),
+            src: "10.0.0.5:6038".parse().unwrap(),
+            interface: None,
+        });
+
+        assert_eq!(handler.counters.parse_failures.load(Ordering::Relaxed), 1);
+        assert!(handler.source_manager.get_all_sources(true).is_empty());
+    }
+}
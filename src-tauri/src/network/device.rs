@@ -0,0 +1,149 @@
+// Device module - Correlates per-protocol sources into physical devices
+//
+// A gateway answering ArtPoll and transmitting sACN shows up as one Art-Net and
+// one sACN source. Sources sharing an IP or a MAC address are grouped into a
+// single device record that keeps the per-protocol sources as sub-entries.
+// Hosts only known from ARP, which never sent a lighting protocol, are listed
+// as devices without sources.
+
+use crate::network::source::{ArpMapping, NetworkSource, Protocol, SourceStatus};
+use serde::{Deserialize, Serialize};
+
+/// A physical device with every source it appears as
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkDevice {
+    pub id: String,
+    pub name: String,
+    pub ips: Vec<String>,
+    pub mac_address: Option<String>,
+    pub mac_vendor: Option<String>,
+    pub protocols: Vec<Protocol>,
+    pub universes: Vec<u16>,
+    pub status: SourceStatus, // Most active status of its sources
+    pub sources: Vec<NetworkSource>,
+}
+
+/// Group sources that share an IP or MAC address into devices, adding the ARP
+/// hosts that match none of them
+pub fn group_devices(
+    sources: Vec<NetworkSource>,
+    arp_hosts: Vec<ArpMapping>,
+) -> Vec<NetworkDevice> {
+    let mut groups: Vec<Vec<NetworkSource>> = Vec::new();
+
+    for source in sources {
+        // Every existing group this source links to, merged into the first
+        let linked: Vec<usize> = groups
+            .iter()
+            .enumerate()
+            .filter(|(_, group)| group.iter().any(|other| same_device(&source, other)))
+            .map(|(i, _)| i)
+            .collect();
+
+        match linked.split_first() {
+            Some((&first, rest)) => {
+                for &i in rest.iter().rev() {
+                    let merged = groups.remove(i);
+                    groups[first].extend(merged);
+                }
+                groups[first].push(source);
+            }
+            None => groups.push(vec![source]),
+        }
+    }
+
+    let mut devices: Vec<NetworkDevice> = groups.into_iter().map(build_device).collect();
+    for host in arp_hosts {
+        let known = devices.iter().any(|device| {
+            device.ips.contains(&host.ip)
+                || device.mac_address.as_deref() == Some(host.mac.as_str())
+        });
+        if !known {
+            devices.push(arp_device(host));
+        }
+    }
+    devices.sort_by(|a, b| a.ips.cmp(&b.ips));
+    devices
+}
+
+fn same_device(a: &NetworkSource, b: &NetworkSource) -> bool {
+    a.ip == b.ip || (a.mac_address.is_some() && a.mac_address == b.mac_address)
+}
+
+fn build_device(mut sources: Vec<NetworkSource>) -> NetworkDevice {
+    sources.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut ips: Vec<String> = sources.iter().map(|s| s.ip.clone()).collect();
+    ips.sort();
+    ips.dedup();
+
+    let mut protocols: Vec<Protocol> = Vec::new();
+    let mut universes: Vec<u16> = Vec::new();
+    for source in &sources {
+        if !protocols.contains(&source.protocol) {
+            protocols.push(source.protocol);
+        }
+        universes.extend(&source.universes);
+    }
+    universes.sort();
+    universes.dedup();
+
+    // A user alias wins, then the Art-Net node name, then any source name
+    let name = sources
+        .iter()
+        .find_map(|s| s.alias.clone())
+        .or_else(|| {
+            sources
+                .iter()
+                .find(|s| {
+                    s.protocol == Protocol::ArtNet
+                        && s.artnet_long_name.as_ref().is_some_and(|n| !n.is_empty())
+                })
+                .map(|s| s.name.clone())
+        })
+        .unwrap_or_else(|| sources[0].name.clone());
+
+    let mac_source = sources.iter().find(|s| s.mac_address.is_some());
+    let status = sources
+        .iter()
+        .map(|s| s.status)
+        .min_by_key(|status| status_rank(*status))
+        .unwrap_or(SourceStatus::Offline);
+
+    NetworkDevice {
+        id: format!("device-{}", ips[0]),
+        name,
+        ips,
+        mac_address: mac_source.and_then(|s| s.mac_address.clone()),
+        mac_vendor: mac_source.and_then(|s| s.mac_vendor.clone()),
+        protocols,
+        universes,
+        status,
+        sources,
+    }
+}
+
+/// A host that answered ARP but never sent Art-Net or sACN
+fn arp_device(host: ArpMapping) -> NetworkDevice {
+    NetworkDevice {
+        id: format!("device-{}", host.ip),
+        name: host.ip.clone(),
+        ips: vec![host.ip],
+        mac_address: Some(host.mac),
+        mac_vendor: host.mac_vendor,
+        protocols: Vec::new(),
+        universes: Vec::new(),
+        status: SourceStatus::Idle, // Present, but not sending lighting data
+        sources: Vec::new(),
+    }
+}
+
+/// Lower is more active
+fn status_rank(status: SourceStatus) -> u8 {
+    match status {
+        SourceStatus::Active => 0,
+        SourceStatus::Idle => 1,
+        SourceStatus::Stale => 2,
+        SourceStatus::Offline => 3,
+    }
+}
@@ -0,0 +1,642 @@
+// Emulator - virtual devices that answer controllers on the network
+//
+// The Art-Net listener hands ArtPoll, ArtTodRequest/ArtTodControl and ArtRdm
+// packets to the emulators here and sends back whatever replies they build.
+
+use crate::network::artnet::{
+    create_artpollreply_packet, create_artrdm_packet, create_arttoddata_packet, ArtPollReply,
+};
+use crate::network::rdm::{
+    create_rdm_message, parse_rdm_message, parse_uid, uid_is_broadcast, uid_matches, uid_to_string,
+    RdmMessage, RdmUid, CC_GET_COMMAND, CC_SET_COMMAND, NR_DATA_OUT_OF_RANGE, NR_FORMAT_ERROR,
+    NR_SUB_DEVICE_OUT_OF_RANGE, NR_UNKNOWN_PID, NR_UNSUPPORTED_COMMAND_CLASS, PID_DEVICE_INFO,
+    PID_DEVICE_LABEL, PID_DEVICE_MODEL_DESCRIPTION, PID_DMX_START_ADDRESS, PID_IDENTIFY_DEVICE,
+    PID_MANUFACTURER_LABEL, PID_SOFTWARE_VERSION_LABEL, PID_SUPPORTED_PARAMETERS,
+    RESPONSE_TYPE_ACK, SUB_DEVICE_ALL_CALL,
+};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+
+/// ESTA manufacturer ID reserved for prototyping, used for generated virtual UIDs
+pub const EMULATOR_MANUFACTURER_ID: u16 = 0x7FF0;
+
+/// Maximum UIDs in a single ArtTodData packet
+const TOD_UIDS_PER_PACKET: usize = 200;
+
+/// Maximum number of ports an emulated node can advertise
+pub const MAX_NODE_PORTS: usize = 256;
+
+/// ArtPollReply bind indexes run 1-255, shared by the node and the RDM responder
+pub const MAX_BIND_INDEXES: usize = 255;
+
+/// ArtPollReply style code of an emulated node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeStyle {
+    Node,
+    Controller,
+    Media,
+    Route,
+    Backup,
+    Config,
+    Visual,
+}
+
+impl NodeStyle {
+    fn code(self) -> u8 {
+        match self {
+            NodeStyle::Node => 0x00,
+            NodeStyle::Controller => 0x01,
+            NodeStyle::Media => 0x02,
+            NodeStyle::Route => 0x03,
+            NodeStyle::Backup => 0x04,
+            NodeStyle::Config => 0x05,
+            NodeStyle::Visual => 0x06,
+        }
+    }
+}
+
+fn default_node_style() -> NodeStyle {
+    NodeStyle::Node
+}
+
+/// Configuration of the emulated Art-Net node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeEmulatorConfig {
+    pub short_name: String,
+    pub long_name: String,
+    /// 15-bit output universes, one per port
+    pub universes: Vec<u16>,
+    #[serde(default = "default_node_style")]
+    pub style: NodeStyle,
+}
+
+impl NodeEmulatorConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.short_name.trim().is_empty() {
+            return Err("Short name must not be empty".to_string());
+        }
+        if self.universes.is_empty() || self.universes.len() > MAX_NODE_PORTS {
+            return Err(format!(
+                "Node must have between 1 and {} ports",
+                MAX_NODE_PORTS
+            ));
+        }
+        if let Some(universe) = self.universes.iter().find(|u| **u > 0x7FFF) {
+            return Err(format!(
+                "Art-Net universe {} out of range (0-32767)",
+                universe
+            ));
+        }
+        let pages = group_pages(&self.universes).len();
+        if pages > MAX_BIND_INDEXES {
+            return Err(format!(
+                "Ports need {} ArtPollReply pages, at most {} fit",
+                pages, MAX_BIND_INDEXES
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Traffic a console has sent to one port of the emulated node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodePortActivity {
+    pub universe: u16,
+    pub frames_received: u64,
+    pub senders: Vec<String>,
+    pub last_length: usize,
+    pub last_received: Option<u64>, // Unix timestamp ms
+}
+
+/// Emulated node status for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeEmulatorStatus {
+    pub running: bool,
+    pub config: Option<NodeEmulatorConfig>,
+    pub polls_received: u64,
+    pub last_poll_from: Option<String>,
+    pub ports: Vec<NodePortActivity>,
+}
+
+/// Answers ArtPoll as a configurable multi-port node and records what it is sent
+pub struct NodeEmulator {
+    config: RwLock<Option<NodeEmulatorConfig>>,
+    ports: RwLock<BTreeMap<u16, NodePortActivity>>,
+    polls_received: Mutex<u64>,
+    last_poll_from: Mutex<Option<IpAddr>>,
+}
+
+impl NodeEmulator {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(None),
+            ports: RwLock::new(BTreeMap::new()),
+            polls_received: Mutex::new(0),
+            last_poll_from: Mutex::new(None),
+        }
+    }
+
+    /// Start emulating a node, clearing previously recorded traffic. The RDM
+    /// responder's replies take `reserved` bind indexes after the node's.
+    pub fn start(&self, config: NodeEmulatorConfig, reserved: usize) -> Result<(), String> {
+        config.validate()?;
+        let pages = group_pages(&config.universes).len();
+        if pages + reserved > MAX_BIND_INDEXES {
+            return Err(format!(
+                "Ports need {} ArtPollReply pages, only {} are left next to the virtual RDM devices",
+                pages,
+                MAX_BIND_INDEXES.saturating_sub(reserved)
+            ));
+        }
+
+        *self.ports.write() = config
+            .universes
+            .iter()
+            .map(|&universe| {
+                let activity = NodePortActivity {
+                    universe,
+                    frames_received: 0,
+                    senders: Vec::new(),
+                    last_length: 0,
+                    last_received: None,
+                };
+                (universe, activity)
+            })
+            .collect();
+        *self.polls_received.lock() = 0;
+        *self.last_poll_from.lock() = None;
+        *self.config.write() = Some(config);
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        *self.config.write() = None;
+    }
+
+    pub fn get_status(&self) -> NodeEmulatorStatus {
+        let config = self.config.read().clone();
+        NodeEmulatorStatus {
+            running: config.is_some(),
+            config,
+            polls_received: *self.polls_received.lock(),
+            last_poll_from: self.last_poll_from.lock().map(|ip| ip.to_string()),
+            ports: self.ports.read().values().cloned().collect(),
+        }
+    }
+
+    /// The running node's ArtPollReply pages
+    fn port_pages(&self) -> Vec<Vec<u16>> {
+        match self.config.read().as_ref() {
+            Some(config) => group_pages(&config.universes),
+            None => Vec::new(),
+        }
+    }
+
+    /// Number of ArtPollReply packets (bind indexes) the node currently uses
+    pub fn reply_count(&self) -> usize {
+        self.port_pages().len()
+    }
+
+    /// Record an ArtPoll and build the node's ArtPollReply packets
+    pub fn poll_replies(&self, ip: Ipv4Addr, from: IpAddr) -> Vec<Vec<u8>> {
+        let Some(config) = self.config.read().clone() else {
+            return Vec::new();
+        };
+        *self.polls_received.lock() += 1;
+        *self.last_poll_from.lock() = Some(from);
+
+        let pages = self.port_pages();
+        pages
+            .iter()
+            .enumerate()
+            .filter_map(|(i, page)| {
+                let bind_index = u8::try_from(i + 1).ok()?;
+                let mut reply = ArtPollReply {
+                    ip_address: ip.octets(),
+                    version_info: 1,
+                    net_switch: ((page[0] >> 8) & 0x7F) as u8,
+                    sub_switch: ((page[0] >> 4) & 0x0F) as u8,
+                    esta_manufacturer: EMULATOR_MANUFACTURER_ID,
+                    short_name: config.short_name.clone(),
+                    long_name: config.long_name.clone(),
+                    node_report: format!(
+                        "#0001 [{:04}] Emulated node",
+                        *self.polls_received.lock()
+                    ),
+                    num_ports: page.len() as u16,
+                    style: config.style.code(),
+                    bind_ip: ip.octets(),
+                    bind_index,
+                    status2: 0x08, // 15-bit port-address supported
+                    ..Default::default()
+                };
+                for (port, universe) in page.iter().enumerate() {
+                    reply.port_types[port] = 0x80; // Output, DMX512
+                    reply.sw_out[port] = (universe & 0x0F) as u8;
+                    // Data being transmitted once a console has sent to the port
+                    if self
+                        .ports
+                        .read()
+                        .get(universe)
+                        .is_some_and(|p| p.frames_received > 0)
+                    {
+                        reply.good_output[port] = 0x80;
+                    }
+                }
+                Some(create_artpollreply_packet(&reply))
+            })
+            .collect()
+    }
+
+    /// Record DMX addressed to one of the node's ports
+    pub fn record_dmx(&self, universe: u16, source_ip: IpAddr, length: usize) {
+        if self.config.read().is_none() {
+            return;
+        }
+        if let Some(port) = self.ports.write().get_mut(&universe) {
+            port.frames_received += 1;
+            port.last_length = length;
+            port.last_received = Some(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64,
+            );
+            let sender = source_ip.to_string();
+            if !port.senders.contains(&sender) {
+                port.senders.push(sender);
+            }
+        }
+    }
+}
+
+impl Default for NodeEmulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Group ports into ArtPollReply pages of up to 4 sharing net and sub-net
+fn group_pages(universes: &[u16]) -> Vec<Vec<u16>> {
+    let universes: BTreeSet<u16> = universes.iter().copied().collect();
+
+    let mut pages: Vec<Vec<u16>> = Vec::new();
+    for universe in universes {
+        match pages.last_mut() {
+            Some(page) if page.len() < 4 && page[0] & 0x7FF0 == universe & 0x7FF0 => {
+                page.push(universe)
+            }
+            _ => pages.push(vec![universe]),
+        }
+    }
+    pages
+}
+
+pub type NodeEmulatorHandle = Arc<NodeEmulator>;
+
+// ============================================================================
+// RDM responder
+// ============================================================================
+
+fn default_footprint() -> u16 {
+    1
+}
+
+fn default_start_address() -> u16 {
+    1
+}
+
+/// Configuration for a virtual RDM device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RdmDeviceConfig {
+    pub universe: u16,
+    /// UID as mmmm:dddddddd (generated if not set)
+    #[serde(default)]
+    pub uid: Option<String>,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default = "default_footprint")]
+    pub footprint: u16,
+    #[serde(default = "default_start_address")]
+    pub start_address: u16,
+}
+
+/// A virtual RDM device and the state controllers can read and change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VirtualRdmDevice {
+    pub uid: String,
+    pub universe: u16,
+    pub manufacturer_label: String,
+    pub model_description: String,
+    pub device_label: String,
+    pub footprint: u16,
+    pub start_address: u16,
+    pub identify: bool,
+    pub requests_handled: u64,
+}
+
+/// Emulates RDM responders behind a virtual Art-Net node
+pub struct RdmResponder {
+    devices: RwLock<BTreeMap<RdmUid, VirtualRdmDevice>>,
+    next_device_id: Mutex<u32>,
+}
+
+impl RdmResponder {
+    pub fn new() -> Self {
+        Self {
+            devices: RwLock::new(BTreeMap::new()),
+            next_device_id: Mutex::new(1),
+        }
+    }
+
+    /// Add a virtual device. The emulated node's replies take `reserved` bind
+    /// indexes before the responder's.
+    pub fn add_device(
+        &self,
+        config: RdmDeviceConfig,
+        reserved: usize,
+    ) -> Result<VirtualRdmDevice, String> {
+        if config.universe > 0x7FFF {
+            return Err(format!(
+                "Art-Net universe {} out of range (0-32767)",
+                config.universe
+            ));
+        }
+        if !(1..=512).contains(&config.footprint) {
+            return Err(format!(
+                "Footprint {} out of range (1-512)",
+                config.footprint
+            ));
+        }
+        if !(1..=512).contains(&config.start_address) {
+            return Err(format!(
+                "Start address {} out of range (1-512)",
+                config.start_address
+            ));
+        }
+
+        let mut devices = self.devices.write();
+        let uid = match &config.uid {
+            Some(value) => {
+                let uid = parse_uid(value).ok_or_else(|| format!("Invalid UID: {}", value))?;
+                if uid_is_broadcast(&uid) {
+                    return Err(format!("UID {} is a broadcast address", value));
+                }
+                uid
+            }
+            None => loop {
+                let id = {
+                    let mut next_id = self.next_device_id.lock();
+                    let id = *next_id;
+                    *next_id += 1;
+                    id
+                };
+                let mut uid = [0u8; 6];
+                uid[..2].copy_from_slice(&EMULATOR_MANUFACTURER_ID.to_be_bytes());
+                uid[2..].copy_from_slice(&id.to_be_bytes());
+                if !devices.contains_key(&uid) {
+                    break uid;
+                }
+            },
+        };
+        if devices.contains_key(&uid) {
+            return Err(format!("Device {} already exists", uid_to_string(&uid)));
+        }
+        // Each universe takes a bind index of its own
+        let universes: BTreeSet<u16> = devices.values().map(|d| d.universe).collect();
+        if !universes.contains(&config.universe) && reserved + universes.len() >= MAX_BIND_INDEXES {
+            return Err(format!(
+                "No ArtPollReply bind index left for universe {}",
+                config.universe
+            ));
+        }
+
+        let device = VirtualRdmDevice {
+            uid: uid_to_string(&uid),
+            universe: config.universe,
+            manufacturer_label: "LXMonitor".to_string(),
+            model_description: "Virtual RDM Device".to_string(),
+            device_label: config.label.unwrap_or_default(),
+            footprint: config.footprint,
+            start_address: config.start_address,
+            identify: false,
+            requests_handled: 0,
+        };
+        devices.insert(uid, device.clone());
+        Ok(device)
+    }
+
+    /// Remove a virtual device
+    pub fn remove_device(&self, uid: &str) -> Result<(), String> {
+        let key = parse_uid(uid).ok_or_else(|| format!("Invalid UID: {}", uid))?;
+        match self.devices.write().remove(&key) {
+            Some(_) => Ok(()),
+            None => Err(format!("Device {} not found", uid)),
+        }
+    }
+
+    pub fn get_devices(&self) -> Vec<VirtualRdmDevice> {
+        self.devices.read().values().cloned().collect()
+    }
+
+    /// Number of ArtPollReply packets (bind indexes) the responder currently uses
+    pub fn reply_count(&self) -> usize {
+        self.universes().len()
+    }
+
+    /// Universes that have at least one virtual device, in bind index order
+    fn universes(&self) -> Vec<u16> {
+        self.devices
+            .read()
+            .values()
+            .map(|d| d.universe)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// ArtPollReply packets advertising one RDM capable output port per universe,
+    /// numbered from `first_bind_index` so they can follow the node emulator's replies
+    pub fn poll_replies(&self, ip: Ipv4Addr, first_bind_index: usize) -> Vec<Vec<u8>> {
+        self.universes()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, universe)| {
+                let bind_index = u8::try_from(first_bind_index + i).ok()?;
+                let reply = ArtPollReply {
+                    ip_address: ip.octets(),
+                    version_info: 1,
+                    net_switch: ((universe >> 8) & 0x7F) as u8,
+                    sub_switch: ((universe >> 4) & 0x0F) as u8,
+                    esta_manufacturer: EMULATOR_MANUFACTURER_ID,
+                    short_name: "LXMonitor RDM".to_string(),
+                    long_name: "LXMonitor virtual RDM responder".to_string(),
+                    node_report: "#0001 [0000] Virtual RDM devices".to_string(),
+                    num_ports: 1,
+                    port_types: [0x80, 0, 0, 0], // Output, DMX512
+                    good_output: [0x80, 0, 0, 0],
+                    sw_out: [(universe & 0x0F) as u8, 0, 0, 0],
+                    bind_ip: ip.octets(),
+                    bind_index,
+                    status2: 0x08, // 15-bit port-address supported
+                    ..Default::default()
+                };
+                Some(create_artpollreply_packet(&reply))
+            })
+            .collect()
+    }
+
+    /// ArtTodData packets listing the virtual devices on each requested universe
+    pub fn tod_replies(&self, requested: &[u16], first_bind_index: usize) -> Vec<Vec<u8>> {
+        let universes = self.universes();
+        let devices = self.devices.read();
+        let mut packets = Vec::new();
+
+        for universe in requested {
+            let Some(bind_index) = universes
+                .iter()
+                .position(|u| u == universe)
+                .and_then(|index| u8::try_from(first_bind_index + index).ok())
+            else {
+                continue;
+            };
+            let uids: Vec<RdmUid> = devices
+                .iter()
+                .filter(|(_, d)| d.universe == *universe)
+                .map(|(uid, _)| *uid)
+                .collect();
+            for (block, chunk) in uids.chunks(TOD_UIDS_PER_PACKET).enumerate() {
+                packets.push(create_arttoddata_packet(
+                    *universe,
+                    bind_index,
+                    uids.len() as u16,
+                    block as u8,
+                    chunk,
+                ));
+            }
+        }
+
+        packets
+    }
+
+    /// Handle an RDM request from ArtRdm, returning the ArtRdm response if one is due
+    pub fn handle_rdm(&self, universe: u16, rdm_packet: &[u8]) -> Option<Vec<u8>> {
+        let request = parse_rdm_message(rdm_packet)?;
+        let broadcast = uid_is_broadcast(&request.destination);
+        let mut devices = self.devices.write();
+        let mut response = None;
+
+        for (uid, device) in devices.iter_mut() {
+            if device.universe != universe || !uid_matches(&request.destination, uid) {
+                continue;
+            }
+            device.requests_handled += 1;
+            let reply = process_request(device, *uid, &request);
+            // Broadcast requests are never answered
+            if !broadcast {
+                response = Some(create_artrdm_packet(universe, &create_rdm_message(&reply)));
+            }
+        }
+
+        response
+    }
+}
+
+impl Default for RdmResponder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type RdmResponderHandle = Arc<RdmResponder>;
+
+/// Apply a GET/SET request to a virtual device and build its response
+fn process_request(device: &mut VirtualRdmDevice, uid: RdmUid, request: &RdmMessage) -> RdmMessage {
+    // The devices have no sub-devices. All-call is valid for SET only, a GET
+    // can't be answered for every sub-device at once.
+    let all_call_set =
+        request.sub_device == SUB_DEVICE_ALL_CALL && request.command_class == CC_SET_COMMAND;
+    if request.sub_device != 0 && !all_call_set {
+        return request.nack(uid, NR_SUB_DEVICE_OUT_OF_RANGE);
+    }
+
+    let ack = |data: Vec<u8>| request.response(uid, RESPONSE_TYPE_ACK, data);
+
+    match (request.command_class, request.pid) {
+        (CC_GET_COMMAND, PID_SUPPORTED_PARAMETERS) => ack([
+            PID_DEVICE_MODEL_DESCRIPTION,
+            PID_MANUFACTURER_LABEL,
+            PID_DEVICE_LABEL,
+        ]
+        .iter()
+        .flat_map(|pid| pid.to_be_bytes())
+        .collect()),
+        (CC_GET_COMMAND, PID_DEVICE_INFO) => {
+            let mut data = Vec::with_capacity(19);
+            data.extend_from_slice(&0x0100u16.to_be_bytes()); // RDM protocol version 1.0
+            data.extend_from_slice(&0x0001u16.to_be_bytes()); // Device model ID
+            data.extend_from_slice(&0x0100u16.to_be_bytes()); // Product category: fixture
+            data.extend_from_slice(&1u32.to_be_bytes()); // Software version ID
+            data.extend_from_slice(&device.footprint.to_be_bytes());
+            data.extend_from_slice(&[1, 1]); // Current / total personalities
+            data.extend_from_slice(&device.start_address.to_be_bytes());
+            data.extend_from_slice(&0u16.to_be_bytes()); // Sub-device count
+            data.push(0); // Sensor count
+            ack(data)
+        }
+        (CC_GET_COMMAND, PID_DEVICE_MODEL_DESCRIPTION) => {
+            ack(device.model_description.as_bytes().to_vec())
+        }
+        (CC_GET_COMMAND, PID_MANUFACTURER_LABEL) => {
+            ack(device.manufacturer_label.as_bytes().to_vec())
+        }
+        (CC_GET_COMMAND, PID_DEVICE_LABEL) => ack(device.device_label.as_bytes().to_vec()),
+        (CC_SET_COMMAND, PID_DEVICE_LABEL) => {
+            if request.data.len() > 32 {
+                return request.nack(uid, NR_FORMAT_ERROR);
+            }
+            device.device_label = String::from_utf8_lossy(&request.data).to_string();
+            ack(Vec::new())
+        }
+        (CC_GET_COMMAND, PID_SOFTWARE_VERSION_LABEL) => {
+            ack(env!("CARGO_PKG_VERSION").as_bytes().to_vec())
+        }
+        (CC_GET_COMMAND, PID_DMX_START_ADDRESS) => ack(device.start_address.to_be_bytes().to_vec()),
+        (CC_SET_COMMAND, PID_DMX_START_ADDRESS) => {
+            let [high, low] = request.data[..] else {
+                return request.nack(uid, NR_FORMAT_ERROR);
+            };
+            let address = u16::from_be_bytes([high, low]);
+            if !(1..=512).contains(&address) {
+                return request.nack(uid, NR_DATA_OUT_OF_RANGE);
+            }
+            device.start_address = address;
+            ack(Vec::new())
+        }
+        (CC_GET_COMMAND, PID_IDENTIFY_DEVICE) => ack(vec![device.identify as u8]),
+        (CC_SET_COMMAND, PID_IDENTIFY_DEVICE) => match request.data[..] {
+            [value @ (0 | 1)] => {
+                device.identify = value == 1;
+                ack(Vec::new())
+            }
+            [_] => request.nack(uid, NR_DATA_OUT_OF_RANGE),
+            _ => request.nack(uid, NR_FORMAT_ERROR),
+        },
+        // Read-only parameters
+        (
+            CC_SET_COMMAND,
+            PID_SUPPORTED_PARAMETERS
+            | PID_DEVICE_INFO
+            | PID_DEVICE_MODEL_DESCRIPTION
+            | PID_MANUFACTURER_LABEL
+            | PID_SOFTWARE_VERSION_LABEL,
+        ) => request.nack(uid, NR_UNSUPPORTED_COMMAND_CLASS),
+        (CC_GET_COMMAND | CC_SET_COMMAND, _) => request.nack(uid, NR_UNKNOWN_PID),
+        _ => request.nack(uid, NR_UNSUPPORTED_COMMAND_CLASS),
+    }
+}
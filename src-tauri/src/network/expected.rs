@@ -0,0 +1,391 @@
+// Expected devices - Show-critical devices that must be present on the network
+//
+// Each expected device is matched against discovered sources by IP, MAC and/or
+// name. Alerts are raised when a device never appears after monitoring starts,
+// drops out (goes stale) mid-show, or comes back.
+
+use crate::network::source::{NetworkSource, SourceStatus};
+use crate::persistence::{load_json, save_json};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// A device that should be on the network. Every criterion given must match.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpectedDevice {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub label: String, // e.g. "FOH console"
+    #[serde(default)]
+    pub ip: Option<String>,
+    #[serde(default)]
+    pub mac: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>, // Source name, alias or Art-Net short/long name
+}
+
+impl ExpectedDevice {
+    /// Trim the criteria and check at least one is given
+    fn normalize(mut self) -> Result<Self, String> {
+        self.label = self.label.trim().to_string();
+        self.ip = non_empty(self.ip);
+        self.mac = non_empty(self.mac);
+        self.name = non_empty(self.name);
+
+        if self.ip.is_none() && self.mac.is_none() && self.name.is_none() {
+            return Err("An expected device needs an IP, MAC or name".to_string());
+        }
+        if let Some(ip) = &self.ip {
+            ip.parse::<std::net::IpAddr>()
+                .map_err(|_| format!("Invalid IP address: {}", ip))?;
+        }
+        if let Some(mac) = &self.mac {
+            if normalize_mac(mac).len() != 12 {
+                return Err(format!("Invalid MAC address: {}", mac));
+            }
+        }
+        if self.label.is_empty() {
+            self.label = self
+                .name
+                .clone()
+                .or_else(|| self.ip.clone())
+                .or_else(|| self.mac.clone())
+                .unwrap_or_default();
+        }
+        if self.id.is_empty() {
+            self.id = uuid::Uuid::new_v4().to_string();
+        }
+        Ok(self)
+    }
+
+    fn matches(&self, source: &NetworkSource) -> bool {
+        if let Some(ip) = &self.ip {
+            if *ip != source.ip {
+                return false;
+            }
+        }
+        if let Some(mac) = &self.mac {
+            let mac = normalize_mac(mac);
+            let seen = [&source.mac_address, &source.link_mac];
+            if !seen
+                .iter()
+                .any(|m| m.as_deref().map(normalize_mac).as_deref() == Some(mac.as_str()))
+            {
+                return false;
+            }
+        }
+        if let Some(name) = &self.name {
+            let names = [
+                Some(&source.name),
+                source.alias.as_ref(),
+                source.artnet_short_name.as_ref(),
+                source.artnet_long_name.as_ref(),
+            ];
+            if !names.iter().flatten().any(|n| n.eq_ignore_ascii_case(name)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Whether an expected device is currently on the network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpectedDeviceState {
+    Waiting, // Not seen yet, still within the grace period
+    Present,
+    Missing,
+}
+
+/// An expected device with its current state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpectedDeviceStatus {
+    pub device: ExpectedDevice,
+    pub state: ExpectedDeviceState,
+    pub source_id: Option<String>, // Matching source while present
+    pub ever_seen: bool,
+    pub missing_since: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpectedDeviceAlertKind {
+    NeverSeen,
+    Dropped,
+    Recovered,
+}
+
+/// Alert raised when an expected device changes state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpectedDeviceAlert {
+    pub device_id: String,
+    pub label: String,
+    pub kind: ExpectedDeviceAlertKind,
+    pub message: String,
+    pub timestamp: u64,
+}
+
+struct TrackedDevice {
+    status: ExpectedDeviceStatus,
+    added: Instant,
+}
+
+impl TrackedDevice {
+    fn new(device: ExpectedDevice) -> Self {
+        Self {
+            status: ExpectedDeviceStatus {
+                device,
+                state: ExpectedDeviceState::Waiting,
+                source_id: None,
+                ever_seen: false,
+                missing_since: None,
+            },
+            added: Instant::now(),
+        }
+    }
+}
+
+/// Persistent list of expected devices and their states
+pub struct ExpectedDevices {
+    devices: RwLock<Vec<TrackedDevice>>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl ExpectedDevices {
+    pub fn new() -> Self {
+        Self {
+            devices: RwLock::new(Vec::new()),
+            path: RwLock::new(None),
+        }
+    }
+
+    /// Load the list from disk and persist future changes to the same file
+    pub fn load(&self, path: PathBuf) {
+        let devices: Vec<ExpectedDevice> = load_json(&path);
+        *self.devices.write() = devices
+            .into_iter()
+            .filter_map(|d| d.normalize().ok())
+            .map(TrackedDevice::new)
+            .collect();
+        *self.path.write() = Some(path);
+    }
+
+    pub fn get_all(&self) -> Vec<ExpectedDeviceStatus> {
+        self.devices
+            .read()
+            .iter()
+            .map(|d| d.status.clone())
+            .collect()
+    }
+
+    /// The devices without their states
+    pub fn get_devices(&self) -> Vec<ExpectedDevice> {
+        self.devices
+            .read()
+            .iter()
+            .map(|d| d.status.device.clone())
+            .collect()
+    }
+
+    /// Replace the whole list, e.g. from a venue profile
+    pub fn replace(&self, devices: Vec<ExpectedDevice>) -> Result<(), String> {
+        let devices = devices
+            .into_iter()
+            .map(ExpectedDevice::normalize)
+            .collect::<Result<Vec<_>, _>>()?;
+        *self.devices.write() = devices.into_iter().map(TrackedDevice::new).collect();
+        self.save()
+    }
+
+    pub fn add(&self, device: ExpectedDevice) -> Result<ExpectedDevice, String> {
+        let device = device.normalize()?;
+        self.devices
+            .write()
+            .push(TrackedDevice::new(device.clone()));
+        self.save()?;
+        Ok(device)
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), String> {
+        {
+            let mut devices = self.devices.write();
+            let before = devices.len();
+            devices.retain(|d| d.status.device.id != id);
+            if devices.len() == before {
+                return Err(format!("Expected device {} not found", id));
+            }
+        }
+        self.save()
+    }
+
+    /// Append devices from a JSON list or a CSV file with a header row
+    /// naming the label, ip, mac and name columns. Returns the number added.
+    pub fn import(&self, path: &Path) -> Result<usize, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let devices = if is_csv {
+            parse_csv(&contents)?
+        } else {
+            serde_json::from_str::<Vec<ExpectedDevice>>(&contents)
+                .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?
+        };
+
+        let devices = devices
+            .into_iter()
+            .enumerate()
+            .map(|(i, d)| d.normalize().map_err(|e| format!("Entry {}: {}", i + 1, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let count = devices.len();
+        self.devices
+            .write()
+            .extend(devices.into_iter().map(TrackedDevice::new));
+        self.save()?;
+        Ok(count)
+    }
+
+    /// Compare against the current sources. Devices not seen within `grace`
+    /// of being added are reported missing.
+    pub fn check(&self, sources: &[NetworkSource], grace: Duration) -> Vec<ExpectedDeviceAlert> {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let mut alerts = Vec::new();
+
+        for tracked in self.devices.write().iter_mut() {
+            let status = &mut tracked.status;
+            let was_seen = status.ever_seen;
+            let source = sources.iter().find(|s| {
+                matches!(s.status, SourceStatus::Active | SourceStatus::Idle)
+                    && status.device.matches(s)
+            });
+
+            let (state, kind) = match (status.state, source.is_some()) {
+                (ExpectedDeviceState::Present, true) | (ExpectedDeviceState::Missing, false) => {
+                    (status.state, None)
+                }
+                (ExpectedDeviceState::Waiting, true) => (ExpectedDeviceState::Present, None),
+                (ExpectedDeviceState::Waiting, false) if tracked.added.elapsed() < grace => {
+                    (ExpectedDeviceState::Waiting, None)
+                }
+                (ExpectedDeviceState::Waiting, false) => (
+                    ExpectedDeviceState::Missing,
+                    Some(ExpectedDeviceAlertKind::NeverSeen),
+                ),
+                (ExpectedDeviceState::Present, false) => (
+                    ExpectedDeviceState::Missing,
+                    Some(ExpectedDeviceAlertKind::Dropped),
+                ),
+                (ExpectedDeviceState::Missing, true) => (
+                    ExpectedDeviceState::Present,
+                    Some(ExpectedDeviceAlertKind::Recovered),
+                ),
+            };
+
+            status.state = state;
+            status.source_id = source.map(|s| s.id.clone());
+            if source.is_some() {
+                status.ever_seen = true;
+                status.missing_since = None;
+            } else if state == ExpectedDeviceState::Missing && status.missing_since.is_none() {
+                status.missing_since = Some(now_ms);
+            }
+
+            if let Some(kind) = kind {
+                let label = &status.device.label;
+                let message = match kind {
+                    ExpectedDeviceAlertKind::NeverSeen => format!("{} has not appeared", label),
+                    ExpectedDeviceAlertKind::Dropped => {
+                        format!("{} dropped off the network", label)
+                    }
+                    ExpectedDeviceAlertKind::Recovered if was_seen => format!("{} is back", label),
+                    ExpectedDeviceAlertKind::Recovered => format!("{} appeared", label),
+                };
+                warn!("[Expected] {}", message);
+                alerts.push(ExpectedDeviceAlert {
+                    device_id: status.device.id.clone(),
+                    label: label.clone(),
+                    kind,
+                    message,
+                    timestamp: now_ms,
+                });
+            }
+        }
+        alerts
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = self.path.read().clone() else {
+            return Ok(());
+        };
+        let devices: Vec<ExpectedDevice> = self
+            .devices
+            .read()
+            .iter()
+            .map(|d| d.status.device.clone())
+            .collect();
+        save_json(&path, &devices)
+    }
+}
+
+impl Default for ExpectedDevices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse a simple CSV (no quoted separators) with a header row
+fn parse_csv(contents: &str) -> Result<Vec<ExpectedDevice>, String> {
+    let mut lines = contents.lines().filter(|l| !l.trim().is_empty());
+    let header: Vec<String> = lines
+        .next()
+        .ok_or("CSV file is empty")?
+        .split(',')
+        .map(|h| h.trim().trim_matches('"').to_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let (label, ip, mac, name) = (column("label"), column("ip"), column("mac"), column("name"));
+    if ip.is_none() && mac.is_none() && name.is_none() {
+        return Err("CSV header needs an ip, mac or name column".to_string());
+    }
+
+    Ok(lines
+        .map(|line| {
+            let fields: Vec<&str> = line
+                .split(',')
+                .map(|f| f.trim().trim_matches('"'))
+                .collect();
+            let field =
+                |index: Option<usize>| index.and_then(|i| fields.get(i)).map(|f| f.to_string());
+            ExpectedDevice {
+                id: String::new(),
+                label: field(label).unwrap_or_default(),
+                ip: field(ip),
+                mac: field(mac),
+                name: field(name),
+            }
+        })
+        .collect())
+}
+
+/// MAC as bare upper-case hex for comparison
+fn normalize_mac(mac: &str) -> String {
+    mac.chars()
+        .filter(|c| c.is_ascii_hexdigit())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
@@ -0,0 +1,96 @@
+// Sequence gaps - Log of every detected break in a sender's sequence numbers
+//
+// The packet loss percentage is a rolling figure. To line up "we saw a blip at
+// 21:43" with actual loss, each gap is also logged with its time, universe,
+// sender and the number of packets missing. Repeated packets and packets
+// arriving slightly out of order are not gaps.
+
+use crate::network::source::Protocol;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Gaps kept in the log, the oldest are dropped first
+const SEQUENCE_GAP_CAPACITY: usize = 10_000;
+
+/// Gaps returned when the caller doesn't ask for a count
+const DEFAULT_GAP_COUNT: usize = 500;
+
+/// A break in the sequence numbers of one universe from one sender
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequenceGap {
+    pub timestamp: u64, // Unix timestamp ms
+    pub universe: u16,
+    pub source_id: String,
+    pub source_name: String,
+    pub ip: String,
+    pub protocol: Protocol,
+    pub previous_sequence: u8,
+    pub sequence: u8,
+    pub missed: u8, // Packets missing between the two
+}
+
+/// Which gaps to return
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SequenceGapFilter {
+    pub since: Option<u64>, // Unix timestamp ms, inclusive
+    pub until: Option<u64>, // Unix timestamp ms, inclusive
+    pub universe: Option<u16>,
+    pub source_id: Option<String>,
+}
+
+/// Ring buffer of the most recent sequence gaps
+pub struct SequenceGapLog {
+    gaps: Mutex<VecDeque<SequenceGap>>,
+}
+
+impl SequenceGapLog {
+    pub fn new() -> Self {
+        Self {
+            gaps: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn push(&self, gap: SequenceGap) {
+        let mut gaps = self.gaps.lock();
+        if gaps.len() == SEQUENCE_GAP_CAPACITY {
+            gaps.pop_front();
+        }
+        gaps.push_back(gap);
+    }
+
+    /// The newest `count` gaps matching the filter, oldest first
+    pub fn query(&self, filter: &SequenceGapFilter, count: Option<usize>) -> Vec<SequenceGap> {
+        let count = count.unwrap_or(DEFAULT_GAP_COUNT);
+        let gaps = self.gaps.lock();
+        let mut matching: Vec<SequenceGap> = gaps
+            .iter()
+            .rev()
+            .filter(|gap| {
+                filter.since.is_none_or(|since| gap.timestamp >= since)
+                    && filter.until.is_none_or(|until| gap.timestamp <= until)
+                    && filter.universe.is_none_or(|u| gap.universe == u)
+                    && filter
+                        .source_id
+                        .as_deref()
+                        .is_none_or(|id| gap.source_id == id)
+            })
+            .take(count)
+            .cloned()
+            .collect();
+        matching.reverse();
+        matching
+    }
+
+    pub fn clear(&self) {
+        self.gaps.lock().clear();
+    }
+}
+
+impl Default for SequenceGapLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,221 @@
+// Network health score - One green/amber/red answer from the diagnostics
+//
+// Each source sending DMX starts at 100 and loses points for packet loss,
+// frame timing jitter, a frame rate outside the warning thresholds, universes
+// it shares with another sender without sACN priority deciding between them,
+// a CID used from several IPs, a wandering frame rate and going stale. The network scores the average
+// of its senders, and is at best amber while any sender is red, so one failing
+// device isn't averaged away.
+
+use crate::network::source::{NetworkSource, Protocol, SourceDirection, SourceStatus};
+
+use serde::{Deserialize, Serialize};
+
+/// Scores from this are green, below it amber
+const GREEN_SCORE: u8 = 80;
+/// Scores below this are red
+const AMBER_SCORE: u8 = 50;
+
+/// Points lost per percent of packet loss, and at most
+const LOSS_PENALTY_PER_PERCENT: f32 = 4.0;
+const MAX_LOSS_PENALTY: f32 = 40.0;
+
+/// Jitter below this is normal for a sender on a busy network
+const JITTER_ALLOWANCE_MS: f32 = 2.0;
+/// Points lost per ms of jitter beyond the allowance, and at most
+const JITTER_PENALTY_PER_MS: f32 = 2.0;
+const MAX_JITTER_PENALTY: f32 = 20.0;
+
+const FPS_WARNING_PENALTY: f32 = 15.0;
+/// Art-Net universes with more than one sender, merged or fighting
+const DUPLICATE_UNIVERSE_PENALTY: f32 = 15.0;
+/// sACN universes shared with a sender at the same priority
+const PRIORITY_CONFLICT_PENALTY: f32 = 30.0;
+const DUPLICATE_CID_PENALTY: f32 = 30.0;
+const WANDERING_RATE_PENALTY: f32 = 10.0;
+const STALE_PENALTY: f32 = 30.0;
+
+/// Traffic light level of a score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthLevel {
+    Green,
+    Amber,
+    Red,
+}
+
+impl HealthLevel {
+    fn from_score(score: u8) -> Self {
+        if score >= GREEN_SCORE {
+            HealthLevel::Green
+        } else if score >= AMBER_SCORE {
+            HealthLevel::Amber
+        } else {
+            HealthLevel::Red
+        }
+    }
+}
+
+/// Health of one sender
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceHealth {
+    pub source_id: String,
+    pub name: String,
+    pub score: u8, // 0 to 100
+    pub level: HealthLevel,
+    pub reasons: Vec<String>, // What cost points, worst first
+}
+
+/// Health of the network and each sender, worst sender first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkHealth {
+    pub score: u8,
+    pub level: HealthLevel,
+    pub summary: String,
+    pub sources: Vec<SourceHealth>,
+}
+
+/// Score the senders among the sources and the network as a whole
+pub fn assess_network_health(sources: &[NetworkSource]) -> NetworkHealth {
+    let mut scored: Vec<SourceHealth> = sources
+        .iter()
+        .filter(|s| {
+            s.status != SourceStatus::Offline
+                && matches!(
+                    s.direction,
+                    SourceDirection::Sending | SourceDirection::Both
+                )
+        })
+        .map(assess_source)
+        .collect();
+    scored.sort_by(|a, b| a.score.cmp(&b.score).then(a.name.cmp(&b.name)));
+
+    if scored.is_empty() {
+        return NetworkHealth {
+            score: 100,
+            level: HealthLevel::Green,
+            summary: "No sources sending DMX".to_string(),
+            sources: scored,
+        };
+    }
+
+    let total: u32 = scored.iter().map(|s| u32::from(s.score)).sum();
+    let score = (total / scored.len() as u32) as u8;
+    let red = scored
+        .iter()
+        .filter(|s| s.level == HealthLevel::Red)
+        .count();
+    let amber = scored
+        .iter()
+        .filter(|s| s.level == HealthLevel::Amber)
+        .count();
+    let mut level = HealthLevel::from_score(score);
+    if red > 0 {
+        level = level.max(HealthLevel::Amber);
+    }
+
+    let summary = if red > 0 || amber > 0 {
+        let worst = &scored[0];
+        format!(
+            "{} of {} source(s) with problems, worst {}: {}",
+            red + amber,
+            scored.len(),
+            worst.name,
+            worst
+                .reasons
+                .first()
+                .map(String::as_str)
+                .unwrap_or("low score")
+        )
+    } else {
+        format!("{} source(s) healthy", scored.len())
+    };
+
+    NetworkHealth {
+        score,
+        level,
+        summary,
+        sources: scored,
+    }
+}
+
+fn assess_source(source: &NetworkSource) -> SourceHealth {
+    let mut penalties: Vec<(f32, String)> = Vec::new();
+
+    if source.packet_loss_percent > 0.0 {
+        penalties.push((
+            (source.packet_loss_percent * LOSS_PENALTY_PER_PERCENT).min(MAX_LOSS_PENALTY),
+            format!("{:.1}% packet loss", source.packet_loss_percent),
+        ));
+    }
+    if source.latency_jitter_ms > JITTER_ALLOWANCE_MS {
+        penalties.push((
+            ((source.latency_jitter_ms - JITTER_ALLOWANCE_MS) * JITTER_PENALTY_PER_MS)
+                .min(MAX_JITTER_PENALTY),
+            format!("{:.1} ms jitter", source.latency_jitter_ms),
+        ));
+    }
+    if let Some(warning) = &source.fps_warning {
+        penalties.push((
+            FPS_WARNING_PENALTY,
+            format!("Frame rate {} ({:.1} fps)", warning, source.fps),
+        ));
+    }
+    // sACN senders sharing a universe are arbitrated by priority, only ties count
+    if source.protocol != Protocol::Sacn && !source.duplicate_universes.is_empty() {
+        penalties.push((
+            DUPLICATE_UNIVERSE_PENALTY,
+            format!(
+                "Universe(s) {} also sent by another source",
+                join_universes(&source.duplicate_universes)
+            ),
+        ));
+    }
+    if !source.priority_conflicts.is_empty() {
+        penalties.push((
+            PRIORITY_CONFLICT_PENALTY,
+            format!(
+                "Universe(s) {} shared at the same priority",
+                join_universes(&source.priority_conflicts)
+            ),
+        ));
+    }
+    if source.duplicate_cid {
+        penalties.push((
+            DUPLICATE_CID_PENALTY,
+            "CID sent from more than one IP".to_string(),
+        ));
+    }
+    if !source.wandering_universes.is_empty() {
+        penalties.push((
+            WANDERING_RATE_PENALTY,
+            format!(
+                "Frame rate wanders on universe(s) {}",
+                join_universes(&source.wandering_universes)
+            ),
+        ));
+    }
+    if source.status == SourceStatus::Stale {
+        penalties.push((STALE_PENALTY, "Not heard recently".to_string()));
+    }
+
+    penalties.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let lost: f32 = penalties.iter().map(|(points, _)| points).sum();
+    let score = (100.0 - lost).clamp(0.0, 100.0).round() as u8;
+
+    SourceHealth {
+        source_id: source.id.clone(),
+        name: source.alias.clone().unwrap_or_else(|| source.name.clone()),
+        score,
+        level: HealthLevel::from_score(score),
+        reasons: penalties.into_iter().map(|(_, reason)| reason).collect(),
+    }
+}
+
+fn join_universes(universes: &[u16]) -> String {
+    universes
+        .iter()
+        .map(|u| u.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,644 @@
+// Packet inspector - Keeps the most recent sniffer frames for inspection
+//
+// The sniffer hands every captured frame to the inspector as raw bytes. Frames
+// are only decoded when the frontend asks for them, into a hex dump and a
+// Wireshark-style tree of protocol layers and their fields, so keeping the
+// buffer costs one copy per packet.
+
+use crate::network::artnet::{parse_artnet_packet, ArtNetOpCode, ArtNetPacket, ARTNET_PORT};
+use crate::network::rdm::uid_to_string;
+use crate::network::sacn::{cid_to_string, parse_sacn_packet, SacnDmx, SacnPacket, SACN_PORT};
+use crate::network::source::{mac_to_string, Protocol};
+
+use bytes::Bytes;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+
+/// Frames kept for inspection
+const INSPECTOR_CAPACITY: usize = 2000;
+
+/// Frames returned when the caller doesn't ask for a count
+const DEFAULT_PACKET_COUNT: usize = 100;
+
+/// DMX slots shown in the decode tree of a DMX frame
+const PREVIEW_SLOTS: usize = 16;
+
+/// pcap link-layer header types (LINKTYPE_* values)
+const LINKTYPE_NULL: i32 = 0;
+const LINKTYPE_ETHERNET: i32 = 1;
+const LINKTYPE_RAW: i32 = 101;
+const LINKTYPE_LOOP: i32 = 108;
+const LINKTYPE_LINUX_SLL: i32 = 113;
+const LINKTYPE_IPV4: i32 = 228;
+const LINKTYPE_IPV6: i32 = 229;
+const LINKTYPE_LINUX_SLL2: i32 = 276;
+
+/// Which captured frames to return
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PacketFilter {
+    pub protocol: Option<Protocol>,
+    pub universe: Option<u16>,
+    pub ip: Option<String>,        // Source or destination address
+    pub interface: Option<String>, // Capture interface
+    pub text: Option<String>,      // Case-insensitive match on the summary
+}
+
+/// One line of a decode tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodeNode {
+    pub label: String,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub children: Vec<DecodeNode>,
+}
+
+impl DecodeNode {
+    fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            children: Vec::new(),
+        }
+    }
+
+    fn field(mut self, name: &str, value: impl std::fmt::Display) -> Self {
+        self.children
+            .push(DecodeNode::new(format!("{}: {}", name, value)));
+        self
+    }
+}
+
+/// A captured frame with its decode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InspectedPacket {
+    pub number: u64,       // Capture order since the sniffer was started
+    pub timestamp: u64,    // Unix time in milliseconds
+    pub interface: String, // Capture interface
+    pub length: usize,
+    pub protocol: Option<Protocol>,
+    pub source: Option<String>,
+    pub destination: Option<String>,
+    pub universe: Option<u16>,
+    pub summary: String,
+    pub hex_dump: String,
+    pub tree: Vec<DecodeNode>,
+}
+
+struct CapturedFrame {
+    number: u64,
+    timestamp: u64,
+    interface: Arc<str>,
+    linktype: i32,
+    data: Vec<u8>,
+}
+
+/// Ring buffer of the most recent captured frames
+pub struct PacketInspector {
+    frames: Mutex<VecDeque<CapturedFrame>>,
+    next_number: Mutex<u64>,
+}
+
+impl PacketInspector {
+    pub fn new() -> Self {
+        Self {
+            frames: Mutex::new(VecDeque::with_capacity(INSPECTOR_CAPACITY)),
+            next_number: Mutex::new(1),
+        }
+    }
+
+    /// Keep a captured frame, dropping the oldest when full
+    #[cfg(feature = "sniffer")]
+    pub fn push(&self, interface: &Arc<str>, linktype: i32, timestamp: u64, data: &[u8]) {
+        let number = {
+            let mut next = self.next_number.lock();
+            let number = *next;
+            *next += 1;
+            number
+        };
+        let mut frames = self.frames.lock();
+        if frames.len() == INSPECTOR_CAPACITY {
+            frames.pop_front();
+        }
+        frames.push_back(CapturedFrame {
+            number,
+            timestamp,
+            interface: interface.clone(),
+            linktype,
+            data: data.to_vec(),
+        });
+    }
+
+    pub fn clear(&self) {
+        self.frames.lock().clear();
+        *self.next_number.lock() = 1;
+    }
+
+    /// The newest `count` frames matching the filter, oldest first
+    pub fn query(&self, filter: &PacketFilter, count: Option<usize>) -> Vec<InspectedPacket> {
+        let count = count.unwrap_or(DEFAULT_PACKET_COUNT);
+        let ip: Option<IpAddr> = filter.ip.as_deref().and_then(|ip| ip.trim().parse().ok());
+        let text = filter.text.as_deref().map(|t| t.to_lowercase());
+
+        let frames = self.frames.lock();
+        let mut packets: Vec<InspectedPacket> = frames
+            .iter()
+            .rev()
+            .filter(|frame| {
+                filter
+                    .interface
+                    .as_deref()
+                    .is_none_or(|i| *frame.interface == *i)
+            })
+            .map(|frame| (frame, decode_frame(frame.linktype, &frame.data)))
+            .filter(|(_, decoded)| {
+                filter.protocol.is_none_or(|p| decoded.protocol == Some(p))
+                    && filter.universe.is_none_or(|u| decoded.universe == Some(u))
+                    && ip.is_none_or(|ip| decoded.src == Some(ip) || decoded.dst == Some(ip))
+                    && text
+                        .as_deref()
+                        .is_none_or(|t| decoded.summary.to_lowercase().contains(t))
+            })
+            .take(count)
+            .map(|(frame, decoded)| InspectedPacket {
+                number: frame.number,
+                timestamp: frame.timestamp,
+                interface: frame.interface.to_string(),
+                length: frame.data.len(),
+                protocol: decoded.protocol,
+                source: decoded.src.map(|ip| ip.to_string()),
+                destination: decoded.dst.map(|ip| ip.to_string()),
+                universe: decoded.universe,
+                summary: decoded.summary,
+                hex_dump: hex_dump(&frame.data),
+                tree: decoded.tree,
+            })
+            .collect();
+        packets.reverse();
+        packets
+    }
+}
+
+impl Default for PacketInspector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Offset, hex and ASCII columns, 16 bytes per line
+fn hex_dump(data: &[u8]) -> String {
+    data.chunks(16)
+        .enumerate()
+        .map(|(line, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!("{:04x}  {:<47}  {}", line * 16, hex.join(" "), ascii)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Decode tree and the facts the filter looks at
+#[derive(Default)]
+struct Decoded {
+    tree: Vec<DecodeNode>,
+    summary: String,
+    protocol: Option<Protocol>,
+    src: Option<IpAddr>,
+    dst: Option<IpAddr>,
+    universe: Option<u16>,
+}
+
+fn decode_frame(linktype: i32, data: &[u8]) -> Decoded {
+    let mut decoded = Decoded::default();
+    decoded.tree.push(DecodeNode::new(format!(
+        "Frame: {} bytes, link type {}",
+        data.len(),
+        linktype
+    )));
+    decode_link(&mut decoded, linktype, data);
+    if decoded.summary.is_empty() {
+        decoded.summary = "Truncated frame".to_string();
+    }
+    decoded
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    data.get(at..at + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn mac_at(data: &[u8], at: usize) -> Option<String> {
+    let mac: [u8; 6] = data.get(at..at + 6)?.try_into().ok()?;
+    Some(mac_to_string(&mac))
+}
+
+fn decode_link(decoded: &mut Decoded, linktype: i32, data: &[u8]) -> Option<()> {
+    match linktype {
+        LINKTYPE_ETHERNET => {
+            let dst = mac_at(data, 0)?;
+            let src = mac_at(data, 6)?;
+            let ethertype = u16_at(data, 12)?;
+            decoded.tree.push(
+                DecodeNode::new(format!("Ethernet II, Src: {}, Dst: {}", src, dst))
+                    .field("Destination", &dst)
+                    .field("Source", &src)
+                    .field("Type", format!("0x{:04x}", ethertype)),
+            );
+            decode_ethertype(decoded, ethertype, &data[14..])
+        }
+        LINKTYPE_LINUX_SLL => {
+            let protocol = u16_at(data, 14)?;
+            let mut node = DecodeNode::new("Linux cooked capture v1")
+                .field("Packet type", u16_at(data, 0)?)
+                .field("Link-layer address type", u16_at(data, 2)?);
+            if u16_at(data, 4)? == 6 {
+                node = node.field("Source", mac_at(data, 6)?);
+            }
+            decoded
+                .tree
+                .push(node.field("Protocol", format!("0x{:04x}", protocol)));
+            decode_ethertype(decoded, protocol, data.get(16..)?)
+        }
+        LINKTYPE_LINUX_SLL2 => {
+            let protocol = u16_at(data, 0)?;
+            let interface_index = u32::from_be_bytes(data.get(4..8)?.try_into().ok()?);
+            let mut node = DecodeNode::new("Linux cooked capture v2")
+                .field("Protocol", format!("0x{:04x}", protocol))
+                .field("Interface index", interface_index)
+                .field("Packet type", data.get(10)?);
+            if *data.get(11)? == 6 {
+                node = node.field("Source", mac_at(data, 12)?);
+            }
+            decoded.tree.push(node);
+            decode_ethertype(decoded, protocol, data.get(20..)?)
+        }
+        LINKTYPE_NULL | LINKTYPE_LOOP => {
+            let family: [u8; 4] = data.get(..4)?.try_into().ok()?;
+            let family = if linktype == LINKTYPE_LOOP {
+                u32::from_be_bytes(family)
+            } else {
+                u32::from_ne_bytes(family)
+            };
+            decoded
+                .tree
+                .push(DecodeNode::new("Null/Loopback").field("Family", family));
+            decode_ip(decoded, &data[4..])
+        }
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => decode_ip(decoded, data),
+        _ => {
+            decoded.summary = format!("Link type {} not decoded", linktype);
+            Some(())
+        }
+    }
+}
+
+/// Decode past up to two VLAN tags to the IP packet
+fn decode_ethertype(decoded: &mut Decoded, mut ethertype: u16, mut rest: &[u8]) -> Option<()> {
+    const VLAN_ETHERTYPES: [u16; 3] = [0x8100, 0x88A8, 0x9100];
+
+    let mut tags = 0;
+    while VLAN_ETHERTYPES.contains(&ethertype) && tags < 2 {
+        let tci = u16_at(rest, 0)?;
+        ethertype = u16_at(rest, 2)?;
+        decoded.tree.push(
+            DecodeNode::new(format!("802.1Q Virtual LAN, ID: {}", tci & 0x0FFF))
+                .field("Priority", tci >> 13)
+                .field("Drop eligible", (tci >> 12) & 1)
+                .field("ID", tci & 0x0FFF)
+                .field("Type", format!("0x{:04x}", ethertype)),
+        );
+        rest = &rest[4..];
+        tags += 1;
+    }
+
+    match ethertype {
+        0x0800 | 0x86DD => decode_ip(decoded, rest),
+        0x0806 => {
+            decoded.summary = "ARP".to_string();
+            Some(())
+        }
+        _ => {
+            decoded.summary = format!("EtherType 0x{:04x}", ethertype);
+            Some(())
+        }
+    }
+}
+
+fn decode_ip(decoded: &mut Decoded, data: &[u8]) -> Option<()> {
+    match data.first()? >> 4 {
+        4 => decode_ipv4(decoded, data),
+        6 => decode_ipv6(decoded, data),
+        version => {
+            decoded.summary = format!("IP version {}", version);
+            Some(())
+        }
+    }
+}
+
+fn decode_ipv4(decoded: &mut Decoded, data: &[u8]) -> Option<()> {
+    let ihl = (data[0] & 0x0F) as usize * 4;
+    if data.len() < 20 || ihl < 20 || ihl > data.len() {
+        return None;
+    }
+    let src = Ipv4Addr::new(data[12], data[13], data[14], data[15]);
+    let dst = Ipv4Addr::new(data[16], data[17], data[18], data[19]);
+    let flags_offset = u16_at(data, 6)?;
+    let protocol = data[9];
+    decoded.src = Some(src.into());
+    decoded.dst = Some(dst.into());
+    decoded.tree.push(
+        DecodeNode::new(format!(
+            "Internet Protocol Version 4, Src: {}, Dst: {}",
+            src, dst
+        ))
+        .field("Header length", format!("{} bytes", ihl))
+        .field("DSCP", data[1] >> 2)
+        .field("Total length", u16_at(data, 2)?)
+        .field("Identification", format!("0x{:04x}", u16_at(data, 4)?))
+        .field("Don't fragment", (flags_offset >> 14) & 1)
+        .field("More fragments", (flags_offset >> 13) & 1)
+        .field("Fragment offset", (flags_offset & 0x1FFF) * 8)
+        .field("Time to live", data[8])
+        .field("Protocol", protocol)
+        .field("Header checksum", format!("0x{:04x}", u16_at(data, 10)?)),
+    );
+
+    // Later fragments carry no UDP header
+    if protocol == 17 && flags_offset & 0x1FFF == 0 {
+        decode_udp(decoded, &data[ihl..])
+    } else {
+        decoded.summary = format!("IPv4 {} → {}, protocol {}", src, dst, protocol);
+        Some(())
+    }
+}
+
+fn decode_ipv6(decoded: &mut Decoded, data: &[u8]) -> Option<()> {
+    const MAX_EXTENSION_HEADERS: usize = 8;
+
+    if data.len() < 40 {
+        return None;
+    }
+    let src = Ipv6Addr::from(<[u8; 16]>::try_from(&data[8..24]).ok()?);
+    let dst = Ipv6Addr::from(<[u8; 16]>::try_from(&data[24..40]).ok()?);
+    decoded.src = Some(src.into());
+    decoded.dst = Some(dst.into());
+
+    let mut node = DecodeNode::new(format!(
+        "Internet Protocol Version 6, Src: {}, Dst: {}",
+        src, dst
+    ))
+    .field("Payload length", u16_at(data, 4)?)
+    .field("Next header", data[6])
+    .field("Hop limit", data[7]);
+
+    let mut next_header = data[6];
+    let mut offset = 40;
+    for _ in 0..MAX_EXTENSION_HEADERS {
+        let (name, length) = match next_header {
+            0 | 43 | 60 => {
+                let name = match next_header {
+                    0 => "Hop-by-hop options",
+                    43 => "Routing",
+                    _ => "Destination options",
+                };
+                (name, (*data.get(offset + 1)? as usize + 1) * 8)
+            }
+            44 => ("Fragment", 8),
+            51 => ("Authentication", (*data.get(offset + 1)? as usize + 2) * 4),
+            _ => break,
+        };
+        node = node.field(name, format!("{} bytes", length));
+        next_header = *data.get(offset)?;
+        offset += length;
+    }
+    decoded.tree.push(node);
+
+    if next_header == 17 {
+        decode_udp(decoded, data.get(offset..)?)
+    } else {
+        decoded.summary = format!("IPv6 {} → {}, next header {}", src, dst, next_header);
+        Some(())
+    }
+}
+
+fn decode_udp(decoded: &mut Decoded, data: &[u8]) -> Option<()> {
+    let src_port = u16_at(data, 0)?;
+    let dst_port = u16_at(data, 2)?;
+    let length = u16_at(data, 4)?;
+    decoded.tree.push(
+        DecodeNode::new(format!(
+            "User Datagram Protocol, Src Port: {}, Dst Port: {}",
+            src_port, dst_port
+        ))
+        .field("Length", length)
+        .field("Checksum", format!("0x{:04x}", u16_at(data, 6)?)),
+    );
+
+    // Ethernet pads short frames, the UDP length says where the payload ends
+    let end = (length as usize).clamp(8, data.len());
+    let payload = Bytes::copy_from_slice(&data[8..end]);
+    let source = SocketAddr::new(decoded.src?, src_port);
+    if src_port == ARTNET_PORT || dst_port == ARTNET_PORT {
+        decoded.protocol = Some(Protocol::ArtNet);
+        decode_artnet(decoded, &payload, source);
+    } else if src_port == SACN_PORT || dst_port == SACN_PORT {
+        decoded.protocol = Some(Protocol::Sacn);
+        decode_sacn(decoded, &payload, source);
+    } else {
+        decoded.summary = format!("UDP {} → {}, {} bytes", src_port, dst_port, payload.len());
+    }
+    Some(())
+}
+
+/// First DMX slots of a frame
+fn slot_preview(data: &[u8]) -> String {
+    let values: Vec<String> = data
+        .iter()
+        .take(PREVIEW_SLOTS)
+        .map(|v| v.to_string())
+        .collect();
+    if data.len() > PREVIEW_SLOTS {
+        format!("{} ...", values.join(" "))
+    } else {
+        values.join(" ")
+    }
+}
+
+fn decode_artnet(decoded: &mut Decoded, payload: &Bytes, source: SocketAddr) {
+    let opcode = payload
+        .get(8..10)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .unwrap_or(0);
+    let Some(packet) = parse_artnet_packet(payload, source) else {
+        decoded.tree.push(DecodeNode::new("Art-Net (malformed)"));
+        decoded.summary = format!("Art-Net malformed, opcode 0x{:04x}", opcode);
+        return;
+    };
+
+    let name = format!("{:?}", ArtNetOpCode::from(opcode));
+    let node = DecodeNode::new(format!("Art-Net, OpCode: {} (0x{:04x})", name, opcode));
+    let node = match packet {
+        ArtNetPacket::Poll => {
+            decoded.summary = "Art-Net OpPoll".to_string();
+            node
+        }
+        ArtNetPacket::PollReply(reply) => {
+            decoded.summary = format!("Art-Net OpPollReply \"{}\"", reply.short_name);
+            node.field("IP address", Ipv4Addr::from(reply.ip_address))
+                .field("Short name", &reply.short_name)
+                .field("Long name", &reply.long_name)
+                .field("Net switch", reply.net_switch)
+                .field("Sub switch", reply.sub_switch)
+                .field("Ports", reply.num_ports)
+                .field("Output universes", format!("{:?}", reply.sw_out))
+                .field("MAC address", mac_to_string(&reply.mac_address))
+                .field("Node report", &reply.node_report)
+        }
+        ArtNetPacket::Dmx(dmx) => {
+            decoded.universe = Some(dmx.universe);
+            decoded.summary = format!(
+                "Art-Net OpDmx universe {}, {} slots, seq {}",
+                dmx.universe,
+                dmx.data.len(),
+                dmx.sequence
+            );
+            node.field("Sequence", dmx.sequence)
+                .field("Physical", dmx.physical)
+                .field("Universe", dmx.universe)
+                .field("Length", dmx.length)
+                .field("Data", slot_preview(&dmx.data))
+        }
+        ArtNetPacket::Nzs(nzs) => {
+            decoded.universe = Some(nzs.universe);
+            decoded.summary = format!(
+                "Art-Net OpNzs universe {}, start code 0x{:02x}",
+                nzs.universe, nzs.start_code
+            );
+            node.field("Start code", format!("0x{:02x}", nzs.start_code))
+                .field("Universe", nzs.universe)
+                .field("Length", nzs.data.len())
+        }
+        ArtNetPacket::TodRequest(request) => {
+            decoded.summary = format!("Art-Net {}", name);
+            node.field("Universes", format!("{:?}", request.universes))
+        }
+        ArtNetPacket::TodData(tod) => {
+            decoded.universe = Some(tod.universe);
+            decoded.summary = format!(
+                "Art-Net OpTodData universe {}, {} of {} UIDs",
+                tod.universe,
+                tod.uids.len(),
+                tod.uid_total
+            );
+            node.field("Port", tod.port)
+                .field("Bind index", tod.bind_index)
+                .field("Universe", tod.universe)
+                .field(
+                    "Command response",
+                    if tod.full { "TodFull" } else { "TodNak" },
+                )
+                .field("UID total", tod.uid_total)
+                .field("Block", tod.block)
+                .field(
+                    "UIDs",
+                    tod.uids
+                        .iter()
+                        .map(uid_to_string)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                )
+        }
+        ArtNetPacket::Rdm(rdm) => {
+            decoded.universe = Some(rdm.universe);
+            decoded.summary = format!("Art-Net OpRdm universe {}", rdm.universe);
+            node.field("Universe", rdm.universe)
+                .field("Command", rdm.command)
+                .field("RDM length", rdm.rdm_packet.len())
+        }
+        ArtNetPacket::TimeCode(timecode) => {
+            decoded.summary = format!("Art-Net OpTimeCode {}", timecode.timecode);
+            node.field("Stream", timecode.stream_id)
+                .field("Timecode", timecode.timecode)
+                .field("Rate", format!("{:?}", timecode.timecode.rate))
+        }
+        ArtNetPacket::Other(_) => {
+            decoded.summary = format!("Art-Net {}", name);
+            node
+        }
+    };
+    decoded.tree.push(node);
+}
+
+fn decode_sacn(decoded: &mut Decoded, payload: &Bytes, source: SocketAddr) {
+    let Some(packet) = parse_sacn_packet(payload, source) else {
+        decoded.tree.push(DecodeNode::new("sACN (malformed)"));
+        decoded.summary = "sACN malformed".to_string();
+        return;
+    };
+
+    let dmx_node = |dmx: &SacnDmx| {
+        DecodeNode::new(format!("sACN Data, Universe: {}", dmx.source.universe))
+            .field("CID", cid_to_string(&dmx.source.cid))
+            .field("Source name", &dmx.source.source_name)
+            .field("Priority", dmx.source.priority)
+            .field("Sync address", dmx.source.sync_address)
+            .field("Sequence", dmx.source.sequence)
+            .field("Options", format!("0x{:02x}", dmx.source.options))
+            .field("Universe", dmx.source.universe)
+            .field("Start code", format!("0x{:02x}", dmx.start_code))
+            .field("Slots", dmx.data.len())
+    };
+    let node = match packet {
+        SacnPacket::Dmx(dmx) => {
+            decoded.universe = Some(dmx.source.universe);
+            decoded.summary = format!(
+                "sACN DMX universe {}, {} slots, priority {}, seq {}",
+                dmx.source.universe,
+                dmx.data.len(),
+                dmx.source.priority,
+                dmx.source.sequence
+            );
+            dmx_node(&dmx).field("Data", slot_preview(&dmx.data))
+        }
+        SacnPacket::AlternateStartCode(dmx) => {
+            decoded.universe = Some(dmx.source.universe);
+            decoded.summary = format!(
+                "sACN universe {}, start code 0x{:02x}",
+                dmx.source.universe, dmx.start_code
+            );
+            dmx_node(&dmx)
+        }
+        SacnPacket::Sync { sync_address } => {
+            decoded.universe = Some(sync_address);
+            decoded.summary = format!("sACN Sync, address {}", sync_address);
+            DecodeNode::new("sACN Synchronization").field("Sync address", sync_address)
+        }
+        SacnPacket::Discovery(discovery) => {
+            decoded.summary = format!(
+                "sACN Universe Discovery \"{}\", {} universes",
+                discovery.source_name,
+                discovery.universes.len()
+            );
+            DecodeNode::new("sACN Universe Discovery")
+                .field("CID", cid_to_string(&discovery.cid))
+                .field("Source name", &discovery.source_name)
+                .field("Universes", format!("{:?}", discovery.universes))
+        }
+        SacnPacket::Unknown => {
+            decoded.summary = "sACN unknown vector".to_string();
+            DecodeNode::new("sACN (unknown vector)")
+        }
+    };
+    decoded.tree.push(node);
+}
@@ -1,23 +1,170 @@
-// Network Listener - UDP socket management for Art-Net and sACN
+// Network Listener - UDP socket management for Art-Net, sACN and registered
+// protocol decoders
 
-use crate::network::artnet::{parse_artnet_packet, ArtNetPacket, ARTNET_PORT};
+use crate::network::anomaly::UniverseAnomaly;
+use crate::network::arrival::{
+    enable_arrival_interface, listen_targets, recv_with_interface, ArrivalFilter, ListenTarget,
+};
+use crate::network::artnet::{
+    create_artpoll_packet, parse_artnet_packet, ArtNetPacket, ARTNET_PORT,
+};
+use crate::network::bus::EventBusHandle;
+use crate::network::decoder::{start_decoder_listener, DecoderRegistry};
+use crate::network::emulator::{NodeEmulatorHandle, RdmResponderHandle};
+use crate::network::expected::ExpectedDeviceAlert;
+use crate::network::poll::PollLatencyTracker;
+use crate::network::rdm_devices::{RdmDeviceTable, RdmStatusMessage, TodChange};
 use crate::network::sacn::{parse_sacn_packet, SacnPacket, SACN_PORT};
-use crate::network::source::{SourceDirection, SourceManagerHandle};
+use crate::network::sniffer::SnifferEvent;
+use crate::network::source::{PriorityChange, Protocol, SourceDirection, SourceManagerHandle};
+use crate::network::storm::StormAlert;
+use crate::network::timecode::{TimecodeDriftAlert, TimecodeTracker};
+use crate::persistence::{load_json, save_json};
 
-use parking_lot::RwLock;
-use std::collections::HashMap;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use bytes::Bytes;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::net::UdpSocket;
-use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+
+/// Number of slots in a full DMX512 frame
+pub const DMX_UNIVERSE_SIZE: usize = 512;
 
 /// DMX data for a universe
 #[derive(Debug, Clone)]
 pub struct DmxData {
     pub universe: u16,
-    pub data: Vec<u8>,
+    pub data: Bytes, // Slice of the received datagram, cloning does not copy the frame
+    pub protocol: Protocol,
     pub source_ip: IpAddr,
+    pub source_port: u16,
     pub timestamp: u64,
+    pub interface: Option<Ipv4Addr>, // Listening interface, None when bound to all
+    pub changed: bool,               // Differs from the previous frame stored for the universe
+}
+
+/// Received frame length for a universe from a single source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameLength {
+    pub universe: u16,
+    pub source_ip: String,
+    pub length: usize,
+    pub short_frame: bool, // Fewer than 512 slots - some fixtures misbehave on these
+    pub last_seen: u64,    // Unix timestamp ms
+}
+
+/// Time since a channel last changed value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelAge {
+    pub channel: u16, // 1-based channel number
+    pub value: u8,
+    pub last_changed: u64, // Unix timestamp ms
+    pub age_ms: u64,
+}
+
+/// Last frame received with a non-zero (alternate) start code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlternateFrame {
+    pub universe: u16,
+    pub start_code: u8,
+    pub start_code_name: String,
+    pub protocol: Protocol,
+    pub source_ip: String,
+    pub data: Bytes,
+    pub frame_count: u64,
+    pub last_seen: u64, // Unix timestamp ms
+}
+
+/// Human-readable name for a DMX512 alternate start code
+pub fn start_code_name(start_code: u8) -> &'static str {
+    match start_code {
+        0x00 => "Null (Dimmer Data)",
+        0x17 => "Text Packet",
+        0x55 => "Test Packet",
+        0x90 => "UTF-8 Text Packet",
+        0x91 => "Manufacturer ID",
+        0xCC => "RDM",
+        0xCF => "System Information Packet",
+        0xDD => "Per-Address Priority",
+        _ => "Unknown",
+    }
+}
+
+/// Storage for alternate start code frames, keyed by (universe, start code, source)
+pub struct AlternateFrameStore {
+    frames: RwLock<HashMap<(u16, u8, IpAddr), AlternateFrame>>,
+}
+
+impl AlternateFrameStore {
+    pub fn new() -> Self {
+        Self {
+            frames: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn update(
+        &self,
+        universe: u16,
+        start_code: u8,
+        source_ip: IpAddr,
+        protocol: Protocol,
+        data: Bytes,
+    ) {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        let mut frames = self.frames.write();
+        let frame = frames
+            .entry((universe, start_code, source_ip))
+            .or_insert_with(|| AlternateFrame {
+                universe,
+                start_code,
+                start_code_name: start_code_name(start_code).to_string(),
+                protocol,
+                source_ip: source_ip.to_string(),
+                data: Bytes::new(),
+                frame_count: 0,
+                last_seen: now_ms,
+            });
+
+        frame.data = data;
+        frame.frame_count += 1;
+        frame.last_seen = now_ms;
+    }
+
+    /// Query stored frames, optionally filtered by universe and/or start code
+    pub fn query(&self, universe: Option<u16>, start_code: Option<u8>) -> Vec<AlternateFrame> {
+        let frames = self.frames.read();
+        let mut result: Vec<AlternateFrame> = frames
+            .values()
+            .filter(|f| universe.is_none_or(|u| f.universe == u))
+            .filter(|f| start_code.is_none_or(|c| f.start_code == c))
+            .cloned()
+            .collect();
+        result.sort_by(|a, b| {
+            a.universe
+                .cmp(&b.universe)
+                .then(a.start_code.cmp(&b.start_code))
+                .then(a.source_ip.cmp(&b.source_ip))
+        });
+        result
+    }
+}
+
+impl Default for AlternateFrameStore {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Event types emitted by the listener
@@ -25,32 +172,191 @@ pub struct DmxData {
 pub enum ListenerEvent {
     SourcesUpdated,
     DmxData(DmxData),
+    ExpectedDeviceAlert(ExpectedDeviceAlert),
+    StormAlert(StormAlert),
+    UniverseAnomaly(Box<UniverseAnomaly>),
+    PriorityChanged(PriorityChange),
+    TodChanged(Box<TodChange>),
+    RdmStatus(RdmStatusMessage),
+    TimecodeDrift(TimecodeDriftAlert),
+    Sniffer(SnifferEvent),
+}
+
+/// Newest frame of one universe, overwritten in place by every update
+struct UniverseSlot {
+    levels: [u8; DMX_UNIVERSE_SIZE], // Zero beyond the received length
+    length: usize,
+    /// Unix timestamp ms of the last value change per channel
+    changed: [u64; DMX_UNIVERSE_SIZE],
+    /// Last received frame length and time per source
+    frame_lengths: HashMap<IpAddr, (usize, u64)>,
 }
 
-/// DMX data storage for all universes
+impl UniverseSlot {
+    fn new(now_ms: u64) -> Self {
+        Self {
+            levels: [0; DMX_UNIVERSE_SIZE],
+            length: 0,
+            changed: [now_ms; DMX_UNIVERSE_SIZE],
+            frame_lengths: HashMap::new(),
+        }
+    }
+
+    /// Store a frame, false when it repeats the stored one exactly
+    fn update(&mut self, source_ip: IpAddr, data: &[u8], now_ms: u64) -> bool {
+        let length = data.len().min(DMX_UNIVERSE_SIZE);
+        self.frame_lengths.insert(source_ip, (length, now_ms));
+        if length == self.length && self.levels[..length] == data[..length] {
+            return false;
+        }
+
+        // Record which channels changed value since the previous frame
+        for (i, &value) in data[..length].iter().enumerate() {
+            if i >= self.length || self.levels[i] != value {
+                self.changed[i] = now_ms;
+            }
+        }
+        self.levels[..length].copy_from_slice(&data[..length]);
+        self.levels[length..].fill(0);
+        self.length = length;
+        true
+    }
+}
+
+/// DMX data storage for all universes. Each universe has its own lock, so
+/// writers to different universes never wait on each other; the map itself is
+/// only write-locked when a universe is seen for the first time.
 pub struct DmxStore {
-    data: RwLock<HashMap<u16, Vec<u8>>>,
+    universes: RwLock<HashMap<u16, Mutex<UniverseSlot>>>,
+    /// Frames with non-zero start codes (RDM, text, SIP, per-address priority)
+    alternate_frames: AlternateFrameStore,
 }
 
 impl DmxStore {
     pub fn new() -> Self {
         Self {
-            data: RwLock::new(HashMap::new()),
+            universes: RwLock::new(HashMap::new()),
+            alternate_frames: AlternateFrameStore::new(),
         }
     }
 
-    pub fn update(&self, universe: u16, data: Vec<u8>) {
-        let mut store = self.data.write();
-        store.insert(universe, data);
+    /// Store a received frame at its received length and record that length.
+    /// A frame identical to the stored one only refreshes the length record
+    /// and returns false.
+    pub fn update(&self, universe: u16, source_ip: IpAddr, data: &[u8]) -> bool {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        if let Some(slot) = self.universes.read().get(&universe) {
+            return slot.lock().update(source_ip, data, now_ms);
+        }
+        self.universes
+            .write()
+            .entry(universe)
+            .or_insert_with(|| Mutex::new(UniverseSlot::new(now_ms)))
+            .get_mut()
+            .update(source_ip, data, now_ms)
     }
 
     pub fn get(&self, universe: u16) -> Option<Vec<u8>> {
-        let store = self.data.read();
-        store.get(&universe).cloned()
+        let universes = self.universes.read();
+        let slot = universes.get(&universe)?.lock();
+        Some(slot.levels[..slot.length].to_vec())
+    }
+
+    /// Get DMX data for a universe, zero-padded to a full 512-slot frame for display
+    pub fn get_padded(&self, universe: u16) -> Option<Vec<u8>> {
+        let universes = self.universes.read();
+        let slot = universes.get(&universe)?.lock();
+        Some(slot.levels.to_vec())
+    }
+
+    /// Get when each channel in a universe last changed value
+    pub fn get_channel_ages(&self, universe: u16) -> Option<Vec<ChannelAge>> {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        let universes = self.universes.read();
+        let slot = universes.get(&universe)?.lock();
+
+        Some(
+            slot.levels[..slot.length]
+                .iter()
+                .zip(slot.changed.iter())
+                .enumerate()
+                .map(|(i, (&value, &changed))| ChannelAge {
+                    channel: i as u16 + 1,
+                    value,
+                    last_changed: changed,
+                    age_ms: now_ms.saturating_sub(changed),
+                })
+                .collect(),
+        )
+    }
+
+    /// Get received frame lengths, optionally filtered to a single universe
+    pub fn get_frame_lengths(&self, universe: Option<u16>) -> Vec<FrameLength> {
+        let universes = self.universes.read();
+        let mut result: Vec<FrameLength> = universes
+            .iter()
+            .filter(|(u, _)| universe.is_none_or(|universe| **u == universe))
+            .flat_map(|(&universe, slot)| {
+                let slot = slot.lock();
+                slot.frame_lengths
+                    .iter()
+                    .map(|(source_ip, &(length, last_seen))| FrameLength {
+                        universe,
+                        source_ip: source_ip.to_string(),
+                        length,
+                        short_frame: length < DMX_UNIVERSE_SIZE,
+                        last_seen,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        result.sort_by(|a, b| {
+            a.universe
+                .cmp(&b.universe)
+                .then(a.source_ip.cmp(&b.source_ip))
+        });
+        result
     }
 
     pub fn get_all(&self) -> HashMap<u16, Vec<u8>> {
-        self.data.read().clone()
+        self.universes
+            .read()
+            .iter()
+            .map(|(universe, slot)| {
+                let slot = slot.lock();
+                (*universe, slot.levels[..slot.length].to_vec())
+            })
+            .collect()
+    }
+
+    /// Store a frame with a non-zero start code, kept apart from the level data
+    pub fn update_alternate(
+        &self,
+        universe: u16,
+        start_code: u8,
+        source_ip: IpAddr,
+        protocol: Protocol,
+        data: Bytes,
+    ) {
+        self.alternate_frames
+            .update(universe, start_code, source_ip, protocol, data);
+    }
+
+    /// Get stored alternate start code frames
+    pub fn get_alternate_frames(
+        &self,
+        universe: Option<u16>,
+        start_code: Option<u8>,
+    ) -> Vec<AlternateFrame> {
+        self.alternate_frames.query(universe, start_code)
     }
 }
 
@@ -63,11 +369,24 @@ impl Default for DmxStore {
 pub type DmxStoreHandle = Arc<DmxStore>;
 
 /// Network listener configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ListenerConfig {
     pub listen_artnet: bool,
     pub listen_sacn: bool,
-    pub bind_address: Ipv4Addr,
+    /// Broadcast an ArtPoll every 10 seconds
+    pub auto_poll: bool,
+    /// Interfaces to listen on, each gets its own listeners. 0.0.0.0 listens on
+    /// all interfaces and can't be combined with others.
+    pub bind_addresses: Vec<Ipv4Addr>,
+    /// sACN universes whose multicast groups are always joined
+    pub sacn_universe_ranges: Vec<UniverseRange>,
+    /// Join sACN groups source-specifically (IGMPv3 SSM), only for the known
+    /// sources of each universe
+    pub sacn_source_specific: bool,
+    /// Socket receive buffer (SO_RCVBUF) in bytes for both listeners, None for
+    /// the OS default
+    pub receive_buffer_size: Option<usize>,
 }
 
 impl Default for ListenerConfig {
@@ -75,7 +394,967 @@ impl Default for ListenerConfig {
         Self {
             listen_artnet: true,
             listen_sacn: true,
-            bind_address: Ipv4Addr::UNSPECIFIED,
+            auto_poll: true,
+            bind_addresses: vec![Ipv4Addr::UNSPECIFIED],
+            sacn_universe_ranges: vec![UniverseRange { start: 1, end: 512 }],
+            sacn_source_specific: false,
+            receive_buffer_size: None,
+        }
+    }
+}
+
+fn check_universe_range(range: UniverseRange) -> Result<(), String> {
+    if range.start == 0 || range.end > MAX_SACN_UNIVERSE || range.start > range.end {
+        return Err(format!(
+            "Invalid universe range {}-{} (universes are 1-{})",
+            range.start, range.end, MAX_SACN_UNIVERSE
+        ));
+    }
+    Ok(())
+}
+
+/// Inclusive range of universes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct UniverseRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+/// How a listener got its UDP port
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BindState {
+    Bound,
+    /// Another application already owns the port (e.g. console software on this
+    /// machine), bound alongside it with address reuse. Broadcast and multicast
+    /// reach both, unicast may only reach one of them.
+    Shared,
+    Failed,
+}
+
+/// Port binding result of one listener
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenerBinding {
+    pub protocol: Protocol,
+    pub interface: String,
+    pub port: u16,
+    pub state: BindState,
+    pub error: Option<String>,
+    pub suggest_sniffer: bool, // The port is in use, sniffer mode sees all traffic regardless
+}
+
+/// Receive statistics of one listener
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenerCounters {
+    pub protocol: Protocol,
+    pub interface: String,
+    pub port: u16,
+    pub packets_received: u64,
+    pub bytes_received: u64,
+    pub parse_failures: u64, // Datagrams that were not valid packets of the protocol
+    pub receive_errors: u64,
+    pub queue_drops: u64,      // Dropped because the parser workers fell behind
+    pub queue_depth: u64,      // Waiting for a parser worker right now
+    pub os_drops: Option<u64>, // Dropped by the kernel on a full receive buffer, Linux only
+}
+
+/// Live counters a listener updates for every datagram
+#[derive(Default)]
+pub(super) struct PacketCounters {
+    packets_received: AtomicU64,
+    bytes_received: AtomicU64,
+    pub(super) parse_failures: AtomicU64,
+    pub(super) receive_errors: AtomicU64,
+    pub(super) queue_drops: AtomicU64,
+    queued: AtomicU64,
+}
+
+impl PacketCounters {
+    pub(super) fn record_packet(&self, len: usize) {
+        self.packets_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received.fetch_add(len as u64, Ordering::Relaxed);
+    }
+}
+
+/// How long a listener waits for a packet before checking whether to stop
+pub(super) const LISTENER_STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Accepted SO_RCVBUF range in bytes
+const MIN_RECEIVE_BUFFER_SIZE: usize = 16 * 1024;
+const MAX_RECEIVE_BUFFER_SIZE: usize = 64 * 1024 * 1024;
+
+/// Highest universe number sACN allows
+const MAX_SACN_UNIVERSE: u16 = 63999;
+
+/// Dynamically joined multicast groups are left after this long without traffic
+/// or a discovery advertisement
+const MULTICAST_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often the sACN listeners join and leave groups for the configured and
+/// active universes
+const MULTICAST_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Listener configuration and the state of the running listeners
+pub struct ListenerState {
+    config: RwLock<ListenerConfig>,
+    config_path: RwLock<Option<PathBuf>>,
+    /// Bumped to make the running listeners exit, e.g. before a restart
+    generation: Mutex<u64>,
+    /// Interfaces with a running listener, per protocol
+    pub artnet_running: Mutex<Vec<Ipv4Addr>>,
+    pub sacn_running: Mutex<Vec<Ipv4Addr>>,
+    /// Receive buffer size the OS granted, per protocol
+    pub artnet_receive_buffer: Mutex<Option<usize>>,
+    pub sacn_receive_buffer: Mutex<Option<usize>>,
+    /// Port binding result per protocol and interface since the last restart
+    pub bindings: Mutex<Vec<ListenerBinding>>,
+    /// Receive counters per protocol, interface and port, kept across restarts
+    counters: Mutex<Vec<(Protocol, SocketAddrV4, Arc<PacketCounters>)>>,
+    /// When each universe was last seen in traffic or sACN discovery
+    active_universes: Mutex<HashMap<u16, Instant>>,
+    /// Decoders of other protocols, listened for alongside Art-Net and sACN
+    pub decoders: DecoderRegistry,
+    /// Reply delays of the nodes to our ArtPolls
+    pub poll_latency: PollLatencyTracker,
+    /// RDM devices the nodes list in their ToDs
+    pub rdm_devices: RdmDeviceTable,
+    /// Timecode feeds and the offsets between them
+    pub timecode: TimecodeTracker,
+    /// CIDs of this application's own sACN output, skipped when it loops back
+    own_sacn_cids: RwLock<HashSet<[u8; 16]>>,
+    /// Source ports of this application's own output sockets, skipped when the
+    /// datagram comes from a local address
+    own_ports: RwLock<HashSet<u16>>,
+    local_ips: RwLock<HashSet<IpAddr>>,
+}
+
+impl ListenerState {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(ListenerConfig::default()),
+            config_path: RwLock::new(None),
+            generation: Mutex::new(0),
+            artnet_running: Mutex::new(Vec::new()),
+            sacn_running: Mutex::new(Vec::new()),
+            artnet_receive_buffer: Mutex::new(None),
+            sacn_receive_buffer: Mutex::new(None),
+            bindings: Mutex::new(Vec::new()),
+            counters: Mutex::new(Vec::new()),
+            active_universes: Mutex::new(HashMap::new()),
+            decoders: DecoderRegistry::new(),
+            poll_latency: PollLatencyTracker::new(),
+            rdm_devices: RdmDeviceTable::new(),
+            timecode: TimecodeTracker::new(),
+            own_sacn_cids: RwLock::new(HashSet::new()),
+            own_ports: RwLock::new(HashSet::new()),
+            local_ips: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// Load the configuration from disk and persist future changes to the same file
+    pub fn load_config(&self, path: PathBuf) {
+        *self.config.write() = load_json(&path);
+        *self.config_path.write() = Some(path);
+    }
+
+    /// Skip received sACN from this CID, e.g. the channel park output, so it
+    /// doesn't reach the DMX store or appear as a source
+    pub fn ignore_sacn_cid(&self, cid: [u8; 16]) {
+        self.own_sacn_cids.write().insert(cid);
+    }
+
+    /// Skip datagrams a local address sends from this port, e.g. the router's
+    /// output looping back, so they don't reach the DMX store or the sources
+    pub fn ignore_local_sender(&self, port: u16) {
+        *self.local_ips.write() = local_ip_address::list_afinet_netifas()
+            .map(|ifaces| ifaces.into_iter().map(|(_, ip)| ip).collect())
+            .unwrap_or_default();
+        self.own_ports.write().insert(port);
+    }
+
+    /// Whether a datagram was sent by one of our own output sockets
+    fn is_own_traffic(&self, src: SocketAddr) -> bool {
+        self.own_ports.read().contains(&src.port())
+            && (src.ip().is_loopback() || self.local_ips.read().contains(&src.ip()))
+    }
+
+    pub fn bind_addrs(&self) -> Vec<Ipv4Addr> {
+        self.config.read().bind_addresses.clone()
+    }
+
+    /// Select the interfaces to listen on (just 0.0.0.0 for all). Takes effect
+    /// when the listeners are restarted.
+    pub fn set_bind_addresses(&self, mut bind_addresses: Vec<Ipv4Addr>) -> Result<(), String> {
+        bind_addresses.sort();
+        bind_addresses.dedup();
+        if bind_addresses.is_empty() {
+            return Err("Select at least one interface".to_string());
+        }
+        if bind_addresses.len() > 1 && bind_addresses.contains(&Ipv4Addr::UNSPECIFIED) {
+            return Err("All interfaces (0.0.0.0) can't be combined with others".to_string());
+        }
+        if let Some(ip) = bind_addresses
+            .iter()
+            .find(|ip| !ip.is_unspecified() && !is_local_ipv4(**ip))
+        {
+            return Err(format!("{} is not an address of this machine", ip));
+        }
+        self.config.write().bind_addresses = bind_addresses;
+        self.save_config()
+    }
+
+    pub fn receive_buffer_size(&self) -> Option<usize> {
+        self.config.read().receive_buffer_size
+    }
+
+    /// Set the socket receive buffer size (None for the OS default). Takes effect
+    /// when the listeners are restarted.
+    pub fn set_receive_buffer_size(&self, size: Option<usize>) -> Result<(), String> {
+        if let Some(size) = size {
+            if !(MIN_RECEIVE_BUFFER_SIZE..=MAX_RECEIVE_BUFFER_SIZE).contains(&size) {
+                return Err(format!(
+                    "Receive buffer size must be between {} KiB and {} MiB",
+                    MIN_RECEIVE_BUFFER_SIZE / 1024,
+                    MAX_RECEIVE_BUFFER_SIZE / (1024 * 1024)
+                ));
+            }
+        }
+        self.config.write().receive_buffer_size = size;
+        self.save_config()
+    }
+
+    pub fn sacn_universe_ranges(&self) -> Vec<UniverseRange> {
+        self.config.read().sacn_universe_ranges.clone()
+    }
+
+    /// Always join the sACN groups of a universe range. The running listeners
+    /// pick up the change within a second.
+    pub fn add_sacn_universe_range(&self, range: UniverseRange) -> Result<(), String> {
+        check_universe_range(range)?;
+        {
+            let mut config = self.config.write();
+            if config.sacn_universe_ranges.contains(&range) {
+                return Err(format!(
+                    "Universe range {}-{} is already joined",
+                    range.start, range.end
+                ));
+            }
+            config.sacn_universe_ranges.push(range);
+            config.sacn_universe_ranges.sort();
+        }
+        self.save_config()
+    }
+
+    /// Replace the always joined universe ranges, e.g. from a venue profile.
+    /// The running listeners pick up the change within a second.
+    pub fn set_sacn_universe_ranges(&self, mut ranges: Vec<UniverseRange>) -> Result<(), String> {
+        for range in &ranges {
+            check_universe_range(*range)?;
+        }
+        ranges.sort();
+        ranges.dedup();
+        self.config.write().sacn_universe_ranges = ranges;
+        self.save_config()
+    }
+
+    pub fn sacn_source_specific(&self) -> bool {
+        self.config.read().sacn_source_specific
+    }
+
+    /// Switch between any-source and source-specific sACN joins. The running
+    /// listeners rejoin their groups within a second.
+    pub fn set_sacn_source_specific(&self, enabled: bool) -> Result<(), String> {
+        self.config.write().sacn_source_specific = enabled;
+        self.save_config()
+    }
+
+    pub fn remove_sacn_universe_range(&self, range: UniverseRange) -> Result<(), String> {
+        {
+            let mut config = self.config.write();
+            let before = config.sacn_universe_ranges.len();
+            config.sacn_universe_ranges.retain(|r| *r != range);
+            if config.sacn_universe_ranges.len() == before {
+                return Err(format!(
+                    "Universe range {}-{} not found",
+                    range.start, range.end
+                ));
+            }
+        }
+        self.save_config()
+    }
+
+    fn save_config(&self) -> Result<(), String> {
+        match self.config_path.read().as_ref() {
+            Some(path) => save_json(path, &*self.config.read()),
+            None => Ok(()),
+        }
+    }
+
+    /// Generation a newly started listener runs under
+    pub fn generation(&self) -> u64 {
+        *self.generation.lock()
+    }
+
+    pub(super) fn should_stop(&self, generation: u64, protocol: Protocol) -> bool {
+        *self.generation.lock() != generation || !self.protocol_enabled(protocol)
+    }
+
+    /// Ask the running listeners to exit and wait (up to a second) until they have
+    /// released their sockets
+    pub async fn stop_listeners(&self) {
+        *self.generation.lock() += 1;
+        self.bindings.lock().clear();
+        self.wait_stopped(&[Protocol::ArtNet, Protocol::Sacn, Protocol::Other])
+            .await;
+    }
+
+    async fn wait_stopped(&self, protocols: &[Protocol]) {
+        for _ in 0..50 {
+            if !protocols.iter().any(|p| self.is_running(*p)) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        warn!("[Listener] Listeners did not stop in time");
+    }
+
+    /// Whether a listener of the protocol is running on any interface
+    pub fn is_running(&self, protocol: Protocol) -> bool {
+        match protocol {
+            Protocol::ArtNet => !self.artnet_running.lock().is_empty(),
+            Protocol::Sacn => !self.sacn_running.lock().is_empty(),
+            Protocol::Other => self.decoders.is_running(),
+        }
+    }
+
+    pub fn protocol_enabled(&self, protocol: Protocol) -> bool {
+        let config = self.config.read();
+        match protocol {
+            Protocol::ArtNet => config.listen_artnet,
+            Protocol::Sacn => config.listen_sacn,
+            // Decoder listeners run while any decoder is registered
+            Protocol::Other => !self.decoders.is_empty(),
+        }
+    }
+
+    /// Enable or disable the listeners of a protocol. Disabled listeners exit and
+    /// are waited for, enabled ones have to be started by the caller.
+    pub async fn set_protocol_enabled(
+        &self,
+        protocol: Protocol,
+        enabled: bool,
+    ) -> Result<(), String> {
+        {
+            let mut config = self.config.write();
+            match protocol {
+                Protocol::ArtNet => config.listen_artnet = enabled,
+                Protocol::Sacn => config.listen_sacn = enabled,
+                Protocol::Other => {
+                    return Err("Decoder listeners run while a decoder is registered".to_string())
+                }
+            }
+        }
+        self.save_config()?;
+        if !enabled {
+            self.wait_stopped(&[protocol]).await;
+            self.bindings.lock().retain(|b| b.protocol != protocol);
+        }
+        Ok(())
+    }
+
+    pub fn auto_poll_enabled(&self) -> bool {
+        self.config.read().auto_poll
+    }
+
+    pub fn set_auto_poll_enabled(&self, enabled: bool) -> Result<(), String> {
+        self.config.write().auto_poll = enabled;
+        self.save_config()
+    }
+
+    /// Counters for a listener, continuing any earlier counts on the same
+    /// interface and port
+    pub(super) fn listener_counters(
+        &self,
+        protocol: Protocol,
+        addr: SocketAddrV4,
+    ) -> Arc<PacketCounters> {
+        let mut counters = self.counters.lock();
+        if let Some((_, _, existing)) = counters
+            .iter()
+            .find(|(p, a, _)| *p == protocol && *a == addr)
+        {
+            return existing.clone();
+        }
+        let new = Arc::new(PacketCounters::default());
+        counters.push((protocol, addr, new.clone()));
+        new
+    }
+
+    /// Receive statistics of every listener started so far
+    pub fn get_counters(&self) -> Vec<ListenerCounters> {
+        let drops = udp_socket_drops();
+        self.counters
+            .lock()
+            .iter()
+            .map(|(protocol, addr, counters)| ListenerCounters {
+                protocol: *protocol,
+                interface: addr.ip().to_string(),
+                port: addr.port(),
+                packets_received: counters.packets_received.load(Ordering::Relaxed),
+                bytes_received: counters.bytes_received.load(Ordering::Relaxed),
+                parse_failures: counters.parse_failures.load(Ordering::Relaxed),
+                receive_errors: counters.receive_errors.load(Ordering::Relaxed),
+                queue_drops: counters.queue_drops.load(Ordering::Relaxed),
+                queue_depth: counters.queued.load(Ordering::Relaxed),
+                os_drops: drops.as_ref().map(|drops| {
+                    drops
+                        .iter()
+                        .filter(|(a, _)| a == addr)
+                        .map(|(_, d)| d)
+                        .sum()
+                }),
+            })
+            .collect()
+    }
+
+    fn record_binding(&self, binding: ListenerBinding) {
+        let mut bindings = self.bindings.lock();
+        bindings.retain(|b| {
+            b.protocol != binding.protocol
+                || b.interface != binding.interface
+                || b.port != binding.port
+        });
+        bindings.push(binding);
+    }
+
+    /// Note traffic or a discovery advertisement for a universe so the sACN
+    /// listeners join its multicast group
+    pub fn mark_universe_active(&self, universe: u16) {
+        if (1..=MAX_SACN_UNIVERSE).contains(&universe) {
+            self.active_universes
+                .lock()
+                .insert(universe, Instant::now());
+        }
+    }
+
+    /// Group memberships the sACN listeners should hold. With source-specific
+    /// joins a universe is only joined for the sources known to send it.
+    fn wanted_sacn_memberships(&self, source_manager: &SourceManagerHandle) -> HashSet<Membership> {
+        let universes = self.wanted_sacn_universes();
+        if !self.sacn_source_specific() {
+            return universes.into_iter().map(|u| (u, None)).collect();
+        }
+        let source_ips = source_manager.sacn_source_ips();
+        universes
+            .into_iter()
+            .filter_map(|u| source_ips.get(&u).map(|ips| (u, ips)))
+            .flat_map(|(u, ips)| ips.iter().map(move |ip| (u, Some(*ip))))
+            .collect()
+    }
+
+    /// Universes whose sACN groups should be joined: the configured ranges plus
+    /// those marked active within the idle timeout, forgetting older ones
+    fn wanted_sacn_universes(&self) -> HashSet<u16> {
+        let mut wanted: HashSet<u16> = self
+            .config
+            .read()
+            .sacn_universe_ranges
+            .iter()
+            .flat_map(|r| r.start..=r.end)
+            .filter(|u| (1..=MAX_SACN_UNIVERSE).contains(u))
+            .collect();
+        let mut active = self.active_universes.lock();
+        active.retain(|_, seen| seen.elapsed() < MULTICAST_IDLE_TIMEOUT);
+        wanted.extend(active.keys());
+        wanted
+    }
+}
+
+impl Default for ListenerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type ListenerStateHandle = Arc<ListenerState>;
+
+/// Add or remove an interface from a running listener list
+fn mark_running(running: &Mutex<Vec<Ipv4Addr>>, interface: Ipv4Addr, is_running: bool) {
+    let mut running = running.lock();
+    running.retain(|i| *i != interface);
+    if is_running {
+        running.push(interface);
+        running.sort();
+    }
+}
+
+/// A universe group membership, source-specific when a source is given
+type Membership = (u16, Option<Ipv4Addr>);
+
+/// sACN multicast groups joined by one listener socket
+#[derive(Default)]
+struct MulticastGroups {
+    joined: HashSet<Membership>,
+    /// Joins the OS refused (e.g. a membership limit), not retried while wanted
+    failed: HashSet<Membership>,
+}
+
+impl MulticastGroups {
+    /// Join newly wanted memberships and leave the ones no longer wanted
+    fn update(&mut self, socket: &UdpSocket, interface: Ipv4Addr, wanted: HashSet<Membership>) {
+        let (mut joined_count, mut left_count, mut failed_count) = (0, 0, 0);
+        let socket = socket2::SockRef::from(socket);
+
+        // Leave before joining, a group can't be joined any-source and
+        // source-specific at the same time
+        self.failed.retain(|m| wanted.contains(m));
+        let unwanted: Vec<Membership> = self.joined.difference(&wanted).copied().collect();
+        for (universe, source) in unwanted {
+            let multicast_addr = crate::network::sacn::sacn_multicast_address(universe);
+            self.joined.remove(&(universe, source));
+            let result = match source {
+                Some(source) => socket.leave_ssm_v4(&source, &multicast_addr, &interface),
+                None => socket.leave_multicast_v4(&multicast_addr, &interface),
+            };
+            match result {
+                Ok(_) => left_count += 1,
+                Err(e) => warn!("[sACN] Failed to leave universe {}: {}", universe, e),
+            }
+        }
+
+        for (universe, source) in wanted {
+            if self.joined.contains(&(universe, source))
+                || self.failed.contains(&(universe, source))
+            {
+                continue;
+            }
+            let multicast_addr = crate::network::sacn::sacn_multicast_address(universe);
+            let result = match source {
+                Some(source) => socket.join_ssm_v4(&source, &multicast_addr, &interface),
+                None => socket.join_multicast_v4(&multicast_addr, &interface),
+            };
+            match result {
+                Ok(_) => {
+                    joined_count += 1;
+                    self.joined.insert((universe, source));
+                }
+                Err(e) => {
+                    failed_count += 1;
+                    self.failed.insert((universe, source));
+                    if failed_count <= 10 {
+                        warn!(
+                            "[sACN] Failed to join multicast for universe {}: {}",
+                            universe, e
+                        );
+                    }
+                }
+            }
+        }
+
+        if joined_count + left_count + failed_count > 0 {
+            info!(
+                "[sACN] Multicast groups on {}: {} joined, {} left, {} failed ({} total)",
+                interface,
+                joined_count,
+                left_count,
+                failed_count,
+                self.joined.len()
+            );
+        }
+    }
+}
+
+/// Create a non-blocking UDP socket bound to addr, optionally with address reuse
+fn bind_udp_socket(addr: SocketAddr, reuse: bool) -> std::io::Result<socket2::Socket> {
+    let socket = socket2::Socket::new(
+        socket2::Domain::IPV4,
+        socket2::Type::DGRAM,
+        Some(socket2::Protocol::UDP),
+    )?;
+
+    if reuse {
+        socket.set_reuse_address(true)?;
+        #[cfg(not(windows))]
+        socket.set_reuse_port(true)?;
+    }
+
+    socket.bind(&addr.into())?;
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
+
+/// Bind a listener socket and record how it went. When another application
+/// already owns the port, fall back to sharing it with address reuse.
+/// `always_reuse` keeps the port shareable for applications started later.
+pub(super) fn bind_listener_socket(
+    listener_state: &ListenerState,
+    protocol: Protocol,
+    addr: SocketAddr,
+    always_reuse: bool,
+) -> std::io::Result<socket2::Socket> {
+    let tag = match protocol {
+        Protocol::ArtNet => "Art-Net",
+        Protocol::Sacn => "sACN",
+        Protocol::Other => "Decoder",
+    };
+    let mut binding = ListenerBinding {
+        protocol,
+        interface: addr.ip().to_string(),
+        port: addr.port(),
+        state: BindState::Bound,
+        error: None,
+        suggest_sniffer: false,
+    };
+
+    // Probe without reuse first, that bind only fails when the port is taken
+    let in_use = match bind_udp_socket(addr, false) {
+        Ok(socket) if !always_reuse => {
+            listener_state.record_binding(binding);
+            return Ok(socket);
+        }
+        Ok(_) => false,
+        Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => true,
+        Err(e) => {
+            binding.state = BindState::Failed;
+            binding.error = Some(e.to_string());
+            listener_state.record_binding(binding);
+            return Err(e);
+        }
+    };
+
+    if in_use {
+        info!(
+            "[{}] Port {} is in use by another application, trying to share it",
+            tag,
+            addr.port()
+        );
+    }
+    binding.suggest_sniffer = in_use;
+    let result = bind_udp_socket(addr, true);
+    match &result {
+        Ok(_) if in_use => binding.state = BindState::Shared,
+        Ok(_) => {}
+        Err(e) => {
+            binding.state = BindState::Failed;
+            binding.error = Some(if in_use {
+                format!(
+                    "Port {} is in use by another application that doesn't allow sharing ({})",
+                    addr.port(),
+                    e
+                )
+            } else {
+                e.to_string()
+            });
+        }
+    }
+    listener_state.record_binding(binding);
+    result
+}
+
+/// Apply the configured receive buffer size and record the size the OS granted
+/// (Linux reports double the requested value to account for bookkeeping)
+fn apply_receive_buffer(
+    socket: socket2::SockRef<'_>,
+    size: Option<usize>,
+    granted: &Mutex<Option<usize>>,
+) -> std::io::Result<()> {
+    if let Some(size) = size {
+        socket.set_recv_buffer_size(size)?;
+    }
+    *granted.lock() = Some(socket.recv_buffer_size()?);
+    Ok(())
+}
+
+/// Datagrams the kernel dropped on full receive buffers, summed over the UDP
+/// sockets bound to a port. Only available on Linux.
+pub fn kernel_drops(port: u16) -> Option<u64> {
+    let drops = udp_socket_drops()?;
+    Some(
+        drops
+            .iter()
+            .filter(|(addr, _)| addr.port() == port)
+            .map(|(_, d)| d)
+            .sum(),
+    )
+}
+
+/// Receive buffer drops of every IPv4 UDP socket on this machine by local
+/// address, from /proc/net/udp. None on other platforms.
+fn udp_socket_drops() -> Option<Vec<(SocketAddrV4, u64)>> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    // Columns: sl local_address rem_address st tx:rx tr tm->when retrnsmt uid
+    // timeout inode ref pointer drops. Addresses are hex, the IP in host byte order.
+    let table = std::fs::read_to_string("/proc/net/udp").ok()?;
+    Some(
+        table
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let (ip, port) = fields.get(1)?.split_once(':')?;
+                let ip = Ipv4Addr::from(u32::from_str_radix(ip, 16).ok()?.to_ne_bytes());
+                let port = u16::from_str_radix(port, 16).ok()?;
+                let drops = fields.last()?.parse::<u64>().ok()?;
+                Some((SocketAddrV4::new(ip, port), drops))
+            })
+            .collect(),
+    )
+}
+
+/// Whether an address belongs to one of this machine's interfaces
+fn is_local_ipv4(ip: Ipv4Addr) -> bool {
+    local_ip_address::list_afinet_netifas()
+        .map(|ifaces| ifaces.iter().any(|(_, addr)| *addr == IpAddr::V4(ip)))
+        .unwrap_or(false)
+}
+
+/// Datagrams a parser worker can have waiting before new ones are dropped
+const PARSER_QUEUE_LEN: usize = 1024;
+
+/// Upper bound on the parser workers of one listener
+const MAX_PARSER_WORKERS: usize = 4;
+
+/// A received datagram waiting for a parser worker
+pub(super) struct Datagram {
+    pub(super) data: Bytes,
+    pub(super) src: SocketAddr,
+    /// Selected interface it arrived on, None when listening on all
+    pub(super) interface: Option<Ipv4Addr>,
+}
+
+/// Parser workers of one listener, each fed by its own bounded queue. The receive
+/// loop only copies datagrams into the queues, so a burst or a slow consumer fills
+/// a queue instead of the socket's receive buffer. All datagrams of one sender go
+/// to the same worker, which keeps them in order for sequence tracking.
+pub(super) struct ParserPool {
+    queues: Vec<mpsc::Sender<Datagram>>,
+    counters: Arc<PacketCounters>,
+}
+
+impl ParserPool {
+    /// Spawn the workers. They exit once the pool is dropped and their queue is empty.
+    pub(super) fn spawn<F, Fut>(counters: Arc<PacketCounters>, handler: F) -> Self
+    where
+        F: Fn(Datagram) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let workers = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(MAX_PARSER_WORKERS);
+        let handler = Arc::new(handler);
+        let queues = (0..workers)
+            .map(|_| {
+                let (tx, mut rx) = mpsc::channel::<Datagram>(PARSER_QUEUE_LEN);
+                let handler = handler.clone();
+                let counters = counters.clone();
+                tokio::spawn(async move {
+                    while let Some(datagram) = rx.recv().await {
+                        counters.queued.fetch_sub(1, Ordering::Relaxed);
+                        handler(datagram).await;
+                    }
+                });
+                tx
+            })
+            .collect();
+        Self { queues, counters }
+    }
+
+    /// Queue a datagram for its sender's worker, false when that queue is full
+    pub(super) fn dispatch(&self, datagram: Datagram) -> bool {
+        let key = match datagram.src.ip() {
+            IpAddr::V4(ip) => u32::from(ip) as usize,
+            IpAddr::V6(ip) => u128::from(ip) as usize,
+        };
+        let queue = &self.queues[key % self.queues.len()];
+        // Counted before sending so the worker never takes it below zero
+        self.counters.queued.fetch_add(1, Ordering::Relaxed);
+        let queued = queue.try_send(datagram).is_ok();
+        if !queued {
+            self.counters.queued.fetch_sub(1, Ordering::Relaxed);
+        }
+        queued
+    }
+}
+
+/// Everything an Art-Net parser worker needs to handle a datagram
+struct ArtNetHandler {
+    source_manager: SourceManagerHandle,
+    dmx_store: DmxStoreHandle,
+    event_tx: EventBusHandle,
+    node_emulator: NodeEmulatorHandle,
+    rdm_responder: RdmResponderHandle,
+    listener_state: ListenerStateHandle,
+    counters: Arc<PacketCounters>,
+    socket: Arc<UdpSocket>, // Emulator replies go out of the listening socket
+    reply_ip: Ipv4Addr,     // Address advertised in ArtPollReply by the emulators
+}
+
+impl ArtNetHandler {
+    async fn handle(
+        &self,
+        Datagram {
+            data,
+            src,
+            interface,
+        }: Datagram,
+    ) {
+        // Our own output (the router) looped back
+        if self.listener_state.is_own_traffic(src) {
+            return;
+        }
+        let Some(packet) = parse_artnet_packet(&data, src) else {
+            self.counters.parse_failures.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+        let source_manager = &self.source_manager;
+        source_manager.record_traffic(src.ip(), Protocol::ArtNet, data.len(), interface);
+        match packet {
+            ArtNetPacket::PollReply(reply) => {
+                let ip = IpAddr::V4(Ipv4Addr::new(
+                    reply.ip_address[0],
+                    reply.ip_address[1],
+                    reply.ip_address[2],
+                    reply.ip_address[3],
+                ));
+
+                // Calculate universes from sw_out
+                let mut universes = Vec::new();
+                for i in 0..reply.num_ports.min(4) as usize {
+                    if reply.port_types[i] & 0x80 != 0 {
+                        // Output port
+                        let uni = crate::network::artnet::calculate_artnet_universe(
+                            reply.net_switch,
+                            reply.sub_switch,
+                            reply.sw_out[i],
+                        );
+                        universes.push(uni);
+                    }
+                }
+
+                source_manager.update_artnet_source(
+                    ip,
+                    &reply.short_name,
+                    &reply.long_name,
+                    Some(reply.mac_address),
+                    Some(universes),
+                    None, // No sequence number for PollReply
+                );
+                source_manager.check_poll_reply_ip(ip, src.ip());
+                self.listener_state.poll_latency.record_reply(ip);
+
+                let _ = self.event_tx.send(ListenerEvent::SourcesUpdated);
+            }
+            ArtNetPacket::Dmx(dmx) => {
+                // Get source IP and update as Art-Net source (sending DMX)
+                let ip = src.ip();
+                source_manager.update_artnet_source_with_direction(
+                    ip,
+                    "",
+                    "",
+                    None,
+                    Some(vec![dmx.universe]),
+                    SourceDirection::Sending,
+                    Some(dmx.sequence),
+                );
+
+                // Join the matching sACN groups in case a gateway converts this
+                // universe, both as numbered and with the usual +1 offset
+                self.listener_state.mark_universe_active(dmx.universe);
+                self.listener_state
+                    .mark_universe_active(dmx.universe.saturating_add(1));
+
+                // Store DMX data
+                let changed = self.dmx_store.update(dmx.universe, ip, &dmx.data);
+                self.node_emulator
+                    .record_dmx(dmx.universe, ip, dmx.data.len());
+
+                let _ = self.event_tx.send(ListenerEvent::DmxData(DmxData {
+                    universe: dmx.universe,
+                    data: dmx.data,
+                    protocol: Protocol::ArtNet,
+                    source_ip: ip,
+                    source_port: src.port(),
+                    timestamp: std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_millis() as u64,
+                    interface,
+                    changed,
+                }));
+            }
+            ArtNetPacket::Nzs(nzs) => {
+                // Alternate start code data is stored separately from levels
+                self.dmx_store.update_alternate(
+                    nzs.universe,
+                    nzs.start_code,
+                    src.ip(),
+                    Protocol::ArtNet,
+                    nzs.data,
+                );
+            }
+            ArtNetPacket::Poll => {
+                // Only emulators answer polls, the monitor itself stays silent.
+                // RDM responder bind indexes follow the emulated node's.
+                let reply_to = SocketAddr::new(src.ip(), ARTNET_PORT);
+                let reply_ip = interface.unwrap_or(self.reply_ip);
+                let mut replies = self.node_emulator.poll_replies(reply_ip, src.ip());
+                let first_bind_index = replies.len() + 1;
+                replies.extend(self.rdm_responder.poll_replies(reply_ip, first_bind_index));
+                for reply in replies {
+                    let _ = self.socket.send_to(&reply, reply_to).await;
+                }
+            }
+            ArtNetPacket::TodRequest(request) => {
+                let reply_to = SocketAddr::new(src.ip(), ARTNET_PORT);
+                let first_bind_index = self.node_emulator.reply_count() + 1;
+                for reply in self
+                    .rdm_responder
+                    .tod_replies(&request.universes, first_bind_index)
+                {
+                    let _ = self.socket.send_to(&reply, reply_to).await;
+                }
+            }
+            ArtNetPacket::TodData(tod) => {
+                if let Some(change) = self.listener_state.rdm_devices.record_tod(src.ip(), &tod) {
+                    info!("[RDM] {} behind {}", change.message, change.node_ip);
+                    let _ = self
+                        .event_tx
+                        .send(ListenerEvent::TodChanged(Box::new(change)));
+                }
+            }
+            ArtNetPacket::Rdm(rdm) if rdm.command == 0x00 => {
+                // ArProcess - other commands are reserved. Responses go to the
+                // device table, requests to the virtual devices.
+                let rdm_devices = &self.listener_state.rdm_devices;
+                if let Some(status) =
+                    rdm_devices.record_response(src.ip(), rdm.universe, &rdm.rdm_packet)
+                {
+                    for message in status {
+                        let _ = self.event_tx.send(ListenerEvent::RdmStatus(message));
+                    }
+                    return;
+                }
+                if let Some(reply) = self.rdm_responder.handle_rdm(rdm.universe, &rdm.rdm_packet) {
+                    let reply_to = SocketAddr::new(src.ip(), ARTNET_PORT);
+                    let _ = self.socket.send_to(&reply, reply_to).await;
+                }
+            }
+            ArtNetPacket::TimeCode(timecode) => {
+                let source_id = format!("artnet-{}", src.ip());
+                for alert in self.listener_state.timecode.record(
+                    &source_id,
+                    timecode.stream_id,
+                    timecode.timecode,
+                ) {
+                    warn!("[Timecode] {}", alert.message);
+                    let _ = self.event_tx.send(ListenerEvent::TimecodeDrift(alert));
+                }
+            }
+            ArtNetPacket::Rdm(_) | ArtNetPacket::Other(_) => {
+                // Ignore other packet types for now
+            }
         }
     }
 }
@@ -84,268 +1363,500 @@ impl Default for ListenerConfig {
 pub async fn start_artnet_listener(
     source_manager: SourceManagerHandle,
     dmx_store: DmxStoreHandle,
-    event_tx: broadcast::Sender<ListenerEvent>,
-    bind_addr: Ipv4Addr,
+    event_tx: EventBusHandle,
+    node_emulator: NodeEmulatorHandle,
+    rdm_responder: RdmResponderHandle,
+    listener_state: ListenerStateHandle,
+    target: ListenTarget,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let generation = listener_state.generation();
+    let bind_addr = target.bind_addr;
     let addr = SocketAddr::new(IpAddr::V4(bind_addr), ARTNET_PORT);
-    let socket = UdpSocket::bind(addr).await?;
+    let socket = bind_listener_socket(&listener_state, Protocol::ArtNet, addr, false)?;
+    let socket = Arc::new(UdpSocket::from_std(socket.into())?);
 
     // Enable broadcast receiving
     socket.set_broadcast(true)?;
+    enable_arrival_interface(&socket)?;
+    apply_receive_buffer(
+        socket2::SockRef::from(&*socket),
+        listener_state.receive_buffer_size(),
+        &listener_state.artnet_receive_buffer,
+    )?;
 
-    println!("[Art-Net] Listening on {}", addr);
+    let reply_ip = match local_ip_address::local_ip() {
+        Ok(IpAddr::V4(ip)) if bind_addr.is_unspecified() => ip,
+        _ => bind_addr,
+    };
+
+    info!(
+        "[Art-Net] Listening on {} for {:?}",
+        addr, target.interfaces
+    );
+    for interface in &target.interfaces {
+        mark_running(&listener_state.artnet_running, *interface, true);
+    }
+    let counters = listener_state
+        .listener_counters(Protocol::ArtNet, SocketAddrV4::new(bind_addr, ARTNET_PORT));
+
+    let handler = Arc::new(ArtNetHandler {
+        source_manager,
+        dmx_store,
+        event_tx,
+        node_emulator,
+        rdm_responder,
+        listener_state: listener_state.clone(),
+        counters: counters.clone(),
+        socket: socket.clone(),
+        reply_ip,
+    });
+    let parsers = ParserPool::spawn(counters.clone(), move |datagram| {
+        let handler = handler.clone();
+        async move { handler.handle(datagram).await }
+    });
 
     let mut buf = vec![0u8; 1500];
+    let mut arrival = ArrivalFilter::new(&target);
 
-    loop {
-        match socket.recv_from(&mut buf).await {
-            Ok((len, src)) => {
-                if let Some(packet) = parse_artnet_packet(&buf[..len], src) {
-                    match packet {
-                        ArtNetPacket::PollReply(reply) => {
-                            let ip = IpAddr::V4(Ipv4Addr::new(
-                                reply.ip_address[0],
-                                reply.ip_address[1],
-                                reply.ip_address[2],
-                                reply.ip_address[3],
-                            ));
-
-                            // Calculate universes from sw_out
-                            let mut universes = Vec::new();
-                            for i in 0..reply.num_ports.min(4) as usize {
-                                if reply.port_types[i] & 0x80 != 0 {
-                                    // Output port
-                                    let uni = crate::network::artnet::calculate_artnet_universe(
-                                        reply.net_switch,
-                                        reply.sub_switch,
-                                        reply.sw_out[i],
-                                    );
-                                    universes.push(uni);
-                                }
-                            }
-
-                            source_manager.update_artnet_source(
-                                ip,
-                                &reply.short_name,
-                                &reply.long_name,
-                                Some(reply.mac_address),
-                                Some(universes),
-                                None, // No sequence number for PollReply
-                            );
-
-                            let _ = event_tx.send(ListenerEvent::SourcesUpdated);
-                        }
-                        ArtNetPacket::Dmx(dmx) => {
-                            // Get source IP and update as Art-Net source (sending DMX)
-                            let ip = src.ip();
-                            source_manager.update_artnet_source_with_direction(
-                                ip,
-                                "",
-                                "",
-                                None,
-                                Some(vec![dmx.universe]),
-                                SourceDirection::Sending,
-                                Some(dmx.sequence),
-                            );
-
-                            // Store DMX data
-                            dmx_store.update(dmx.universe, dmx.data.clone());
-
-                            let _ = event_tx.send(ListenerEvent::DmxData(DmxData {
-                                universe: dmx.universe,
-                                data: dmx.data,
-                                source_ip: ip,
-                                timestamp: std::time::SystemTime::now()
-                                    .duration_since(std::time::UNIX_EPOCH)
-                                    .unwrap_or_default()
-                                    .as_millis() as u64,
-                            }));
-                        }
-                        ArtNetPacket::Poll => {
-                            // We don't respond to polls in monitor mode
-                        }
-                        ArtNetPacket::Other(_) => {
-                            // Ignore other packet types for now
-                        }
-                    }
+    while !listener_state.should_stop(generation, Protocol::ArtNet) {
+        // Time out regularly so a stop request is noticed on a quiet network
+        let Ok(result) = tokio::time::timeout(
+            LISTENER_STOP_CHECK_INTERVAL,
+            recv_with_interface(&socket, &mut buf),
+        )
+        .await
+        else {
+            continue;
+        };
+        match result {
+            Ok((len, src, if_index)) => {
+                // Traffic of interfaces that aren't selected
+                let Some(interface) = arrival.accept(if_index) else {
+                    continue;
+                };
+                counters.record_packet(len);
+                let datagram = Datagram {
+                    data: Bytes::copy_from_slice(&buf[..len]),
+                    src,
+                    interface,
+                };
+                if !parsers.dispatch(datagram) {
+                    counters.queue_drops.fetch_add(1, Ordering::Relaxed);
                 }
             }
             Err(e) => {
-                eprintln!("[Art-Net] Receive error: {}", e);
+                counters.receive_errors.fetch_add(1, Ordering::Relaxed);
+                warn!("[Art-Net] Receive error: {}", e);
             }
         }
     }
+
+    info!("[Art-Net] Stopped listening on {}", addr);
+    for interface in &target.interfaces {
+        mark_running(&listener_state.artnet_running, *interface, false);
+    }
+    Ok(())
 }
 
-/// Start the sACN listener
-pub async fn start_sacn_listener(
+/// Everything an sACN parser worker needs to handle a datagram
+struct SacnHandler {
     source_manager: SourceManagerHandle,
     dmx_store: DmxStoreHandle,
-    event_tx: broadcast::Sender<ListenerEvent>,
-    bind_addr: Ipv4Addr,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let addr = SocketAddr::new(IpAddr::V4(bind_addr), SACN_PORT);
-    let discovery_addr = Ipv4Addr::new(239, 255, 0, 0);
-
-    // Create socket with socket2 for multicast support
-    let socket = socket2::Socket::new(
-        socket2::Domain::IPV4,
-        socket2::Type::DGRAM,
-        Some(socket2::Protocol::UDP),
-    )?;
+    event_tx: EventBusHandle,
+    listener_state: ListenerStateHandle,
+    counters: Arc<PacketCounters>,
+}
 
-    socket.set_reuse_address(true)?;
-    #[cfg(not(windows))]
-    socket.set_reuse_port(true)?;
+impl SacnHandler {
+    fn handle(
+        &self,
+        Datagram {
+            data,
+            src,
+            interface,
+        }: Datagram,
+    ) {
+        let Some(packet) = parse_sacn_packet(&data, src) else {
+            self.counters.parse_failures.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+        // Our own output (the router, channel park) looped back
+        if self.listener_state.is_own_traffic(src)
+            || packet
+                .cid()
+                .is_some_and(|cid| self.listener_state.own_sacn_cids.read().contains(cid))
+        {
+            return;
+        }
+        let source_manager = &self.source_manager;
+        source_manager.record_traffic(src.ip(), Protocol::Sacn, data.len(), interface);
+        match packet {
+            SacnPacket::Dmx(dmx) => {
+                // Keeps a dynamically joined group from going idle
+                self.listener_state
+                    .mark_universe_active(dmx.source.universe);
+                if let Some(change) = source_manager.update_sacn_source_with_direction(
+                    src.ip(),
+                    &dmx.source.source_name,
+                    &dmx.source.cid,
+                    dmx.source.priority,
+                    dmx.source.universe,
+                    SourceDirection::Sending,
+                    Some(dmx.source.sequence),
+                ) {
+                    let _ = self.event_tx.send(ListenerEvent::PriorityChanged(change));
+                }
 
-    socket.bind(&addr.into())?;
-    socket.set_nonblocking(true)?;
+                // Store DMX data
+                let changed = self
+                    .dmx_store
+                    .update(dmx.source.universe, src.ip(), &dmx.data);
 
-    // Join discovery multicast group
-    let multicast_interface = bind_addr;
-    match socket.join_multicast_v4(&discovery_addr, &multicast_interface) {
-        Ok(_) => println!(
-            "[sACN] Joined universe discovery group ({})",
-            discovery_addr
-        ),
-        Err(e) => eprintln!("[sACN] Failed to join discovery group: {}", e),
-    }
-
-    // Join multicast groups for universes 1-512 initially
-    let mut joined_universes = std::collections::HashSet::new();
-    let mut joined_count = 0;
-    let mut failed_count = 0;
-
-    for universe in 1..=512 {
-        let multicast_addr = crate::network::sacn::sacn_multicast_address(universe);
-        match socket.join_multicast_v4(&multicast_addr, &multicast_interface) {
-            Ok(_) => {
-                joined_count += 1;
-                joined_universes.insert(universe);
-                if universe <= 10 {
-                    println!(
-                        "[sACN] Joined multicast group for universe {} ({})",
-                        universe, multicast_addr
-                    );
-                }
+                let _ = self.event_tx.send(ListenerEvent::DmxData(DmxData {
+                    universe: dmx.source.universe,
+                    data: dmx.data,
+                    protocol: Protocol::Sacn,
+                    source_ip: src.ip(),
+                    source_port: src.port(),
+                    timestamp: std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_millis() as u64,
+                    interface,
+                    changed,
+                }));
             }
-            Err(e) => {
-                failed_count += 1;
-                if universe <= 10 {
-                    eprintln!(
-                        "[sACN] Failed to join multicast for universe {}: {}",
-                        universe, e
+            SacnPacket::AlternateStartCode(dmx) => {
+                // Alternate start code data is stored separately from levels
+                self.dmx_store.update_alternate(
+                    dmx.source.universe,
+                    dmx.start_code,
+                    src.ip(),
+                    Protocol::Sacn,
+                    dmx.data,
+                );
+            }
+            SacnPacket::Discovery(discovery) => {
+                // Update source with discovered universes
+                for universe in &discovery.universes {
+                    let universe = *universe;
+                    source_manager.update_sacn_source(
+                        src.ip(),
+                        &discovery.source_name,
+                        &discovery.cid,
+                        100, // Default priority for discovery
+                        universe,
+                        None, // No sequence number for Discovery
                     );
+
+                    self.listener_state.mark_universe_active(universe);
                 }
+                let _ = self.event_tx.send(ListenerEvent::SourcesUpdated);
             }
+            SacnPacket::Sync { .. } => {
+                // Sync packets are handled elsewhere if needed
+            }
+            SacnPacket::Unknown => {}
         }
     }
+}
 
-    println!(
-        "[sACN] Initial multicast groups: {} joined, {} failed (1-512)",
-        joined_count, failed_count
-    );
+/// Start the sACN listener
+pub async fn start_sacn_listener(
+    source_manager: SourceManagerHandle,
+    dmx_store: DmxStoreHandle,
+    event_tx: EventBusHandle,
+    listener_state: ListenerStateHandle,
+    target: ListenTarget,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let generation = listener_state.generation();
+    let bind_addr = target.bind_addr;
+    let addr = SocketAddr::new(IpAddr::V4(bind_addr), SACN_PORT);
+    let discovery_addr = Ipv4Addr::new(239, 255, 0, 0);
+
+    // Create socket with socket2 for multicast support. The port stays shared
+    // so other sACN receivers on this machine can still bind it.
+    let socket = bind_listener_socket(&listener_state, Protocol::Sacn, addr, true)?;
+    apply_receive_buffer(
+        socket2::SockRef::from(&socket),
+        listener_state.receive_buffer_size(),
+        &listener_state.sacn_receive_buffer,
+    )?;
+
+    // Join discovery multicast group on each selected interface
+    for interface in &target.interfaces {
+        match socket.join_multicast_v4(&discovery_addr, interface) {
+            Ok(_) => info!(
+                "[sACN] Joined universe discovery group ({}) on {}",
+                discovery_addr, interface
+            ),
+            Err(e) => warn!(
+                "[sACN] Failed to join discovery group on {}: {}",
+                interface, e
+            ),
+        }
+    }
 
     let socket: std::net::UdpSocket = socket.into();
     let socket = UdpSocket::from_std(socket)?;
+    enable_arrival_interface(&socket)?;
+
+    info!(
+        "[sACN] Listening on {} (multicast) for {:?}",
+        addr, target.interfaces
+    );
+    for interface in &target.interfaces {
+        mark_running(&listener_state.sacn_running, *interface, true);
+    }
+    let counters =
+        listener_state.listener_counters(Protocol::Sacn, SocketAddrV4::new(bind_addr, SACN_PORT));
 
-    println!("[sACN] Listening on {} (multicast)", addr);
+    let handler = Arc::new(SacnHandler {
+        source_manager: source_manager.clone(),
+        dmx_store,
+        event_tx,
+        listener_state: listener_state.clone(),
+        counters: counters.clone(),
+    });
+    let parsers = ParserPool::spawn(counters.clone(), move |datagram| {
+        let handler = handler.clone();
+        async move { handler.handle(datagram) }
+    });
 
     let mut buf = vec![0u8; 1500];
+    let mut arrival = ArrivalFilter::new(&target);
+    // Universe groups are joined on each selected interface from the configured
+    // ranges, then follow discovery and traffic
+    let mut groups: Vec<(Ipv4Addr, MulticastGroups)> = target
+        .interfaces
+        .iter()
+        .map(|interface| (*interface, MulticastGroups::default()))
+        .collect();
+    let mut last_group_update: Option<Instant> = None;
 
-    loop {
-        match socket.recv_from(&mut buf).await {
-            Ok((len, src)) => {
-
-                if let Some(packet) = parse_sacn_packet(&buf[..len], src) {
-                    match packet {
-                        SacnPacket::Dmx(dmx) => {
-                            source_manager.update_sacn_source_with_direction(
-                                src.ip(),
-                                &dmx.source.source_name,
-                                &dmx.source.cid,
-                                dmx.source.priority,
-                                dmx.source.universe,
-                                SourceDirection::Sending,
-                                Some(dmx.source.sequence),
-                            );
-
-                            // Store DMX data
-                            dmx_store.update(dmx.source.universe, dmx.data.clone());
-
-                            let _ = event_tx.send(ListenerEvent::DmxData(DmxData {
-                                universe: dmx.source.universe,
-                                data: dmx.data,
-                                source_ip: src.ip(),
-                                timestamp: std::time::SystemTime::now()
-                                    .duration_since(std::time::UNIX_EPOCH)
-                                    .unwrap_or_default()
-                                    .as_millis() as u64,
-                            }));
-                        }
-                        SacnPacket::Discovery(discovery) => {
-                            // Update source with discovered universes
-                            for universe in &discovery.universes {
-                                let universe = *universe;
-                                source_manager.update_sacn_source(
-                                    src.ip(),
-                                    &discovery.source_name,
-                                    &discovery.cid,
-                                    100, // Default priority for discovery
-                                    universe,
-                                    None, // No sequence number for Discovery
-                                );
-
-                                // Dynamically join discovered universe if not already joined
-                                if !joined_universes.contains(&universe) && universe > 0 {
-                                    let multicast_addr =
-                                        crate::network::sacn::sacn_multicast_address(universe);
-                                    match socket.join_multicast_v4(multicast_addr, bind_addr) {
-                                        Ok(_) => {
-                                            println!(
-                                                "[sACN] Dynamically joined universe {} ({})",
-                                                universe, multicast_addr
-                                            );
-                                            joined_universes.insert(universe);
-                                        }
-                                        Err(e) => {
-                                            eprintln!(
-                                                "[sACN] Failed to dynamically join universe {}: {}",
-                                                universe, e
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-                            let _ = event_tx.send(ListenerEvent::SourcesUpdated);
-                        }
-                        SacnPacket::Sync { .. } => {
-                            // Sync packets are handled elsewhere if needed
-                        }
-                        SacnPacket::Unknown => {}
-                    }
+    while !listener_state.should_stop(generation, Protocol::Sacn) {
+        if last_group_update.is_none_or(|at| at.elapsed() >= MULTICAST_UPDATE_INTERVAL) {
+            last_group_update = Some(Instant::now());
+            let wanted = listener_state.wanted_sacn_memberships(&source_manager);
+            for (interface, groups) in &mut groups {
+                groups.update(&socket, *interface, wanted.clone());
+            }
+        }
+
+        // Time out regularly so a stop request is noticed on a quiet network
+        let Ok(result) = tokio::time::timeout(
+            LISTENER_STOP_CHECK_INTERVAL,
+            recv_with_interface(&socket, &mut buf),
+        )
+        .await
+        else {
+            continue;
+        };
+        match result {
+            Ok((len, src, if_index)) => {
+                // Traffic of interfaces that aren't selected
+                let Some(interface) = arrival.accept(if_index) else {
+                    continue;
+                };
+                counters.record_packet(len);
+                let datagram = Datagram {
+                    data: Bytes::copy_from_slice(&buf[..len]),
+                    src,
+                    interface,
+                };
+                if !parsers.dispatch(datagram) {
+                    counters.queue_drops.fetch_add(1, Ordering::Relaxed);
                 }
             }
             Err(e) => {
                 if e.kind() != std::io::ErrorKind::WouldBlock {
-                    eprintln!("[sACN] Receive error: {}", e);
+                    counters.receive_errors.fetch_add(1, Ordering::Relaxed);
+                    warn!("[sACN] Receive error: {}", e);
                 }
             }
         }
     }
+
+    info!("[sACN] Stopped listening on {}", addr);
+    for interface in &target.interfaces {
+        mark_running(&listener_state.sacn_running, *interface, false);
+    }
+    Ok(())
 }
 
+/// How often the device database is written to disk
+const KNOWN_SOURCES_SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
 /// Start the status update loop
-pub async fn start_status_updater(
-    source_manager: SourceManagerHandle,
-    event_tx: broadcast::Sender<ListenerEvent>,
-) {
+pub async fn start_status_updater(source_manager: SourceManagerHandle, event_tx: EventBusHandle) {
     let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+    let mut last_save = std::time::Instant::now();
 
     loop {
         interval.tick().await;
         source_manager.update_statuses();
         source_manager.cleanup_stale_sources();
         let _ = event_tx.send(ListenerEvent::SourcesUpdated);
+        for alert in source_manager.check_expected_devices() {
+            let _ = event_tx.send(ListenerEvent::ExpectedDeviceAlert(alert));
+        }
+
+        // Persist the device database periodically
+        if last_save.elapsed() >= KNOWN_SOURCES_SAVE_INTERVAL {
+            last_save = std::time::Instant::now();
+            if let Err(e) = source_manager.save_known_sources() {
+                warn!("[Sources] Failed to save device database: {}", e);
+            }
+        }
+    }
+}
+
+/// Start the network listeners and the tasks that run alongside them. Needs to
+/// run inside the tokio runtime.
+pub fn start_listeners(
+    source_manager: SourceManagerHandle,
+    dmx_store: DmxStoreHandle,
+    event_tx: EventBusHandle,
+    node_emulator: NodeEmulatorHandle,
+    rdm_responder: RdmResponderHandle,
+    listener_state: ListenerStateHandle,
+) {
+    spawn_protocol_listeners(
+        source_manager.clone(),
+        dmx_store,
+        event_tx.clone(),
+        node_emulator,
+        rdm_responder,
+        listener_state.clone(),
+        &[Protocol::ArtNet, Protocol::Sacn, Protocol::Other],
+    );
+
+    // Start status updater
+    let sm = source_manager.clone();
+    let tx = event_tx.clone();
+    tokio::spawn(async move {
+        start_status_updater(sm, tx).await;
+    });
+
+    // Start auto-poll task (every 10 seconds)
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(10));
+        loop {
+            interval.tick().await;
+            if !listener_state.auto_poll_enabled() {
+                continue;
+            }
+            if let Err(e) = send_artpoll(&listener_state) {
+                warn!("[Art-Net] Periodical ArtPoll error: {}", e);
+            }
+        }
+    });
+}
+
+/// Start a listener for each of the given protocols that is enabled, covering
+/// the configured interfaces. Protocol::Other starts the registered decoders.
+pub fn spawn_protocol_listeners(
+    source_manager: SourceManagerHandle,
+    dmx_store: DmxStoreHandle,
+    event_tx: EventBusHandle,
+    node_emulator: NodeEmulatorHandle,
+    rdm_responder: RdmResponderHandle,
+    listener_state: ListenerStateHandle,
+    protocols: &[Protocol],
+) {
+    let start = |protocol: Protocol| {
+        protocols.contains(&protocol) && listener_state.protocol_enabled(protocol)
+    };
+    let (start_artnet, start_sacn) = (start(Protocol::ArtNet), start(Protocol::Sacn));
+    let decoders = if start(Protocol::Other) {
+        listener_state.decoders.decoders()
+    } else {
+        Vec::new()
+    };
+
+    for target in listen_targets(&listener_state.bind_addrs()) {
+        // Start Art-Net listener
+        if start_artnet {
+            let sm = source_manager.clone();
+            let ds = dmx_store.clone();
+            let tx = event_tx.clone();
+            let ne = node_emulator.clone();
+            let rr = rdm_responder.clone();
+            let ls = listener_state.clone();
+            let target = target.clone();
+            tokio::spawn(async move {
+                let bind_addr = target.bind_addr;
+                if let Err(e) = start_artnet_listener(sm, ds, tx, ne, rr, ls, target).await {
+                    error!("[Art-Net] Listener error on {}: {}", bind_addr, e);
+                }
+            });
+        }
+
+        // Start sACN listener
+        if start_sacn {
+            let sm = source_manager.clone();
+            let ds = dmx_store.clone();
+            let tx = event_tx.clone();
+            let ls = listener_state.clone();
+            let target = target.clone();
+            tokio::spawn(async move {
+                let bind_addr = target.bind_addr;
+                if let Err(e) = start_sacn_listener(sm, ds, tx, ls, target).await {
+                    error!("[sACN] Listener error on {}: {}", bind_addr, e);
+                }
+            });
+        }
+
+        // Start a listener per decoder port
+        for decoder in &decoders {
+            for port in decoder.ports() {
+                let decoder = decoder.clone();
+                let sm = source_manager.clone();
+                let ds = dmx_store.clone();
+                let tx = event_tx.clone();
+                let ls = listener_state.clone();
+                let target = target.clone();
+                tokio::spawn(async move {
+                    let name = decoder.name().to_string();
+                    let bind_addr = target.bind_addr;
+                    if let Err(e) =
+                        start_decoder_listener(decoder, sm, ds, tx, ls, target, port).await
+                    {
+                        error!("[Decoder] {} listener error on {}: {}", name, bind_addr, e);
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Broadcast an ArtPoll from each listening interface (0.0.0.0 lets the OS
+/// choose), timing the replies
+pub fn send_artpoll(listener_state: &ListenerState) -> Result<(), String> {
+    listener_state.poll_latency.record_poll();
+    for bind_addr in listener_state.bind_addrs() {
+        send_artpoll_from(bind_addr)?;
     }
+    Ok(())
+}
+
+fn send_artpoll_from(bind_addr: Ipv4Addr) -> Result<(), String> {
+    use std::net::UdpSocket;
+
+    let socket =
+        UdpSocket::bind((bind_addr, 0)).map_err(|e| format!("Failed to create socket: {}", e))?;
+
+    socket
+        .set_broadcast(true)
+        .map_err(|e| format!("Failed to enable broadcast: {}", e))?;
+
+    let poll_packet = create_artpoll_packet();
+    let broadcast_addr = format!("255.255.255.255:{}", ARTNET_PORT);
+
+    socket
+        .send_to(&poll_packet, &broadcast_addr)
+        .map_err(|e| format!("Failed to send ArtPoll: {}", e))?;
+
+    debug!("[Art-Net] Sent ArtPoll broadcast from {}", bind_addr);
+    Ok(())
 }
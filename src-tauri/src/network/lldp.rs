@@ -0,0 +1,482 @@
+// Switch discovery - LLDP and CDP announcements seen by the sniffer
+//
+// Switches announce themselves on every port with LLDP (IEEE 802.1AB) or, on
+// Cisco gear, CDP: their name, the port the frame left from, its VLAN and a
+// management address. Capturing from a switch port or a mirrored trunk shows
+// which switch and port the capture hangs off, and with it where the lighting
+// traffic captured there comes from. When exactly one switch port is heard on
+// an interface, the sources captured on it are tagged with that switch and
+// port. Announcements expire after the time to live they carry.
+
+use crate::network::source::mac_to_string;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::{Duration, Instant};
+
+/// EtherType of LLDP frames
+pub const ETHERTYPE_LLDP: u16 = 0x88CC;
+
+/// LLC/SNAP header of CDP frames: Cisco OUI, protocol 0x2000
+pub const CDP_SNAP_HEADER: [u8; 8] = [0xAA, 0xAA, 0x03, 0x00, 0x00, 0x0C, 0x20, 0x00];
+
+/// Time to live when a frame carries none
+const DEFAULT_NEIGHBOR_TTL: Duration = Duration::from_secs(180);
+
+/// LLDP capability and CDP capability bits of a bridge/switch
+const LLDP_CAPABILITY_BRIDGE: u16 = 0x0004;
+const CDP_CAPABILITY_SWITCH: u32 = 0x0008 | 0x0002; // Switch, transparent bridge
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiscoveryProtocol {
+    Lldp,
+    Cdp,
+}
+
+/// A device announcing itself on a captured link, usually a switch port
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchNeighbor {
+    pub protocol: DiscoveryProtocol,
+    pub interface: String, // Capture interface it was heard on
+    pub chassis_id: String,
+    pub system_name: Option<String>,
+    pub port_id: String,
+    pub port_description: Option<String>,
+    pub management_ip: Option<String>,
+    pub vlan: Option<u16>, // Port VLAN ID (LLDP) or native VLAN (CDP)
+    pub is_switch: bool,
+    pub last_seen: u64, // Unix timestamp ms
+}
+
+impl SwitchNeighbor {
+    /// Switch name shown on sources, the system name when announced
+    pub fn switch_name(&self) -> String {
+        self.system_name
+            .clone()
+            .unwrap_or_else(|| self.chassis_id.clone())
+    }
+}
+
+/// Contents of one LLDP or CDP frame
+#[derive(Debug, Clone, Default)]
+pub struct NeighborAnnouncement {
+    chassis_id: String,
+    system_name: Option<String>,
+    port_id: String,
+    port_description: Option<String>,
+    management_ip: Option<String>,
+    vlan: Option<u16>,
+    is_switch: bool,
+    ttl: Option<Duration>,
+}
+
+/// Capture interface, chassis ID and port ID of a neighbour
+type NeighborKey = (String, String, String);
+
+/// LLDP and CDP neighbours by capture interface, chassis and port
+pub struct SwitchNeighborTable {
+    neighbors: Mutex<HashMap<NeighborKey, (SwitchNeighbor, Instant)>>, // With expiry
+}
+
+impl SwitchNeighborTable {
+    pub fn new() -> Self {
+        Self {
+            neighbors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record(
+        &self,
+        interface: &str,
+        protocol: DiscoveryProtocol,
+        announcement: NeighborAnnouncement,
+    ) {
+        let expires = Instant::now() + announcement.ttl.unwrap_or(DEFAULT_NEIGHBOR_TTL);
+        let neighbor = SwitchNeighbor {
+            protocol,
+            interface: interface.to_string(),
+            chassis_id: announcement.chassis_id,
+            system_name: announcement.system_name,
+            port_id: announcement.port_id,
+            port_description: announcement.port_description,
+            management_ip: announcement.management_ip,
+            vlan: announcement.vlan,
+            is_switch: announcement.is_switch,
+            last_seen: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        };
+        let key = (
+            neighbor.interface.clone(),
+            neighbor.chassis_id.clone(),
+            neighbor.port_id.clone(),
+        );
+        self.neighbors.lock().insert(key, (neighbor, expires));
+    }
+
+    /// Neighbours still within their time to live, by interface and name
+    pub fn get_all(&self) -> Vec<SwitchNeighbor> {
+        let now = Instant::now();
+        let mut neighbors = self.neighbors.lock();
+        neighbors.retain(|_, (_, expires)| *expires > now);
+        let mut all: Vec<SwitchNeighbor> = neighbors.values().map(|(n, _)| n.clone()).collect();
+        all.sort_by(|a, b| {
+            a.interface
+                .cmp(&b.interface)
+                .then(a.switch_name().cmp(&b.switch_name()))
+                .then(a.port_id.cmp(&b.port_id))
+        });
+        all
+    }
+
+    /// Switch name and port of the only switch port heard on an interface,
+    /// None when there is none or several (a mirror of many ports)
+    pub fn switch_port_on(&self, interface: &str) -> Option<(String, String)> {
+        let now = Instant::now();
+        let neighbors = self.neighbors.lock();
+        let mut ports = neighbors
+            .values()
+            .filter(|(n, expires)| n.interface == interface && n.is_switch && *expires > now)
+            .map(|(n, _)| n);
+        let port = ports.next()?;
+        if ports.next().is_some() {
+            return None;
+        }
+        Some((port.switch_name(), port.port_id.clone()))
+    }
+
+    pub fn clear(&self) {
+        self.neighbors.lock().clear();
+    }
+}
+
+impl Default for SwitchNeighborTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse the TLVs of an LLDP frame (after the EtherType)
+pub fn parse_lldp(mut data: &[u8]) -> Option<NeighborAnnouncement> {
+    let mut announcement = NeighborAnnouncement::default();
+    while data.len() >= 2 {
+        let header = u16::from_be_bytes([data[0], data[1]]);
+        let (kind, length) = ((header >> 9) as u8, (header & 0x01FF) as usize);
+        let value = data.get(2..2 + length)?;
+        data = &data[2 + length..];
+        match kind {
+            0 => break, // End of LLDPDU
+            1 => announcement.chassis_id = lldp_id(value, 4, 5)?,
+            2 => announcement.port_id = lldp_id(value, 3, 4)?,
+            3 if length >= 2 => {
+                let seconds = u16::from_be_bytes([value[0], value[1]]);
+                announcement.ttl = Some(Duration::from_secs(seconds as u64));
+            }
+            4 => announcement.port_description = text(value),
+            5 => announcement.system_name = text(value),
+            7 if length >= 4 => {
+                let enabled = u16::from_be_bytes([value[2], value[3]]);
+                announcement.is_switch = enabled & LLDP_CAPABILITY_BRIDGE != 0;
+            }
+            8 if announcement.management_ip.is_none() => {
+                // Address string length, then the address family and address
+                announcement.management_ip = value
+                    .split_first()
+                    .and_then(|(&length, rest)| rest.get(..length as usize))
+                    .and_then(|address| {
+                        let (&family, address) = address.split_first()?;
+                        network_address(family, address)
+                    });
+            }
+            // IEEE 802.1 organisationally specific: port VLAN ID
+            127 if value.len() >= 6 && value[..4] == [0x00, 0x80, 0xC2, 0x01] => {
+                announcement.vlan = Some(u16::from_be_bytes([value[4], value[5]]));
+            }
+            _ => {}
+        }
+    }
+    // Chassis ID, port ID and TTL are mandatory
+    (!announcement.chassis_id.is_empty() && !announcement.port_id.is_empty())
+        .then_some(announcement)
+}
+
+/// Parse a CDP frame (after the LLC/SNAP header)
+pub fn parse_cdp(data: &[u8]) -> Option<NeighborAnnouncement> {
+    let mut announcement = NeighborAnnouncement {
+        ttl: Some(Duration::from_secs(*data.get(1)? as u64)),
+        ..Default::default()
+    };
+    let mut tlvs = data.get(4..)?;
+    while tlvs.len() >= 4 {
+        let kind = u16::from_be_bytes([tlvs[0], tlvs[1]]);
+        let length = u16::from_be_bytes([tlvs[2], tlvs[3]]) as usize;
+        if length < 4 {
+            return None;
+        }
+        let value = tlvs.get(4..length)?;
+        tlvs = &tlvs[length..];
+        match kind {
+            0x0001 => announcement.chassis_id = text(value)?,
+            0x0002 => announcement.management_ip = cdp_address(value),
+            0x0003 => announcement.port_id = text(value)?,
+            0x0004 if value.len() >= 4 => {
+                let capabilities = u32::from_be_bytes([value[0], value[1], value[2], value[3]]);
+                announcement.is_switch = capabilities & CDP_CAPABILITY_SWITCH != 0;
+            }
+            0x000A if value.len() >= 2 => {
+                announcement.vlan = Some(u16::from_be_bytes([value[0], value[1]]));
+            }
+            _ => {}
+        }
+    }
+    (!announcement.chassis_id.is_empty() && !announcement.port_id.is_empty())
+        .then_some(announcement)
+}
+
+/// An LLDP chassis or port ID: a subtype byte and the ID, shown as a MAC
+/// address, a network address or text depending on the subtype. The MAC and
+/// address subtypes differ between chassis and port IDs.
+fn lldp_id(value: &[u8], mac_subtype: u8, address_subtype: u8) -> Option<String> {
+    let (&subtype, id) = value.split_first()?;
+    if subtype == mac_subtype {
+        let mac: [u8; 6] = id.try_into().ok()?;
+        Some(mac_to_string(&mac))
+    } else if subtype == address_subtype {
+        let (&family, address) = id.split_first()?;
+        network_address(family, address)
+    } else {
+        text(id)
+    }
+}
+
+/// IANA address family 1 (IPv4) or 2 (IPv6)
+fn network_address(family: u8, address: &[u8]) -> Option<String> {
+    match family {
+        1 => <[u8; 4]>::try_from(address)
+            .ok()
+            .map(|a| Ipv4Addr::from(a).to_string()),
+        2 => <[u8; 16]>::try_from(address)
+            .ok()
+            .map(|a| Ipv6Addr::from(a).to_string()),
+        _ => None,
+    }
+}
+
+/// First IPv4 address of a CDP address list
+fn cdp_address(value: &[u8]) -> Option<String> {
+    let count = u32::from_be_bytes(value.get(..4)?.try_into().ok()?);
+    let mut rest = &value[4..];
+    for _ in 0..count {
+        let protocol_length = *rest.get(1)? as usize;
+        let protocol = rest.get(2..2 + protocol_length)?;
+        let at = 2 + protocol_length;
+        let address_length = u16::from_be_bytes(rest.get(at..at + 2)?.try_into().ok()?) as usize;
+        let address = rest.get(at + 2..at + 2 + address_length)?;
+        // NLPID 0xCC is IP
+        if protocol == [0xCC] {
+            return network_address(1, address);
+        }
+        rest = &rest[at + 2 + address_length..];
+    }
+    None
+}
+
+fn text(value: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(value)
+        .trim_matches(char::from(0))
+        .trim()
+        .to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lldp_tlv(kind: u8, value: &[u8]) -> Vec<u8> {
+        let header = (kind as u16) << 9 | value.len() as u16;
+        [&header.to_be_bytes()[..], value].concat()
+    }
+
+    fn cdp_tlv(kind: u16, value: &[u8]) -> Vec<u8> {
+        let length = (value.len() + 4) as u16;
+        [&kind.to_be_bytes()[..], &length.to_be_bytes(), value].concat()
+    }
+
+    fn lldp_chassis() -> Vec<u8> {
+        lldp_tlv(1, &[4, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55])
+    }
+
+    fn lldp_port() -> Vec<u8> {
+        lldp_tlv(2, b"\x05Gi1/0/5")
+    }
+
+    /// LLDPDU of a switch port with every TLV the parser reads
+    fn lldp_frame() -> Vec<u8> {
+        [
+            lldp_chassis(),
+            lldp_port(),
+            lldp_tlv(3, &[0, 120]),
+            lldp_tlv(4, b"FOH rack"),
+            lldp_tlv(5, b"sw-foh"),
+            lldp_tlv(7, &[0x00, 0x14, 0x00, 0x04]),
+            lldp_tlv(8, &[5, 1, 10, 0, 0, 2, 2, 0, 0, 0, 1, 0]),
+            lldp_tlv(127, &[0x00, 0x80, 0xC2, 0x01, 0x00, 100]),
+            lldp_tlv(0, &[]),
+        ]
+        .concat()
+    }
+
+    fn cdp_frame(tlvs: &[Vec<u8>]) -> Vec<u8> {
+        [&[2, 180, 0, 0][..], &tlvs.concat()].concat()
+    }
+
+    fn cdp_tlvs() -> Vec<Vec<u8>> {
+        vec![
+            cdp_tlv(0x0001, b"sw-foh"),
+            // One address: NLPID protocol 0xCC (IP), 4 bytes
+            cdp_tlv(0x0002, &[0, 0, 0, 1, 1, 1, 0xCC, 0, 4, 10, 0, 0, 2]),
+            cdp_tlv(0x0003, b"GigabitEthernet1/0/5"),
+            cdp_tlv(0x0004, &[0, 0, 0, 0x28]),
+            cdp_tlv(0x000A, &[0, 100]),
+        ]
+    }
+
+    #[test]
+    fn parse_lldp_reads_a_switch_port() {
+        let announcement = parse_lldp(&lldp_frame()).expect("valid LLDPDU");
+        assert_eq!(announcement.chassis_id, "00:11:22:33:44:55");
+        assert_eq!(announcement.port_id, "Gi1/0/5");
+        assert_eq!(announcement.ttl, Some(Duration::from_secs(120)));
+        assert_eq!(announcement.port_description.as_deref(), Some("FOH rack"));
+        assert_eq!(announcement.system_name.as_deref(), Some("sw-foh"));
+        assert!(announcement.is_switch);
+        assert_eq!(announcement.management_ip.as_deref(), Some("10.0.0.2"));
+        assert_eq!(announcement.vlan, Some(100));
+    }
+
+    #[test]
+    fn parse_lldp_rejects_malformed_frames() {
+        let cases = [
+            ("empty", vec![]),
+            (
+                "no port ID",
+                [lldp_chassis(), lldp_tlv(3, &[0, 120])].concat(),
+            ),
+            (
+                "no chassis ID",
+                [lldp_port(), lldp_tlv(3, &[0, 120])].concat(),
+            ),
+            (
+                "ID after the end TLV",
+                [lldp_chassis(), lldp_tlv(0, &[]), lldp_port()].concat(),
+            ),
+            (
+                "length past the end",
+                [lldp_chassis(), lldp_tlv(2, b"\x05Gi1/0/5")[..6].to_vec()].concat(),
+            ),
+            (
+                "short MAC chassis ID",
+                [lldp_tlv(1, &[4, 0x00, 0x11, 0x22]), lldp_port()].concat(),
+            ),
+            (
+                "empty text port ID",
+                [lldp_chassis(), lldp_tlv(2, &[5, 0, 0])].concat(),
+            ),
+            ("subtype only", [lldp_chassis(), lldp_tlv(2, &[])].concat()),
+        ];
+        for (name, frame) in cases {
+            assert!(parse_lldp(&frame).is_none(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn parse_lldp_skips_short_optional_tlvs() {
+        let frame = [
+            lldp_chassis(),
+            lldp_port(),
+            lldp_tlv(3, &[0]),
+            lldp_tlv(7, &[0x00, 0x14]),
+            lldp_tlv(8, &[200, 1, 10]),
+            lldp_tlv(8, &[]),
+            lldp_tlv(127, &[0x00, 0x80, 0xC2, 0x01, 0x00]),
+        ]
+        .concat();
+        let announcement = parse_lldp(&frame).expect("mandatory TLVs present");
+        assert_eq!(announcement.ttl, None);
+        assert!(!announcement.is_switch);
+        assert_eq!(announcement.management_ip, None);
+        assert_eq!(announcement.vlan, None);
+    }
+
+    #[test]
+    fn parse_cdp_reads_a_switch_port() {
+        let announcement = parse_cdp(&cdp_frame(&cdp_tlvs())).expect("valid CDP frame");
+        assert_eq!(announcement.chassis_id, "sw-foh");
+        assert_eq!(announcement.port_id, "GigabitEthernet1/0/5");
+        assert_eq!(announcement.ttl, Some(Duration::from_secs(180)));
+        assert_eq!(announcement.management_ip.as_deref(), Some("10.0.0.2"));
+        assert!(announcement.is_switch);
+        assert_eq!(announcement.vlan, Some(100));
+    }
+
+    #[test]
+    fn parse_cdp_rejects_malformed_frames() {
+        let mut tlvs = cdp_tlvs();
+        let port = tlvs.remove(2);
+        let cases = [
+            ("empty", vec![]),
+            ("header only", cdp_frame(&[])),
+            ("no port ID", cdp_frame(&tlvs)),
+            (
+                "length below the TLV header",
+                cdp_frame(&[tlvs[0].clone(), vec![0x00, 0x03, 0x00, 0x02], port.clone()]),
+            ),
+            (
+                "length past the end",
+                cdp_frame(&[tlvs[0].clone(), port[..10].to_vec()]),
+            ),
+        ];
+        for (name, frame) in cases {
+            assert!(parse_cdp(&frame).is_none(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn parse_cdp_ignores_bad_address_lists() {
+        let addresses: [&[u8]; 4] = [
+            &[0, 0],
+            // Two addresses announced, one present
+            &[0, 0, 0, 2, 1, 1, 0xCC, 0, 4, 10, 0, 0],
+            // Protocol length past the end
+            &[0, 0, 0, 1, 1, 9, 0xCC],
+            // Not IP but CLNS
+            &[0, 0, 0, 1, 1, 1, 0x81, 0, 1, 0x49],
+        ];
+        for address in addresses {
+            let frame = cdp_frame(&[
+                cdp_tlv(0x0001, b"sw-foh"),
+                cdp_tlv(0x0002, address),
+                cdp_tlv(0x0003, b"Gi1/0/5"),
+            ]);
+            let announcement = parse_cdp(&frame).expect("ID TLVs present");
+            assert_eq!(announcement.management_ip, None, "{:?}", address);
+        }
+    }
+
+    #[test]
+    fn truncated_frames_do_not_panic() {
+        let lldp = lldp_frame();
+        for len in 0..lldp.len() {
+            parse_lldp(&lldp[..len]);
+        }
+        let cdp = cdp_frame(&cdp_tlvs());
+        for len in 0..cdp.len() {
+            parse_cdp(&cdp[..len]);
+        }
+    }
+}
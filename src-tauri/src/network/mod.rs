@@ -2,12 +2,60 @@
 
 pub mod artnet;
 pub mod sacn;
+pub mod anomaly;
+pub mod arrival;
+pub mod arp_scan;
+pub mod bandwidth;
+pub mod bus;
+pub mod decoder;
+pub mod device;
+pub mod emulator;
+pub mod expected;
+pub mod gaps;
+pub mod health;
+pub mod inspector;
 pub mod listener;
+pub mod lldp;
+pub mod numbering;
+pub mod oui;
+pub mod phase;
+pub mod ping;
+pub mod poll;
+pub mod rdm;
+pub mod rdm_devices;
+pub mod routing;
 pub mod source;
 pub mod sniffer;
+pub mod storm;
+pub mod timecode;
+pub mod topology;
+pub mod transmit;
 
 pub use artnet::*;
 pub use sacn::*;
+pub use anomaly::*;
+pub use arrival::*;
+pub use arp_scan::*;
+pub use bandwidth::*;
+pub use bus::*;
+pub use decoder::*;
+pub use device::*;
+pub use emulator::*;
+pub use expected::*;
+pub use gaps::*;
+pub use health::*;
+pub use inspector::*;
 pub use listener::*;
+pub use lldp::*;
+pub use numbering::*;
+pub use phase::*;
+pub use ping::*;
+pub use poll::*;
+pub use rdm_devices::*;
+pub use routing::*;
 pub use source::*;
 pub use sniffer::*;
+pub use storm::*;
+pub use timecode::*;
+pub use topology::*;
+pub use transmit::*;
@@ -0,0 +1,222 @@
+// Universe numbering - Art-Net and sACN universes one apart with the same levels
+//
+// Art-Net numbers universes from 0 and sACN from 1, and consoles and gateways
+// disagree on whether Art-Net 0 is "universe 1". When a console sends Art-Net
+// and a gateway converts it, or the other way round, the same levels then turn
+// up one universe apart in the two protocols and the rig is patched one
+// universe off. Frames of each protocol are compared with the other's
+// neighbouring universes; a pair flags when their levels match while the
+// same-numbered universe doesn't. Frames with only a few channels up are too
+// common to tell anything and are ignored.
+
+use crate::network::bus::EventReceiver;
+use crate::network::listener::{DmxData, ListenerEvent};
+use crate::network::source::Protocol;
+
+use bytes::Bytes;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Channels that must be up for a frame to be compared
+const MIN_LIT_CHANNELS: usize = 3;
+
+/// Frames older than this are not compared
+const FRAME_TIMEOUT_MS: u64 = 3000;
+
+/// A pair stops being reported this long after its levels last matched
+const MISMATCH_TIMEOUT_MS: u64 = 10_000;
+
+/// A probable numbering mismatch between an Art-Net and an sACN universe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NumberingMismatch {
+    pub artnet_universe: u16,
+    pub sacn_universe: u16,
+    pub offset: i32, // sACN universe minus Art-Net universe
+    pub artnet_ip: String,
+    pub sacn_ip: String,
+    pub matched_frames: u64, // Distinct frames seen on both, more is more certain
+    pub lit_channels: usize,
+    pub last_match: u64, // Unix timestamp ms
+    pub message: String,
+}
+
+/// Newest frame of a universe in one protocol
+struct Frame {
+    data: Bytes,
+    ip: IpAddr,
+    at: u64,
+}
+
+/// An Art-Net and sACN universe whose levels have matched
+struct Match {
+    matched_frames: u64,
+    last_data: Bytes,
+    last_match: u64,
+}
+
+#[derive(Default)]
+struct Frames {
+    frames: HashMap<(Protocol, u16), Frame>,
+    matches: HashMap<(u16, u16), Match>, // (Art-Net, sACN) universe
+}
+
+/// Compares the levels of Art-Net and sACN universes one apart
+pub struct NumberingCheck {
+    state: Mutex<Frames>,
+}
+
+impl NumberingCheck {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(Frames::default()),
+        }
+    }
+
+    /// Record a frame and compare it with the other protocol's neighbouring universes
+    pub fn record(&self, data: &DmxData) {
+        let (other, neighbours) = match data.protocol {
+            Protocol::ArtNet => (
+                Protocol::Sacn,
+                [data.universe.checked_add(1), data.universe.checked_sub(1)],
+            ),
+            Protocol::Sacn => (
+                Protocol::ArtNet,
+                [data.universe.checked_sub(1), data.universe.checked_add(1)],
+            ),
+            Protocol::Other => return,
+        };
+        let mut state = self.state.lock();
+        state.frames.insert(
+            (data.protocol, data.universe),
+            Frame {
+                data: data.data.clone(),
+                ip: data.source_ip,
+                at: data.timestamp,
+            },
+        );
+        if lit_channels(&data.data) < MIN_LIT_CHANNELS {
+            return;
+        }
+
+        let Frames { frames, matches } = &mut *state;
+        for universe in neighbours.into_iter().flatten() {
+            let Some(frame) = frames.get(&(other, universe)) else {
+                continue;
+            };
+            if data.timestamp.saturating_sub(frame.at) > FRAME_TIMEOUT_MS
+                || trimmed(&frame.data) != trimmed(&data.data)
+            {
+                continue;
+            }
+            let pair = if data.protocol == Protocol::ArtNet {
+                (data.universe, universe)
+            } else {
+                (universe, data.universe)
+            };
+            let entry = matches.entry(pair).or_insert_with(|| Match {
+                matched_frames: 0,
+                last_data: Bytes::new(),
+                last_match: 0,
+            });
+            if entry.last_data != data.data {
+                entry.matched_frames += 1;
+                entry.last_data = data.data.clone();
+            }
+            entry.last_match = data.timestamp;
+        }
+    }
+
+    /// Pairs currently matching, most certain first
+    pub fn get_mismatches(&self, now: u64) -> Vec<NumberingMismatch> {
+        let mut state = self.state.lock();
+        state
+            .frames
+            .retain(|_, frame| now.saturating_sub(frame.at) <= MISMATCH_TIMEOUT_MS);
+        state
+            .matches
+            .retain(|_, m| now.saturating_sub(m.last_match) <= MISMATCH_TIMEOUT_MS);
+
+        let mut mismatches: Vec<NumberingMismatch> = state
+            .matches
+            .iter()
+            .filter_map(|(&(artnet_universe, sacn_universe), m)| {
+                let artnet = state.frames.get(&(Protocol::ArtNet, artnet_universe))?;
+                let sacn = state.frames.get(&(Protocol::Sacn, sacn_universe))?;
+                // Levels also on the same-numbered universe leave nothing to tell
+                if same_levels(&state.frames, (Protocol::Sacn, artnet_universe), &artnet.data)
+                    || same_levels(&state.frames, (Protocol::ArtNet, sacn_universe), &sacn.data)
+                {
+                    return None;
+                }
+                let offset = i32::from(sacn_universe) - i32::from(artnet_universe);
+                Some(NumberingMismatch {
+                    artnet_universe,
+                    sacn_universe,
+                    offset,
+                    artnet_ip: artnet.ip.to_string(),
+                    sacn_ip: sacn.ip.to_string(),
+                    matched_frames: m.matched_frames,
+                    lit_channels: lit_channels(&m.last_data),
+                    last_match: m.last_match,
+                    message: format!(
+                        "Art-Net universe {} ({}) carries the same levels as sACN universe {} ({}), the sender and the gateway probably number universes {} apart",
+                        artnet_universe, artnet.ip, sacn_universe, sacn.ip, offset.abs()
+                    ),
+                })
+            })
+            .collect();
+        mismatches.sort_by(|a, b| {
+            b.matched_frames
+                .cmp(&a.matched_frames)
+                .then(a.artnet_universe.cmp(&b.artnet_universe))
+        });
+        mismatches
+    }
+}
+
+impl Default for NumberingCheck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type NumberingCheckHandle = Arc<NumberingCheck>;
+
+/// Compare the DMX frames sent on the event bus until it closes
+pub async fn run_numbering_check(check: NumberingCheckHandle, mut events: EventReceiver) {
+    loop {
+        match events.recv().await {
+            Ok(ListenerEvent::DmxData(data)) => check.record(&data),
+            Ok(_) | Err(RecvError::Lagged(_)) => {}
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+fn lit_channels(data: &[u8]) -> usize {
+    data.iter().filter(|level| **level > 0).count()
+}
+
+/// Levels without the trailing zeros, so short and full frames compare equal
+fn trimmed(data: &[u8]) -> &[u8] {
+    let end = data
+        .iter()
+        .rposition(|level| *level > 0)
+        .map_or(0, |i| i + 1);
+    &data[..end]
+}
+
+/// Whether a universe has these levels
+fn same_levels(
+    frames: &HashMap<(Protocol, u16), Frame>,
+    key: (Protocol, u16),
+    data: &[u8],
+) -> bool {
+    frames
+        .get(&key)
+        .is_some_and(|frame| trimmed(&frame.data) == trimmed(data))
+}
@@ -0,0 +1,137 @@
+// OUI module - Resolves MAC address prefixes to vendor names
+//
+// A small table of vendors common on lighting networks is built in. A full
+// table can be dropped into the app data directory as oui.txt, either the
+// IEEE oui.txt download or Wireshark's manuf file.
+
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::info;
+
+/// Built-in vendors by 24-bit OUI
+const BUILTIN_OUIS: &[([u8; 3], &str)] = &[
+    ([0x00, 0x00, 0x0C], "Cisco"),
+    ([0x00, 0x04, 0xA3], "Microchip"),
+    ([0x00, 0x08, 0xDC], "WIZnet"),
+    ([0x00, 0x0C, 0x29], "VMware"),
+    ([0x00, 0x14, 0x22], "Dell"),
+    ([0x00, 0x15, 0x5D], "Microsoft Hyper-V"),
+    ([0x00, 0x1B, 0x21], "Intel"),
+    ([0x00, 0x1D, 0xC1], "Audinate"),
+    ([0x00, 0x50, 0x56], "VMware"),
+    ([0x00, 0x80, 0xA3], "Lantronix"),
+    ([0x00, 0x90, 0xE8], "Moxa"),
+    ([0x08, 0x00, 0x27], "VirtualBox"),
+    ([0x24, 0x0A, 0xC4], "Espressif"),
+    ([0x24, 0xA4, 0x3C], "Ubiquiti"),
+    ([0x2C, 0xF4, 0x32], "Espressif"),
+    ([0x30, 0xAE, 0xA4], "Espressif"),
+    ([0x80, 0x2A, 0xA8], "Ubiquiti"),
+    ([0x84, 0xF3, 0xEB], "Espressif"),
+    ([0xB8, 0x27, 0xEB], "Raspberry Pi"),
+    ([0xDC, 0xA6, 0x32], "Raspberry Pi"),
+    ([0xE4, 0x5F, 0x01], "Raspberry Pi"),
+    ([0xFC, 0xEC, 0xDA], "Ubiquiti"),
+];
+
+/// Vendor lookup table: built-in entries plus any loaded from file
+pub struct OuiDatabase {
+    loaded: RwLock<HashMap<[u8; 3], String>>,
+}
+
+impl OuiDatabase {
+    pub fn new() -> Self {
+        Self {
+            loaded: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Load an IEEE oui.txt or Wireshark manuf file. A missing file is not an error.
+    pub fn load_file(&self, path: &Path) {
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return;
+        };
+
+        let mut loaded = self.loaded.write();
+        loaded.clear();
+        for line in contents.lines() {
+            if let Some((oui, vendor)) = parse_oui_line(line) {
+                loaded.insert(oui, vendor);
+            }
+        }
+        info!(
+            "[OUI] Loaded {} vendors from {}",
+            loaded.len(),
+            path.display()
+        );
+    }
+
+    /// Resolve a MAC address string (colon or dash separated) to a vendor
+    pub fn lookup(&self, mac: &str) -> Option<String> {
+        let oui = parse_oui(mac)?;
+
+        // Randomised and virtual interface MACs have no registered vendor
+        if oui[0] & 0x02 != 0 {
+            return Some("Locally administered".to_string());
+        }
+
+        if let Some(vendor) = self.loaded.read().get(&oui) {
+            return Some(vendor.clone());
+        }
+        BUILTIN_OUIS
+            .iter()
+            .find(|(prefix, _)| *prefix == oui)
+            .map(|(_, vendor)| vendor.to_string())
+    }
+}
+
+impl Default for OuiDatabase {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// First three bytes of a MAC or OUI string
+fn parse_oui(value: &str) -> Option<[u8; 3]> {
+    let hex: String = value
+        .chars()
+        .filter(|c| *c != ':' && *c != '-' && *c != '.')
+        .take(6)
+        .collect();
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut oui = [0u8; 3];
+    for (i, byte) in oui.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(oui)
+}
+
+/// Parse one line of either format:
+///   IEEE:      "2C-F4-32   (hex)\t\tEspressif Inc."
+///   Wireshark: "2C:F4:32\tEspressif\tEspressif Inc."
+/// Wireshark entries for longer (MA-M/MA-S) prefixes are skipped.
+fn parse_oui_line(line: &str) -> Option<([u8; 3], String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (prefix, rest) = line.split_once(char::is_whitespace)?;
+    if prefix.len() != 8 {
+        return None;
+    }
+    let oui = parse_oui(prefix)?;
+
+    let rest = rest.trim_start();
+    let vendor = match rest.strip_prefix("(hex)") {
+        Some(name) => name.trim(),
+        None => rest.split('\t').next()?.trim(),
+    };
+    if vendor.is_empty() {
+        return None;
+    }
+    Some((oui, vendor.to_string()))
+}
@@ -0,0 +1,278 @@
+// Frame phase - Relative timing of redundant senders of a universe
+//
+// When a main and a backup console both send a universe, their frames arrive
+// at a fixed offset only if their clocks agree. In practice one runs slightly
+// faster, so the offset slips through a whole frame period over and over. A
+// merging receiver sees this as beating: each time the frames line up, which
+// of them arrives first flips and the output can flicker between the two.
+//
+// Frame arrival times are kept per universe and sender for the last minute.
+// For each pair of senders the phase of the second's frames within the first's
+// frame period is tracked over time; its slope is the difference of their
+// frame rates, which gives the clock drift, how often the phase slips a full
+// frame (the beat period) and when the frames will next line up.
+
+use crate::network::bus::EventReceiver;
+use crate::network::listener::ListenerEvent;
+use crate::network::source::Protocol;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Frame arrivals kept per sender, at most
+const PHASE_WINDOW_MS: u64 = 60_000;
+const MAX_PHASE_FRAMES: usize = 3000;
+
+/// Frames needed from each sender before a pair is analysed
+const MIN_PHASE_FRAMES: usize = 50;
+
+/// Senders not heard for this long are left out
+const SENDER_TIMEOUT_MS: u64 = 2000;
+
+/// Phase slips slower than this (cycles per second, once an hour) count as locked
+const LOCKED_SLIP_RATE: f64 = 1.0 / 3600.0;
+
+/// A sender of a universe, told apart by protocol and address
+type SenderKey = (Protocol, IpAddr);
+
+/// One sender of a compared pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseSender {
+    pub ip: String,
+    pub protocol: Protocol,
+    pub fps: f32,
+    pub frames: usize, // Frames in the analysis window
+}
+
+/// Relative timing of two senders of the same universe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FramePhase {
+    pub universe: u16,
+    pub first: PhaseSender,
+    pub second: PhaseSender,
+    /// Frame rate of the second relative to the first, in parts per million
+    pub drift_ppm: f32,
+    /// Where the second's frames fall within the first's frame period, 0 to 1
+    pub phase: f32,
+    /// Frame periods the phase slips per minute
+    pub slip_per_minute: f32,
+    /// Time for the phase to slip a whole frame, None while the clocks agree
+    pub beat_period_seconds: Option<f32>,
+    /// Time until the frames of both arrive together, None while the clocks agree
+    pub next_alignment_seconds: Option<f32>,
+    pub locked: bool,
+}
+
+/// Recent frame arrival times per universe and sender
+pub struct FramePhaseAnalyzer {
+    arrivals: Mutex<HashMap<u16, HashMap<SenderKey, VecDeque<u64>>>>,
+}
+
+impl FramePhaseAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            arrivals: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record the arrival of a frame (Unix ms)
+    pub fn record(&self, universe: u16, sender: SenderKey, timestamp: u64) {
+        let mut arrivals = self.arrivals.lock();
+        let frames = arrivals
+            .entry(universe)
+            .or_default()
+            .entry(sender)
+            .or_default();
+        frames.push_back(timestamp);
+        while frames.len() > MAX_PHASE_FRAMES
+            || frames
+                .front()
+                .is_some_and(|first| timestamp.saturating_sub(*first) > PHASE_WINDOW_MS)
+        {
+            frames.pop_front();
+        }
+    }
+
+    /// Phase of every pair of current senders, of one universe or of all
+    pub fn get_phases(&self, universe: Option<u16>, now: u64) -> Vec<FramePhase> {
+        let mut arrivals = self.arrivals.lock();
+        // Forget senders that stopped
+        for senders in arrivals.values_mut() {
+            senders.retain(|_, frames| {
+                frames
+                    .back()
+                    .is_some_and(|last| now.saturating_sub(*last) <= PHASE_WINDOW_MS)
+            });
+        }
+        arrivals.retain(|_, senders| !senders.is_empty());
+
+        let mut universes: Vec<u16> = arrivals
+            .keys()
+            .copied()
+            .filter(|u| universe.is_none_or(|wanted| *u == wanted))
+            .collect();
+        universes.sort();
+
+        let mut phases = Vec::new();
+        for u in universes {
+            let mut senders: Vec<(&SenderKey, &VecDeque<u64>)> = arrivals[&u]
+                .iter()
+                .filter(|(_, frames)| {
+                    frames.len() >= MIN_PHASE_FRAMES
+                        && frames
+                            .back()
+                            .is_some_and(|last| now.saturating_sub(*last) <= SENDER_TIMEOUT_MS)
+                })
+                .collect();
+            senders.sort_by_key(|((_, ip), _)| *ip);
+            for (i, first) in senders.iter().enumerate() {
+                for second in &senders[i + 1..] {
+                    if let Some(phase) = analyse_pair(u, *first, *second, now) {
+                        phases.push(phase);
+                    }
+                }
+            }
+        }
+        phases
+    }
+}
+
+impl Default for FramePhaseAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type FramePhaseAnalyzerHandle = Arc<FramePhaseAnalyzer>;
+
+/// Record the DMX frames sent on the event bus until it closes
+pub async fn run_frame_phase(analyzer: FramePhaseAnalyzerHandle, mut events: EventReceiver) {
+    loop {
+        match events.recv().await {
+            Ok(ListenerEvent::DmxData(data)) => analyzer.record(
+                data.universe,
+                (data.protocol, data.source_ip),
+                data.timestamp,
+            ),
+            Ok(_) | Err(RecvError::Lagged(_)) => {}
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+fn phase_sender((protocol, ip): &SenderKey, frames: &VecDeque<u64>) -> PhaseSender {
+    PhaseSender {
+        ip: ip.to_string(),
+        protocol: *protocol,
+        fps: frame_rate(frames) as f32,
+        frames: frames.len(),
+    }
+}
+
+/// Mean frame rate over the window
+fn frame_rate(frames: &VecDeque<u64>) -> f64 {
+    match (frames.front(), frames.back()) {
+        (Some(first), Some(last)) if last > first => {
+            (frames.len() - 1) as f64 * 1000.0 / (last - first) as f64
+        }
+        _ => 0.0,
+    }
+}
+
+/// Median interval between frames in ms, robust to lost frames
+fn median_period(frames: &VecDeque<u64>) -> Option<f64> {
+    let mut intervals: Vec<u64> = frames
+        .iter()
+        .zip(frames.iter().skip(1))
+        .map(|(a, b)| b - a)
+        .filter(|interval| *interval > 0)
+        .collect();
+    if intervals.is_empty() {
+        return None;
+    }
+    intervals.sort_unstable();
+    Some(intervals[intervals.len() / 2] as f64)
+}
+
+fn analyse_pair(
+    universe: u16,
+    (first_key, first): (&SenderKey, &VecDeque<u64>),
+    (second_key, second): (&SenderKey, &VecDeque<u64>),
+    now: u64,
+) -> Option<FramePhase> {
+    let period = median_period(first)?;
+
+    // Phase of each of the second's frames within the first's frame period,
+    // unwrapped so slips through a whole period keep counting
+    let mut samples: Vec<(f64, f64)> = Vec::new();
+    let mut previous: Option<f64> = None;
+    let mut turns = 0.0;
+    for &t in second {
+        let index = first.partition_point(|a| *a <= t);
+        if index == 0 {
+            continue;
+        }
+        let offset = (t - first[index - 1]) as f64;
+        let phase = (offset / period).fract();
+        if let Some(previous) = previous {
+            if phase - previous > 0.5 {
+                turns -= 1.0;
+            } else if previous - phase > 0.5 {
+                turns += 1.0;
+            }
+        }
+        previous = Some(phase);
+        samples.push((t as f64 / 1000.0, phase + turns));
+    }
+    if samples.len() < MIN_PHASE_FRAMES {
+        return None;
+    }
+
+    // Least squares slope of the unwrapped phase: cycles per second
+    let n = samples.len() as f64;
+    let mean_t = samples.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let mean_p = samples.iter().map(|(_, p)| p).sum::<f64>() / n;
+    let (covariance, variance) = samples.iter().fold((0.0, 0.0), |(c, v), (t, p)| {
+        (c + (t - mean_t) * (p - mean_p), v + (t - mean_t).powi(2))
+    });
+    if variance == 0.0 {
+        return None;
+    }
+    let slip_rate = covariance / variance;
+    let phase = previous.unwrap_or_default();
+
+    // The phase grows when the second's frames come later each time, i.e. the
+    // second sender is the slower one
+    let first_rate = frame_rate(first);
+    let locked = slip_rate.abs() < LOCKED_SLIP_RATE;
+    let (beat_period_seconds, next_alignment_seconds) = if locked {
+        (None, None)
+    } else {
+        let elapsed = now.saturating_sub(*second.back()?) as f64 / 1000.0;
+        let until = if slip_rate > 0.0 {
+            (1.0 - phase) / slip_rate
+        } else {
+            phase / -slip_rate
+        };
+        (
+            Some((1.0 / slip_rate.abs()) as f32),
+            Some((until - elapsed).max(0.0) as f32),
+        )
+    };
+
+    Some(FramePhase {
+        universe,
+        first: phase_sender(first_key, first),
+        second: phase_sender(second_key, second),
+        drift_ppm: (-slip_rate / first_rate * 1_000_000.0) as f32,
+        phase: phase as f32,
+        slip_per_minute: (slip_rate * 60.0) as f32,
+        beat_period_seconds,
+        next_alignment_seconds,
+        locked,
+    })
+}
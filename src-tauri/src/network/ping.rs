@@ -0,0 +1,310 @@
+// ICMP ping - Echo requests to a source, to check whether a silent node is reachable
+//
+// The traceroute sends them with a growing TTL: each router on the way drops
+// the request whose TTL runs out there and answers with Time Exceeded. Lighting
+// traffic should reach its receivers without crossing a router, so any hop
+// before the source is worth knowing about.
+//
+// An unprivileged ICMP datagram socket is used where the OS offers one (macOS,
+// Linux within ping_group_range), a raw socket otherwise, which needs
+// administrator rights on Windows and root elsewhere. Raw sockets (and macOS
+// datagram sockets) deliver the IPv4 header in front of the ICMP message. On
+// Linux datagram sockets the kernel replaces the identifier, so replies are
+// matched on sequence number and payload only.
+
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+const ICMP_ECHO_REPLY: u8 = 0;
+const ICMP_DESTINATION_UNREACHABLE: u8 = 3;
+const ICMP_ECHO_REQUEST: u8 = 8;
+const ICMP_TIME_EXCEEDED: u8 = 11;
+
+/// Payload of the echo requests, to tell our replies from other pings
+const PING_PAYLOAD: &[u8] = b"LXMonitor ping";
+
+/// Time to wait for each reply
+const PING_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Echo requests per ping, at most
+const MAX_PING_COUNT: u32 = 20;
+
+/// Routers a traceroute looks past before giving up
+const MAX_TRACE_HOPS: u8 = 16;
+
+/// Sequence numbers of traceroute probes start here, apart from pings
+const TRACE_SEQUENCE_BASE: u16 = 0x8000;
+
+/// Outcome of pinging a source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PingResult {
+    pub ip: String,
+    pub sent: u32,
+    pub received: u32,
+    pub loss_percent: f32,
+    pub min_ms: Option<f32>,
+    pub avg_ms: Option<f32>,
+    pub max_ms: Option<f32>,
+    pub replies: Vec<Option<f32>>, // Round trip of each request, None when it timed out
+}
+
+/// One hop of a traceroute
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceHop {
+    pub ttl: u8,
+    pub ip: Option<String>, // None when nothing answered in time
+    pub round_trip_ms: Option<f32>,
+}
+
+/// Path to a source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceRoute {
+    pub ip: String,
+    pub hops: Vec<TraceHop>, // The last is the source itself when reached
+    pub reached: bool,
+    pub routers: u8, // Hops before the source, 0 when it is on the local network
+    pub message: String,
+}
+
+/// What answered an ICMP probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IcmpResponse {
+    EchoReply,
+    /// A router dropped the probe as its TTL ran out
+    TimeExceeded,
+    Unreachable,
+}
+
+/// An ICMP socket sending echo requests and matching the answers to them
+pub struct IcmpSocket {
+    socket: UdpSocket, // Only used for its send_to/recv_from, the protocol is ICMP
+    identifier: u16,
+}
+
+impl IcmpSocket {
+    pub fn open() -> Result<Self, String> {
+        let socket = socket2::Socket::new(
+            socket2::Domain::IPV4,
+            socket2::Type::DGRAM,
+            Some(socket2::Protocol::ICMPV4),
+        )
+        .or_else(|_| {
+            socket2::Socket::new(
+                socket2::Domain::IPV4,
+                socket2::Type::RAW,
+                Some(socket2::Protocol::ICMPV4),
+            )
+        })
+        .map_err(|e| {
+            format!(
+                "Failed to open an ICMP socket, sending pings may need administrator rights: {}",
+                e
+            )
+        })?;
+        Ok(Self {
+            socket: socket.into(),
+            identifier: std::process::id() as u16,
+        })
+    }
+
+    pub fn set_ttl(&self, ttl: u32) -> Result<(), String> {
+        self.socket.set_ttl(ttl).map_err(|e| e.to_string())
+    }
+
+    pub fn send_echo(&self, ip: Ipv4Addr, sequence: u16) -> Result<(), String> {
+        let mut packet = Vec::with_capacity(8 + PING_PAYLOAD.len());
+        packet.extend_from_slice(&[ICMP_ECHO_REQUEST, 0, 0, 0]);
+        packet.extend_from_slice(&self.identifier.to_be_bytes());
+        packet.extend_from_slice(&sequence.to_be_bytes());
+        packet.extend_from_slice(PING_PAYLOAD);
+        let checksum = icmp_checksum(&packet);
+        packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+        self.socket
+            .send_to(&packet, SocketAddr::new(IpAddr::V4(ip), 0))
+            .map(|_| ())
+            .map_err(|e| format!("Failed to send ICMP echo request to {}: {}", ip, e))
+    }
+
+    /// Wait until the deadline for the answer to the echo request with this
+    /// sequence number, returning who answered and how
+    pub fn receive(
+        &self,
+        sequence: u16,
+        deadline: Instant,
+    ) -> Result<Option<(Ipv4Addr, IcmpResponse)>, String> {
+        let mut buffer = [0u8; 1500];
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+            self.socket
+                .set_read_timeout(Some(remaining))
+                .map_err(|e| e.to_string())?;
+            let (length, from) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(None)
+                }
+                Err(e) => return Err(format!("Failed to receive ICMP: {}", e)),
+            };
+            let IpAddr::V4(from) = from.ip() else {
+                continue;
+            };
+            let message = strip_ip_header(&buffer[..length]);
+            if let Some(response) = self.match_response(message, sequence) {
+                return Ok(Some((from, response)));
+            }
+        }
+    }
+
+    /// Whether an ICMP message answers our echo request with this sequence number
+    fn match_response(&self, message: &[u8], sequence: u16) -> Option<IcmpResponse> {
+        if message.len() < 8 {
+            return None;
+        }
+        let response = match message[0] {
+            ICMP_ECHO_REPLY => {
+                return (u16::from_be_bytes([message[6], message[7]]) == sequence
+                    && message[8..].starts_with(PING_PAYLOAD))
+                .then_some(IcmpResponse::EchoReply);
+            }
+            ICMP_TIME_EXCEEDED => IcmpResponse::TimeExceeded,
+            ICMP_DESTINATION_UNREACHABLE => IcmpResponse::Unreachable,
+            _ => return None,
+        };
+        // Errors quote the IPv4 header and first 8 bytes of the request
+        let quoted = strip_ip_header(&message[8..]);
+        let ours = quoted.len() >= 8
+            && quoted[0] == ICMP_ECHO_REQUEST
+            && u16::from_be_bytes([quoted[4], quoted[5]]) == self.identifier
+            && u16::from_be_bytes([quoted[6], quoted[7]]) == sequence;
+        ours.then_some(response)
+    }
+}
+
+/// Send `count` echo requests to an address, one a second, and collect the round trips
+pub fn ping(ip: IpAddr, count: u32) -> Result<PingResult, String> {
+    let IpAddr::V4(ipv4) = ip else {
+        return Err("Only IPv4 addresses can be pinged".to_string());
+    };
+    if count == 0 || count > MAX_PING_COUNT {
+        return Err(format!("Ping count must be 1-{}", MAX_PING_COUNT));
+    }
+    let socket = IcmpSocket::open()?;
+
+    let mut replies = Vec::with_capacity(count as usize);
+    for sequence in 0..count as u16 {
+        let sent_at = Instant::now();
+        let deadline = sent_at + PING_TIMEOUT;
+        socket.send_echo(ipv4, sequence)?;
+        let round_trip = match socket.receive(sequence, deadline)? {
+            Some((from, IcmpResponse::EchoReply)) if from == ipv4 => {
+                Some(sent_at.elapsed().as_secs_f32() * 1000.0)
+            }
+            _ => None,
+        };
+        replies.push(round_trip);
+        // Wait out the rest of the second before the next request
+        if sequence + 1 < count as u16 {
+            std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+        }
+    }
+
+    let times: Vec<f32> = replies.iter().flatten().copied().collect();
+    let received = times.len() as u32;
+    Ok(PingResult {
+        ip: ip.to_string(),
+        sent: count,
+        received,
+        loss_percent: (count - received) as f32 / count as f32 * 100.0,
+        min_ms: times.iter().copied().reduce(f32::min),
+        avg_ms: (!times.is_empty()).then(|| times.iter().sum::<f32>() / times.len() as f32),
+        max_ms: times.iter().copied().reduce(f32::max),
+        replies,
+    })
+}
+
+/// Probe the path to an address hop by hop until it answers. Linux datagram
+/// sockets don't deliver Time Exceeded, so the routers stay anonymous there
+/// unless run with a raw socket.
+pub fn traceroute(ip: IpAddr) -> Result<TraceRoute, String> {
+    let IpAddr::V4(ipv4) = ip else {
+        return Err("Only IPv4 addresses can be traced".to_string());
+    };
+    let socket = IcmpSocket::open()?;
+
+    let mut hops = Vec::new();
+    let mut reached = false;
+    for ttl in 1..=MAX_TRACE_HOPS {
+        let sequence = TRACE_SEQUENCE_BASE + ttl as u16;
+        socket.set_ttl(ttl as u32)?;
+        let sent_at = Instant::now();
+        socket.send_echo(ipv4, sequence)?;
+        let response = socket.receive(sequence, sent_at + PING_TIMEOUT)?;
+        let round_trip_ms = response.map(|_| sent_at.elapsed().as_secs_f32() * 1000.0);
+        hops.push(TraceHop {
+            ttl,
+            ip: response.map(|(from, _)| from.to_string()),
+            round_trip_ms,
+        });
+        match response {
+            Some((_, IcmpResponse::EchoReply)) => {
+                reached = true;
+                break;
+            }
+            Some((_, IcmpResponse::Unreachable)) => break,
+            _ => {}
+        }
+    }
+
+    let routers = if reached { hops.len() as u8 - 1 } else { 0 };
+    let message = match (reached, routers) {
+        (true, 0) => format!("{} is on the local network", ip),
+        (true, routers) => format!(
+            "Traffic to {} crosses {} router{}, lighting traffic is normally never routed",
+            ip,
+            routers,
+            if routers == 1 { "" } else { "s" }
+        ),
+        (false, _) => format!("{} did not answer within {} hops", ip, hops.len()),
+    };
+    Ok(TraceRoute {
+        ip: ip.to_string(),
+        hops,
+        reached,
+        routers,
+        message,
+    })
+}
+
+/// The ICMP message of a received packet, skipping the IPv4 header raw
+/// sockets put in front of it. A bare ICMP message never starts with 0x4_,
+/// no ICMP type being 64-79.
+fn strip_ip_header(packet: &[u8]) -> &[u8] {
+    match packet.first() {
+        Some(first) if first >> 4 == 4 => {
+            let header = ((first & 0x0f) as usize) * 4;
+            packet.get(header..).unwrap_or_default()
+        }
+        _ => packet,
+    }
+}
+
+/// Internet checksum (RFC 1071)
+fn icmp_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair.get(1).copied().unwrap_or(0)]) as u32)
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
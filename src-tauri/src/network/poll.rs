@@ -0,0 +1,196 @@
+// ArtPoll latency - How quickly Art-Net nodes answer an ArtPoll
+//
+// Every ArtPoll sent is timestamped, and the delay to each node's first
+// ArtPollReply to it is recorded. Replies arriving later than the reply window
+// are unsolicited (nodes also reply when their state changes) and not counted,
+// nor are the further pages of nodes with more than four ports. Nodes that take
+// long to reply on average, vary a lot or miss polls are flagged.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+/// Controllers consider a node gone when it hasn't replied within 3 seconds
+const POLL_REPLY_WINDOW: Duration = Duration::from_secs(3);
+
+/// Reply delays kept per node for the statistics
+const LATENCY_SAMPLES: usize = 20;
+
+/// Replies needed before a node is flagged
+const MIN_FLAG_SAMPLES: usize = 3;
+
+/// Average delay above which a node is flagged as slow
+const SLOW_REPLY_MS: f32 = 1000.0;
+
+/// Standard deviation of the delay above which a node is flagged as inconsistent
+const INCONSISTENT_JITTER_MS: f32 = 250.0;
+
+/// Share of missed polls above which a node is flagged as inconsistent
+const INCONSISTENT_MISSED_PERCENT: f32 = 20.0;
+
+/// Why a node's ArtPoll replies stand out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PollLatencyFlag {
+    Slow,
+    Inconsistent,
+}
+
+/// ArtPoll reply delay of one node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodePollLatency {
+    pub ip: String,
+    pub source_id: String,
+    pub last_ms: f32,
+    pub average_ms: f32,
+    pub min_ms: f32,
+    pub max_ms: f32,
+    pub jitter_ms: f32, // Standard deviation over the recent replies
+    pub samples: usize,
+    pub replies: u64,    // Polls answered
+    pub missed: u64,     // Polls since the first reply that went unanswered
+    pub last_reply: u64, // Unix timestamp ms
+    pub flag: Option<PollLatencyFlag>,
+}
+
+struct NodeSamples {
+    delays: VecDeque<f32>,
+    answered: u64, // Number of the last poll answered
+    replies: u64,
+    missed: u64,
+    last_reply: u64,
+}
+
+struct PollState {
+    number: u64, // Polls sent, the current one's number
+    sent_at: Option<Instant>,
+    nodes: HashMap<IpAddr, NodeSamples>,
+}
+
+/// Reply delays of the nodes to the ArtPolls sent
+pub struct PollLatencyTracker {
+    state: Mutex<PollState>,
+}
+
+impl PollLatencyTracker {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(PollState {
+                number: 0,
+                sent_at: None,
+                nodes: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Note an ArtPoll going out. Nodes that didn't answer the previous one
+    /// missed it.
+    pub fn record_poll(&self) {
+        let mut state = self.state.lock();
+        let previous = state.number;
+        for node in state.nodes.values_mut() {
+            if node.answered < previous {
+                node.missed += 1;
+            }
+        }
+        state.number += 1;
+        state.sent_at = Some(Instant::now());
+    }
+
+    /// Note an ArtPollReply from a node, recording its delay when it answers
+    /// the current poll
+    pub fn record_reply(&self, ip: IpAddr) {
+        let mut state = self.state.lock();
+        let number = state.number;
+        let Some(sent_at) = state.sent_at else {
+            return;
+        };
+        let delay = sent_at.elapsed();
+        if delay > POLL_REPLY_WINDOW {
+            return;
+        }
+        let node = state.nodes.entry(ip).or_insert_with(|| NodeSamples {
+            delays: VecDeque::with_capacity(LATENCY_SAMPLES),
+            answered: 0,
+            replies: 0,
+            missed: 0,
+            last_reply: 0,
+        });
+        if node.answered == number {
+            return;
+        }
+        node.answered = number;
+        node.replies += 1;
+        node.last_reply = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        if node.delays.len() == LATENCY_SAMPLES {
+            node.delays.pop_front();
+        }
+        node.delays.push_back(delay.as_secs_f32() * 1000.0);
+    }
+
+    /// Reply delays per node, slowest first
+    pub fn get_latencies(&self) -> Vec<NodePollLatency> {
+        let state = self.state.lock();
+        let mut latencies: Vec<NodePollLatency> = state
+            .nodes
+            .iter()
+            .filter(|(_, node)| !node.delays.is_empty())
+            .map(|(ip, node)| node_latency(*ip, node))
+            .collect();
+        latencies.sort_by(|a, b| b.average_ms.total_cmp(&a.average_ms));
+        latencies
+    }
+
+    pub fn clear(&self) {
+        self.state.lock().nodes.clear();
+    }
+}
+
+impl Default for PollLatencyTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn node_latency(ip: IpAddr, node: &NodeSamples) -> NodePollLatency {
+    let samples = node.delays.len();
+    let average_ms = node.delays.iter().sum::<f32>() / samples as f32;
+    let variance = node
+        .delays
+        .iter()
+        .map(|delay| (delay - average_ms).powi(2))
+        .sum::<f32>()
+        / samples as f32;
+    let jitter_ms = variance.sqrt();
+    let missed_percent = node.missed as f32 / (node.replies + node.missed) as f32 * 100.0;
+
+    let flag = if samples < MIN_FLAG_SAMPLES {
+        None
+    } else if average_ms > SLOW_REPLY_MS {
+        Some(PollLatencyFlag::Slow)
+    } else if jitter_ms > INCONSISTENT_JITTER_MS || missed_percent > INCONSISTENT_MISSED_PERCENT {
+        Some(PollLatencyFlag::Inconsistent)
+    } else {
+        None
+    };
+
+    NodePollLatency {
+        ip: ip.to_string(),
+        source_id: format!("artnet-{}", ip),
+        last_ms: node.delays.back().copied().unwrap_or_default(),
+        average_ms,
+        min_ms: node.delays.iter().copied().fold(f32::MAX, f32::min),
+        max_ms: node.delays.iter().copied().fold(0.0, f32::max),
+        jitter_ms,
+        samples,
+        replies: node.replies,
+        missed: node.missed,
+        last_reply: node.last_reply,
+        flag,
+    }
+}
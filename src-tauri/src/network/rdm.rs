@@ -0,0 +1,361 @@
+// RDM (ANSI E1.20) Protocol Implementation
+// Remote Device Management messages as carried in ArtRdm (without the 0xCC start code)
+
+/// RDM start codes
+pub const RDM_START_CODE: u8 = 0xCC;
+pub const RDM_SUB_START_CODE: u8 = 0x01;
+
+/// Command classes
+pub const CC_GET_COMMAND: u8 = 0x20;
+pub const CC_GET_COMMAND_RESPONSE: u8 = 0x21;
+pub const CC_SET_COMMAND: u8 = 0x30;
+pub const CC_SET_COMMAND_RESPONSE: u8 = 0x31;
+
+/// Response types
+pub const RESPONSE_TYPE_ACK: u8 = 0x00;
+pub const RESPONSE_TYPE_ACK_TIMER: u8 = 0x01;
+pub const RESPONSE_TYPE_NACK_REASON: u8 = 0x02;
+pub const RESPONSE_TYPE_ACK_OVERFLOW: u8 = 0x03;
+
+/// NACK reason codes
+pub const NR_UNKNOWN_PID: u16 = 0x0000;
+pub const NR_FORMAT_ERROR: u16 = 0x0001;
+pub const NR_HARDWARE_FAULT: u16 = 0x0002;
+pub const NR_PROXY_REJECT: u16 = 0x0003;
+pub const NR_WRITE_PROTECT: u16 = 0x0004;
+pub const NR_UNSUPPORTED_COMMAND_CLASS: u16 = 0x0005;
+pub const NR_DATA_OUT_OF_RANGE: u16 = 0x0006;
+pub const NR_BUFFER_FULL: u16 = 0x0007;
+pub const NR_SUB_DEVICE_OUT_OF_RANGE: u16 = 0x0009;
+
+/// Parameter IDs
+pub const PID_QUEUED_MESSAGE: u16 = 0x0020;
+pub const PID_STATUS_MESSAGES: u16 = 0x0030;
+pub const PID_SUPPORTED_PARAMETERS: u16 = 0x0050;
+pub const PID_DEVICE_INFO: u16 = 0x0060;
+pub const PID_DEVICE_MODEL_DESCRIPTION: u16 = 0x0080;
+pub const PID_MANUFACTURER_LABEL: u16 = 0x0081;
+pub const PID_DEVICE_LABEL: u16 = 0x0082;
+pub const PID_SOFTWARE_VERSION_LABEL: u16 = 0x00C0;
+pub const PID_DMX_PERSONALITY: u16 = 0x00E0;
+pub const PID_DMX_START_ADDRESS: u16 = 0x00F0;
+pub const PID_IDENTIFY_DEVICE: u16 = 0x1000;
+
+/// Status types of status messages. Requests name the lowest type wanted,
+/// advisory asking for everything.
+pub const STATUS_ADVISORY: u8 = 0x02;
+pub const STATUS_WARNING: u8 = 0x03;
+pub const STATUS_ERROR: u8 = 0x04;
+pub const STATUS_ADVISORY_CLEARED: u8 = 0x12;
+pub const STATUS_WARNING_CLEARED: u8 = 0x13;
+pub const STATUS_ERROR_CLEARED: u8 = 0x14;
+
+/// Sub-device addressing every sub-device (SET only)
+pub const SUB_DEVICE_ALL_CALL: u16 = 0xFFFF;
+
+/// UID the monitor sends its own requests from, in the prototype manufacturer range
+pub const CONTROLLER_UID: RdmUid = [0x7F, 0xF0, 0x00, 0x00, 0x00, 0x00];
+
+/// RDM unique ID: 2 byte ESTA manufacturer ID followed by a 4 byte device ID
+pub type RdmUid = [u8; 6];
+
+/// Parsed RDM message
+#[derive(Debug, Clone)]
+pub struct RdmMessage {
+    pub destination: RdmUid,
+    pub source: RdmUid,
+    pub transaction: u8,
+    /// Port ID in requests, response type in responses
+    pub port_or_response: u8,
+    pub message_count: u8,
+    pub sub_device: u16,
+    pub command_class: u8,
+    pub pid: u16,
+    pub data: Vec<u8>,
+}
+
+impl RdmMessage {
+    /// Build a response to this request from the addressed device
+    /// (response command classes are the request class + 1)
+    pub fn response(&self, uid: RdmUid, response_type: u8, data: Vec<u8>) -> RdmMessage {
+        RdmMessage {
+            destination: self.source,
+            source: uid,
+            transaction: self.transaction,
+            port_or_response: response_type,
+            message_count: 0,
+            sub_device: self.sub_device,
+            command_class: self.command_class + 1,
+            pid: self.pid,
+            data,
+        }
+    }
+
+    /// Build a NACK response with a reason code
+    pub fn nack(&self, uid: RdmUid, reason: u16) -> RdmMessage {
+        self.response(
+            uid,
+            RESPONSE_TYPE_NACK_REASON,
+            reason.to_be_bytes().to_vec(),
+        )
+    }
+}
+
+/// Parse an RDM message. Accepts data with or without the leading start code,
+/// since some controllers include it in ArtRdm despite the spec.
+pub fn parse_rdm_message(data: &[u8]) -> Option<RdmMessage> {
+    let data = match data {
+        [RDM_START_CODE, RDM_SUB_START_CODE, ..] => &data[1..],
+        _ => data,
+    };
+
+    // Sub start code (byte 0), message length incl. start code (byte 1)
+    if data.len() < 25 || data[0] != RDM_SUB_START_CODE {
+        return None;
+    }
+    let message_length = data[1] as usize;
+    let pdl = data[22] as usize;
+    if message_length != 24 + pdl || data.len() < message_length + 1 {
+        return None;
+    }
+
+    // Checksum covers the start code and every byte up to the checksum itself
+    let body = &data[..message_length - 1];
+    let checksum = u16::from_be_bytes([data[message_length - 1], data[message_length]]);
+    if rdm_checksum(body) != checksum {
+        return None;
+    }
+
+    let mut destination = [0u8; 6];
+    destination.copy_from_slice(&data[2..8]);
+    let mut source = [0u8; 6];
+    source.copy_from_slice(&data[8..14]);
+
+    Some(RdmMessage {
+        destination,
+        source,
+        transaction: data[14],
+        port_or_response: data[15],
+        message_count: data[16],
+        sub_device: u16::from_be_bytes([data[17], data[18]]),
+        command_class: data[19],
+        pid: u16::from_be_bytes([data[20], data[21]]),
+        data: data[23..23 + pdl].to_vec(),
+    })
+}
+
+/// Encode an RDM message without the start code, as carried in ArtRdm
+pub fn create_rdm_message(message: &RdmMessage) -> Vec<u8> {
+    let pdl = message.data.len().min(231);
+    let mut packet = Vec::with_capacity(25 + pdl);
+
+    packet.push(RDM_SUB_START_CODE);
+    packet.push((24 + pdl) as u8);
+    packet.extend_from_slice(&message.destination);
+    packet.extend_from_slice(&message.source);
+    packet.push(message.transaction);
+    packet.push(message.port_or_response);
+    packet.push(message.message_count);
+    packet.extend_from_slice(&message.sub_device.to_be_bytes());
+    packet.push(message.command_class);
+    packet.extend_from_slice(&message.pid.to_be_bytes());
+    packet.push(pdl as u8);
+    packet.extend_from_slice(&message.data[..pdl]);
+
+    let checksum = rdm_checksum(&packet);
+    packet.extend_from_slice(&checksum.to_be_bytes());
+    packet
+}
+
+/// RDM checksum: 16-bit sum of the start code and all message bytes
+fn rdm_checksum(data: &[u8]) -> u16 {
+    data.iter()
+        .fold(RDM_START_CODE as u16, |sum, &b| sum.wrapping_add(b as u16))
+}
+
+/// Name of a parameter ID, for messages
+pub fn pid_name(pid: u16) -> String {
+    match pid {
+        PID_QUEUED_MESSAGE => "QUEUED_MESSAGE".to_string(),
+        PID_STATUS_MESSAGES => "STATUS_MESSAGES".to_string(),
+        PID_SUPPORTED_PARAMETERS => "SUPPORTED_PARAMETERS".to_string(),
+        PID_DEVICE_INFO => "DEVICE_INFO".to_string(),
+        PID_DEVICE_MODEL_DESCRIPTION => "DEVICE_MODEL_DESCRIPTION".to_string(),
+        PID_MANUFACTURER_LABEL => "MANUFACTURER_LABEL".to_string(),
+        PID_DEVICE_LABEL => "DEVICE_LABEL".to_string(),
+        PID_SOFTWARE_VERSION_LABEL => "SOFTWARE_VERSION_LABEL".to_string(),
+        PID_DMX_PERSONALITY => "DMX_PERSONALITY".to_string(),
+        PID_DMX_START_ADDRESS => "DMX_START_ADDRESS".to_string(),
+        PID_IDENTIFY_DEVICE => "IDENTIFY_DEVICE".to_string(),
+        _ => format!("PID 0x{:04X}", pid),
+    }
+}
+
+/// Description of a NACK reason code
+pub fn nack_reason(reason: u16) -> &'static str {
+    match reason {
+        NR_UNKNOWN_PID => "parameter not supported",
+        NR_FORMAT_ERROR => "malformed request",
+        NR_HARDWARE_FAULT => "hardware fault",
+        NR_PROXY_REJECT => "rejected by proxy",
+        NR_WRITE_PROTECT => "write protected",
+        NR_UNSUPPORTED_COMMAND_CLASS => "command class not supported",
+        NR_DATA_OUT_OF_RANGE => "value out of range",
+        NR_BUFFER_FULL => "buffer full",
+        NR_SUB_DEVICE_OUT_OF_RANGE => "sub-device out of range",
+        _ => "unknown reason",
+    }
+}
+
+/// Text of a status message ID (E1.20 table B-2) with its two data values,
+/// e.g. sensor number and temperature
+pub fn status_message_text(id: u16, value1: i16, value2: i16) -> String {
+    match id {
+        0x0001 => format!("Slot {} failed calibration", value1),
+        0x0002 => format!("Sensor {} not found", value1),
+        0x0003 => format!("Sensor {} always on", value1),
+        0x0011 => "Lamp doused".to_string(),
+        0x0012 => "Lamp failed to strike".to_string(),
+        0x0021 => format!("Sensor {} over temp at {} °C", value1, value2),
+        0x0022 => format!("Sensor {} under temp at {} °C", value1, value2),
+        0x0023 => format!("Sensor {} out of range", value1),
+        0x0031 => format!("Phase {} over voltage at {} V", value1, value2),
+        0x0032 => format!("Phase {} under voltage at {} V", value1, value2),
+        0x0033 => format!("Over current at {} A", value1),
+        0x0034 => format!("Under current at {} A", value1),
+        0x0035 => format!("Phase {} is at {} degrees", value1, value2),
+        0x0036 => format!("Phase {} error", value1),
+        0x0037 => format!("{} amps", value1),
+        0x0038 => format!("{} volts", value1),
+        0x0041 => "No dimmer".to_string(),
+        0x0042 => "Tripped breaker".to_string(),
+        0x0043 => format!("{} watts", value1),
+        0x0044 => "Dimmer failure".to_string(),
+        0x0045 => "Dimmer panic mode".to_string(),
+        0x0050 => "Ready".to_string(),
+        0x0051 => "Not ready".to_string(),
+        0x0052 => "Low fluid".to_string(),
+        _ => format!("Status 0x{:04X} ({}, {})", id, value1, value2),
+    }
+}
+
+/// Whether a destination UID addresses the given device (directly or by broadcast)
+pub fn uid_matches(destination: &RdmUid, uid: &RdmUid) -> bool {
+    destination == uid
+        || (destination[2..] == [0xFF; 4]
+            && (destination[..2] == [0xFF; 2] || destination[..2] == uid[..2]))
+}
+
+/// Whether a destination UID is a broadcast (all devices or all of a manufacturer)
+pub fn uid_is_broadcast(destination: &RdmUid) -> bool {
+    destination[2..] == [0xFF; 4]
+}
+
+/// UID to string (mmmm:dddddddd)
+pub fn uid_to_string(uid: &RdmUid) -> String {
+    format!(
+        "{:02x}{:02x}:{:02x}{:02x}{:02x}{:02x}",
+        uid[0], uid[1], uid[2], uid[3], uid[4], uid[5]
+    )
+}
+
+/// Parse a UID string (mmmm:dddddddd, separator optional)
+pub fn parse_uid(value: &str) -> Option<RdmUid> {
+    let hex: String = value
+        .trim()
+        .chars()
+        .filter(|c| *c != ':' && *c != '-')
+        .collect();
+    if hex.len() != 12 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut uid = [0u8; 6];
+    for (i, byte) in uid.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(uid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_device_info() -> RdmMessage {
+        RdmMessage {
+            destination: [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC],
+            source: CONTROLLER_UID,
+            transaction: 7,
+            port_or_response: 1,
+            message_count: 0,
+            sub_device: 0,
+            command_class: CC_GET_COMMAND,
+            pid: PID_DEVICE_INFO,
+            data: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parse_uid_accepts_separators_and_case() {
+        let uid = [0x7F, 0xF0, 0x00, 0x00, 0x00, 0x2A];
+        assert_eq!(parse_uid("7ff0:0000002a"), Some(uid));
+        assert_eq!(parse_uid(" 7FF0-0000002A "), Some(uid));
+        assert_eq!(parse_uid("7ff00000002a"), Some(uid));
+        assert_eq!(parse_uid(&uid_to_string(&uid)), Some(uid));
+    }
+
+    #[test]
+    fn parse_uid_rejects_invalid_input() {
+        assert_eq!(parse_uid(""), None);
+        assert_eq!(parse_uid("7ff0:000000"), None);
+        assert_eq!(parse_uid("7ff0:0000002a00"), None);
+        assert_eq!(parse_uid("7ff0:0000002g"), None);
+        assert_eq!(parse_uid("+7f0:0000002a"), None);
+        // 12 bytes, but not 12 characters: must not slice inside a character
+        assert_eq!(parse_uid("€€€€"), None);
+        assert_eq!(parse_uid("7ff0:0000é2a"), None);
+    }
+
+    #[test]
+    fn checksum_sums_start_code_and_message() {
+        assert_eq!(rdm_checksum(&[]), RDM_START_CODE as u16);
+        assert_eq!(rdm_checksum(&[0x01, 0x18]), 0xCC + 0x01 + 0x18);
+        // Wraps at 16 bits
+        assert_eq!(rdm_checksum(&[0xFF; 300]), (0xCC + 0xFF * 300) as u16);
+    }
+
+    #[test]
+    fn message_round_trips_with_checksum() {
+        let request = get_device_info();
+        let packet = create_rdm_message(&request);
+        // Message length counts the start code, which ArtRdm leaves out
+        assert_eq!(packet.len(), 25);
+        assert_eq!(packet[1], 24);
+        let checksum = u16::from_be_bytes([packet[23], packet[24]]);
+        assert_eq!(checksum, rdm_checksum(&packet[..23]));
+
+        let parsed = parse_rdm_message(&packet).expect("valid message");
+        assert_eq!(parsed.destination, request.destination);
+        assert_eq!(parsed.source, CONTROLLER_UID);
+        assert_eq!(parsed.transaction, 7);
+        assert_eq!(parsed.pid, PID_DEVICE_INFO);
+
+        // With the start code in front, as some controllers send it
+        let mut with_start_code = vec![RDM_START_CODE];
+        with_start_code.extend_from_slice(&packet);
+        assert!(parse_rdm_message(&with_start_code).is_some());
+    }
+
+    #[test]
+    fn message_with_bad_checksum_is_rejected() {
+        let response = get_device_info().response(
+            [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC],
+            RESPONSE_TYPE_ACK,
+            vec![0x01, 0x00],
+        );
+        let mut packet = create_rdm_message(&response);
+        assert!(parse_rdm_message(&packet).is_some());
+        let last = packet.len() - 1;
+        packet[last] ^= 0x01;
+        assert!(parse_rdm_message(&packet).is_none());
+    }
+}
@@ -0,0 +1,770 @@
+// RDM devices - Fixtures behind the Art-Net nodes, by node and port
+//
+// Nodes publish the RDM UIDs discovered on each output port in ArtTodData,
+// their table of devices (ToD), when a controller asks for it and when it
+// changes. Every full ToD received replaces the node port's device list, and
+// the UIDs that appeared or disappeared are reported as a ToD change. A ToD of
+// more than 200 UIDs arrives in blocks and is only compared once complete.
+//
+// The device details (manufacturer, model, DMX address, footprint and
+// personality) are read with RDM GETs carried in ArtRdm. Nodes send the
+// answers to port 6454, so they arrive through the Art-Net listener, which
+// hands them back here to the request waiting for them. Responses to other
+// controllers that reach the listener fill in the table as well.
+//
+// Devices report faults such as a lamp failing to strike or overheating as
+// status messages, and changes made on the fixture itself as queued messages.
+// Every response says how many messages are queued; whenever the monitor
+// talks to a device it collects them, and new status messages are raised as
+// events.
+
+use crate::network::artnet::{
+    create_artrdm_packet, create_arttodrequest_packet, ArtTodData, ARTNET_PORT,
+};
+use crate::network::rdm::{
+    create_rdm_message, nack_reason, parse_rdm_message, parse_uid, pid_name, status_message_text,
+    uid_to_string, RdmMessage, RdmUid, CC_GET_COMMAND, CC_GET_COMMAND_RESPONSE, CC_SET_COMMAND,
+    CC_SET_COMMAND_RESPONSE, CONTROLLER_UID, PID_DEVICE_INFO, PID_DEVICE_LABEL,
+    PID_DEVICE_MODEL_DESCRIPTION, PID_DMX_PERSONALITY, PID_DMX_START_ADDRESS, PID_IDENTIFY_DEVICE,
+    PID_MANUFACTURER_LABEL, PID_QUEUED_MESSAGE, PID_SOFTWARE_VERSION_LABEL, PID_STATUS_MESSAGES,
+    RESPONSE_TYPE_ACK, RESPONSE_TYPE_ACK_OVERFLOW, RESPONSE_TYPE_ACK_TIMER,
+    RESPONSE_TYPE_NACK_REASON, STATUS_ADVISORY, STATUS_ADVISORY_CLEARED, STATUS_ERROR,
+    STATUS_ERROR_CLEARED, STATUS_WARNING, STATUS_WARNING_CLEARED,
+};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::info;
+
+/// Parameters read from a device, enough to audit its addressing
+const READ_PIDS: [u16; 6] = [
+    PID_DEVICE_INFO,
+    PID_MANUFACTURER_LABEL,
+    PID_DEVICE_MODEL_DESCRIPTION,
+    PID_SOFTWARE_VERSION_LABEL,
+    PID_DMX_START_ADDRESS,
+    PID_DEVICE_LABEL,
+];
+
+/// Time a node gets to pass a request on and return the device's answer
+const RDM_RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Time nodes get to answer an ArtTodRequest, discovery can take a while
+const TOD_WAIT: Duration = Duration::from_secs(3);
+
+/// ACK_OVERFLOW answers accepted for one GET before giving up on the device
+const MAX_OVERFLOW_RESPONSES: usize = 16;
+
+/// Status messages kept per device
+const MAX_STATUS_MESSAGES: usize = 50;
+
+/// Queued messages fetched in one go, the rest wait for the next collection
+const MAX_QUEUED_FETCH: usize = 32;
+
+/// Severity of an RDM status message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RdmStatusType {
+    Advisory,
+    Warning,
+    Error,
+    AdvisoryCleared,
+    WarningCleared,
+    ErrorCleared,
+}
+
+/// A status message reported by an RDM device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RdmStatusMessage {
+    pub source_id: String,
+    pub uid: String,
+    pub universe: u16,
+    pub sub_device: u16,
+    pub status_type: RdmStatusType,
+    pub message_id: u16,
+    pub data_value1: i16,
+    pub data_value2: i16,
+    pub timestamp: u64, // Unix timestamp ms
+    pub message: String,
+}
+
+/// An RDM device found behind an Art-Net node port
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RdmDevice {
+    pub uid: String,
+    pub manufacturer_id: u16, // ESTA manufacturer ID, the first 2 bytes of the UID
+    pub source_id: String,    // Art-Net node it is behind
+    pub node_ip: String,
+    pub port: u8, // Node port, 1-4
+    pub bind_index: u8,
+    pub universe: u16,
+    pub manufacturer: Option<String>,
+    pub model_id: Option<u16>,
+    pub model: Option<String>,
+    pub label: Option<String>,
+    pub software_version: Option<String>,
+    pub dmx_address: Option<u16>, // None when the device has no DMX footprint
+    pub footprint: Option<u16>,
+    pub personality: Option<u8>,
+    pub personality_count: Option<u8>,
+    pub identify: Option<bool>,
+    pub queued_messages: u8, // Message count of the last response
+    pub status_messages: Vec<RdmStatusMessage>, // Oldest first
+    pub first_seen: u64,     // Unix timestamp ms
+    pub last_seen: u64,      // Unix timestamp ms of the last ToD listing it
+    pub last_response: Option<u64>, // Unix timestamp ms of the last parameter read
+}
+
+/// UIDs that appeared on or disappeared from a node port's ToD
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodChange {
+    pub source_id: String,
+    pub node_ip: String,
+    pub port: u8,
+    pub universe: u16,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub device_count: usize,
+    pub timestamp: u64, // Unix timestamp ms
+    pub message: String,
+}
+
+/// ToD of one node port
+#[derive(Default)]
+struct PortTod {
+    devices: BTreeMap<RdmUid, RdmDevice>,
+    pending: Vec<RdmUid>, // Blocks of a ToD still being received
+}
+
+/// RDM devices per Art-Net node and universe
+pub struct RdmDeviceTable {
+    ports: Mutex<HashMap<(IpAddr, u16), PortTod>>,
+    /// Requests sent, by device and transaction number, with their answer once in
+    waiting: Mutex<HashMap<(RdmUid, u8), Option<RdmMessage>>>,
+    responded: Notify,
+    transaction: Mutex<u8>,
+    /// Data of GET answers split with ACK_OVERFLOW, by device and PID, until the
+    /// closing ACK
+    overflow: Mutex<HashMap<(RdmUid, u16), Vec<u8>>>,
+}
+
+impl RdmDeviceTable {
+    pub fn new() -> Self {
+        Self {
+            ports: Mutex::new(HashMap::new()),
+            waiting: Mutex::new(HashMap::new()),
+            responded: Notify::new(),
+            transaction: Mutex::new(0),
+            overflow: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Apply an ArtTodData block from a node, returning the change once a
+    /// complete ToD differs from the previous one
+    pub fn record_tod(&self, ip: IpAddr, tod: &ArtTodData) -> Option<TodChange> {
+        if !tod.full {
+            return None;
+        }
+        let mut ports = self.ports.lock();
+        let port = ports.entry((ip, tod.universe)).or_default();
+        if tod.block == 0 {
+            port.pending.clear();
+        }
+        port.pending.extend_from_slice(&tod.uids);
+        if port.pending.len() < tod.uid_total as usize {
+            return None;
+        }
+
+        let uids: BTreeSet<RdmUid> = port.pending.drain(..).collect();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let source_id = format!("artnet-{}", ip);
+        let removed: Vec<String> = port
+            .devices
+            .keys()
+            .filter(|uid| !uids.contains(*uid))
+            .map(uid_to_string)
+            .collect();
+        port.devices.retain(|uid, _| uids.contains(uid));
+        let mut added = Vec::new();
+        for uid in uids {
+            let device = port.devices.entry(uid).or_insert_with(|| {
+                added.push(uid_to_string(&uid));
+                RdmDevice {
+                    uid: uid_to_string(&uid),
+                    manufacturer_id: u16::from_be_bytes([uid[0], uid[1]]),
+                    source_id: source_id.clone(),
+                    node_ip: ip.to_string(),
+                    port: tod.port,
+                    bind_index: tod.bind_index,
+                    universe: tod.universe,
+                    manufacturer: None,
+                    model_id: None,
+                    model: None,
+                    label: None,
+                    software_version: None,
+                    dmx_address: None,
+                    footprint: None,
+                    personality: None,
+                    personality_count: None,
+                    identify: None,
+                    queued_messages: 0,
+                    status_messages: Vec::new(),
+                    first_seen: now,
+                    last_seen: now,
+                    last_response: None,
+                }
+            });
+            device.port = tod.port;
+            device.bind_index = tod.bind_index;
+            device.last_seen = now;
+        }
+
+        if added.is_empty() && removed.is_empty() {
+            return None;
+        }
+        let message = match (added.len(), removed.len()) {
+            (added, 0) => format!(
+                "{} RDM device{} appeared on universe {}",
+                added,
+                if added == 1 { "" } else { "s" },
+                tod.universe
+            ),
+            (0, removed) => format!(
+                "{} RDM device{} disappeared from universe {}",
+                removed,
+                if removed == 1 { "" } else { "s" },
+                tod.universe
+            ),
+            (added, removed) => format!(
+                "{} RDM devices appeared and {} disappeared on universe {}",
+                added, removed, tod.universe
+            ),
+        };
+        Some(TodChange {
+            source_id,
+            node_ip: ip.to_string(),
+            port: tod.port,
+            universe: tod.universe,
+            added,
+            removed,
+            device_count: port.devices.len(),
+            timestamp: now,
+            message,
+        })
+    }
+
+    /// Devices behind a node, by universe and UID
+    pub fn get_devices(&self, source_id: &str) -> Vec<RdmDevice> {
+        let ports = self.ports.lock();
+        let mut devices: Vec<RdmDevice> = ports
+            .values()
+            .flat_map(|port| port.devices.values())
+            .filter(|device| device.source_id == source_id)
+            .cloned()
+            .collect();
+        devices.sort_by(|a, b| a.universe.cmp(&b.universe).then(a.uid.cmp(&b.uid)));
+        devices
+    }
+
+    /// Apply an RDM response received in ArtRdm to the device it came from
+    /// and hand it to the request waiting for it, returning the new status
+    /// messages it carried. None when the message isn't a response.
+    pub fn record_response(
+        &self,
+        ip: IpAddr,
+        universe: u16,
+        rdm_packet: &[u8],
+    ) -> Option<Vec<RdmStatusMessage>> {
+        let mut response = parse_rdm_message(rdm_packet)?;
+        if !matches!(
+            response.command_class,
+            CC_GET_COMMAND_RESPONSE | CC_SET_COMMAND_RESPONSE
+        ) {
+            return None;
+        }
+        if response.command_class == CC_GET_COMMAND_RESPONSE {
+            // Join the parts of an overflowed answer, so only the whole is applied
+            let key = (response.source, response.pid);
+            let mut overflow = self.overflow.lock();
+            match response.port_or_response {
+                RESPONSE_TYPE_ACK_OVERFLOW => {
+                    overflow
+                        .entry(key)
+                        .or_default()
+                        .extend_from_slice(&response.data);
+                }
+                RESPONSE_TYPE_ACK => {
+                    if let Some(mut data) = overflow.remove(&key) {
+                        data.extend_from_slice(&response.data);
+                        response.data = data;
+                    }
+                }
+                _ => {
+                    overflow.remove(&key);
+                }
+            }
+        }
+        let mut status = Vec::new();
+        if let Some(device) = self
+            .ports
+            .lock()
+            .get_mut(&(ip, universe))
+            .and_then(|port| port.devices.get_mut(&response.source))
+        {
+            device.queued_messages = response.message_count;
+            let acknowledged_get = response.command_class == CC_GET_COMMAND_RESPONSE
+                && response.port_or_response == RESPONSE_TYPE_ACK;
+            if acknowledged_get && response.pid == PID_STATUS_MESSAGES {
+                status = status_messages(device, &response.data);
+                device.status_messages.extend(status.iter().cloned());
+                let excess = device
+                    .status_messages
+                    .len()
+                    .saturating_sub(MAX_STATUS_MESSAGES);
+                device.status_messages.drain(..excess);
+            } else if acknowledged_get && response.sub_device == 0 {
+                apply_parameter(device, response.pid, &response.data);
+            }
+        }
+
+        let key = (response.source, response.transaction);
+        if let Some(answer) = self.waiting.lock().get_mut(&key) {
+            *answer = Some(response);
+            self.responded.notify_waiters();
+        }
+        Some(status)
+    }
+
+    /// A device behind a node, by UID
+    pub fn get_device(&self, source_id: &str, uid: &str) -> Result<RdmDevice, String> {
+        let key = parse_uid(uid).ok_or_else(|| format!("Invalid UID: {}", uid))?;
+        let ports = self.ports.lock();
+        ports
+            .values()
+            .filter_map(|port| port.devices.get(&key))
+            .find(|device| device.source_id == source_id)
+            .cloned()
+            .ok_or_else(|| format!("RDM device {} not found behind {}", uid, source_id))
+    }
+
+    /// Read the addressing parameters of a device with RDM GETs. Parameters
+    /// the device doesn't support are left as they were.
+    pub async fn read_device(&self, source_id: &str, uid: &str) -> Result<RdmDevice, String> {
+        let device = self.get_device(source_id, uid)?;
+        let mut answered = false;
+        let mut first_error = None;
+        for pid in READ_PIDS {
+            match self.request(&device, CC_GET_COMMAND, pid, Vec::new()).await {
+                Ok(_) => answered = true,
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        if let (false, Some(e)) = (answered, first_error) {
+            return Err(e);
+        }
+        self.fetch_queued(&device).await;
+        self.get_device(source_id, uid)
+    }
+
+    /// Collect a device's status messages and the messages it has queued,
+    /// such as parameter changes made on the fixture itself
+    pub async fn collect_status(&self, source_id: &str, uid: &str) -> Result<RdmDevice, String> {
+        let device = self.get_device(source_id, uid)?;
+        self.request(
+            &device,
+            CC_GET_COMMAND,
+            PID_STATUS_MESSAGES,
+            vec![STATUS_ADVISORY],
+        )
+        .await?;
+        self.fetch_queued(&device).await;
+        self.get_device(source_id, uid)
+    }
+
+    /// Fetch queued messages while the device reports some. Each answer is
+    /// the queued parameter's own response, applied like any other.
+    async fn fetch_queued(&self, device: &RdmDevice) {
+        for _ in 0..MAX_QUEUED_FETCH {
+            let queued = self
+                .get_device(&device.source_id, &device.uid)
+                .map_or(0, |d| d.queued_messages);
+            if queued == 0 {
+                break;
+            }
+            match self
+                .request(
+                    device,
+                    CC_GET_COMMAND,
+                    PID_QUEUED_MESSAGE,
+                    vec![STATUS_ADVISORY],
+                )
+                .await
+            {
+                // An empty status message list means nothing is left
+                Ok(response) if response.pid == PID_STATUS_MESSAGES && response.data.is_empty() => {
+                    break
+                }
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// Ask a node for the ToD of its universes, then read every device it
+    /// lists. Devices that don't answer keep what was known about them.
+    pub async fn audit_node(
+        &self,
+        ip: IpAddr,
+        source_id: &str,
+        universes: &[u16],
+    ) -> Result<Vec<RdmDevice>, String> {
+        let mut by_net: BTreeMap<u8, Vec<u8>> = BTreeMap::new();
+        for universe in universes {
+            by_net
+                .entry((universe >> 8) as u8)
+                .or_default()
+                .push((universe & 0xFF) as u8);
+        }
+        for (net, addresses) in by_net {
+            for chunk in addresses.chunks(32) {
+                send_artnet(ip, &create_arttodrequest_packet(net, chunk))?;
+            }
+        }
+        tokio::time::sleep(TOD_WAIT).await;
+
+        for device in self.get_devices(source_id) {
+            let _ = self.read_device(source_id, &device.uid).await;
+            let _ = self.collect_status(source_id, &device.uid).await;
+        }
+        Ok(self.get_devices(source_id))
+    }
+
+    /// Switch a device's identify mode (flashing or moving so it can be
+    /// found), confirmed by reading it back
+    pub async fn set_identify(
+        &self,
+        source_id: &str,
+        uid: &str,
+        identify: bool,
+    ) -> Result<RdmDevice, String> {
+        let device = self.get_device(source_id, uid)?;
+        self.set_and_confirm(&device, PID_IDENTIFY_DEVICE, vec![identify as u8])
+            .await?;
+        self.get_device(source_id, uid)
+    }
+
+    /// Move a device to a new DMX start address, confirmed by reading it back
+    pub async fn set_dmx_address(
+        &self,
+        source_id: &str,
+        uid: &str,
+        address: u16,
+    ) -> Result<RdmDevice, String> {
+        let device = self.get_device(source_id, uid)?;
+        if !(1..=512).contains(&address) {
+            return Err(format!("Start address {} out of range (1-512)", address));
+        }
+        if let Some(footprint) = device
+            .footprint
+            .filter(|&f| u32::from(address) + u32::from(f) > 513)
+        {
+            return Err(format!(
+                "A footprint of {} channels doesn't fit from address {}",
+                footprint, address
+            ));
+        }
+        self.set_and_confirm(
+            &device,
+            PID_DMX_START_ADDRESS,
+            address.to_be_bytes().to_vec(),
+        )
+        .await?;
+        info!("[RDM] {} moved to address {}", device.uid, address);
+        self.get_device(source_id, uid)
+    }
+
+    /// Switch a device to another personality (channel layout), confirmed by
+    /// reading it back. The device info is read again for the new footprint.
+    pub async fn set_personality(
+        &self,
+        source_id: &str,
+        uid: &str,
+        personality: u8,
+    ) -> Result<RdmDevice, String> {
+        let device = self.get_device(source_id, uid)?;
+        let count = device.personality_count.unwrap_or(u8::MAX);
+        if personality == 0 || personality > count {
+            return Err(format!(
+                "Personality {} out of range (1-{})",
+                personality, count
+            ));
+        }
+        self.set_and_confirm(&device, PID_DMX_PERSONALITY, vec![personality])
+            .await?;
+        let _ = self
+            .request(&device, CC_GET_COMMAND, PID_DEVICE_INFO, Vec::new())
+            .await;
+        info!(
+            "[RDM] {} switched to personality {}",
+            device.uid, personality
+        );
+        self.get_device(source_id, uid)
+    }
+
+    /// SET a parameter, then GET it to check the device took the new value
+    async fn set_and_confirm(
+        &self,
+        device: &RdmDevice,
+        pid: u16,
+        data: Vec<u8>,
+    ) -> Result<(), String> {
+        self.request(device, CC_SET_COMMAND, pid, data.clone())
+            .await?;
+        let response = self
+            .request(device, CC_GET_COMMAND, pid, Vec::new())
+            .await
+            .map_err(|e| format!("Change sent, but it couldn't be confirmed: {}", e))?;
+        if !response.data.starts_with(&data) {
+            return Err(format!(
+                "{} accepted the {} change but reads back a different value",
+                device.uid,
+                pid_name(pid)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Send an RDM request to a device through its node and wait for the
+    /// acknowledged response. A GET answered with ACK_OVERFLOW is repeated
+    /// until the closing ACK, which then carries the data of all parts.
+    async fn request(
+        &self,
+        device: &RdmDevice,
+        command_class: u8,
+        pid: u16,
+        data: Vec<u8>,
+    ) -> Result<RdmMessage, String> {
+        let uid = parse_uid(&device.uid).ok_or_else(|| format!("Invalid UID: {}", device.uid))?;
+        // Drop the parts of an earlier answer that never completed
+        self.overflow.lock().remove(&(uid, pid));
+        for _ in 0..MAX_OVERFLOW_RESPONSES {
+            let response = self
+                .request_once(device, uid, command_class, pid, data.clone())
+                .await?;
+            if response.port_or_response != RESPONSE_TYPE_ACK_OVERFLOW {
+                return Ok(response);
+            }
+            if command_class != CC_GET_COMMAND {
+                return Err(format!(
+                    "{} answered {} with ACK_OVERFLOW",
+                    device.uid,
+                    pid_name(pid)
+                ));
+            }
+        }
+        self.overflow.lock().remove(&(uid, pid));
+        Err(format!(
+            "{} split {} over more than {} answers",
+            device.uid,
+            pid_name(pid),
+            MAX_OVERFLOW_RESPONSES
+        ))
+    }
+
+    /// One request and its answer, an acknowledgement or the first part of one
+    async fn request_once(
+        &self,
+        device: &RdmDevice,
+        uid: RdmUid,
+        command_class: u8,
+        pid: u16,
+        data: Vec<u8>,
+    ) -> Result<RdmMessage, String> {
+        let ip: IpAddr = device
+            .node_ip
+            .parse()
+            .map_err(|_| format!("Invalid node IP: {}", device.node_ip))?;
+        let transaction = {
+            let mut transaction = self.transaction.lock();
+            *transaction = transaction.wrapping_add(1);
+            *transaction
+        };
+        let request = RdmMessage {
+            destination: uid,
+            source: CONTROLLER_UID,
+            transaction,
+            port_or_response: 1,
+            message_count: 0,
+            sub_device: 0,
+            command_class,
+            pid,
+            data,
+        };
+
+        let key = (uid, transaction);
+        self.waiting.lock().insert(key, None);
+        let packet = create_artrdm_packet(device.universe, &create_rdm_message(&request));
+        let response = match send_artnet(ip, &packet) {
+            Ok(()) => self.wait_response(key).await,
+            Err(e) => {
+                self.waiting.lock().remove(&key);
+                return Err(e);
+            }
+        };
+
+        let name = pid_name(pid);
+        match response {
+            None => Err(format!("No answer from {} to {}", device.uid, name)),
+            Some(response) => match response.port_or_response {
+                RESPONSE_TYPE_ACK | RESPONSE_TYPE_ACK_OVERFLOW => Ok(response),
+                RESPONSE_TYPE_NACK_REASON => {
+                    let reason = match response.data[..] {
+                        [high, low, ..] => nack_reason(u16::from_be_bytes([high, low])),
+                        _ => "no reason given",
+                    };
+                    Err(format!("{} refused {}: {}", device.uid, name, reason))
+                }
+                RESPONSE_TYPE_ACK_TIMER => Err(format!(
+                    "{} is busy and will answer {} later",
+                    device.uid, name
+                )),
+                other => Err(format!(
+                    "{} answered {} with response type {}",
+                    device.uid, name, other
+                )),
+            },
+        }
+    }
+
+    /// Wait for the answer to a request until the response timeout
+    async fn wait_response(&self, key: (RdmUid, u8)) -> Option<RdmMessage> {
+        let deadline = tokio::time::Instant::now() + RDM_RESPONSE_TIMEOUT;
+        loop {
+            // Created before checking, so an answer arriving in between still wakes it
+            let notified = self.responded.notified();
+            if matches!(self.waiting.lock().get(&key), Some(Some(_))) {
+                break;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                break;
+            }
+        }
+        self.waiting.lock().remove(&key).flatten()
+    }
+
+    pub fn clear(&self) {
+        self.ports.lock().clear();
+    }
+}
+
+impl Default for RdmDeviceTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Store a parameter from an acknowledged GET response on the device
+fn apply_parameter(device: &mut RdmDevice, pid: u16, data: &[u8]) {
+    match pid {
+        PID_DEVICE_INFO if data.len() >= 19 => {
+            // Protocol version (0-1), model ID (2-3), product category (4-5),
+            // software version ID (6-9), footprint (10-11), current and total
+            // personalities (12, 13), start address (14-15)
+            device.model_id = Some(u16::from_be_bytes([data[2], data[3]]));
+            device.footprint = Some(u16::from_be_bytes([data[10], data[11]]));
+            device.personality = Some(data[12]);
+            device.personality_count = Some(data[13]);
+            device.dmx_address = dmx_address(data[14], data[15]);
+        }
+        PID_DMX_START_ADDRESS if data.len() >= 2 => {
+            device.dmx_address = dmx_address(data[0], data[1]);
+        }
+        PID_DMX_PERSONALITY if data.len() >= 2 => {
+            device.personality = Some(data[0]);
+            device.personality_count = Some(data[1]);
+        }
+        PID_IDENTIFY_DEVICE if !data.is_empty() => device.identify = Some(data[0] == 1),
+        PID_MANUFACTURER_LABEL => device.manufacturer = text(data),
+        PID_DEVICE_MODEL_DESCRIPTION => device.model = text(data),
+        PID_SOFTWARE_VERSION_LABEL => device.software_version = text(data),
+        PID_DEVICE_LABEL => device.label = text(data),
+        _ => return,
+    }
+    device.last_response = Some(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+    );
+}
+
+/// Status messages of a STATUS_MESSAGES response, 9 bytes each: sub-device,
+/// status type, message ID and two data values
+fn status_messages(device: &RdmDevice, data: &[u8]) -> Vec<RdmStatusMessage> {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    data.chunks_exact(9)
+        .filter_map(|entry| {
+            let status_type = match entry[2] {
+                STATUS_ADVISORY => RdmStatusType::Advisory,
+                STATUS_WARNING => RdmStatusType::Warning,
+                STATUS_ERROR => RdmStatusType::Error,
+                STATUS_ADVISORY_CLEARED => RdmStatusType::AdvisoryCleared,
+                STATUS_WARNING_CLEARED => RdmStatusType::WarningCleared,
+                STATUS_ERROR_CLEARED => RdmStatusType::ErrorCleared,
+                _ => return None,
+            };
+            let message_id = u16::from_be_bytes([entry[3], entry[4]]);
+            let data_value1 = i16::from_be_bytes([entry[5], entry[6]]);
+            let data_value2 = i16::from_be_bytes([entry[7], entry[8]]);
+            Some(RdmStatusMessage {
+                source_id: device.source_id.clone(),
+                uid: device.uid.clone(),
+                universe: device.universe,
+                sub_device: u16::from_be_bytes([entry[0], entry[1]]),
+                status_type,
+                message_id,
+                data_value1,
+                data_value2,
+                timestamp,
+                message: status_message_text(message_id, data_value1, data_value2),
+            })
+        })
+        .collect()
+}
+
+/// DMX start address, 0xFFFF meaning the device has no footprint
+fn dmx_address(high: u8, low: u8) -> Option<u16> {
+    let address = u16::from_be_bytes([high, low]);
+    (address != 0xFFFF).then_some(address)
+}
+
+fn text(data: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(data)
+        .trim_matches(char::from(0))
+        .trim()
+        .to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Send an Art-Net packet to a node's Art-Net port. Its answers go to port
+/// 6454 of this machine, where the listener picks them up.
+fn send_artnet(ip: IpAddr, packet: &[u8]) -> Result<(), String> {
+    let socket = UdpSocket::bind(SocketAddr::new(std::net::Ipv4Addr::UNSPECIFIED.into(), 0))
+        .map_err(|e| format!("Failed to create socket: {}", e))?;
+    socket
+        .send_to(packet, SocketAddr::new(ip, ARTNET_PORT))
+        .map(|_| ())
+        .map_err(|e| format!("Failed to send to {}: {}", ip, e))
+}
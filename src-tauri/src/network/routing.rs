@@ -0,0 +1,454 @@
+// Routing - Retransmit and remap received universes within or between Art-Net and sACN
+//
+// The router subscribes to the listener event bus and re-emits DMX frames that
+// match a route, turning the monitor into an emergency protocol converter or a
+// universe remapper (e.g. sACN universe 10 -> universe 110 unicast to a node).
+
+use crate::network::artnet::{create_artdmx_packet, ARTNET_PORT};
+use crate::network::bus::EventReceiver;
+use crate::network::listener::{DmxData, ListenerEvent, ListenerStateHandle};
+use crate::network::sacn::{
+    create_sacn_data_packet, sacn_multicast_address, SacnSource, SACN_PORT,
+};
+use crate::network::source::{Protocol, RECEIVE_ONLY_PROTOCOL};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+fn default_route_priority() -> u8 {
+    100
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// User-defined route between protocols
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteConfig {
+    pub input_protocol: Protocol,
+    pub output_protocol: Protocol,
+    /// First input universe handled by this route
+    pub universe_start: u16,
+    /// Last input universe handled by this route (inclusive)
+    pub universe_end: u16,
+    /// Added to the input universe to get the output universe (e.g. +1 for Art-Net 0 -> sACN 1)
+    #[serde(default)]
+    pub universe_offset: i32,
+    /// sACN priority for routed output
+    #[serde(default = "default_route_priority")]
+    pub priority: u8,
+    /// Unicast destination IP (Art-Net broadcast / sACN multicast if not set)
+    #[serde(default)]
+    pub destination: Option<String>,
+    /// Cap on output frame rate per universe, for legacy fixtures that choke on 44 fps
+    #[serde(default)]
+    pub max_fps: Option<f32>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl RouteConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.universe_start > self.universe_end {
+            return Err("Route universe range start is after its end".to_string());
+        }
+        if self.priority > 200 {
+            return Err(format!(
+                "sACN priority {} out of range (0-200)",
+                self.priority
+            ));
+        }
+        if let Some(max_fps) = self.max_fps {
+            if !(max_fps > 0.0 && max_fps <= 44.0) {
+                return Err("Frame rate cap must be between 0 and 44 fps".to_string());
+            }
+        }
+        if self.output_protocol == Protocol::Other {
+            return Err(RECEIVE_ONLY_PROTOCOL.to_string());
+        }
+        let unicast = self.unicast_destination()?;
+        if self.input_protocol == self.output_protocol
+            && self.universe_offset == 0
+            && unicast.is_none()
+        {
+            return Err("Route would retransmit onto its own input universes".to_string());
+        }
+        // Both ends of the range must map to a valid output universe
+        for universe in [self.universe_start, self.universe_end] {
+            if self.output_universe(universe).is_none() {
+                return Err(format!(
+                    "Input universe {} maps outside the valid {:?} range",
+                    universe, self.output_protocol
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn unicast_destination(&self) -> Result<Option<IpAddr>, String> {
+        match &self.destination {
+            Some(dest) if !dest.trim().is_empty() => dest
+                .trim()
+                .parse::<IpAddr>()
+                .map(Some)
+                .map_err(|_| format!("Invalid destination address: {}", dest)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Destination for an output universe
+    pub fn destination_addr(&self, universe: u16) -> Result<SocketAddr, String> {
+        let unicast = self.unicast_destination().ok().flatten();
+        Ok(match self.output_protocol {
+            Protocol::ArtNet => SocketAddr::new(
+                unicast.unwrap_or(IpAddr::V4(Ipv4Addr::BROADCAST)),
+                ARTNET_PORT,
+            ),
+            Protocol::Sacn => SocketAddr::new(
+                unicast.unwrap_or(IpAddr::V4(sacn_multicast_address(universe))),
+                SACN_PORT,
+            ),
+            Protocol::Other => return Err(RECEIVE_ONLY_PROTOCOL.to_string()),
+        })
+    }
+
+    /// Minimum time between output frames when rate limited
+    pub fn min_frame_interval(&self) -> Option<Duration> {
+        self.max_fps.map(|fps| Duration::from_secs_f32(1.0 / fps))
+    }
+
+    /// Whether a received frame is handled by this route
+    pub fn matches(&self, protocol: Protocol, universe: u16) -> bool {
+        self.enabled
+            && self.input_protocol == protocol
+            && (self.universe_start..=self.universe_end).contains(&universe)
+    }
+
+    /// Map an input universe to the output universe, if valid for the output protocol
+    pub fn output_universe(&self, universe: u16) -> Option<u16> {
+        let mapped = universe as i32 + self.universe_offset;
+        let valid = match self.output_protocol {
+            Protocol::ArtNet => 0..=0x7FFF,
+            Protocol::Sacn => 1..=63999,
+            Protocol::Other => return None,
+        };
+        if valid.contains(&mapped) {
+            Some(mapped as u16)
+        } else {
+            None
+        }
+    }
+}
+
+/// Per-route statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouteStats {
+    pub frames_in: u64,
+    pub frames_out: u64,
+    /// Frames dropped by the rate limit (a newer frame replaced them before sending)
+    pub frames_suppressed: u64,
+    pub errors: u64,
+    pub last_error: Option<String>,
+    pub last_routed: Option<u64>, // Unix timestamp ms
+}
+
+/// A route with its assigned id and statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Route {
+    pub id: u32,
+    pub config: RouteConfig,
+    pub stats: RouteStats,
+}
+
+/// Internal route tracking
+struct RouteEntry {
+    config: RouteConfig,
+    stats: RouteStats,
+}
+
+/// Route table
+pub struct RoutingManager {
+    routes: RwLock<BTreeMap<u32, RouteEntry>>,
+    next_id: Mutex<u32>,
+    cid: [u8; 16],
+}
+
+impl RoutingManager {
+    pub fn new() -> Self {
+        Self {
+            routes: RwLock::new(BTreeMap::new()),
+            next_id: Mutex::new(1),
+            cid: *uuid::Uuid::new_v4().as_bytes(),
+        }
+    }
+
+    pub fn add_route(&self, config: RouteConfig) -> Result<u32, String> {
+        config.validate()?;
+        let id = {
+            let mut next_id = self.next_id.lock();
+            let id = *next_id;
+            *next_id += 1;
+            id
+        };
+        self.routes.write().insert(
+            id,
+            RouteEntry {
+                config,
+                stats: RouteStats::default(),
+            },
+        );
+        Ok(id)
+    }
+
+    pub fn update_route(&self, id: u32, config: RouteConfig) -> Result<(), String> {
+        config.validate()?;
+        match self.routes.write().get_mut(&id) {
+            Some(route) => {
+                route.config = config;
+                Ok(())
+            }
+            None => Err(format!("Route {} not found", id)),
+        }
+    }
+
+    pub fn remove_route(&self, id: u32) -> Result<(), String> {
+        match self.routes.write().remove(&id) {
+            Some(_) => Ok(()),
+            None => Err(format!("Route {} not found", id)),
+        }
+    }
+
+    pub fn get_routes(&self) -> Vec<Route> {
+        self.routes
+            .read()
+            .iter()
+            .map(|(&id, entry)| Route {
+                id,
+                config: entry.config.clone(),
+                stats: entry.stats.clone(),
+            })
+            .collect()
+    }
+
+    /// Reset the statistics of every route
+    pub fn reset_stats(&self) {
+        for entry in self.routes.write().values_mut() {
+            entry.stats = RouteStats::default();
+        }
+    }
+
+    /// Routes matching a received frame, counting the frame as input for each
+    fn matching_routes(&self, protocol: Protocol, universe: u16) -> Vec<(u32, RouteConfig)> {
+        let mut routes = self.routes.write();
+        routes
+            .iter_mut()
+            .filter(|(_, r)| r.config.matches(protocol, universe))
+            .map(|(&id, r)| {
+                r.stats.frames_in += 1;
+                (id, r.config.clone())
+            })
+            .collect()
+    }
+
+    /// Record a frame dropped by a route's rate limit
+    fn record_suppressed(&self, id: u32) {
+        if let Some(entry) = self.routes.write().get_mut(&id) {
+            entry.stats.frames_suppressed += 1;
+        }
+    }
+
+    /// Record the outcome of sending a routed frame
+    fn record_send(&self, id: u32, result: Result<(), String>) {
+        if let Some(entry) = self.routes.write().get_mut(&id) {
+            match result {
+                Ok(()) => {
+                    entry.stats.frames_out += 1;
+                    entry.stats.last_routed = Some(
+                        std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_millis() as u64,
+                    );
+                }
+                Err(e) => {
+                    entry.stats.errors += 1;
+                    entry.stats.last_error = Some(e);
+                }
+            }
+        }
+    }
+}
+
+impl Default for RoutingManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type RoutingManagerHandle = Arc<RoutingManager>;
+
+/// How often rate-limited routes flush their held frames
+const RATE_LIMIT_FLUSH_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Run the router, retransmitting DMX events that match a route
+pub async fn start_router(
+    routing: RoutingManagerHandle,
+    listener_state: ListenerStateHandle,
+    mut event_rx: EventReceiver,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let socket = UdpSocket::bind(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0)).await?;
+    socket.set_broadcast(true)?;
+
+    // Our own output loops back to the listeners; they recognise it by local
+    // address + our socket's port and keep it out of the DMX store and the
+    // sources. The sniffer still sees it, so routed frames are skipped here too
+    // and never routed again.
+    let own_port = socket.local_addr()?.port();
+    listener_state.ignore_local_sender(own_port);
+    let local_ips: HashSet<IpAddr> = local_ip_address::list_afinet_netifas()
+        .map(|ifaces| ifaces.into_iter().map(|(_, ip)| ip).collect())
+        .unwrap_or_default();
+
+    // Per output stream (route id, output universe): sequence number, last send
+    // time, and the newest frame held back by a rate limit
+    let mut sequences: HashMap<(u32, u16), u8> = HashMap::new();
+    let mut last_sent: HashMap<(u32, u16), Instant> = HashMap::new();
+    let mut pending: HashMap<(u32, u16), (RouteConfig, DmxData)> = HashMap::new();
+
+    let mut flush_interval = tokio::time::interval(RATE_LIMIT_FLUSH_INTERVAL);
+    flush_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    info!("[Routing] Router started");
+
+    loop {
+        tokio::select! {
+            event = event_rx.recv() => {
+                let data = match event {
+                    Ok(ListenerEvent::DmxData(data)) => data,
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("[Routing] Router lagged {} messages", n);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                if data.source_port == own_port
+                    && (data.source_ip.is_loopback() || local_ips.contains(&data.source_ip))
+                {
+                    continue;
+                }
+
+                for (id, route) in routing.matching_routes(data.protocol, data.universe) {
+                    let Some(universe) = route.output_universe(data.universe) else {
+                        continue;
+                    };
+                    let key = (id, universe);
+
+                    // Hold frames that arrive faster than the route's cap; only the
+                    // newest held frame is sent, older ones count as suppressed
+                    if let Some(interval) = route.min_frame_interval() {
+                        let due = last_sent
+                            .get(&key)
+                            .is_none_or(|t| t.elapsed() >= interval);
+                        if !due {
+                            if pending.insert(key, (route, data.clone())).is_some() {
+                                routing.record_suppressed(id);
+                            }
+                            continue;
+                        }
+                    }
+
+                    pending.remove(&key);
+                    last_sent.insert(key, Instant::now());
+                    let sequence = sequences.entry(key).or_insert(0);
+                    send_routed(&socket, &routing, id, &route, universe, sequence, &data).await;
+                }
+            }
+            _ = flush_interval.tick() => {
+                let due: Vec<(u32, u16)> = pending
+                    .iter()
+                    .filter(|(key, (route, _))| {
+                        route
+                            .min_frame_interval()
+                            .is_none_or(|i| last_sent.get(key).is_none_or(|t| t.elapsed() >= i))
+                    })
+                    .map(|(key, _)| *key)
+                    .collect();
+
+                for key in due {
+                    if let Some((route, data)) = pending.remove(&key) {
+                        last_sent.insert(key, Instant::now());
+                        let sequence = sequences.entry(key).or_insert(0);
+                        send_routed(&socket, &routing, key.0, &route, key.1, sequence, &data).await;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Send a routed frame and record the outcome on the route
+async fn send_routed(
+    socket: &UdpSocket,
+    routing: &RoutingManager,
+    id: u32,
+    route: &RouteConfig,
+    universe: u16,
+    sequence: &mut u8,
+    data: &DmxData,
+) {
+    let result = match route.destination_addr(universe) {
+        Ok(dest) => match build_routed_packet(routing, route, universe, sequence, data) {
+            Ok(packet) => socket
+                .send_to(&packet, dest)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("Send to {} failed: {}", dest, e)),
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    };
+    routing.record_send(id, result);
+}
+
+/// Build the output packet for a routed frame
+fn build_routed_packet(
+    routing: &RoutingManager,
+    route: &RouteConfig,
+    universe: u16,
+    sequence: &mut u8,
+    data: &DmxData,
+) -> Result<Vec<u8>, String> {
+    Ok(match route.output_protocol {
+        Protocol::ArtNet => {
+            // Sequence runs 1-255, 0 is reserved for "sequencing disabled"
+            *sequence = if *sequence == 255 { 1 } else { *sequence + 1 };
+            create_artdmx_packet(universe, *sequence, &data.data)
+        }
+        Protocol::Sacn => {
+            *sequence = sequence.wrapping_add(1);
+            let source = SacnSource {
+                cid: routing.cid,
+                source_name: "LXMonitor Bridge".to_string(),
+                priority: route.priority,
+                sync_address: 0,
+                sequence: *sequence,
+                options: 0,
+                universe,
+            };
+            create_sacn_data_packet(&source, 0x00, &data.data)
+        }
+        Protocol::Other => return Err(RECEIVE_ONLY_PROTOCOL.to_string()),
+    })
+}
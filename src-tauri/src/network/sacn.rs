@@ -1,6 +1,7 @@
 // sACN (E1.31) Protocol Implementation
 // ANSI E1.31 - 2018 Streaming ACN Protocol
 
+use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
@@ -64,7 +65,7 @@ impl Default for SacnSource {
 pub struct SacnDmx {
     pub source: SacnSource,
     pub start_code: u8,
-    pub data: Vec<u8>,
+    pub data: Bytes, // Slice of the received datagram
 }
 
 /// Parsed sACN Universe Discovery packet
@@ -79,13 +80,26 @@ pub struct SacnDiscovery {
 #[derive(Debug, Clone)]
 pub enum SacnPacket {
     Dmx(SacnDmx),
+    AlternateStartCode(SacnDmx),
     Sync { sync_address: u16 },
     Discovery(SacnDiscovery),
     Unknown,
 }
 
-/// Parse an sACN packet from raw bytes
-pub fn parse_sacn_packet(data: &[u8], _source: SocketAddr) -> Option<SacnPacket> {
+impl SacnPacket {
+    /// CID of the sending component, when the packet carries one
+    pub fn cid(&self) -> Option<&[u8; 16]> {
+        match self {
+            SacnPacket::Dmx(dmx) | SacnPacket::AlternateStartCode(dmx) => Some(&dmx.source.cid),
+            SacnPacket::Discovery(discovery) => Some(&discovery.cid),
+            SacnPacket::Sync { .. } | SacnPacket::Unknown => None,
+        }
+    }
+}
+
+/// Parse an sACN packet from raw bytes. DMX data is sliced out of the
+/// datagram buffer, not copied.
+pub fn parse_sacn_packet(data: &Bytes, _source: SocketAddr) -> Option<SacnPacket> {
     // Minimum packet size for root layer
     if data.len() < 38 {
         return None;
@@ -127,7 +141,7 @@ pub fn parse_sacn_packet(data: &[u8], _source: SocketAddr) -> Option<SacnPacket>
 }
 
 /// Parse sACN data packet (contains DMX or sync)
-fn parse_data_packet(data: &[u8], cid: [u8; 16]) -> Option<SacnPacket> {
+fn parse_data_packet(data: &Bytes, cid: [u8; 16]) -> Option<SacnPacket> {
     // Minimum size for framing layer
     if data.len() < 115 {
         return None;
@@ -184,23 +198,16 @@ fn parse_data_packet(data: &[u8], cid: [u8; 16]) -> Option<SacnPacket> {
     let property_count = u16::from_be_bytes([data[123], data[124]]) as usize;
 
     // Start code (byte 125)
-    // Only process packets with start code 0 (standard DMX512 data)
-    // Non-zero start codes indicate alternative data types (e.g., RDM, text packets)
-    // Ignoring non-zero start codes fixes flashing issues with ETC Ion consoles
+    // Only start code 0 is standard DMX512 level data. Non-zero start codes carry
+    // alternative data (e.g. 0xDD per-address priority, 0x17 text, 0xCF SIP) and
+    // must not be merged into the level data - doing so caused flashing with ETC Ion consoles
     let start_code = data[125];
-    if start_code != 0 {
-        println!(
-            "[sACN DEBUG] Ignoring packet with non-zero start code: {} (priority: {}, universe: {})",
-            start_code, priority, universe
-        );
-        return Some(SacnPacket::Unknown);
-    }
 
     // DMX data starts at byte 126
     let dmx_length = (property_count.saturating_sub(1))
         .min(512)
         .min(data.len() - 126);
-    let dmx_data = data[126..126 + dmx_length].to_vec();
+    let dmx_data = data.slice(126..126 + dmx_length);
 
     let source = SacnSource {
         cid,
@@ -212,17 +219,23 @@ fn parse_data_packet(data: &[u8], cid: [u8; 16]) -> Option<SacnPacket> {
         universe,
     };
 
-    Some(SacnPacket::Dmx(SacnDmx {
+    let dmx = SacnDmx {
         source,
         start_code,
         data: dmx_data,
-    }))
+    };
+
+    if start_code != 0 {
+        Some(SacnPacket::AlternateStartCode(dmx))
+    } else {
+        Some(SacnPacket::Dmx(dmx))
+    }
 }
 
 /// Parse sACN extended packet (contains discovery)
 fn parse_extended_packet(data: &[u8], cid: [u8; 16]) -> Option<SacnPacket> {
-    // Extended packets contain universe discovery
-    if data.len() < 120 {
+    // Extended packets contain synchronization or universe discovery
+    if data.len() < 44 {
         return None;
     }
 
@@ -230,6 +243,19 @@ fn parse_extended_packet(data: &[u8], cid: [u8; 16]) -> Option<SacnPacket> {
     // Framing vector (bytes 40-43)
     let framing_vector = u32::from_be_bytes([data[40], data[41], data[42], data[43]]);
 
+    if framing_vector == FRAMING_VECTOR_SYNC {
+        // Sequence (byte 44), sync address (bytes 45-46), reserved (bytes 47-48)
+        if data.len() < 49 {
+            return None;
+        }
+        let sync_address = u16::from_be_bytes([data[45], data[46]]);
+        return Some(SacnPacket::Sync { sync_address });
+    }
+
+    if data.len() < 120 {
+        return None;
+    }
+
     if framing_vector != 0x00000002 {
         // Not a discovery packet
         return Some(SacnPacket::Unknown);
@@ -269,6 +295,83 @@ fn parse_extended_packet(data: &[u8], cid: [u8; 16]) -> Option<SacnPacket> {
     }))
 }
 
+/// Create an E1.31 data packet for a source/universe
+/// Uses the source's CID, name, priority, sync address, sequence, options and universe
+pub fn create_sacn_data_packet(source: &SacnSource, start_code: u8, data: &[u8]) -> Vec<u8> {
+    let slot_count = data.len().min(512);
+    let total_len = 126 + slot_count;
+    let mut packet = Vec::with_capacity(total_len);
+
+    // Root layer - preamble size, post-amble size, ACN packet identifier
+    packet.extend_from_slice(&0x0010u16.to_be_bytes());
+    packet.extend_from_slice(&0x0000u16.to_be_bytes());
+    packet.extend_from_slice(ACN_PACKET_IDENTIFIER);
+    packet.extend_from_slice(&flags_and_length(total_len - 16));
+    packet.extend_from_slice(&(RootVector::Data as u32).to_be_bytes());
+    packet.extend_from_slice(&source.cid);
+
+    // Framing layer
+    packet.extend_from_slice(&flags_and_length(total_len - 38));
+    packet.extend_from_slice(&FRAMING_VECTOR_DMP.to_be_bytes());
+    let mut name = [0u8; 64];
+    let name_bytes = source.source_name.as_bytes();
+    let name_len = name_bytes.len().min(63); // Always leave a null terminator
+    name[..name_len].copy_from_slice(&name_bytes[..name_len]);
+    packet.extend_from_slice(&name);
+    packet.push(source.priority);
+    packet.extend_from_slice(&source.sync_address.to_be_bytes());
+    packet.push(source.sequence);
+    packet.push(source.options);
+    packet.extend_from_slice(&source.universe.to_be_bytes());
+
+    // DMP layer - SET_PROPERTY, address & data type 0xa1, first address 0, increment 1
+    packet.extend_from_slice(&flags_and_length(total_len - 115));
+    packet.push(0x02);
+    packet.push(0xa1);
+    packet.extend_from_slice(&0x0000u16.to_be_bytes());
+    packet.extend_from_slice(&0x0001u16.to_be_bytes());
+    packet.extend_from_slice(&((slot_count + 1) as u16).to_be_bytes());
+    packet.push(start_code);
+    packet.extend_from_slice(&data[..slot_count]);
+
+    packet
+}
+
+/// Create an E1.31 synchronization packet for a sync address
+pub fn create_sacn_sync_packet(cid: &[u8; 16], sequence: u8, sync_address: u16) -> Vec<u8> {
+    let total_len = 49;
+    let mut packet = Vec::with_capacity(total_len);
+
+    // Root layer - preamble size, post-amble size, ACN packet identifier
+    packet.extend_from_slice(&0x0010u16.to_be_bytes());
+    packet.extend_from_slice(&0x0000u16.to_be_bytes());
+    packet.extend_from_slice(ACN_PACKET_IDENTIFIER);
+    packet.extend_from_slice(&flags_and_length(total_len - 16));
+    packet.extend_from_slice(&(RootVector::Extended as u32).to_be_bytes());
+    packet.extend_from_slice(cid);
+
+    // Framing layer - sequence, sync address, two reserved bytes
+    packet.extend_from_slice(&flags_and_length(total_len - 38));
+    packet.extend_from_slice(&FRAMING_VECTOR_SYNC.to_be_bytes());
+    packet.push(sequence);
+    packet.extend_from_slice(&sync_address.to_be_bytes());
+    packet.extend_from_slice(&[0, 0]);
+
+    packet
+}
+
+/// Encode an ACN PDU flags (0x7) and length field
+fn flags_and_length(length: usize) -> [u8; 2] {
+    (0x7000 | (length as u16 & 0x0FFF)).to_be_bytes()
+}
+
+/// Parse a UUID string (with or without dashes) into a CID
+pub fn parse_cid(value: &str) -> Option<[u8; 16]> {
+    uuid::Uuid::parse_str(value.trim())
+        .ok()
+        .map(|u| *u.as_bytes())
+}
+
 /// Extract null-terminated UTF-8 string from bytes
 fn extract_string(data: &[u8]) -> String {
     let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
@@ -1,38 +1,64 @@
 // Sniffer module - Promiscuous mode packet capture for Art-Net and sACN
 //
-// This module provides optional packet capture functionality using pcap/Npcap
-// to see traffic destined for other IPs on the network (requires port mirroring).
+// This module provides optional packet capture functionality using pcap to see
+// traffic destined for other IPs on the network (requires port mirroring).
+// Capture runs on Npcap on Windows and on libpcap (AF_PACKET on Linux, BPF on
+// macOS) elsewhere.
 //
-// The sniffer feature requires the Npcap SDK to be installed for building.
-// When the feature is disabled, stub implementations are provided.
+// The sniffer feature requires the Npcap SDK (Windows) or the libpcap headers
+// to be installed for building. When the feature is disabled, stub
+// implementations are provided.
+
+use crate::network::bandwidth::BandwidthTable;
+use crate::network::inspector::PacketInspector;
+use crate::network::listener::ListenerEvent;
+use crate::network::lldp::SwitchNeighborTable;
+use crate::network::storm::StormDetector;
 
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use std::sync::Arc;
 
 #[cfg(feature = "sniffer")]
 use crate::network::artnet::{parse_artnet_packet, ARTNET_PORT};
 #[cfg(feature = "sniffer")]
-use crate::network::listener::{DmxData, DmxStoreHandle, ListenerEvent};
+use crate::network::bus::EventBusHandle;
+#[cfg(feature = "sniffer")]
+use crate::network::listener::{DmxData, DmxStoreHandle};
+#[cfg(feature = "sniffer")]
+use crate::network::lldp::{
+    parse_cdp, parse_lldp, DiscoveryProtocol, NeighborAnnouncement, CDP_SNAP_HEADER, ETHERTYPE_LLDP,
+};
 #[cfg(feature = "sniffer")]
 use crate::network::sacn::{parse_sacn_packet, SACN_PORT};
 #[cfg(feature = "sniffer")]
-use crate::network::source::{SourceDirection, SourceManagerHandle};
+use crate::network::source::{mac_to_string, Protocol, SourceDirection, SourceManagerHandle};
+#[cfg(feature = "sniffer")]
+use crate::network::storm::StormTraffic;
 
 #[cfg(feature = "sniffer")]
-use pcap::{Capture, Device};
+use bytes::Bytes;
+#[cfg(feature = "sniffer")]
+use pcap::{Activated, Capture, Device, Linktype, Packet, PacketHeader, Savefile};
+#[cfg(feature = "sniffer")]
+use std::collections::VecDeque;
+#[cfg(feature = "sniffer")]
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 #[cfg(feature = "sniffer")]
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
 #[cfg(feature = "sniffer")]
-use tokio::sync::broadcast;
+use std::time::{Duration, Instant};
+#[cfg(feature = "sniffer")]
+use tracing::{info, warn};
 
 // Re-export types needed by lib.rs even without feature
 #[cfg(not(feature = "sniffer"))]
-use crate::network::listener::{DmxStoreHandle, ListenerEvent};
+use crate::network::bus::EventBusHandle;
 #[cfg(not(feature = "sniffer"))]
-use crate::network::source::SourceManagerHandle;
+use crate::network::listener::DmxStoreHandle;
 #[cfg(not(feature = "sniffer"))]
-use tokio::sync::broadcast;
+use crate::network::source::SourceManagerHandle;
 
 /// Capture interface info for the frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,43 +67,321 @@ pub struct CaptureInterface {
     pub description: Option<String>,
 }
 
-/// Sniffer status
+/// Packet capture support on this platform
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureSupport {
+    pub available: bool,
+    pub library: String,         // Npcap on Windows, libpcap elsewhere
+    pub message: Option<String>, // What to install or change when capture is unavailable
+    pub download_url: Option<String>,
+}
+
+/// Capture library sniffer mode runs on
+#[cfg(windows)]
+pub const CAPTURE_LIBRARY: &str = "Npcap";
+#[cfg(not(windows))]
+pub const CAPTURE_LIBRARY: &str = "libpcap";
+
+/// What the user has to do to make packet capture work on this platform
+pub fn capture_setup_hint() -> &'static str {
+    if !cfg!(feature = "sniffer") {
+        "Sniffer support is not compiled in. Rebuild with --features sniffer"
+    } else if cfg!(windows) {
+        "Install Npcap from https://npcap.com/ and restart LXMonitor"
+    } else if cfg!(target_os = "macos") {
+        "Capture needs read access to /dev/bpf*. Install Wireshark's ChmodBPF helper or run LXMonitor as root"
+    } else {
+        "Install libpcap, then run LXMonitor as root or grant it capture rights with \
+         sudo setcap cap_net_raw,cap_net_admin=eip <path to lxmonitor>"
+    }
+}
+
+pub fn capture_support() -> CaptureSupport {
+    let available = is_capture_available();
+    CaptureSupport {
+        available,
+        library: CAPTURE_LIBRARY.to_string(),
+        message: (!available).then(|| capture_setup_hint().to_string()),
+        download_url: cfg!(windows).then(|| "https://npcap.com/".to_string()),
+    }
+}
+
+/// Sniffer status. The top-level counters and error sum up all interfaces.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SnifferStatus {
     pub enabled: bool,
-    pub interface: Option<String>,
-    pub npcap_available: bool,
+    pub interface: Option<String>, // First capture interface
+    pub interfaces: Vec<InterfaceCaptureStatus>,
+    pub capture_available: bool,
     pub packets_captured: u64,
     pub error: Option<String>,
+    pub user_filter: Option<String>, // Applied on top of the Art-Net/sACN filter
+    pub all_traffic: bool,           // Capturing everything, not just Art-Net/sACN
+    pub armed: bool,                 // Recording waits for its trigger
+    pub triggered: Option<TriggerEvent>,
 }
 
-/// Sniffer state
-pub struct SnifferState {
-    pub enabled: Mutex<bool>,
-    pub interface: Mutex<Option<String>>,
+/// Capture status of one interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterfaceCaptureStatus {
+    pub interface: String,
+    pub running: bool,
+    pub packets_captured: u64,
+    pub error: Option<String>,
+    pub restarts: u32, // Automatic restarts after capture errors
+    pub recording: Option<RecordingStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnifferEventKind {
+    Started,
+    Error,
+    Restarting,
+    Stopped,
+}
+
+/// Capture starting, failing, restarting or stopping on an interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnifferEvent {
+    pub interface: String,
+    pub kind: SnifferEventKind,
+    pub message: Option<String>,
+    pub timestamp: u64, // Unix timestamp ms
+}
+
+/// Raw capture file settings. Without a size or age limit everything goes to
+/// `path`; with one, numbered files are written next to it instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureRecording {
+    pub path: String,               // e.g. /home/me/show.pcap
+    pub max_file_mb: Option<u64>,   // Start a new file at this size
+    pub max_file_secs: Option<u64>, // Start a new file after this long
+    pub max_files: Option<usize>,   // Delete the oldest files beyond this count
+    #[serde(default)]
+    pub trigger: Option<CaptureTrigger>, // Only start saving once this happens
+    #[serde(default)]
+    pub pre_trigger_packets: Option<usize>, // Frames before the trigger to keep, 1000 by default
+}
+
+/// Frames held back for a triggered recording when no count is given
+#[cfg(feature = "sniffer")]
+const DEFAULT_PRE_TRIGGER_PACKETS: usize = 1000;
+const MAX_PRE_TRIGGER_PACKETS: usize = 100_000;
+
+/// Event that starts a triggered recording, for catching intermittent faults
+/// without recording the whole night
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CaptureTrigger {
+    /// Any source's packet loss goes above the percentage
+    PacketLoss { percent: f32 },
+    /// DMX for the universe is captured
+    Universe { universe: u16 },
+    /// A channel (1-512) of the universe goes above the value
+    ChannelAbove {
+        universe: u16,
+        channel: u16,
+        value: u8,
+    },
+}
+
+/// When and why the recording trigger fired
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerEvent {
+    pub timestamp: u64, // Unix timestamp ms
+    pub reason: String,
+}
+
+#[cfg(feature = "sniffer")]
+impl CaptureRecording {
+    fn rotates(&self) -> bool {
+        self.max_file_mb.is_some() || self.max_file_secs.is_some()
+    }
+}
+
+/// Progress of the raw capture file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordingStatus {
+    pub current_file: Option<String>,
+    pub files: Vec<String>, // Files written this session and still kept, oldest first
+    pub packets_written: u64,
+    pub bytes_written: u64,
+}
+
+/// Capture running on one interface
+pub struct InterfaceCapture {
+    pub name: String,
+    pub running: Mutex<bool>,
     pub packets_captured: Mutex<u64>,
     pub error: Mutex<Option<String>>,
+    pub restarts: Mutex<u32>,
+    pub recording_status: Mutex<Option<RecordingStatus>>,
+    pub thread: Mutex<Option<std::thread::JoinHandle<()>>>, // Capture thread, joined on stop
+}
+
+impl InterfaceCapture {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            running: Mutex::new(true),
+            packets_captured: Mutex::new(0),
+            error: Mutex::new(None),
+            restarts: Mutex::new(0),
+            recording_status: Mutex::new(None),
+            thread: Mutex::new(None),
+        }
+    }
+
+    fn get_status(&self) -> InterfaceCaptureStatus {
+        InterfaceCaptureStatus {
+            interface: self.name.clone(),
+            running: *self.running.lock(),
+            packets_captured: *self.packets_captured.lock(),
+            error: self.error.lock().clone(),
+            restarts: *self.restarts.lock(),
+            recording: self.recording_status.lock().clone(),
+        }
+    }
+}
+
+/// Sniffer state. Settings and the inspector, bandwidth and storm tables are
+/// shared by the captures on all interfaces.
+pub struct SnifferState {
+    pub captures: Mutex<Vec<Arc<InterfaceCapture>>>,
     pub stop_flag: Mutex<bool>,
+    pub user_filter: Mutex<Option<String>>,
+    pub all_traffic: Mutex<bool>,
+    pub recording: Mutex<Option<CaptureRecording>>,
+    pub triggered: Mutex<Option<TriggerEvent>>,
+    pub inspector: PacketInspector,    // Most recent captured frames
+    pub bandwidth: BandwidthTable,     // Traffic per sending device
+    pub storm: StormDetector,          // Broadcast and multicast rates
+    pub switches: SwitchNeighborTable, // LLDP and CDP neighbours
 }
 
 impl SnifferState {
     pub fn new() -> Self {
         Self {
-            enabled: Mutex::new(false),
-            interface: Mutex::new(None),
-            packets_captured: Mutex::new(0),
-            error: Mutex::new(None),
+            captures: Mutex::new(Vec::new()),
             stop_flag: Mutex::new(false),
+            user_filter: Mutex::new(None),
+            all_traffic: Mutex::new(false),
+            recording: Mutex::new(None),
+            triggered: Mutex::new(None),
+            inspector: PacketInspector::new(),
+            bandwidth: BandwidthTable::new(),
+            storm: StormDetector::new(),
+            switches: SwitchNeighborTable::new(),
         }
     }
 
+    /// Whether a capture is still running on any interface. A capture whose
+    /// thread ended without clearing its flag (a panic) doesn't count.
+    pub fn is_running(&self) -> bool {
+        self.captures.lock().iter().any(|c| {
+            *c.running.lock() && !c.thread.lock().as_ref().is_some_and(|t| t.is_finished())
+        })
+    }
+
     pub fn get_status(&self) -> SnifferStatus {
+        let interfaces: Vec<InterfaceCaptureStatus> = self
+            .captures
+            .lock()
+            .iter()
+            .map(|c| c.get_status())
+            .collect();
         SnifferStatus {
-            enabled: *self.enabled.lock(),
-            interface: self.interface.lock().clone(),
-            npcap_available: is_npcap_available(),
-            packets_captured: *self.packets_captured.lock(),
-            error: self.error.lock().clone(),
+            enabled: interfaces.iter().any(|i| i.running),
+            interface: interfaces.first().map(|i| i.interface.clone()),
+            capture_available: is_capture_available(),
+            packets_captured: interfaces.iter().map(|i| i.packets_captured).sum(),
+            error: interfaces
+                .iter()
+                .find_map(|i| i.error.as_ref().map(|e| format!("{}: {}", i.interface, e))),
+            user_filter: self.user_filter.lock().clone(),
+            all_traffic: *self.all_traffic.lock(),
+            armed: self
+                .recording
+                .lock()
+                .as_ref()
+                .is_some_and(|r| r.trigger.is_some())
+                && self.triggered.lock().is_none(),
+            triggered: self.triggered.lock().clone(),
+            interfaces,
+        }
+    }
+
+    #[cfg(feature = "sniffer")]
+    fn fire_trigger(&self, reason: String) {
+        let mut triggered = self.triggered.lock();
+        if triggered.is_none() {
+            info!("[Sniffer] Recording triggered: {}", reason);
+            *triggered = Some(TriggerEvent {
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64,
+                reason,
+            });
+        }
+    }
+
+    /// Fire a universe or channel trigger on a captured DMX frame
+    #[cfg(feature = "sniffer")]
+    fn check_dmx_trigger(&self, universe: u16, data: &[u8]) {
+        if self.triggered.lock().is_some() {
+            return;
+        }
+        let trigger = match self.recording.lock().as_ref() {
+            Some(recording) => recording.trigger.clone(),
+            None => return,
+        };
+        match trigger {
+            Some(CaptureTrigger::Universe { universe: wanted }) if wanted == universe => {
+                self.fire_trigger(format!("DMX captured on universe {}", universe));
+            }
+            Some(CaptureTrigger::ChannelAbove {
+                universe: wanted,
+                channel,
+                value,
+            }) if wanted == universe => {
+                let level = data.get(channel as usize - 1).copied().unwrap_or(0);
+                if level > value {
+                    self.fire_trigger(format!(
+                        "Universe {} channel {} at {} (above {})",
+                        universe, channel, level, value
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Fire a packet loss trigger from the sources' current loss
+    #[cfg(feature = "sniffer")]
+    fn check_loss_trigger(&self, source_manager: &SourceManagerHandle) {
+        if self.triggered.lock().is_some() {
+            return;
+        }
+        let percent = match self
+            .recording
+            .lock()
+            .as_ref()
+            .and_then(|r| r.trigger.clone())
+        {
+            Some(CaptureTrigger::PacketLoss { percent }) => percent,
+            _ => return,
+        };
+        if let Some(source) = source_manager
+            .get_all_sources(true)
+            .into_iter()
+            .find(|s| s.packet_loss_percent > percent)
+        {
+            self.fire_trigger(format!(
+                "{:.1}% packet loss from {} ({})",
+                source.packet_loss_percent, source.name, source.ip
+            ));
         }
     }
 }
@@ -90,15 +394,132 @@ impl Default for SnifferState {
 
 pub type SnifferStateHandle = Arc<SnifferState>;
 
+/// Check capture file settings before capture starts
+pub fn validate_capture_recording(recording: &CaptureRecording) -> Result<(), String> {
+    let path = Path::new(&recording.path);
+    if recording.path.trim().is_empty() || path.file_name().is_none() {
+        return Err("Capture file path must name a file".to_string());
+    }
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        if !dir.is_dir() {
+            return Err(format!("Folder does not exist: {}", dir.display()));
+        }
+    }
+    if recording.max_file_mb == Some(0) {
+        return Err("Capture file size limit must be at least 1 MB".to_string());
+    }
+    if recording.max_file_secs == Some(0) {
+        return Err("Capture file time limit must be at least 1 second".to_string());
+    }
+    if recording.max_files == Some(0) {
+        return Err("At least one capture file must be kept".to_string());
+    }
+    if recording
+        .pre_trigger_packets
+        .is_some_and(|n| n > MAX_PRE_TRIGGER_PACKETS)
+    {
+        return Err(format!(
+            "At most {} frames can be kept from before the trigger",
+            MAX_PRE_TRIGGER_PACKETS
+        ));
+    }
+    match recording.trigger {
+        Some(CaptureTrigger::PacketLoss { percent }) if !(0.0..100.0).contains(&percent) => {
+            return Err("Packet loss trigger must be between 0 and 100%".to_string());
+        }
+        Some(CaptureTrigger::ChannelAbove { channel, .. }) if !(1..=512).contains(&channel) => {
+            return Err("Trigger channel must be between 1 and 512".to_string());
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 // ============================================================================
 // With sniffer feature enabled
 // ============================================================================
 
 #[cfg(feature = "sniffer")]
-pub fn is_npcap_available() -> bool {
+pub fn is_capture_available() -> bool {
     Device::list().is_ok()
 }
 
+/// Why a capture session ended, and whether restarting it may help
+#[cfg(feature = "sniffer")]
+struct CaptureFailure {
+    message: String,
+    retry: bool,
+}
+
+#[cfg(feature = "sniffer")]
+impl CaptureFailure {
+    /// A setting problem that a restart won't fix
+    fn fatal(message: String) -> Self {
+        Self {
+            message,
+            retry: false,
+        }
+    }
+
+    /// An interface or driver problem that may clear up, e.g. a USB adapter
+    /// unplugged or a link going down
+    fn transient(message: String) -> Self {
+        Self {
+            message,
+            retry: true,
+        }
+    }
+}
+
+/// Failure of a capture step. Permission problems get the platform's setup
+/// hint, the usual cause on Linux and macOS, and aren't retried.
+#[cfg(feature = "sniffer")]
+fn capture_error(step: &str, e: pcap::Error) -> CaptureFailure {
+    let message = format!("{}: {}", step, e);
+    let lower = message.to_lowercase();
+    if lower.contains("permission") || lower.contains("not permitted") {
+        CaptureFailure::fatal(format!("{}. {}", message, capture_setup_hint()))
+    } else {
+        CaptureFailure::transient(message)
+    }
+}
+
+/// First wait before restarting a failed capture, doubled per failure
+#[cfg(feature = "sniffer")]
+const RESTART_BACKOFF_MIN: Duration = Duration::from_secs(1);
+#[cfg(feature = "sniffer")]
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(30);
+/// A capture that ran this long before failing starts the backoff over
+#[cfg(feature = "sniffer")]
+const RESTART_BACKOFF_RESET: Duration = Duration::from_secs(60);
+
+fn send_sniffer_event(
+    event_tx: &EventBusHandle,
+    capture: &InterfaceCapture,
+    kind: SnifferEventKind,
+    message: Option<String>,
+) {
+    let _ = event_tx.send(ListenerEvent::Sniffer(SnifferEvent {
+        interface: capture.name.clone(),
+        kind,
+        message,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+    }));
+}
+
+/// Raw capture file state, kept across restarts so a restarted capture
+/// carries on with the same files
+#[cfg(feature = "sniffer")]
+struct SessionRecording {
+    armed: Option<CaptureRecording>, // Triggered recording not yet started
+    recorder: Option<CaptureRecorder>,
+    pre_trigger: VecDeque<(PacketHeader, Vec<u8>)>,
+    pre_trigger_len: usize,
+}
+
 #[cfg(feature = "sniffer")]
 pub fn list_capture_interfaces() -> Vec<CaptureInterface> {
     match Device::list() {
@@ -113,91 +534,363 @@ pub fn list_capture_interfaces() -> Vec<CaptureInterface> {
     }
 }
 
+/// How long each interface is watched when picking one automatically
+#[cfg(feature = "sniffer")]
+const INTERFACE_SAMPLE_TIME: Duration = Duration::from_millis(1500);
+
+/// Pick the interface to capture on when the user didn't choose one. Every
+/// interface is sampled at once and the one carrying the most Art-Net/sACN
+/// wins, since the first interface is often a virtual adapter on Windows.
+/// Without any lighting traffic the first interface with an IPv4 address is
+/// used.
+#[cfg(feature = "sniffer")]
+pub fn select_capture_interface() -> Option<String> {
+    let devices = Device::list().ok()?;
+    let fallback = devices
+        .iter()
+        .find(|d| d.addresses.iter().any(|a| a.addr.is_ipv4()))
+        .or(devices.first())
+        .map(|d| d.name.clone());
+
+    let samplers: Vec<_> = devices
+        .into_iter()
+        .map(|device| {
+            let name = device.name.clone();
+            (
+                name,
+                std::thread::spawn(move || count_lighting_packets(device)),
+            )
+        })
+        .collect();
+    let busiest = samplers
+        .into_iter()
+        .map(|(name, sampler)| (name, sampler.join().unwrap_or(0)))
+        .filter(|(_, packets)| *packets > 0)
+        .max_by_key(|(_, packets)| *packets);
+
+    match busiest {
+        Some((name, packets)) => {
+            info!(
+                "[Sniffer] Selected interface {} ({} lighting packets sampled)",
+                name, packets
+            );
+            Some(name)
+        }
+        None => fallback,
+    }
+}
+
+/// Count the Art-Net/sACN frames an interface sees during the sample time,
+/// 0 when it can't be captured on
+#[cfg(feature = "sniffer")]
+fn count_lighting_packets(device: Device) -> usize {
+    let cap = match Capture::from_device(device) {
+        Ok(c) => c.promisc(true).snaplen(1500).timeout(100).open(),
+        Err(_) => return 0,
+    };
+    let mut cap = match cap {
+        Ok(c) => c,
+        Err(_) => return 0,
+    };
+    if !is_supported_linktype(cap.get_datalink())
+        || cap.filter(&capture_filter(None, false), true).is_err()
+    {
+        return 0;
+    }
+
+    let start = Instant::now();
+    let mut packets = 0;
+    let linktype = cap.get_datalink();
+    while start.elapsed() < INTERFACE_SAMPLE_TIME {
+        match cap.next_packet() {
+            // The filter also lets ARP through
+            Ok(packet) if parse_udp_frame(linktype, packet.data).is_some() => packets += 1,
+            Ok(_) => {}
+            Err(pcap::Error::TimeoutExpired) => {}
+            Err(_) => break,
+        }
+    }
+    packets
+}
+
+/// Capture on one interface until stopped. Capture errors are reported as
+/// events and the capture is restarted with backoff, unless the error comes
+/// from a setting a restart can't fix.
 #[cfg(feature = "sniffer")]
 pub fn start_sniffer_blocking(
-    interface_name: &str,
+    capture: Arc<InterfaceCapture>,
     source_manager: SourceManagerHandle,
     dmx_store: DmxStoreHandle,
-    event_tx: broadcast::Sender<ListenerEvent>,
+    event_tx: EventBusHandle,
     sniffer_state: SnifferStateHandle,
 ) {
-    // Find the device
-    let devices = match Device::list() {
-        Ok(d) => d,
-        Err(e) => {
-            *sniffer_state.error.lock() = Some(format!("Failed to list devices: {}", e));
-            return;
-        }
+    // A triggered recording stays armed, holding back the newest frames so the
+    // file shows what led up to the trigger
+    let armed = sniffer_state.recording.lock().clone();
+    let mut recording = SessionRecording {
+        pre_trigger_len: armed
+            .as_ref()
+            .filter(|r| r.trigger.is_some())
+            .map_or(0, |r| {
+                r.pre_trigger_packets.unwrap_or(DEFAULT_PRE_TRIGGER_PACKETS)
+            }),
+        armed,
+        recorder: None,
+        pre_trigger: VecDeque::new(),
     };
+    let mut backoff = RESTART_BACKOFF_MIN;
 
-    let device = match devices.into_iter().find(|d| d.name == interface_name) {
-        Some(d) => d,
-        None => {
-            *sniffer_state.error.lock() = Some(format!("Interface not found: {}", interface_name));
-            return;
+    loop {
+        let started = Instant::now();
+        let failure = match run_capture(
+            &capture,
+            &source_manager,
+            &dmx_store,
+            &event_tx,
+            &sniffer_state,
+            &mut recording,
+        ) {
+            Ok(()) => break,
+            Err(failure) => failure,
+        };
+        warn!("[Sniffer] {} on {}", failure.message, capture.name);
+        *capture.error.lock() = Some(failure.message.clone());
+        send_sniffer_event(
+            &event_tx,
+            &capture,
+            SnifferEventKind::Error,
+            Some(failure.message),
+        );
+        if !failure.retry {
+            break;
         }
-    };
 
-    // Open the capture
-    let mut cap = match Capture::from_device(device) {
-        Ok(c) => c,
-        Err(e) => {
-            *sniffer_state.error.lock() = Some(format!("Failed to open device: {}", e));
-            return;
+        // Wait out the backoff, still answering the stop flag
+        if started.elapsed() >= RESTART_BACKOFF_RESET {
+            backoff = RESTART_BACKOFF_MIN;
         }
-    };
+        let restart_at = Instant::now() + backoff;
+        while Instant::now() < restart_at && !*sniffer_state.stop_flag.lock() {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        if *sniffer_state.stop_flag.lock() {
+            break;
+        }
+        *capture.restarts.lock() += 1;
+        send_sniffer_event(
+            &event_tx,
+            &capture,
+            SnifferEventKind::Restarting,
+            Some(format!("Restarting after {}s", backoff.as_secs())),
+        );
+        backoff = (backoff * 2).min(RESTART_BACKOFF_MAX);
+    }
+
+    if let Some(mut rec) = recording.recorder.take() {
+        rec.finish(&capture);
+    }
+    *capture.running.lock() = false;
+    send_sniffer_event(&event_tx, &capture, SnifferEventKind::Stopped, None);
+}
+
+/// One capture session, from opening the interface until the stop flag is
+/// set (Ok) or capture fails
+#[cfg(feature = "sniffer")]
+fn run_capture(
+    capture: &InterfaceCapture,
+    source_manager: &SourceManagerHandle,
+    dmx_store: &DmxStoreHandle,
+    event_tx: &EventBusHandle,
+    sniffer_state: &SnifferStateHandle,
+    recording: &mut SessionRecording,
+) -> Result<(), CaptureFailure> {
+    let interface_name = capture.name.as_str();
+    let inspector_tag: Arc<str> = Arc::from(interface_name);
+
+    // Find the device
+    let devices = Device::list().map_err(|e| capture_error("Failed to list devices", e))?;
+
+    let device = devices
+        .into_iter()
+        .find(|d| d.name == interface_name)
+        .ok_or_else(|| {
+            CaptureFailure::transient(format!("Interface not found: {}", interface_name))
+        })?;
+
+    // DMX and traffic are tagged with the interface's IPv4 address, when it has one
+    let capture_ip = device.addresses.iter().find_map(|a| match a.addr {
+        IpAddr::V4(ip) => Some(ip),
+        IpAddr::V6(_) => None,
+    });
+
+    // Open the capture
+    let cap =
+        Capture::from_device(device).map_err(|e| capture_error("Failed to open device", e))?;
 
     // Configure capture
     let cap = cap.promisc(true).snaplen(1500).timeout(100); // 100ms timeout for checking stop flag
 
-    let mut cap = match cap.open() {
-        Ok(c) => c,
-        Err(e) => {
-            *sniffer_state.error.lock() = Some(format!("Failed to start capture: {}", e));
-            return;
-        }
-    };
+    let mut cap = cap
+        .open()
+        .map_err(|e| capture_error("Failed to start capture", e))?;
 
-    // Set BPF filter for Art-Net and sACN ports
-    let filter = format!("udp port {} or udp port {}", ARTNET_PORT, SACN_PORT);
+    // Set BPF filter for Art-Net and sACN ports, narrowed by the user's filter
+    let filter = capture_filter(
+        sniffer_state.user_filter.lock().as_deref(),
+        *sniffer_state.all_traffic.lock(),
+    );
     if let Err(e) = cap.filter(&filter, true) {
-        *sniffer_state.error.lock() = Some(format!("Failed to set filter: {}", e));
-        return;
+        return Err(CaptureFailure::fatal(format!(
+            "Failed to set filter: {}",
+            e
+        )));
+    }
+
+    // Linux's "any" device and loopback interfaces don't deliver Ethernet frames
+    let linktype = cap.get_datalink();
+    if !is_supported_linktype(linktype) {
+        return Err(CaptureFailure::fatal(format!(
+            "Unsupported link type {:?} on interface {}",
+            linktype, interface_name
+        )));
+    }
+
+    if let Some(config) = recording.armed.take_if(|r| r.trigger.is_none()) {
+        let rec = CaptureRecorder::open(&cap, config, capture, sniffer_state)
+            .map_err(CaptureFailure::fatal)?;
+        recording.recorder = Some(rec);
     }
+    let mut last_loss_check = Instant::now();
 
-    println!(
-        "[Sniffer] Started on interface {} with filter: {}",
-        interface_name, filter
+    info!(
+        "[Sniffer] Started on interface {} ({:?}) with filter: {}",
+        interface_name, linktype, filter
+    );
+    *capture.error.lock() = None;
+    send_sniffer_event(
+        event_tx,
+        capture,
+        SnifferEventKind::Started,
+        Some(format!("Filter: {}", filter)),
     );
-    *sniffer_state.error.lock() = None;
 
     // Capture loop
     loop {
         // Check stop flag
         if *sniffer_state.stop_flag.lock() {
-            println!("[Sniffer] Stopped by user");
-            break;
+            info!("[Sniffer] Stopped by user");
+            return Ok(());
+        }
+
+        sniffer_state.bandwidth.roll_if_due();
+
+        if recording.armed.is_some() && last_loss_check.elapsed() >= Duration::from_secs(1) {
+            last_loss_check = Instant::now();
+            sniffer_state.check_loss_trigger(source_manager);
+        }
+
+        // Open an armed recording once its trigger fired, starting with the held frames
+        if recording.armed.is_some() && sniffer_state.triggered.lock().is_some() {
+            if let Some(config) = recording.armed.take() {
+                match CaptureRecorder::open(&cap, config, capture, sniffer_state) {
+                    Ok(mut rec) => {
+                        for (header, data) in recording.pre_trigger.drain(..) {
+                            rec.write(&Packet::new(&header, &data), capture);
+                        }
+                        recording.recorder = Some(rec);
+                    }
+                    Err(e) => {
+                        warn!("[Sniffer] {}", e);
+                        *capture.error.lock() = Some(e);
+                    }
+                }
+            }
+        }
+
+        // Rotate between packets, the savefile needs the capture handle
+        if let Some(rec) = recording.recorder.as_mut() {
+            if let Err(e) = rec.rotate_if_due(&cap, capture) {
+                warn!("[Sniffer] {}", e);
+                *capture.error.lock() = Some(e);
+                recording.recorder = None;
+            }
         }
 
         // Try to get next packet
         match cap.next_packet() {
             Ok(packet) => {
                 // Increment packet count
-                *sniffer_state.packets_captured.lock() += 1;
+                *capture.packets_captured.lock() += 1;
+
+                if let Some(rec) = recording.recorder.as_mut() {
+                    rec.write(&packet, capture);
+                } else if recording.armed.is_some() && recording.pre_trigger_len > 0 {
+                    if recording.pre_trigger.len() == recording.pre_trigger_len {
+                        recording.pre_trigger.pop_front();
+                    }
+                    recording
+                        .pre_trigger
+                        .push_back((*packet.header, packet.data.to_vec()));
+                }
+                let captured_at =
+                    packet.header.ts.tv_sec as u64 * 1000 + packet.header.ts.tv_usec as u64 / 1000;
+                sniffer_state
+                    .inspector
+                    .push(&inspector_tag, linktype.0, captured_at, packet.data);
+
+                let (src_mac, src_ip, class) = classify_frame(linktype, packet.data);
+                let lighting = class == "Art-Net" || class == "sACN";
+                sniffer_state.bandwidth.record(
+                    src_mac,
+                    src_ip,
+                    &class,
+                    packet.header.len as usize,
+                    lighting,
+                );
+                if let Some(traffic) = frame_cast(linktype, packet.data) {
+                    let sender = src_ip
+                        .map(|ip| ip.to_string())
+                        .or_else(|| src_mac.as_ref().map(mac_to_string));
+                    sniffer_state.storm.record(traffic, sender);
+                }
+                if let Some((mac, ip)) = arp_sender(linktype, packet.data) {
+                    source_manager.record_arp(IpAddr::V4(ip), &mac);
+                }
+                if let Some((protocol, announcement)) = discovery_frame(linktype, packet.data) {
+                    sniffer_state
+                        .switches
+                        .record(interface_name, protocol, announcement);
+                }
 
                 // Parse the packet - we need to extract IP header info
-                if let Some((src_ip, dst_ip, src_port, dst_port, payload)) =
-                    parse_ip_udp_packet(packet.data)
+                if let Some(UdpFrame {
+                    src_mac,
+                    vlans,
+                    src_ip,
+                    dst_ip,
+                    src_port,
+                    dst_port,
+                    ttl,
+                    payload,
+                }) = parse_udp_frame(linktype, packet.data)
                 {
-                    let src_addr = SocketAddr::new(IpAddr::V4(src_ip), src_port);
-                    let dst_addr = SocketAddr::new(IpAddr::V4(dst_ip), dst_port);
+                    let src_addr = SocketAddr::new(src_ip, src_port);
+                    let dst_addr = SocketAddr::new(dst_ip, dst_port);
 
                     // Determine direction based on which port matches
                     let is_artnet = src_port == ARTNET_PORT || dst_port == ARTNET_PORT;
                     let is_sacn = src_port == SACN_PORT || dst_port == SACN_PORT;
 
                     if is_artnet {
-                        if let Some(packet) = parse_artnet_packet(payload, src_addr) {
+                        if let Some(packet) =
+                            parse_artnet_packet(&Bytes::copy_from_slice(payload), src_addr)
+                        {
+                            source_manager.record_traffic(
+                                src_addr.ip(),
+                                Protocol::ArtNet,
+                                payload.len(),
+                                capture_ip,
+                            );
                             match packet {
                                 crate::network::artnet::ArtNetPacket::Dmx(dmx) => {
                                     // Source is sending
@@ -212,9 +905,7 @@ pub fn start_sniffer_blocking(
                                     );
 
                                     // Destination is receiving (if not broadcast)
-                                    if !dst_ip.is_broadcast()
-                                        && dst_ip != Ipv4Addr::new(255, 255, 255, 255)
-                                    {
+                                    if !is_broadcast(dst_ip) {
                                         source_manager.update_artnet_source_with_direction(
                                             dst_addr.ip(),
                                             "",
@@ -227,17 +918,23 @@ pub fn start_sniffer_blocking(
                                     }
 
                                     // Store DMX data
-                                    dmx_store.update(dmx.universe, dmx.data.clone());
+                                    let changed =
+                                        dmx_store.update(dmx.universe, src_addr.ip(), &dmx.data);
+                                    sniffer_state.check_dmx_trigger(dmx.universe, &dmx.data);
 
                                     let _ = event_tx.send(ListenerEvent::DmxData(DmxData {
                                         universe: dmx.universe,
                                         data: dmx.data,
+                                        protocol: Protocol::ArtNet,
                                         source_ip: src_addr.ip(),
+                                        source_port: src_port,
                                         timestamp: std::time::SystemTime::now()
                                             .duration_since(std::time::UNIX_EPOCH)
                                             .unwrap_or_default()
                                             .as_millis()
                                             as u64,
+                                        interface: capture_ip,
+                                        changed,
                                     }));
                                 }
                                 crate::network::artnet::ArtNetPacket::PollReply(reply) => {
@@ -270,29 +967,52 @@ pub fn start_sniffer_blocking(
                                         SourceDirection::Receiving,
                                         None, // No sequence for PollReply
                                     );
+                                    source_manager.check_poll_reply_ip(ip, src_addr.ip());
 
                                     let _ = event_tx.send(ListenerEvent::SourcesUpdated);
                                 }
+                                crate::network::artnet::ArtNetPacket::Nzs(nzs) => {
+                                    dmx_store.update_alternate(
+                                        nzs.universe,
+                                        nzs.start_code,
+                                        src_addr.ip(),
+                                        Protocol::ArtNet,
+                                        nzs.data,
+                                    );
+                                }
                                 _ => {}
                             }
                         }
                     } else if is_sacn {
-                        if let Some(packet) = parse_sacn_packet(payload, src_addr) {
+                        if let Some(packet) =
+                            parse_sacn_packet(&Bytes::copy_from_slice(payload), src_addr)
+                        {
+                            source_manager.record_traffic(
+                                src_addr.ip(),
+                                Protocol::Sacn,
+                                payload.len(),
+                                capture_ip,
+                            );
                             match packet {
                                 crate::network::sacn::SacnPacket::Dmx(dmx) => {
                                     // Source is sending
-                                    source_manager.update_sacn_source_with_direction(
-                                        src_addr.ip(),
-                                        &dmx.source.source_name,
-                                        &dmx.source.cid,
-                                        dmx.source.priority,
-                                        dmx.source.universe,
-                                        SourceDirection::Sending,
-                                        Some(dmx.source.sequence),
-                                    );
+                                    if let Some(change) = source_manager
+                                        .update_sacn_source_with_direction(
+                                            src_addr.ip(),
+                                            &dmx.source.source_name,
+                                            &dmx.source.cid,
+                                            dmx.source.priority,
+                                            dmx.source.universe,
+                                            SourceDirection::Sending,
+                                            Some(dmx.source.sequence),
+                                        )
+                                    {
+                                        let _ =
+                                            event_tx.send(ListenerEvent::PriorityChanged(change));
+                                    }
 
                                     // For unicast sACN, mark destination as receiving
-                                    if !dst_ip.is_multicast() && !dst_ip.is_broadcast() {
+                                    if !dst_ip.is_multicast() && !is_broadcast(dst_ip) {
                                         source_manager.update_sacn_source_with_direction(
                                             dst_addr.ip(),
                                             "",
@@ -304,87 +1024,769 @@ pub fn start_sniffer_blocking(
                                         );
                                     }
 
-                                    dmx_store.update(dmx.source.universe, dmx.data.clone());
+                                    let changed = dmx_store.update(
+                                        dmx.source.universe,
+                                        src_addr.ip(),
+                                        &dmx.data,
+                                    );
+                                    sniffer_state.check_dmx_trigger(dmx.source.universe, &dmx.data);
 
                                     let _ = event_tx.send(ListenerEvent::DmxData(DmxData {
                                         universe: dmx.source.universe,
                                         data: dmx.data,
+                                        protocol: Protocol::Sacn,
                                         source_ip: src_addr.ip(),
+                                        source_port: src_port,
                                         timestamp: std::time::SystemTime::now()
                                             .duration_since(std::time::UNIX_EPOCH)
                                             .unwrap_or_default()
                                             .as_millis()
                                             as u64,
+                                        interface: capture_ip,
+                                        changed,
                                     }));
                                 }
+                                crate::network::sacn::SacnPacket::AlternateStartCode(dmx) => {
+                                    dmx_store.update_alternate(
+                                        dmx.source.universe,
+                                        dmx.start_code,
+                                        src_addr.ip(),
+                                        Protocol::Sacn,
+                                        dmx.data,
+                                    );
+                                }
                                 _ => {}
                             }
                         }
                     }
+
+                    // Done after the updates above so newly seen sources get it too
+                    if is_artnet || is_sacn {
+                        if let Some(src_mac) = src_mac {
+                            source_manager.record_link_mac(src_addr.ip(), &src_mac);
+                        }
+                        source_manager.record_vlan(src_addr.ip(), vlans.vlan, vlans.outer_vlan);
+                        source_manager.record_ttl(src_addr.ip(), ttl);
+                        if let Some(port) = sniffer_state.switches.switch_port_on(interface_name) {
+                            source_manager.record_switch_port(src_addr.ip(), port);
+                        }
+                    }
                 }
             }
             Err(pcap::Error::TimeoutExpired) => {
                 continue;
             }
             Err(e) => {
-                eprintln!("[Sniffer] Capture error: {}", e);
-                *sniffer_state.error.lock() = Some(format!("Capture error: {}", e));
-                break;
+                return Err(CaptureFailure::transient(format!("Capture error: {}", e)));
+            }
+        }
+    }
+}
+
+/// pcap file header and per-packet record header sizes
+#[cfg(feature = "sniffer")]
+const PCAP_FILE_HEADER_LEN: u64 = 24;
+#[cfg(feature = "sniffer")]
+const PCAP_RECORD_HEADER_LEN: u64 = 16;
+
+/// Writes the raw captured frames to pcap files, rotating by size and age
+#[cfg(feature = "sniffer")]
+struct CaptureRecorder {
+    config: CaptureRecording,
+    tag: Option<String>, // Interface name in file names when capturing several
+    savefile: Savefile,
+    opened: Instant,
+    file_bytes: u64,
+    file_count: u32,
+    files: VecDeque<PathBuf>,
+}
+
+#[cfg(feature = "sniffer")]
+impl CaptureRecorder {
+    fn open<T: Activated + ?Sized>(
+        cap: &Capture<T>,
+        config: CaptureRecording,
+        capture: &InterfaceCapture,
+        sniffer_state: &SnifferState,
+    ) -> Result<Self, String> {
+        let tag = (sniffer_state.captures.lock().len() > 1).then(|| {
+            capture
+                .name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect::<String>()
+        });
+        let path = Self::file_path(&config, tag.as_deref(), 1);
+        let savefile = Self::create(cap, &path)?;
+        let mut recorder = Self {
+            config,
+            tag,
+            savefile,
+            opened: Instant::now(),
+            file_bytes: PCAP_FILE_HEADER_LEN,
+            file_count: 1,
+            files: VecDeque::new(),
+        };
+        *capture.recording_status.lock() = Some(RecordingStatus::default());
+        recorder.started(path, capture);
+        Ok(recorder)
+    }
+
+    fn create<T: Activated + ?Sized>(cap: &Capture<T>, path: &Path) -> Result<Savefile, String> {
+        cap.savefile(path)
+            .map_err(|e| format!("Failed to create capture file {}: {}", path.display(), e))
+    }
+
+    /// The configured path, or a numbered file beside it when rotating. The
+    /// start time keeps names from earlier sessions from being overwritten, the
+    /// tag keeps the files of simultaneous captures apart.
+    fn file_path(config: &CaptureRecording, tag: Option<&str>, count: u32) -> PathBuf {
+        let path = PathBuf::from(&config.path);
+        if !config.rotates() && tag.is_none() {
+            return path;
+        }
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "capture".to_string());
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "pcap".to_string());
+        let started = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let stem = match tag {
+            Some(tag) => format!("{}-{}", stem, tag),
+            None => stem,
+        };
+        if !config.rotates() {
+            return path.with_file_name(format!("{}.{}", stem, extension));
+        }
+        path.with_file_name(format!("{}-{}-{:03}.{}", stem, started, count, extension))
+    }
+
+    /// Track a newly opened file, deleting the oldest ones beyond max_files
+    fn started(&mut self, path: PathBuf, capture: &InterfaceCapture) {
+        info!("[Sniffer] Recording to {}", path.display());
+        self.files.push_back(path);
+        if let Some(max_files) = self.config.max_files {
+            while self.files.len() > max_files {
+                if let Some(old) = self.files.pop_front() {
+                    if let Err(e) = std::fs::remove_file(&old) {
+                        warn!("[Sniffer] Failed to delete {}: {}", old.display(), e);
+                    }
+                }
             }
         }
+
+        if let Some(status) = capture.recording_status.lock().as_mut() {
+            status.current_file = self.files.back().map(|p| p.display().to_string());
+            status.files = self.files.iter().map(|p| p.display().to_string()).collect();
+        }
+    }
+
+    fn rotate_if_due<T: Activated + ?Sized>(
+        &mut self,
+        cap: &Capture<T>,
+        capture: &InterfaceCapture,
+    ) -> Result<(), String> {
+        let too_big = self
+            .config
+            .max_file_mb
+            .is_some_and(|mb| self.file_bytes >= mb * 1024 * 1024);
+        let too_old = self
+            .config
+            .max_file_secs
+            .is_some_and(|secs| self.opened.elapsed() >= Duration::from_secs(secs));
+        if !too_big && !too_old {
+            return Ok(());
+        }
+
+        let path = Self::file_path(&self.config, self.tag.as_deref(), self.file_count + 1);
+        let savefile = Self::create(cap, &path)?;
+        // Replacing the savefile closes the previous file
+        let _ = self.savefile.flush();
+        self.savefile = savefile;
+        self.opened = Instant::now();
+        self.file_bytes = PCAP_FILE_HEADER_LEN;
+        self.file_count += 1;
+        self.started(path, capture);
+        Ok(())
+    }
+
+    fn write(&mut self, packet: &Packet<'_>, capture: &InterfaceCapture) {
+        self.savefile.write(packet);
+        let bytes = PCAP_RECORD_HEADER_LEN + packet.header.caplen as u64;
+        self.file_bytes += bytes;
+        if let Some(status) = capture.recording_status.lock().as_mut() {
+            status.packets_written += 1;
+            status.bytes_written += bytes;
+        }
+    }
+
+    fn finish(&mut self, capture: &InterfaceCapture) {
+        if let Err(e) = self.savefile.flush() {
+            warn!("[Sniffer] Failed to flush capture file: {}", e);
+        }
+        if let Some(status) = capture.recording_status.lock().as_mut() {
+            status.current_file = None;
+        }
+    }
+}
+
+/// VLAN IDs of a tagged frame
+#[cfg(feature = "sniffer")]
+#[derive(Debug, Clone, Copy, Default)]
+struct VlanTags {
+    vlan: Option<u16>,       // Innermost tag, the VLAN the devices are on
+    outer_vlan: Option<u16>, // Service tag of a double-tagged (QinQ) frame
+}
+
+/// IP packet taken out of a captured frame, with what the link layer said about it
+#[cfg(feature = "sniffer")]
+struct LinkFrame<'a> {
+    src_mac: Option<[u8; 6]>, // None on link layers without MAC addresses
+    vlans: VlanTags,
+    ip_packet: &'a [u8],
+}
+
+/// UDP datagram extracted from a captured frame
+#[cfg(feature = "sniffer")]
+struct UdpFrame<'a> {
+    src_mac: Option<[u8; 6]>,
+    vlans: VlanTags,
+    src_ip: IpAddr,
+    dst_ip: IpAddr,
+    src_port: u16,
+    dst_port: u16,
+    ttl: u8, // IPv6 hop limit for IPv6
+    payload: &'a [u8],
+}
+
+#[cfg(feature = "sniffer")]
+const ETHERTYPE_IPV4: u16 = 0x0800;
+#[cfg(feature = "sniffer")]
+const ETHERTYPE_IPV6: u16 = 0x86DD;
+#[cfg(feature = "sniffer")]
+const ETHERTYPE_ARP: u16 = 0x0806;
+
+/// IP protocol number of UDP
+#[cfg(feature = "sniffer")]
+const IPPROTO_UDP: u8 = 17;
+
+/// EtherTypes of 802.1Q customer tags, 802.1ad service tags and legacy QinQ tags
+#[cfg(feature = "sniffer")]
+const VLAN_ETHERTYPES: [u16; 3] = [0x8100, 0x88A8, 0x9100];
+
+/// Capture filter for Art-Net and sACN, untagged or behind one or two VLAN tags.
+/// Each `vlan` moves the following checks past one tag. `udp port` only sees
+/// IPv6 UDP right after the fixed header, so IPv6 UDP behind extension headers
+/// is let through for the parser to check the ports. A user filter goes first,
+/// so its own `vlan` checks see the frame before the tags are skipped. With
+/// `all_traffic` only the user filter applies, empty meaning every frame.
+#[cfg(feature = "sniffer")]
+fn capture_filter(user_filter: Option<&str>, all_traffic: bool) -> String {
+    if all_traffic {
+        return user_filter.unwrap_or_default().to_string();
     }
+    // ARP is let through too, for the MAC addresses of the senders, and LLDP
+    // and CDP for the switch ports
+    let ports = format!(
+        "(arp or udp port {} or udp port {} or (ip6 protochain {} and not ip6 proto {}))",
+        ARTNET_PORT, SACN_PORT, IPPROTO_UDP, IPPROTO_UDP
+    );
+    let lighting = format!(
+        "{} or (vlan and ({} or (vlan and {}))) or ether proto 0x{:04x} or ether dst 01:00:0c:cc:cc:cc",
+        ports, ports, ports, ETHERTYPE_LLDP
+    );
+    match user_filter {
+        Some(user_filter) => format!("({}) and ({})", user_filter, lighting),
+        None => lighting,
+    }
+}
 
-    *sniffer_state.enabled.lock() = false;
+/// Check that a BPF expression compiles, so a typo is reported before capture starts
+#[cfg(feature = "sniffer")]
+pub fn validate_capture_filter(filter: &str) -> Result<(), String> {
+    let capture = Capture::dead(Linktype::ETHERNET)
+        .map_err(|e| format!("Failed to check capture filter: {}", e))?;
+    capture
+        .compile(&capture_filter(Some(filter), false), true)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid capture filter \"{}\": {}", filter, e))
+}
+
+/// Link types the sniffer can take IP packets from
+#[cfg(feature = "sniffer")]
+fn is_supported_linktype(linktype: Linktype) -> bool {
+    matches!(
+        linktype,
+        Linktype::ETHERNET
+            | Linktype::LINUX_SLL
+            | Linktype::LINUX_SLL2
+            | Linktype::NULL
+            | Linktype::LOOP
+            | Linktype::RAW
+            | Linktype::IPV4
+            | Linktype::IPV6
+    )
 }
 
+/// Follow an EtherType past up to two VLAN tags, `rest` being the bytes after
+/// it. Returns the IPv4 or IPv6 packet and the tags, or None for anything else.
 #[cfg(feature = "sniffer")]
-fn parse_ip_udp_packet(data: &[u8]) -> Option<(Ipv4Addr, Ipv4Addr, u16, u16, &[u8])> {
-    if data.len() < 42 {
+fn ip_after_ethertype(mut ethertype: u16, mut rest: &[u8]) -> Option<(&[u8], VlanTags)> {
+    let mut tags = Vec::with_capacity(2);
+    while VLAN_ETHERTYPES.contains(&ethertype) {
+        if tags.len() == 2 || rest.len() < 4 {
+            return None;
+        }
+        // Tag control information: priority, drop eligible, 12-bit VLAN ID
+        tags.push(u16::from_be_bytes([rest[0], rest[1]]) & 0x0FFF);
+        ethertype = u16::from_be_bytes([rest[2], rest[3]]);
+        rest = &rest[4..];
+    }
+    if ethertype != ETHERTYPE_IPV4 && ethertype != ETHERTYPE_IPV6 {
         return None;
     }
 
-    let eth_type = u16::from_be_bytes([data[12], data[13]]);
-    if eth_type != 0x0800 {
+    let vlans = VlanTags {
+        vlan: tags.last().copied(),
+        outer_vlan: if tags.len() == 2 { Some(tags[0]) } else { None },
+    };
+    Some((rest, vlans))
+}
+
+/// Split a captured frame into the sender's MAC address, when the link layer
+/// carries one, its VLAN tags and the IP packet inside it
+#[cfg(feature = "sniffer")]
+fn link_payload(linktype: Linktype, data: &[u8]) -> Option<LinkFrame<'_>> {
+    let u16_at = |at: usize| {
+        data.get(at..at + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+    };
+    let mac_at = |at: usize| {
+        let mut mac = [0u8; 6];
+        mac.copy_from_slice(data.get(at..at + 6)?);
+        Some(mac)
+    };
+    let untagged = |ip_packet| LinkFrame {
+        src_mac: None,
+        vlans: VlanTags::default(),
+        ip_packet,
+    };
+
+    match linktype {
+        Linktype::ETHERNET => {
+            let (ip_packet, vlans) = ip_after_ethertype(u16_at(12)?, &data[14..])?;
+            Some(LinkFrame {
+                src_mac: mac_at(6),
+                vlans,
+                ip_packet,
+            })
+        }
+        // Linux cooked capture (the "any" device): link address at 6, protocol at 14
+        Linktype::LINUX_SLL => {
+            let (ip_packet, vlans) = ip_after_ethertype(u16_at(14)?, &data[16..])?;
+            let src_mac = if u16_at(4)? == 6 { mac_at(6) } else { None };
+            Some(LinkFrame {
+                src_mac,
+                vlans,
+                ip_packet,
+            })
+        }
+        // Linux cooked capture v2: protocol at 0, link address length at 11
+        Linktype::LINUX_SLL2 => {
+            let (ip_packet, vlans) = ip_after_ethertype(u16_at(0)?, data.get(20..)?)?;
+            let src_mac = if data[11] == 6 { mac_at(12) } else { None };
+            Some(LinkFrame {
+                src_mac,
+                vlans,
+                ip_packet,
+            })
+        }
+        // BSD loopback: a 4-byte address family in host (NULL) or network (LOOP)
+        // order. AF_INET is 2 everywhere, AF_INET6 differs between systems.
+        Linktype::NULL | Linktype::LOOP => {
+            const AF_INET_VALUES: [u32; 5] = [2, 10, 24, 28, 30];
+            let family: [u8; 4] = data.get(..4)?.try_into().ok()?;
+            if !AF_INET_VALUES.contains(&u32::from_ne_bytes(family))
+                && !AF_INET_VALUES.contains(&u32::from_be_bytes(family))
+            {
+                return None;
+            }
+            Some(untagged(&data[4..]))
+        }
+        Linktype::RAW | Linktype::IPV4 | Linktype::IPV6 => Some(untagged(data)),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "sniffer")]
+fn parse_udp_frame(linktype: Linktype, data: &[u8]) -> Option<UdpFrame<'_>> {
+    parse_ip_udp_packet(link_payload(linktype, data)?)
+}
+
+#[cfg(feature = "sniffer")]
+fn parse_ip_udp_packet(link: LinkFrame<'_>) -> Option<UdpFrame<'_>> {
+    let (src_ip, dst_ip, udp) = match link.ip_packet.first()? >> 4 {
+        4 => ipv4_udp(link.ip_packet)?,
+        6 => ipv6_udp(link.ip_packet)?,
+        _ => return None,
+    };
+    if udp.len() < 8 {
         return None;
     }
+    // Both parsers above checked that the fixed header is there
+    let ttl = link.ip_packet[if src_ip.is_ipv4() { 8 } else { 7 }];
 
-    let ip_start = 14;
-    let ip_header = &data[ip_start..];
+    let src_port = u16::from_be_bytes([udp[0], udp[1]]);
+    let dst_port = u16::from_be_bytes([udp[2], udp[3]]);
+
+    Some(UdpFrame {
+        src_mac: link.src_mac,
+        vlans: link.vlans,
+        src_ip,
+        dst_ip,
+        src_port,
+        dst_port,
+        ttl,
+        payload: &udp[8..],
+    })
+}
 
-    let version = (ip_header[0] >> 4) & 0x0F;
-    if version != 4 {
+/// Addresses and UDP segment of an IPv4 packet
+#[cfg(feature = "sniffer")]
+fn ipv4_udp(data: &[u8]) -> Option<(IpAddr, IpAddr, &[u8])> {
+    if data.len() < 20 {
         return None;
     }
 
-    let ihl = (ip_header[0] & 0x0F) as usize * 4;
-    if ihl < 20 || ip_start + ihl > data.len() {
+    let ihl = (data[0] & 0x0F) as usize * 4;
+    if ihl < 20 || ihl > data.len() {
         return None;
     }
 
-    let protocol = ip_header[9];
-    if protocol != 17 {
+    if data[9] != IPPROTO_UDP {
         return None;
     }
 
-    let src_ip = Ipv4Addr::new(ip_header[12], ip_header[13], ip_header[14], ip_header[15]);
-    let dst_ip = Ipv4Addr::new(ip_header[16], ip_header[17], ip_header[18], ip_header[19]);
+    let src_ip = Ipv4Addr::new(data[12], data[13], data[14], data[15]);
+    let dst_ip = Ipv4Addr::new(data[16], data[17], data[18], data[19]);
+    Some((src_ip.into(), dst_ip.into(), &data[ihl..]))
+}
+
+/// Addresses and UDP segment of an IPv6 packet, skipping extension headers
+#[cfg(feature = "sniffer")]
+fn ipv6_udp(data: &[u8]) -> Option<(IpAddr, IpAddr, &[u8])> {
+    // More headers than this is not something a lighting device sends
+    const MAX_EXTENSION_HEADERS: usize = 8;
 
-    let udp_start = ip_start + ihl;
-    if udp_start + 8 > data.len() {
+    if data.len() < 40 {
         return None;
     }
 
-    let udp_header = &data[udp_start..];
-    let src_port = u16::from_be_bytes([udp_header[0], udp_header[1]]);
-    let dst_port = u16::from_be_bytes([udp_header[2], udp_header[3]]);
+    let src: [u8; 16] = data[8..24].try_into().ok()?;
+    let dst: [u8; 16] = data[24..40].try_into().ok()?;
+
+    let mut next_header = data[6];
+    let mut offset = 40;
+    for _ in 0..MAX_EXTENSION_HEADERS {
+        if next_header == IPPROTO_UDP {
+            break;
+        }
 
-    let payload_start = udp_start + 8;
-    if payload_start > data.len() {
+        let header = data.get(offset..offset + 8)?;
+        let length = match next_header {
+            // Hop-by-hop options, routing, destination options: length in
+            // 8-octet units, not counting the first 8 octets
+            0 | 43 | 60 => (header[1] as usize + 1) * 8,
+            // Fragment: only the first fragment carries the UDP header
+            44 => {
+                let fragment_offset = u16::from_be_bytes([header[2], header[3]]) >> 3;
+                if fragment_offset != 0 {
+                    return None;
+                }
+                8
+            }
+            // Authentication header: length in 4-octet units, minus 2
+            51 => (header[1] as usize + 2) * 4,
+            _ => return None,
+        };
+        next_header = header[0];
+        offset += length;
+    }
+    if next_header != IPPROTO_UDP {
         return None;
     }
 
-    let payload = &data[payload_start..];
-    Some((src_ip, dst_ip, src_port, dst_port, payload))
+    let src_ip = Ipv6Addr::from(src);
+    let dst_ip = Ipv6Addr::from(dst);
+    Some((src_ip.into(), dst_ip.into(), data.get(offset..)?))
+}
+
+/// Sender MAC and IP of any captured frame, lighting or not, with the protocol
+/// its traffic is counted under
+#[cfg(feature = "sniffer")]
+fn classify_frame(linktype: Linktype, data: &[u8]) -> (Option<[u8; 6]>, Option<IpAddr>, String) {
+    if let Some(link) = link_payload(linktype, data) {
+        let (src_ip, class) = classify_ip_packet(link.ip_packet);
+        return (link.src_mac, src_ip, class);
+    }
+
+    // Not IP: name the EtherType where the link layer has one
+    let (src_mac, ethertype) = ethertype_payload(linktype, data);
+    let Some((ethertype, _)) = ethertype else {
+        return (src_mac, None, "Other".to_string());
+    };
+    let class = match ethertype {
+        ETHERTYPE_ARP => "ARP".to_string(),
+        0x88CC => "LLDP".to_string(),
+        0x88F7 => "PTP".to_string(),
+        // A length instead of an EtherType: 802.3 with LLC (spanning tree, CDP)
+        0..=0x05FF => "802.3 LLC".to_string(),
+        _ => format!("EtherType 0x{:04X}", ethertype),
+    };
+    (src_mac, None, class)
+}
+
+/// Sender MAC of a frame and its EtherType with the payload behind any VLAN tags
+#[cfg(feature = "sniffer")]
+type EtherPayload<'a> = (Option<[u8; 6]>, Option<(u16, &'a [u8])>);
+
+/// Split a frame into its sender MAC, EtherType and payload, for link layers
+/// that have them
+#[cfg(feature = "sniffer")]
+fn ethertype_payload(linktype: Linktype, data: &[u8]) -> EtherPayload<'_> {
+    let mac_at = |at: usize| -> Option<[u8; 6]> { data.get(at..at + 6)?.try_into().ok() };
+    let (src_mac, ethertype_at, payload_at) = match linktype {
+        Linktype::ETHERNET => (mac_at(6), 12, 14),
+        Linktype::LINUX_SLL => {
+            let has_mac = data.get(4..6) == Some(&[0, 6]);
+            (if has_mac { mac_at(6) } else { None }, 14, 16)
+        }
+        Linktype::LINUX_SLL2 => {
+            let has_mac = data.get(11) == Some(&6);
+            (if has_mac { mac_at(12) } else { None }, 0, 20)
+        }
+        _ => return (None, None),
+    };
+    let Some(mut ethertype) = data
+        .get(ethertype_at..ethertype_at + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+    else {
+        return (src_mac, None);
+    };
+    let mut rest = data.get(payload_at..).unwrap_or_default();
+    while VLAN_ETHERTYPES.contains(&ethertype) && rest.len() >= 4 {
+        ethertype = u16::from_be_bytes([rest[2], rest[3]]);
+        rest = &rest[4..];
+    }
+    (src_mac, Some((ethertype, rest)))
+}
+
+/// Sender MAC and IPv4 address of an ARP request or reply. Probes, which
+/// have no sender IP yet, are skipped.
+#[cfg(feature = "sniffer")]
+pub(crate) fn arp_sender(linktype: Linktype, data: &[u8]) -> Option<([u8; 6], Ipv4Addr)> {
+    let (_, Some((ETHERTYPE_ARP, arp))) = ethertype_payload(linktype, data) else {
+        return None;
+    };
+    // Ethernet hardware (1) and IPv4 protocol addresses, 6 and 4 bytes long
+    if arp.get(..6)? != [0, 1, 0x08, 0x00, 6, 4] {
+        return None;
+    }
+    let mac: [u8; 6] = arp.get(8..14)?.try_into().ok()?;
+    let ip: [u8; 4] = arp.get(14..18)?.try_into().ok()?;
+    let ip = Ipv4Addr::from(ip);
+    if ip.is_unspecified() {
+        return None;
+    }
+    Some((mac, ip))
+}
+
+/// LLDP or CDP announcement carried by a frame. CDP frames are 802.3 frames,
+/// their EtherType field holds the length and an LLC/SNAP header follows.
+#[cfg(feature = "sniffer")]
+fn discovery_frame(
+    linktype: Linktype,
+    data: &[u8],
+) -> Option<(DiscoveryProtocol, NeighborAnnouncement)> {
+    let (_, Some((ethertype, payload))) = ethertype_payload(linktype, data) else {
+        return None;
+    };
+    if ethertype == ETHERTYPE_LLDP {
+        Some((DiscoveryProtocol::Lldp, parse_lldp(payload)?))
+    } else if ethertype <= 1500 && payload.starts_with(&CDP_SNAP_HEADER) {
+        let cdp = &payload[CDP_SNAP_HEADER.len()..];
+        Some((DiscoveryProtocol::Cdp, parse_cdp(cdp)?))
+    } else {
+        None
+    }
+}
+
+/// Source address and protocol class of an IP packet
+#[cfg(feature = "sniffer")]
+fn classify_ip_packet(packet: &[u8]) -> (Option<IpAddr>, String) {
+    let version = packet.first().map(|b| b >> 4);
+    let udp = match version {
+        Some(4) => ipv4_udp(packet),
+        Some(6) => ipv6_udp(packet),
+        _ => None,
+    };
+    if let Some((src_ip, _, udp)) = udp {
+        let class = match udp.get(..4) {
+            Some(ports) => udp_class(
+                u16::from_be_bytes([ports[0], ports[1]]),
+                u16::from_be_bytes([ports[2], ports[3]]),
+                &udp[8.min(udp.len())..],
+            ),
+            None => "UDP".to_string(),
+        };
+        return (Some(src_ip), class);
+    }
+
+    let (src_ip, protocol, header_len) = match version {
+        Some(4) if packet.len() >= 20 => (
+            IpAddr::from(Ipv4Addr::new(
+                packet[12], packet[13], packet[14], packet[15],
+            )),
+            packet[9],
+            (packet[0] & 0x0F) as usize * 4,
+        ),
+        Some(6) if packet.len() >= 40 => {
+            let src: [u8; 16] = packet[8..24].try_into().unwrap_or_default();
+            (IpAddr::from(Ipv6Addr::from(src)), packet[6], 40)
+        }
+        _ => return (None, "IP".to_string()),
+    };
+    let class = match protocol {
+        1 => "ICMP".to_string(),
+        2 => "IGMP".to_string(),
+        6 => match packet.get(header_len..header_len + 4) {
+            Some(ports) => tcp_class(
+                u16::from_be_bytes([ports[0], ports[1]]),
+                u16::from_be_bytes([ports[2], ports[3]]),
+            ),
+            None => "TCP".to_string(),
+        },
+        58 => "ICMPv6".to_string(),
+        IPPROTO_UDP => "UDP".to_string(),
+        protocol => format!("IP protocol {}", protocol),
+    };
+    (Some(src_ip), class)
+}
+
+/// Whether a frame was sent to everyone or to a multicast group, from the
+/// destination MAC, the packet type of Linux cooked captures or the IP address
+#[cfg(feature = "sniffer")]
+fn frame_cast(linktype: Linktype, data: &[u8]) -> Option<StormTraffic> {
+    match linktype {
+        Linktype::ETHERNET => {
+            let dst = data.get(..6)?;
+            if dst.iter().all(|&b| b == 0xFF) {
+                Some(StormTraffic::Broadcast)
+            } else if dst[0] & 0x01 != 0 {
+                Some(StormTraffic::Multicast)
+            } else {
+                None
+            }
+        }
+        // Packet type 1 is broadcast, 2 multicast
+        Linktype::LINUX_SLL | Linktype::LINUX_SLL2 => {
+            let packet_type = if linktype == Linktype::LINUX_SLL {
+                *data.get(1)?
+            } else {
+                *data.get(10)?
+            };
+            match packet_type {
+                1 => Some(StormTraffic::Broadcast),
+                2 => Some(StormTraffic::Multicast),
+                _ => None,
+            }
+        }
+        _ => {
+            let ip_packet = link_payload(linktype, data)?.ip_packet;
+            let dst = match ip_packet.first()? >> 4 {
+                4 => IpAddr::from(<[u8; 4]>::try_from(ip_packet.get(16..20)?).ok()?),
+                6 => IpAddr::from(<[u8; 16]>::try_from(ip_packet.get(24..40)?).ok()?),
+                _ => return None,
+            };
+            if is_broadcast(dst) {
+                Some(StormTraffic::Broadcast)
+            } else if dst.is_multicast() {
+                Some(StormTraffic::Multicast)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Protocol of a UDP datagram from its well-known port or a streaming payload,
+/// else the lower port
+#[cfg(feature = "sniffer")]
+fn udp_class(src_port: u16, dst_port: u16, payload: &[u8]) -> String {
+    for port in [dst_port, src_port] {
+        let name = match port {
+            ARTNET_PORT => "Art-Net",
+            SACN_PORT => "sACN",
+            53 => "DNS",
+            67 | 68 => "DHCP",
+            123 => "NTP",
+            137 | 138 => "NetBIOS",
+            161 | 162 => "SNMP",
+            319 | 320 => "PTP",
+            1900 => "SSDP",
+            3702 => "WS-Discovery",
+            5353 => "mDNS",
+            5355 => "LLMNR",
+            5960..=5969 => "NDI",
+            _ => continue,
+        };
+        return name.to_string();
+    }
+
+    // Streams on dynamic ports, recognised by their payload: MPEG transport
+    // stream packets start with 0x47 and are 188 bytes, RTP is version 2 with
+    // a dynamic or video payload type
+    if !payload.is_empty() && payload.len().is_multiple_of(188) && payload[0] == 0x47 {
+        return "MPEG-TS".to_string();
+    }
+    if let [first, second, ..] = payload {
+        if payload.len() >= 12
+            && first >> 6 == 2
+            && matches!(second & 0x7F, 26 | 31..=34 | 96..=127)
+        {
+            return "RTP".to_string();
+        }
+    }
+    format!("UDP {}", src_port.min(dst_port))
+}
+
+/// Protocol class of a TCP segment from its well-known port
+#[cfg(feature = "sniffer")]
+fn tcp_class(src_port: u16, dst_port: u16) -> String {
+    for port in [dst_port, src_port] {
+        let name = match port {
+            80 | 443 => "HTTP",
+            139 => "NetBIOS",
+            445 => "SMB",
+            554 => "RTSP",
+            5960..=5969 => "NDI",
+            _ => continue,
+        };
+        return name.to_string();
+    }
+    "TCP".to_string()
+}
+
+/// Whether an address is the IPv4 limited broadcast address (IPv6 has no broadcast)
+#[cfg(feature = "sniffer")]
+fn is_broadcast(ip: IpAddr) -> bool {
+    matches!(ip, IpAddr::V4(ip) if ip.is_broadcast())
 }
 
 // ============================================================================
@@ -392,7 +1794,7 @@ fn parse_ip_udp_packet(data: &[u8]) -> Option<(Ipv4Addr, Ipv4Addr, u16, u16, &[u
 // ============================================================================
 
 #[cfg(not(feature = "sniffer"))]
-pub fn is_npcap_available() -> bool {
+pub fn is_capture_available() -> bool {
     false
 }
 
@@ -401,15 +1803,308 @@ pub fn list_capture_interfaces() -> Vec<CaptureInterface> {
     Vec::new()
 }
 
+#[cfg(not(feature = "sniffer"))]
+pub fn select_capture_interface() -> Option<String> {
+    None
+}
+
+#[cfg(not(feature = "sniffer"))]
+pub fn validate_capture_filter(_filter: &str) -> Result<(), String> {
+    Ok(())
+}
+
 #[cfg(not(feature = "sniffer"))]
 pub fn start_sniffer_blocking(
-    _interface_name: &str,
+    capture: Arc<InterfaceCapture>,
     _source_manager: SourceManagerHandle,
     _dmx_store: DmxStoreHandle,
-    _event_tx: broadcast::Sender<ListenerEvent>,
-    sniffer_state: SnifferStateHandle,
+    event_tx: EventBusHandle,
+    _sniffer_state: SnifferStateHandle,
 ) {
-    *sniffer_state.error.lock() =
-        Some("Sniffer feature not compiled. Rebuild with --features sniffer".to_string());
-    *sniffer_state.enabled.lock() = false;
+    let message = "Sniffer feature not compiled. Rebuild with --features sniffer".to_string();
+    *capture.error.lock() = Some(message.clone());
+    *capture.running.lock() = false;
+    send_sniffer_event(&event_tx, &capture, SnifferEventKind::Error, Some(message));
+    send_sniffer_event(&event_tx, &capture, SnifferEventKind::Stopped, None);
+}
+
+// The parsers above need pcap, so these only run in sniffer builds
+#[cfg(all(test, feature = "sniffer"))]
+mod tests {
+    use super::*;
+
+    const SRC_MAC: [u8; 6] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x01];
+
+    /// UDP datagram to the Art-Net port with a 4-byte payload
+    fn udp_datagram() -> Vec<u8> {
+        let mut udp = vec![0x19, 0x36, 0x19, 0x36, 0x00, 0x0C, 0x00, 0x00];
+        udp.extend_from_slice(b"Art-");
+        udp
+    }
+
+    fn ipv4_packet(udp: &[u8]) -> Vec<u8> {
+        let total_len = (20 + udp.len()) as u16;
+        let mut packet = vec![0x45, 0x00];
+        packet.extend_from_slice(&total_len.to_be_bytes());
+        packet.extend_from_slice(&[0, 0, 0, 0, 64, IPPROTO_UDP, 0, 0]);
+        packet.extend_from_slice(&[10, 0, 0, 1, 10, 255, 255, 255]);
+        packet.extend_from_slice(udp);
+        packet
+    }
+
+    /// Ethernet frame with the given (TPID, TCI) tags in front of the EtherType
+    fn ethernet_frame(tags: &[(u16, u16)], ethertype: u16, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0xFF; 6];
+        frame.extend_from_slice(&SRC_MAC);
+        for (tpid, tci) in tags {
+            frame.extend_from_slice(&tpid.to_be_bytes());
+            frame.extend_from_slice(&tci.to_be_bytes());
+        }
+        frame.extend_from_slice(&ethertype.to_be_bytes());
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn sll_frame(payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0x00, 0x01, 0x00, 0x01, 0x00, 0x06];
+        frame.extend_from_slice(&SRC_MAC);
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(&ETHERTYPE_IPV4.to_be_bytes());
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn sll2_frame(payload: &[u8]) -> Vec<u8> {
+        let mut frame = ETHERTYPE_IPV4.to_be_bytes().to_vec();
+        frame.extend_from_slice(&[0, 0, 0, 0, 0, 2, 0x00, 0x01, 0x01, 0x06]);
+        frame.extend_from_slice(&SRC_MAC);
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn null_frame(payload: &[u8]) -> Vec<u8> {
+        let mut frame = 2u32.to_ne_bytes().to_vec();
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    #[test]
+    fn link_payload_reads_vlan_tags() {
+        let ip = ipv4_packet(&udp_datagram());
+        let cases = [
+            (vec![], None, None),
+            (vec![(0x8100, 100)], Some(100), None),
+            // Priority and drop eligible bits are not part of the VLAN ID
+            (vec![(0x8100, 0xF064)], Some(100), None),
+            (vec![(0x88A8, 200), (0x8100, 100)], Some(100), Some(200)),
+            (vec![(0x9100, 300), (0x8100, 100)], Some(100), Some(300)),
+        ];
+        for (tags, vlan, outer_vlan) in cases {
+            let frame = ethernet_frame(&tags, ETHERTYPE_IPV4, &ip);
+            let link = link_payload(Linktype::ETHERNET, &frame).expect("IP frame");
+            assert_eq!(link.src_mac, Some(SRC_MAC), "tags {:?}", tags);
+            assert_eq!(link.vlans.vlan, vlan, "tags {:?}", tags);
+            assert_eq!(link.vlans.outer_vlan, outer_vlan, "tags {:?}", tags);
+            assert_eq!(link.ip_packet, &ip[..], "tags {:?}", tags);
+        }
+    }
+
+    #[test]
+    fn link_payload_rejects_other_tagged_frames() {
+        let ip = ipv4_packet(&udp_datagram());
+        let cases = [
+            (
+                vec![(0x88A8, 300), (0x8100, 200), (0x8100, 100)],
+                ETHERTYPE_IPV4,
+            ),
+            (vec![(0x8100, 100)], ETHERTYPE_ARP),
+            (vec![(0x88A8, 200), (0x8100, 100)], ETHERTYPE_LLDP),
+        ];
+        for (tags, ethertype) in cases {
+            let frame = ethernet_frame(&tags, ethertype, &ip);
+            assert!(
+                link_payload(Linktype::ETHERNET, &frame).is_none(),
+                "tags {:?}",
+                tags
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_frames_are_rejected_without_panicking() {
+        let ip = ipv4_packet(&udp_datagram());
+        // Frame and the length of its link-layer header
+        let cases = [
+            (
+                Linktype::ETHERNET,
+                ethernet_frame(&[], ETHERTYPE_IPV4, &ip),
+                14,
+            ),
+            (
+                Linktype::ETHERNET,
+                ethernet_frame(&[(0x88A8, 200), (0x8100, 100)], ETHERTYPE_IPV4, &ip),
+                22,
+            ),
+            (Linktype::LINUX_SLL, sll_frame(&ip), 16),
+            (Linktype::LINUX_SLL2, sll2_frame(&ip), 20),
+            (Linktype::NULL, null_frame(&ip), 4),
+            (Linktype::RAW, ip.clone(), 0),
+        ];
+        for (linktype, frame, header_len) in cases {
+            let udp = parse_udp_frame(linktype, &frame).expect("complete frame");
+            assert_eq!(udp.dst_port, ARTNET_PORT);
+            assert_eq!(udp.ttl, 64);
+            assert_eq!(udp.payload, b"Art-");
+
+            for len in 0..frame.len() {
+                let data = &frame[..len];
+                // The IPv4 and UDP headers are needed, the payload may be cut short
+                assert_eq!(
+                    parse_udp_frame(linktype, data).is_some(),
+                    len >= header_len + 28,
+                    "{:?} cut to {} bytes",
+                    linktype,
+                    len
+                );
+                classify_frame(linktype, data);
+                frame_cast(linktype, data);
+                arp_sender(linktype, data);
+                discovery_frame(linktype, data);
+            }
+        }
+    }
+
+    /// IPv6 packet from fe80::1 to ff18::1 with extension headers before the UDP datagram
+    fn ipv6_packet(next_header: u8, extensions: &[u8], udp: &[u8]) -> Vec<u8> {
+        let payload_len = (extensions.len() + udp.len()) as u16;
+        let mut packet = vec![0x60, 0, 0, 0];
+        packet.extend_from_slice(&payload_len.to_be_bytes());
+        packet.extend_from_slice(&[next_header, 64]);
+        packet.extend_from_slice(&Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1).octets());
+        packet.extend_from_slice(&Ipv6Addr::new(0xff18, 0, 0, 0, 0, 0, 0, 1).octets());
+        packet.extend_from_slice(extensions);
+        packet.extend_from_slice(udp);
+        packet
+    }
+
+    /// Hop-by-hop, routing or destination options header of `units` extra 8-octet units
+    fn options_header(next_header: u8, units: u8) -> Vec<u8> {
+        let mut header = vec![next_header, units];
+        header.resize((units as usize + 1) * 8, 0);
+        header
+    }
+
+    fn fragment_header(next_header: u8, offset: u16, more: bool) -> Vec<u8> {
+        let mut header = vec![next_header, 0];
+        header.extend_from_slice(&(offset << 3 | more as u16).to_be_bytes());
+        header.extend_from_slice(&[0, 0, 0, 1]);
+        header
+    }
+
+    /// Authentication header with a 4-octet ICV, 16 bytes in all
+    fn auth_header(next_header: u8) -> Vec<u8> {
+        let mut header = vec![next_header, 2];
+        header.resize(16, 0);
+        header
+    }
+
+    #[test]
+    fn ipv6_udp_follows_extension_headers() {
+        let udp = udp_datagram();
+        let cases = [
+            ("no extensions", IPPROTO_UDP, vec![]),
+            ("hop-by-hop", 0, options_header(IPPROTO_UDP, 0)),
+            (
+                "hop-by-hop, routing, destination options",
+                0,
+                [
+                    options_header(43, 0),
+                    options_header(60, 1),
+                    options_header(IPPROTO_UDP, 2),
+                ]
+                .concat(),
+            ),
+            ("first fragment", 44, fragment_header(IPPROTO_UDP, 0, true)),
+            ("authentication", 51, auth_header(IPPROTO_UDP)),
+            (
+                "authenticated fragment",
+                51,
+                [auth_header(44), fragment_header(IPPROTO_UDP, 0, true)].concat(),
+            ),
+            (
+                "longest chain",
+                60,
+                [
+                    vec![options_header(60, 0); 7].concat(),
+                    options_header(IPPROTO_UDP, 0),
+                ]
+                .concat(),
+            ),
+        ];
+        for (name, next_header, extensions) in cases {
+            let packet = ipv6_packet(next_header, &extensions, &udp);
+            let (src_ip, dst_ip, segment) = ipv6_udp(&packet).expect(name);
+            assert_eq!(src_ip, "fe80::1".parse::<IpAddr>().unwrap(), "{}", name);
+            assert_eq!(dst_ip, "ff18::1".parse::<IpAddr>().unwrap(), "{}", name);
+            assert_eq!(segment, &udp[..], "{}", name);
+
+            // Cut anywhere in the headers, the packet is rejected
+            let headers_len = packet.len() - udp.len();
+            for len in 0..packet.len() {
+                assert_eq!(
+                    ipv6_udp(&packet[..len]).is_some(),
+                    len >= headers_len,
+                    "{} cut to {} bytes",
+                    name,
+                    len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ipv6_udp_rejects_other_packets() {
+        let udp = udp_datagram();
+        let cases = [
+            ("TCP", 6, vec![]),
+            ("TCP after hop-by-hop", 0, options_header(6, 0)),
+            ("no next header", 59, vec![]),
+            (
+                "later fragment",
+                44,
+                fragment_header(IPPROTO_UDP, 185, false),
+            ),
+            (
+                "chain too long",
+                60,
+                [
+                    vec![options_header(60, 0); 8].concat(),
+                    options_header(IPPROTO_UDP, 0),
+                ]
+                .concat(),
+            ),
+            // Header length pointing past the end of the packet
+            (
+                "oversized options",
+                0,
+                options_header(IPPROTO_UDP, 255)[..8].to_vec(),
+            ),
+        ];
+        for (name, next_header, extensions) in cases {
+            let packet = ipv6_packet(next_header, &extensions, &udp);
+            assert!(ipv6_udp(&packet).is_none(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn ipv6_frames_keep_the_hop_limit() {
+        let packet = ipv6_packet(0, &options_header(IPPROTO_UDP, 0), &udp_datagram());
+        let frame = ethernet_frame(&[(0x8100, 100)], ETHERTYPE_IPV6, &packet);
+        let udp = parse_udp_frame(Linktype::ETHERNET, &frame).expect("IPv6 frame");
+        assert_eq!(udp.vlans.vlan, Some(100));
+        assert_eq!(udp.ttl, 64);
+        assert_eq!(udp.dst_port, ARTNET_PORT);
+        assert_eq!(udp.payload, b"Art-");
+    }
 }
@@ -1,28 +1,44 @@
 // Source Tracking - Manages discovered network sources
 
+use crate::network::expected::{
+    ExpectedDevice, ExpectedDeviceAlert, ExpectedDeviceStatus, ExpectedDevices,
+};
+use crate::network::gaps::{SequenceGap, SequenceGapFilter, SequenceGapLog};
+use crate::network::oui::OuiDatabase;
+use crate::persistence::{load_json, save_json};
+
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tracing::{info, warn};
 
 /// Protocol type enum
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Protocol {
     ArtNet,
     #[serde(rename = "sACN")]
     Sacn,
+    /// Received by a registered protocol decoder, see NetworkSource::decoder.
+    /// Receive only.
+    Other,
 }
 
-/// Source status based on last activity
+/// Error for attempts to send a protocol other than Art-Net or sACN
+pub const RECEIVE_ONLY_PROTOCOL: &str = "Only Art-Net and sACN can be sent";
+
+/// Source status based on last activity (default thresholds, see SourceThresholds)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SourceStatus {
-    Active, // Received data within last 3 seconds
-    Idle,   // No data for 3-10 seconds
-    Stale,  // No data for 10+ seconds
+    Active,  // Received data within last 3 seconds
+    Idle,    // No data for 3-10 seconds
+    Stale,   // No data for 10+ seconds
+    Offline, // Known from the device database, not heard from for 60+ seconds
 }
 
 /// Source direction - whether the device is sending or receiving DMX
@@ -62,20 +78,84 @@ pub struct NetworkSource {
     pub duplicate_universes: Vec<u16>, // Universes with multiple senders
     #[serde(default)]
     pub latency_jitter_ms: f32,
+    #[serde(default)]
+    pub bytes_per_second: f32, // UDP payload bytes from this source's IP
+    #[serde(default)]
+    pub packets_per_second: f32,
+    #[serde(default)]
+    pub interfaces: Vec<String>, // Local interfaces the source was heard on
+    #[serde(default)]
+    pub priority_conflicts: Vec<u16>, // sACN universes shared with a source at the same priority
+    #[serde(default)]
+    pub backup_universes: Vec<u16>, // sACN universes where a higher priority source wins
+    #[serde(default)]
+    pub duplicate_cid: bool, // Same sACN CID sent from more than one IP
+    #[serde(default)]
+    pub cid_ips: Vec<String>, // IPs sending with this CID, set while duplicated
+    #[serde(default)]
+    pub wandering_universes: Vec<u16>, // Universes whose frame rate wanders
+    #[serde(default)]
+    pub lost_packets: u64, // Sequence numbers never received, across universes
+    #[serde(default)]
+    pub out_of_order_packets: u64, // Arrived after a later packet
+    #[serde(default)]
+    pub duplicate_packets: u64, // Repeated a sequence number already received
+
+    // User annotations
+    #[serde(default)]
+    pub alias: Option<String>, // e.g. "Dimmer rack SL"
+    #[serde(default)]
+    pub note: Option<String>, // Rack location, known quirks
+    #[serde(default)]
+    pub pinned: bool, // Always listed, kept when sources are cleared
+    #[serde(default)]
+    pub hidden: bool, // Left out of source lists unless requested
 
     // Art-Net specific
     #[serde(skip_serializing_if = "Option::is_none")]
     pub artnet_short_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub artnet_long_name: Option<String>,
+    // Sender IP of the ArtPollReply when it differs from the IP the reply
+    // reports (node IP changed, NAT or multi-homing)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_ip_mismatch: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mac_address: Option<String>,
 
+    // Sniffer mode: Ethernet source MAC of captured frames. Differs from
+    // mac_address when the traffic reaches us through a router.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_mac: Option<String>,
+    // Sniffer mode: 802.1Q VLAN of captured frames, and the outer (service)
+    // tag of double-tagged frames
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vlan: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outer_vlan: Option<u16>,
+    // Sniffer mode: IP TTL (IPv6 hop limit) of captured frames and the routers
+    // it suggests they crossed. Lighting traffic is normally never routed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub router_hops: Option<u8>,
+    // Sniffer mode: switch and port the capture link hangs off, from LLDP/CDP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub switch_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub switch_port: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac_vendor: Option<String>, // Resolved from mac_address
+
     // sACN specific
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sacn_cid: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sacn_priority: Option<u8>,
+
+    // Decoder specific: name of the decoder that received the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decoder: Option<String>,
 }
 
 impl NetworkSource {
@@ -91,12 +171,7 @@ impl NetworkSource {
             .unwrap_or_default()
             .as_millis() as u64;
 
-        let mac_string = mac.map(|m| {
-            format!(
-                "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
-                m[0], m[1], m[2], m[3], m[4], m[5]
-            )
-        });
+        let mac_string = mac.as_ref().map(mac_to_string);
 
         let name = if !long_name.is_empty() {
             long_name.to_string()
@@ -124,12 +199,38 @@ impl NetworkSource {
             fps_warning: None,
             duplicate_universes: Vec::new(),
             latency_jitter_ms: 0.0,
+            bytes_per_second: 0.0,
+            packets_per_second: 0.0,
+            interfaces: Vec::new(),
+            priority_conflicts: Vec::new(),
+            backup_universes: Vec::new(),
+            duplicate_cid: false,
+            wandering_universes: Vec::new(),
+            lost_packets: 0,
+            out_of_order_packets: 0,
+            duplicate_packets: 0,
+            cid_ips: Vec::new(),
+            // User annotations
+            alias: None,
+            note: None,
+            pinned: false,
+            hidden: false,
             // Art-Net specific
             artnet_short_name: Some(short_name.to_string()),
             artnet_long_name: Some(long_name.to_string()),
+            reply_ip_mismatch: None,
             mac_address: mac_string,
+            link_mac: None,
+            vlan: None,
+            outer_vlan: None,
+            ttl: None,
+            router_hops: None,
+            switch_name: None,
+            switch_port: None,
+            mac_vendor: None,
             sacn_cid: None,
             sacn_priority: None,
+            decoder: None,
         }
     }
 
@@ -166,21 +267,118 @@ impl NetworkSource {
             fps_warning: None,
             duplicate_universes: Vec::new(),
             latency_jitter_ms: 0.0,
+            bytes_per_second: 0.0,
+            packets_per_second: 0.0,
+            interfaces: Vec::new(),
+            priority_conflicts: Vec::new(),
+            backup_universes: Vec::new(),
+            duplicate_cid: false,
+            wandering_universes: Vec::new(),
+            lost_packets: 0,
+            out_of_order_packets: 0,
+            duplicate_packets: 0,
+            cid_ips: Vec::new(),
+            // User annotations
+            alias: None,
+            note: None,
+            pinned: false,
+            hidden: false,
             // Art-Net specific
             artnet_short_name: None,
             artnet_long_name: None,
+            reply_ip_mismatch: None,
             mac_address: None,
+            link_mac: None,
+            vlan: None,
+            outer_vlan: None,
+            ttl: None,
+            router_hops: None,
+            switch_name: None,
+            switch_port: None,
+            mac_vendor: None,
             sacn_cid: Some(cid_string),
             sacn_priority: Some(priority),
+            decoder: None,
+        }
+    }
+
+    /// Create a new source from traffic of a protocol decoder
+    pub fn from_decoder(ip: IpAddr, decoder: &str, source_name: &str) -> Self {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        let name = if !source_name.is_empty() {
+            source_name.to_string()
+        } else {
+            format!("{} @ {}", decoder, ip)
+        };
+
+        Self {
+            id: format!("{}-{}", decoder, ip),
+            ip: ip.to_string(),
+            hostname: None,
+            name,
+            protocol: Protocol::Other,
+            universes: Vec::new(),
+            status: SourceStatus::Active,
+            direction: SourceDirection::Unknown,
+            fps: 0.0,
+            packet_count: 0,
+            first_seen: now_ms,
+            last_seen: now_ms,
+            // Diagnostics
+            packet_loss_percent: 0.0,
+            fps_warning: None,
+            duplicate_universes: Vec::new(),
+            latency_jitter_ms: 0.0,
+            bytes_per_second: 0.0,
+            packets_per_second: 0.0,
+            interfaces: Vec::new(),
+            priority_conflicts: Vec::new(),
+            backup_universes: Vec::new(),
+            duplicate_cid: false,
+            wandering_universes: Vec::new(),
+            lost_packets: 0,
+            out_of_order_packets: 0,
+            duplicate_packets: 0,
+            cid_ips: Vec::new(),
+            // User annotations
+            alias: None,
+            note: None,
+            pinned: false,
+            hidden: false,
+            // Art-Net specific
+            artnet_short_name: None,
+            artnet_long_name: None,
+            reply_ip_mismatch: None,
+            mac_address: None,
+            link_mac: None,
+            vlan: None,
+            outer_vlan: None,
+            ttl: None,
+            router_hops: None,
+            switch_name: None,
+            switch_port: None,
+            mac_vendor: None,
+            sacn_cid: None,
+            sacn_priority: None,
+            decoder: Some(decoder.to_string()),
         }
     }
 
     /// Update source status based on time since last seen
-    pub fn update_status(&mut self, now: Instant, last_packet: Instant) {
+    pub fn update_status(
+        &mut self,
+        now: Instant,
+        last_packet: Instant,
+        thresholds: &SourceThresholds,
+    ) {
         let elapsed = now.duration_since(last_packet);
-        self.status = if elapsed < Duration::from_secs(3) {
+        self.status = if elapsed < Duration::from_millis(thresholds.idle_after_ms) {
             SourceStatus::Active
-        } else if elapsed < Duration::from_secs(10) {
+        } else if elapsed < Duration::from_millis(thresholds.stale_after_ms) {
             SourceStatus::Idle
         } else {
             SourceStatus::Stale
@@ -188,32 +386,105 @@ impl NetworkSource {
     }
 }
 
-/// FPS calculator for a single universe
+/// Status and FPS warning thresholds, adjustable at runtime
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceThresholds {
+    pub idle_after_ms: u64,    // Active -> Idle
+    pub stale_after_ms: u64,   // Idle -> Stale
+    pub offline_after_ms: u64, // Stale -> Offline
+    pub fps_low: f32,
+    pub fps_high: f32,
+    pub forget_after_ms: u64,     // Offline -> removed from the device database, unless pinned
+    pub max_known_sources: usize, // Unpinned offline sources beyond this are forgotten, oldest first
+}
+
+impl SourceThresholds {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.idle_after_ms == 0
+            || self.stale_after_ms <= self.idle_after_ms
+            || self.offline_after_ms <= self.stale_after_ms
+        {
+            return Err("Thresholds must satisfy 0 < idle < stale < offline".to_string());
+        }
+        if self.forget_after_ms <= self.offline_after_ms {
+            return Err("Sources must be forgotten later than they go offline".to_string());
+        }
+        if self.max_known_sources == 0 {
+            return Err("The device database must hold at least one source".to_string());
+        }
+        if !(self.fps_low >= 0.0 && self.fps_high > self.fps_low) {
+            return Err("FPS thresholds must satisfy 0 <= low < high".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for SourceThresholds {
+    fn default() -> Self {
+        Self {
+            idle_after_ms: 3_000,
+            stale_after_ms: 10_000,
+            offline_after_ms: 60_000,
+            fps_low: 20.0,
+            fps_high: 44.0,
+            forget_after_ms: 7 * 24 * 60 * 60 * 1000,
+            max_known_sources: 2000,
+        }
+    }
+}
+
+/// Buckets in the one second FPS window
+const FPS_BUCKETS: usize = 10;
+const FPS_BUCKET_LENGTH: Duration = Duration::from_millis(100);
+
+/// FPS calculator for a single universe. Packets are counted in a ring of
+/// 100 ms buckets covering the last second, so recording and reading are O(1).
 #[derive(Debug, Clone)]
 pub struct FpsCounter {
-    packet_times: Vec<Instant>,
-    window_size: Duration,
+    origin: Instant,
+    buckets: [(u64, u32); FPS_BUCKETS], // (bucket number since origin, packets)
 }
 
 impl FpsCounter {
     pub fn new() -> Self {
         Self {
-            packet_times: Vec::new(),
-            window_size: Duration::from_secs(1),
+            origin: Instant::now(),
+            buckets: [(0, 0); FPS_BUCKETS],
         }
     }
 
+    /// Bucket number of a point in time, counted from the counter's creation
+    fn bucket(&self, at: Instant) -> u64 {
+        (at.duration_since(self.origin).as_nanos() / FPS_BUCKET_LENGTH.as_nanos()) as u64
+    }
+
     pub fn record_packet(&mut self) {
-        let now = Instant::now();
-        // Remove old packets outside the window
-        self.packet_times
-            .retain(|&t| now.duration_since(t) < self.window_size);
-        self.packet_times.push(now);
+        let current = self.bucket(Instant::now());
+        let bucket = &mut self.buckets[current as usize % FPS_BUCKETS];
+        // A bucket left over from an earlier lap of the ring starts again from zero
+        if bucket.0 != current {
+            *bucket = (current, 0);
+        }
+        bucket.1 += 1;
     }
 
     pub fn fps(&self) -> f32 {
-        // record_packet() already prunes old entries, so length == count in window
-        self.packet_times.len() as f32
+        let now = Instant::now();
+        let current = self.bucket(now);
+        let packets: u32 = self
+            .buckets
+            .iter()
+            .filter(|(bucket, _)| current.saturating_sub(*bucket) < FPS_BUCKETS as u64)
+            .map(|(_, packets)| packets)
+            .sum();
+
+        // The newest bucket is still filling: the window is the earlier buckets
+        // plus the part of the newest one that has elapsed
+        let elapsed = now.duration_since(self.origin).as_nanos() % FPS_BUCKET_LENGTH.as_nanos();
+        let window =
+            FPS_BUCKET_LENGTH * (FPS_BUCKETS as u32 - 1) + Duration::from_nanos(elapsed as u64);
+        packets as f32 / window.as_secs_f32()
     }
 }
 
@@ -223,29 +494,123 @@ impl Default for FpsCounter {
     }
 }
 
-/// Sequence tracker for packet loss detection
+/// A jump in sequence numbers after a longer silence is a restarted sender,
+/// not a gap
+const MAX_GAP_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Steps back in sequence numbers smaller than this are packets arriving out of
+/// order rather than gaps (E1.31 6.7.2)
+const OUT_OF_ORDER_WINDOW: u8 = 20;
+
+/// Sequence tracker for packet loss detection. Packets arriving behind the
+/// newest one are told apart from lost ones: a number not yet received is out
+/// of order (it was counted lost when skipped and no longer is), one already
+/// received is a duplicate. Neither counts towards the loss percentage.
 #[derive(Debug, Clone)]
 pub struct SequenceTracker {
     last_sequence: Option<u8>,
+    last_packet: Option<Instant>,
     expected_packets: u64,
     received_packets: u64,
     window_start: Instant,
+    recent: VecDeque<u8>, // Sequence numbers received within the out of order window
+    lost_packets: u64,
+    out_of_order_packets: u64,
+    duplicate_packets: u64,
 }
 
 impl SequenceTracker {
     pub fn new() -> Self {
         Self {
             last_sequence: None,
+            last_packet: None,
             expected_packets: 0,
             received_packets: 0,
             window_start: Instant::now(),
+            recent: VecDeque::with_capacity(OUT_OF_ORDER_WINDOW as usize),
+            lost_packets: 0,
+            out_of_order_packets: 0,
+            duplicate_packets: 0,
+        }
+    }
+
+    /// Whether a packet is behind the newest one received (repeating it or
+    /// stepping back less than the out of order window)
+    fn is_behind(&self, sequence: u8, skips_zero: bool) -> bool {
+        let (Some(last), Some(last_packet)) = (self.last_sequence, self.last_packet) else {
+            return false;
+        };
+        if last_packet.elapsed() > MAX_GAP_INTERVAL || (skips_zero && sequence == 0) {
+            return false;
+        }
+        let step = sequence.wrapping_sub(last);
+        step == 0 || step > u8::MAX - OUT_OF_ORDER_WINDOW + 1
+    }
+
+    fn remember(&mut self, sequence: u8) {
+        if self.recent.len() == OUT_OF_ORDER_WINDOW as usize {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(sequence);
+    }
+
+    pub fn lost_packets(&self) -> u64 {
+        self.lost_packets
+    }
+
+    pub fn out_of_order_packets(&self) -> u64 {
+        self.out_of_order_packets
+    }
+
+    pub fn duplicate_packets(&self) -> u64 {
+        self.duplicate_packets
+    }
+
+    /// Packets missing before this one, with the previous sequence number, when
+    /// it skips ahead. Art-Net sequences wrap from 255 to 1, 0 being reserved
+    /// for senders that don't sequence.
+    pub fn gap(&self, sequence: u8, skips_zero: bool) -> Option<(u8, u8)> {
+        let last = self.last_sequence?;
+        if self.last_packet?.elapsed() > MAX_GAP_INTERVAL
+            || (skips_zero && (sequence == 0 || last == 0))
+        {
+            return None;
         }
+        let step = sequence.wrapping_sub(last);
+        if step <= 1 || step > u8::MAX - OUT_OF_ORDER_WINDOW + 1 {
+            return None;
+        }
+        let missed = if skips_zero && sequence < last {
+            step - 2
+        } else {
+            step - 1
+        };
+        (missed > 0).then_some((last, missed))
     }
 
     /// Record a packet and return loss percentage
-    pub fn record_packet(&mut self, sequence: u8) -> f32 {
+    pub fn record_packet(&mut self, sequence: u8, skips_zero: bool) -> f32 {
+        if self.is_behind(sequence, skips_zero) {
+            self.last_packet = Some(Instant::now());
+            if self.recent.contains(&sequence) {
+                self.duplicate_packets += 1;
+            } else {
+                // Late rather than lost: it fills a slot already expected
+                self.out_of_order_packets += 1;
+                self.lost_packets = self.lost_packets.saturating_sub(1);
+                self.received_packets += 1;
+                self.remember(sequence);
+            }
+            return self.loss_percent();
+        }
+        if let Some((_, missed)) = self.gap(sequence, skips_zero) {
+            self.lost_packets += missed as u64;
+        }
+        self.remember(sequence);
+
         // Reset window every 5 seconds
         let now = Instant::now();
+        self.last_packet = Some(now);
         if now.duration_since(self.window_start) > Duration::from_secs(5) {
             self.expected_packets = 0;
             self.received_packets = 0;
@@ -269,14 +634,18 @@ impl SequenceTracker {
         }
 
         self.last_sequence = Some(sequence);
+        self.loss_percent()
+    }
 
+    /// Loss percentage of the current window
+    fn loss_percent(&self) -> f32 {
         if self.expected_packets == 0 {
             0.0
         } else {
-            let loss = (self.expected_packets - self.received_packets) as f32
+            let loss = self.expected_packets.saturating_sub(self.received_packets) as f32
                 / self.expected_packets as f32
                 * 100.0;
-            loss.max(0.0).min(100.0)
+            loss.clamp(0.0, 100.0)
         }
     }
 }
@@ -350,181 +719,935 @@ impl Default for LatencyTracker {
     }
 }
 
-/// Internal source tracking with timing data
-struct SourceEntry {
-    source: NetworkSource,
-    last_packet: Instant,
-    fps_counter: FpsCounter,
-    sequence_tracker: SequenceTracker,
-    latency_tracker: LatencyTracker,
+/// Frame intervals kept per universe for the stability statistics
+const FRAME_INTERVAL_SAMPLES: usize = 500;
+
+/// Intervals longer than this are pauses in the stream rather than its rate
+const MAX_FRAME_INTERVAL: Duration = Duration::from_secs(1);
+
+/// One second frame rates kept per universe, to tell a wandering rate from jitter
+const FPS_HISTORY_SECONDS: usize = 30;
+
+/// History and mean rate needed before a rate can count as wandering
+const MIN_WANDER_HISTORY: usize = 10;
+const MIN_WANDER_FPS: f32 = 10.0;
+
+/// Standard deviation of the one second rates relative to their mean above
+/// which the rate wanders, typical of an overloaded console or media server
+const WANDERING_FPS_VARIATION: f32 = 0.15;
+
+/// Frame timing statistics of a universe from one source
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FrameRateStability {
+    pub interval_mean_ms: f32,
+    pub interval_stddev_ms: f32,
+    pub interval_p50_ms: f32,
+    pub interval_p95_ms: f32,
+    pub interval_p99_ms: f32,
+    pub interval_max_ms: f32,
+    pub fps_mean: f32, // Of the one second rates
+    pub fps_stddev: f32,
+    pub wandering: bool,
 }
 
-/// Central source manager
-pub struct SourceManager {
-    sources: RwLock<HashMap<String, SourceEntry>>,
-    /// Track which sources are outputting to each universe (for duplicate detection)
-    universe_sources: RwLock<HashMap<u16, Vec<String>>>,
-    /// FPS warning thresholds
-    fps_low_threshold: f32,
-    fps_high_threshold: f32,
+/// Frame intervals and one second frame rates of a universe
+#[derive(Debug, Clone)]
+pub struct FrameRateTracker {
+    last_frame: Option<Instant>,
+    intervals: VecDeque<f32>, // ms
+    second_start: Instant,
+    second_frames: u32,
+    fps_history: VecDeque<f32>,
 }
 
-impl SourceManager {
+impl FrameRateTracker {
     pub fn new() -> Self {
         Self {
-            sources: RwLock::new(HashMap::new()),
-            universe_sources: RwLock::new(HashMap::new()),
-            fps_low_threshold: 20.0,
-            fps_high_threshold: 44.0,
+            last_frame: None,
+            intervals: VecDeque::with_capacity(FRAME_INTERVAL_SAMPLES),
+            second_start: Instant::now(),
+            second_frames: 0,
+            fps_history: VecDeque::with_capacity(FPS_HISTORY_SECONDS),
         }
     }
 
-    /// Update or add an Art-Net source
-    pub fn update_artnet_source(
-        &self,
-        ip: IpAddr,
-        short_name: &str,
-        long_name: &str,
-        mac: Option<[u8; 6]>,
-        universes: Option<Vec<u16>>,
-        sequence: Option<u8>,
-    ) {
-        let id = format!("artnet-{}", ip);
-        let mut sources = self.sources.write();
-
-        let entry = sources.entry(id.clone()).or_insert_with(|| SourceEntry {
-            source: NetworkSource::from_artnet(ip, short_name, long_name, mac),
-            last_packet: Instant::now(),
-            fps_counter: FpsCounter::new(),
-            sequence_tracker: SequenceTracker::new(),
-            latency_tracker: LatencyTracker::new(),
-        });
-
-        entry.last_packet = Instant::now();
-        entry.fps_counter.record_packet();
-
-        // Track sequence number for packet loss
-        if let Some(seq) = sequence {
-            entry.source.packet_loss_percent = entry.sequence_tracker.record_packet(seq);
+    pub fn record_frame(&mut self, now: Instant) {
+        if let Some(last) = self.last_frame {
+            let interval = now.duration_since(last);
+            if interval <= MAX_FRAME_INTERVAL {
+                if self.intervals.len() == FRAME_INTERVAL_SAMPLES {
+                    self.intervals.pop_front();
+                }
+                self.intervals.push_back(interval.as_secs_f32() * 1000.0);
+            }
         }
-
-        // Track jitter
-        entry.source.latency_jitter_ms = entry.latency_tracker.record_packet();
-
-        entry.source.packet_count += 1;
-        entry.source.fps = entry.fps_counter.fps();
-        entry.source.last_seen = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u64;
-        entry
-            .source
-            .update_status(Instant::now(), entry.last_packet);
-
-        // Update universes if provided
-        if let Some(univs) = universes {
-            for u in univs {
-                if !entry.source.universes.contains(&u) {
-                    entry.source.universes.push(u);
-                    entry.source.universes.sort();
+        self.last_frame = Some(now);
+
+        // Close the seconds that have passed, silent ones counting as 0 fps.
+        // After a long pause the history starts over.
+        let elapsed = now.duration_since(self.second_start).as_secs() as usize;
+        if elapsed > FPS_HISTORY_SECONDS {
+            self.fps_history.clear();
+            self.second_start = now;
+            self.second_frames = 0;
+        } else {
+            for _ in 0..elapsed {
+                if self.fps_history.len() == FPS_HISTORY_SECONDS {
+                    self.fps_history.pop_front();
                 }
+                self.fps_history.push_back(self.second_frames as f32);
+                self.second_frames = 0;
+                self.second_start += Duration::from_secs(1);
             }
         }
+        self.second_frames += 1;
     }
 
-    /// Update or add an sACN source
-    pub fn update_sacn_source(
-        &self,
-        ip: IpAddr,
-        source_name: &str,
-        cid: &[u8; 16],
-        priority: u8,
-        universe: u16,
-        sequence: Option<u8>,
-    ) {
-        let cid_string = crate::network::sacn::cid_to_string(cid);
-        let id = format!("sacn-{}", cid_string);
-        let mut sources = self.sources.write();
-
-        let entry = sources.entry(id.clone()).or_insert_with(|| SourceEntry {
-            source: NetworkSource::from_sacn(ip, source_name, cid, priority),
-            last_packet: Instant::now(),
-            fps_counter: FpsCounter::new(),
-            sequence_tracker: SequenceTracker::new(),
-            latency_tracker: LatencyTracker::new(),
-        });
+    /// Mean and standard deviation of the one second rates
+    fn fps_spread(&self) -> (f32, f32) {
+        mean_and_stddev(self.fps_history.iter().copied())
+    }
 
-        entry.last_packet = Instant::now();
-        entry.fps_counter.record_packet();
+    /// Whether the stream is live and its rate wanders
+    pub fn is_wandering(&self, now: Instant) -> bool {
+        let live = self
+            .last_frame
+            .is_some_and(|last| now.duration_since(last) <= MAX_FRAME_INTERVAL);
+        let (mean, stddev) = self.fps_spread();
+        live && self.fps_history.len() >= MIN_WANDER_HISTORY
+            && mean >= MIN_WANDER_FPS
+            && stddev / mean > WANDERING_FPS_VARIATION
+    }
 
-        // Track sequence number for packet loss
-        if let Some(seq) = sequence {
-            entry.source.packet_loss_percent = entry.sequence_tracker.record_packet(seq);
+    pub fn stability(&self, now: Instant) -> FrameRateStability {
+        let (interval_mean_ms, interval_stddev_ms) =
+            mean_and_stddev(self.intervals.iter().copied());
+        let mut sorted: Vec<f32> = self.intervals.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        let percentile = |p: f32| {
+            if sorted.is_empty() {
+                0.0
+            } else {
+                sorted[((sorted.len() - 1) as f32 * p).round() as usize]
+            }
+        };
+        let (fps_mean, fps_stddev) = self.fps_spread();
+        FrameRateStability {
+            interval_mean_ms,
+            interval_stddev_ms,
+            interval_p50_ms: percentile(0.5),
+            interval_p95_ms: percentile(0.95),
+            interval_p99_ms: percentile(0.99),
+            interval_max_ms: sorted.last().copied().unwrap_or_default(),
+            fps_mean,
+            fps_stddev,
+            wandering: self.is_wandering(now),
         }
+    }
+}
 
-        // Track jitter
-        entry.source.latency_jitter_ms = entry.latency_tracker.record_packet();
-
-        entry.source.packet_count += 1;
-        entry.source.fps = entry.fps_counter.fps();
-        entry.source.last_seen = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u64;
-        entry
-            .source
-            .update_status(Instant::now(), entry.last_packet);
-        entry.source.sacn_priority = Some(priority);
-
-        // Add universe
-        if !entry.source.universes.contains(&universe) {
-            entry.source.universes.push(universe);
-            entry.source.universes.sort();
-        }
+impl Default for FrameRateTracker {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    /// Update or add an Art-Net source with direction info (for sniffer mode)
-    pub fn update_artnet_source_with_direction(
-        &self,
-        ip: IpAddr,
-        short_name: &str,
-        long_name: &str,
-        mac: Option<[u8; 6]>,
-        universes: Option<Vec<u16>>,
-        direction: SourceDirection,
-        sequence: Option<u8>,
-    ) {
-        let id = format!("artnet-{}", ip);
-        let mut sources = self.sources.write();
+/// Population mean and standard deviation, zeros when empty
+fn mean_and_stddev(values: impl Iterator<Item = f32> + Clone) -> (f32, f32) {
+    let count = values.clone().count();
+    if count == 0 {
+        return (0.0, 0.0);
+    }
+    let mean = values.clone().sum::<f32>() / count as f32;
+    let variance = values.map(|v| (v - mean).powi(2)).sum::<f32>() / count as f32;
+    (mean, variance.sqrt())
+}
 
-        let entry = sources.entry(id.clone()).or_insert_with(|| SourceEntry {
-            source: NetworkSource::from_artnet(ip, short_name, long_name, mac),
-            last_packet: Instant::now(),
-            fps_counter: FpsCounter::new(),
-            sequence_tracker: SequenceTracker::new(),
-            latency_tracker: LatencyTracker::new(),
-        });
+/// Number of history events kept per source
+const MAX_SOURCE_EVENTS: usize = 200;
 
-        entry.last_packet = Instant::now();
-        entry.fps_counter.record_packet();
+/// Kind of source history event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceEventKind {
+    Online,
+    Idle,
+    Stale,
+    Offline,
+    NameChanged,
+    UniverseAdded,
+    PriorityChanged,
+    DuplicateCid,
+    IpMismatch,
+    Routed,
+    IpChanged,
+}
 
-        // Track sequence number for packet loss
-        if let Some(seq) = sequence {
-            entry.source.packet_loss_percent = entry.sequence_tracker.record_packet(seq);
-        }
+/// Something that happened to a source, for diagnosing intermittent devices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceEvent {
+    pub timestamp: u64, // Unix timestamp ms
+    pub kind: SourceEventKind,
+    pub message: String,
+}
 
-        // Track jitter
-        entry.source.latency_jitter_ms = entry.latency_tracker.record_packet();
+/// A source with its event history, as kept across a crash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceHistory {
+    pub source: NetworkSource,
+    pub events: Vec<SourceEvent>,
+}
 
-        entry.source.packet_count += 1;
-        entry.source.fps = entry.fps_counter.fps();
-        entry.source.last_seen = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u64;
-        entry
-            .source
-            .update_status(Instant::now(), entry.last_packet);
+/// FPS and packet loss of one universe sent by a source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceUniverseStats {
+    pub universe: u16,
+    pub fps: f32,
+    pub packet_loss_percent: f32,
+    pub packet_count: u64,
+    pub last_seen: u64, // Unix timestamp ms
+    pub stability: FrameRateStability,
+    pub priority: Option<u8>, // sACN only
+    pub lost_packets: u64,
+    pub out_of_order_packets: u64, // Reordering points to the switches, not drops
+    pub duplicate_packets: u64,
+}
+
+/// The sACN priority a source sends a universe with changed, e.g. a backup
+/// console raising its priority to take over
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriorityChange {
+    pub source_id: String,
+    pub source_name: String,
+    pub ip: String,
+    pub universe: u16,
+    pub previous_priority: u8,
+    pub priority: u8,
+    pub timestamp: u64, // Unix timestamp ms
+    pub message: String,
+}
+
+/// How receivers resolve a universe with more than one sender
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UniverseConflictKind {
+    /// Art-Net senders, merged (HTP/LTP) or flickering depending on the node
+    Merge,
+    /// sACN senders tied at the highest priority
+    PriorityConflict,
+    /// sACN senders at different priorities, the highest wins
+    Backup,
+    /// Art-Net and sACN senders, resolved by whatever converts between them
+    MixedProtocols,
+}
+
+/// A sender of a shared universe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictSource {
+    pub source_id: String,
+    pub name: String,
+    pub ip: String,
+    pub protocol: Protocol,
+    pub priority: Option<u8>, // sACN only
+    pub status: SourceStatus,
+    pub fps: f32,
+}
+
+/// A universe sent by more than one source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniverseConflict {
+    pub universe: u16,
+    pub kind: UniverseConflictKind,
+    pub winner: Option<String>, // Source id of the sACN sender with the highest priority
+    pub sources: Vec<ConflictSource>, // Highest priority first
+}
+
+/// Bandwidth used by one protocol across all sources
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolBandwidth {
+    pub protocol: Protocol,
+    pub bytes_per_second: f32,
+    pub packets_per_second: f32,
+    pub total_bytes: u64,
+    pub total_packets: u64,
+}
+
+/// MAC address of an IP learned from captured ARP traffic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArpMapping {
+    pub ip: String,
+    pub mac: String,
+    pub mac_vendor: Option<String>,
+    pub last_seen_secs: f32, // Seconds since the last ARP packet from the IP
+}
+
+/// Byte and packet rates of one sender, measured over the status update interval
+struct TrafficCounter {
+    total_bytes: u64,
+    total_packets: u64,
+    window_bytes: u64,
+    window_packets: u64,
+    window_start: Instant,
+    last_packet: Instant,
+    bytes_per_second: f32,
+    packets_per_second: f32,
+    /// Listening interfaces the packets arrived on
+    interfaces: Vec<Ipv4Addr>,
+}
+
+impl TrafficCounter {
+    fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            total_bytes: 0,
+            total_packets: 0,
+            window_bytes: 0,
+            window_packets: 0,
+            window_start: now,
+            last_packet: now,
+            bytes_per_second: 0.0,
+            packets_per_second: 0.0,
+            interfaces: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, bytes: usize, interface: Option<Ipv4Addr>) {
+        if let Some(interface) = interface.filter(|i| !self.interfaces.contains(i)) {
+            self.interfaces.push(interface);
+            self.interfaces.sort();
+        }
+        self.total_bytes += bytes as u64;
+        self.total_packets += 1;
+        self.window_bytes += bytes as u64;
+        self.window_packets += 1;
+        self.last_packet = Instant::now();
+    }
+
+    /// Turn the counts since the last call into rates and start a new window
+    fn roll(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.window_start).as_secs_f32();
+        if elapsed > 0.0 {
+            self.bytes_per_second = self.window_bytes as f32 / elapsed;
+            self.packets_per_second = self.window_packets as f32 / elapsed;
+        }
+        self.window_bytes = 0;
+        self.window_packets = 0;
+        self.window_start = now;
+    }
+}
+
+/// Per-universe tracking - sequence numbers run independently per universe,
+/// so loss is only meaningful at this level
+struct UniverseTracker {
+    fps_counter: FpsCounter,
+    frame_rate: FrameRateTracker,
+    sequence_tracker: SequenceTracker,
+    packet_loss_percent: f32,
+    packet_count: u64,
+    last_seen: u64,
+    priority: Option<u8>, // sACN data packets only
+}
+
+impl UniverseTracker {
+    fn new() -> Self {
+        Self {
+            fps_counter: FpsCounter::new(),
+            frame_rate: FrameRateTracker::new(),
+            sequence_tracker: SequenceTracker::new(),
+            packet_loss_percent: 0.0,
+            packet_count: 0,
+            last_seen: 0,
+            priority: None,
+        }
+    }
+
+    /// Record a packet, returning the previous sequence number and the packets
+    /// missed when it follows a gap
+    fn record_packet(&mut self, sequence: u8, skips_zero: bool, now_ms: u64) -> Option<(u8, u8)> {
+        let gap = self.sequence_tracker.gap(sequence, skips_zero);
+        self.fps_counter.record_packet();
+        self.frame_rate.record_frame(Instant::now());
+        self.packet_loss_percent = self.sequence_tracker.record_packet(sequence, skips_zero);
+        self.packet_count += 1;
+        self.last_seen = now_ms;
+        gap
+    }
+}
+
+/// Internal source tracking with timing data
+struct SourceEntry {
+    source: NetworkSource,
+    last_packet: Instant,
+    fps_counter: FpsCounter,
+    universe_trackers: HashMap<u16, UniverseTracker>,
+    latency_tracker: LatencyTracker,
+    offline: bool, // Loaded from the device database or expired, not currently heard
+    events: VecDeque<SourceEvent>,
+    /// sACN: last packet time per IP sending with this CID
+    cid_ips: HashMap<IpAddr, Instant>,
+}
+
+impl SourceEntry {
+    /// Entry for a newly discovered source
+    fn new(source: NetworkSource) -> Self {
+        let mut entry = Self {
+            source,
+            last_packet: Instant::now(),
+            fps_counter: FpsCounter::new(),
+            universe_trackers: HashMap::new(),
+            latency_tracker: LatencyTracker::new(),
+            offline: false,
+            events: VecDeque::new(),
+            cid_ips: HashMap::new(),
+        };
+        entry.push_event(SourceEventKind::Online, "Discovered".to_string());
+        entry
+    }
+
+    /// Entry for a source from the device database that hasn't been heard this session
+    fn offline(source: NetworkSource) -> Self {
+        let mut entry = Self {
+            source,
+            last_packet: Instant::now(),
+            fps_counter: FpsCounter::new(),
+            universe_trackers: HashMap::new(),
+            latency_tracker: LatencyTracker::new(),
+            offline: true,
+            events: VecDeque::new(),
+            cid_ips: HashMap::new(),
+        };
+        entry.reset_statistics();
+        entry
+    }
+
+    /// Take an expired source offline
+    fn take_offline(&mut self) {
+        self.offline = true;
+        self.reset_statistics();
+        self.push_event(SourceEventKind::Offline, "Went offline".to_string());
+    }
+
+    /// Clear live statistics of a source that is no longer heard
+    fn reset_statistics(&mut self) {
+        self.source.status = SourceStatus::Offline;
+        self.source.fps = 0.0;
+        self.source.packet_count = 0;
+        self.source.packet_loss_percent = 0.0;
+        self.source.fps_warning = None;
+        self.source.duplicate_universes.clear();
+        self.source.priority_conflicts.clear();
+        self.source.backup_universes.clear();
+        self.source.latency_jitter_ms = 0.0;
+        self.source.bytes_per_second = 0.0;
+        self.source.packets_per_second = 0.0;
+        self.source.duplicate_cid = false;
+        self.source.cid_ips.clear();
+        self.source.wandering_universes.clear();
+        self.source.lost_packets = 0;
+        self.source.out_of_order_packets = 0;
+        self.source.duplicate_packets = 0;
+        self.fps_counter = FpsCounter::new();
+        self.universe_trackers.clear();
+        self.latency_tracker = LatencyTracker::new();
+        self.cid_ips.clear();
+    }
+
+    /// Update the status from the last packet time, logging transitions
+    fn update_status(&mut self, now: Instant, thresholds: &SourceThresholds) {
+        let previous = self.source.status;
+        self.source.update_status(now, self.last_packet, thresholds);
+        if self.source.status != previous {
+            let (kind, message) = match self.source.status {
+                SourceStatus::Active => (SourceEventKind::Online, "Came online"),
+                SourceStatus::Idle => (SourceEventKind::Idle, "Went idle"),
+                SourceStatus::Stale => (SourceEventKind::Stale, "Went stale"),
+                SourceStatus::Offline => (SourceEventKind::Offline, "Went offline"),
+            };
+            self.push_event(kind, message.to_string());
+        }
+    }
+
+    /// Update the names from an ArtPollReply (empty names are ignored)
+    fn update_artnet_names(&mut self, short_name: &str, long_name: &str) {
+        if !short_name.is_empty() {
+            self.source.artnet_short_name = Some(short_name.to_string());
+        }
+        if !long_name.is_empty() {
+            self.source.artnet_long_name = Some(long_name.to_string());
+        }
+        self.update_name(if !long_name.is_empty() {
+            long_name
+        } else {
+            short_name
+        });
+    }
+
+    /// Update the MAC address from an ArtPollReply. The vendor is resolved
+    /// again on the next status update.
+    fn update_mac(&mut self, mac: Option<[u8; 6]>) {
+        let Some(mac) = mac.as_ref().map(mac_to_string) else {
+            return;
+        };
+        if self.source.mac_address.as_deref() != Some(mac.as_str()) {
+            self.source.mac_address = Some(mac);
+            self.source.mac_vendor = None;
+        }
+    }
+
+    /// Update the display name (empty names are ignored)
+    fn update_name(&mut self, name: &str) {
+        if !name.is_empty() && self.source.name != name {
+            let message = format!("Name changed from \"{}\" to \"{}\"", self.source.name, name);
+            self.source.name = name.to_string();
+            self.push_event(SourceEventKind::NameChanged, message);
+        }
+    }
+
+    fn add_universe(&mut self, universe: u16) {
+        if !self.source.universes.contains(&universe) {
+            self.source.universes.push(universe);
+            self.source.universes.sort();
+            self.push_event(
+                SourceEventKind::UniverseAdded,
+                format!("Universe {} added", universe),
+            );
+        }
+    }
+
+    /// Record the priority a universe is sent with, logging and returning a
+    /// change. Each universe is tracked on its own, as consoles can send
+    /// universes at different priorities.
+    fn set_priority(&mut self, universe: u16, priority: u8) -> Option<PriorityChange> {
+        self.source.sacn_priority = Some(priority);
+        let previous = self
+            .universe_trackers
+            .entry(universe)
+            .or_insert_with(UniverseTracker::new)
+            .priority
+            .replace(priority)
+            .filter(|p| *p != priority)?;
+
+        let message = format!(
+            "Universe {} priority changed from {} to {}",
+            universe, previous, priority
+        );
+        self.push_event(SourceEventKind::PriorityChanged, message.clone());
+        Some(PriorityChange {
+            source_id: self.source.id.clone(),
+            source_name: self
+                .source
+                .alias
+                .clone()
+                .unwrap_or_else(|| self.source.name.clone()),
+            ip: self.source.ip.clone(),
+            universe,
+            previous_priority: previous,
+            priority,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            message,
+        })
+    }
+
+    /// sACN priority of a source that transmits data (None for Art-Net and
+    /// receivers inferred in sniffer mode)
+    fn sending_priority(&self) -> Option<u8> {
+        match (self.source.protocol, self.source.direction) {
+            (Protocol::Sacn, SourceDirection::Receiving) => None,
+            (Protocol::Sacn, _) => self.source.sacn_priority,
+            _ => None,
+        }
+    }
+
+    /// Record the IP an sACN packet with this source's CID came from. A second
+    /// IP means a cloned console config or a restored backup on another machine.
+    fn record_cid_ip(&mut self, ip: IpAddr) {
+        let now = Instant::now();
+        if self.cid_ips.insert(ip, now).is_none() && self.cid_ips.len() > 1 {
+            self.push_event(
+                SourceEventKind::DuplicateCid,
+                format!("Same CID also sent from {}", ip),
+            );
+            self.update_duplicate_cid(now, Duration::MAX);
+        }
+    }
+
+    /// Forget IPs not heard within max_age and refresh the duplicate CID warning
+    fn update_duplicate_cid(&mut self, now: Instant, max_age: Duration) {
+        self.cid_ips
+            .retain(|_, last| now.duration_since(*last) < max_age);
+        self.source.duplicate_cid = self.cid_ips.len() > 1;
+        self.source.cid_ips = if self.source.duplicate_cid {
+            let mut ips: Vec<IpAddr> = self.cid_ips.keys().copied().collect();
+            ips.sort();
+            ips.iter().map(|ip| ip.to_string()).collect()
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// MAC address identifying the device: the reported one, else the sender
+    /// MAC of captured frames when they weren't routed (a router's MAC would
+    /// be shared by every device behind it)
+    fn identity_mac(&self) -> Option<&str> {
+        match &self.source.mac_address {
+            Some(mac) => Some(mac),
+            None if self.source.router_hops == Some(0) => self.source.link_mac.as_deref(),
+            None => None,
+        }
+    }
+
+    /// Take over the identity of the entry a device had before it moved to a
+    /// new IP: its annotations, first sighting, universes and event history
+    fn inherit(&mut self, old: SourceEntry) {
+        let message = format!("IP changed from {} to {}", old.source.ip, self.source.ip);
+        self.source.first_seen = old.source.first_seen;
+        self.source.alias = old.source.alias;
+        self.source.note = old.source.note;
+        self.source.pinned = old.source.pinned;
+        self.source.hidden = old.source.hidden;
+        for universe in old.source.universes {
+            if !self.source.universes.contains(&universe) {
+                self.source.universes.push(universe);
+            }
+        }
+        self.source.universes.sort();
+        self.events = old.events;
+        self.push_event(SourceEventKind::IpChanged, message);
+    }
+
+    fn push_event(&mut self, kind: SourceEventKind, message: String) {
+        self.events.push_back(SourceEvent {
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            kind,
+            message,
+        });
+        if self.events.len() > MAX_SOURCE_EVENTS {
+            self.events.pop_front();
+        }
+    }
+
+    /// Record a sequenced DMX packet against each of its universes, logging
+    /// sequence gaps; the source's loss is that of its worst universe
+    fn record_universe_packet(&mut self, universes: &[u16], sequence: u8, gaps: &SequenceGapLog) {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let skips_zero = self.source.protocol == Protocol::ArtNet;
+        for universe in universes {
+            let gap = self
+                .universe_trackers
+                .entry(*universe)
+                .or_insert_with(UniverseTracker::new)
+                .record_packet(sequence, skips_zero, now_ms);
+            if let Some((previous_sequence, missed)) = gap {
+                gaps.push(SequenceGap {
+                    timestamp: now_ms,
+                    universe: *universe,
+                    source_id: self.source.id.clone(),
+                    source_name: self
+                        .source
+                        .alias
+                        .clone()
+                        .unwrap_or_else(|| self.source.name.clone()),
+                    ip: self.source.ip.clone(),
+                    protocol: self.source.protocol,
+                    previous_sequence,
+                    sequence,
+                    missed,
+                });
+            }
+        }
+        self.source.packet_loss_percent = self
+            .universe_trackers
+            .values()
+            .map(|t| t.packet_loss_percent)
+            .fold(0.0, f32::max);
+        let trackers = self.universe_trackers.values().map(|t| &t.sequence_tracker);
+        self.source.lost_packets = trackers.clone().map(SequenceTracker::lost_packets).sum();
+        self.source.out_of_order_packets = trackers
+            .clone()
+            .map(SequenceTracker::out_of_order_packets)
+            .sum();
+        self.source.duplicate_packets = trackers.map(SequenceTracker::duplicate_packets).sum();
+    }
+}
+
+/// Central source manager
+pub struct SourceManager {
+    sources: RwLock<HashMap<String, SourceEntry>>,
+    /// Track which sources are outputting to each universe (for duplicate detection)
+    universe_sources: RwLock<HashMap<u16, Vec<String>>>,
+    /// Status and FPS warning thresholds
+    thresholds: RwLock<SourceThresholds>,
+    thresholds_path: RwLock<Option<PathBuf>>,
+    /// Device database file, sources are persisted here when set
+    known_sources_path: RwLock<Option<PathBuf>>,
+    /// MAC vendor lookup
+    oui: OuiDatabase,
+    /// Devices that must be present, checked on every status update
+    expected: ExpectedDevices,
+    /// Bandwidth per sender IP and protocol. Kept apart from the sources since
+    /// every packet is counted, including ones that don't update a source.
+    traffic: RwLock<HashMap<(IpAddr, Protocol), TrafficCounter>>,
+    /// MAC address and last sighting per IP from ARP seen by the sniffer
+    arp_table: RwLock<HashMap<IpAddr, (String, Instant)>>,
+    /// Every sequence gap detected, for correlating glitches with loss
+    sequence_gaps: SequenceGapLog,
+}
+
+impl SourceManager {
+    pub fn new() -> Self {
+        Self {
+            sources: RwLock::new(HashMap::new()),
+            universe_sources: RwLock::new(HashMap::new()),
+            thresholds: RwLock::new(SourceThresholds::default()),
+            thresholds_path: RwLock::new(None),
+            known_sources_path: RwLock::new(None),
+            oui: OuiDatabase::new(),
+            expected: ExpectedDevices::new(),
+            traffic: RwLock::new(HashMap::new()),
+            arp_table: RwLock::new(HashMap::new()),
+            sequence_gaps: SequenceGapLog::new(),
+        }
+    }
+
+    /// Load thresholds from disk and persist future changes to the same file
+    pub fn load_thresholds(&self, path: PathBuf) {
+        let thresholds: SourceThresholds = load_json(&path);
+        match thresholds.validate() {
+            Ok(()) => *self.thresholds.write() = thresholds,
+            Err(e) => warn!("[Sources] Ignoring saved thresholds: {}", e),
+        }
+        *self.thresholds_path.write() = Some(path);
+    }
+
+    pub fn get_thresholds(&self) -> SourceThresholds {
+        *self.thresholds.read()
+    }
+
+    /// Replace the thresholds; they apply from the next status update
+    pub fn set_thresholds(&self, thresholds: SourceThresholds) -> Result<(), String> {
+        thresholds.validate()?;
+        *self.thresholds.write() = thresholds;
+        match self.thresholds_path.read().as_ref() {
+            Some(path) => save_json(path, &thresholds),
+            None => Ok(()),
+        }
+    }
+
+    /// Load the device database, adding its sources as offline, and persist
+    /// discovered sources to the same file from now on
+    pub fn load_known_sources(&self, path: PathBuf) {
+        let known: Vec<NetworkSource> = load_json(&path);
+        let mut sources = self.sources.write();
+        for source in known {
+            sources
+                .entry(source.id.clone())
+                .or_insert_with(|| SourceEntry::offline(source));
+        }
+        *self.known_sources_path.write() = Some(path);
+    }
+
+    /// Load a full OUI vendor table, extending the built-in one
+    pub fn load_oui_file(&self, path: &Path) {
+        self.oui.load_file(path);
+    }
+
+    /// Load the expected device list and persist future changes to the same file
+    pub fn load_expected_devices(&self, path: PathBuf) {
+        self.expected.load(path);
+    }
+
+    pub fn get_expected_devices(&self) -> Vec<ExpectedDeviceStatus> {
+        self.expected.get_all()
+    }
+
+    pub fn get_expected_device_list(&self) -> Vec<ExpectedDevice> {
+        self.expected.get_devices()
+    }
+
+    pub fn set_expected_devices(&self, devices: Vec<ExpectedDevice>) -> Result<(), String> {
+        self.expected.replace(devices)
+    }
+
+    pub fn add_expected_device(&self, device: ExpectedDevice) -> Result<ExpectedDevice, String> {
+        self.expected.add(device)
+    }
+
+    pub fn remove_expected_device(&self, id: &str) -> Result<(), String> {
+        self.expected.remove(id)
+    }
+
+    /// Append expected devices from a JSON or CSV file
+    pub fn import_expected_devices(&self, path: &Path) -> Result<usize, String> {
+        self.expected.import(path)
+    }
+
+    /// Compare expected devices against the current sources. Devices get
+    /// the stale threshold to show up before they are reported missing.
+    pub fn check_expected_devices(&self) -> Vec<ExpectedDeviceAlert> {
+        let grace = Duration::from_millis(self.get_thresholds().stale_after_ms);
+        self.expected.check(&self.get_all_sources(true), grace)
+    }
+
+    /// Write every known source to the device database
+    pub fn save_known_sources(&self) -> Result<(), String> {
+        let Some(path) = self.known_sources_path.read().clone() else {
+            return Ok(());
+        };
+        let mut known = self.get_all_sources(true);
+        known.sort_by(|a, b| a.id.cmp(&b.id));
+        save_json(&path, &known)
+    }
+
+    /// Update or add an Art-Net source
+    pub fn update_artnet_source(
+        &self,
+        ip: IpAddr,
+        short_name: &str,
+        long_name: &str,
+        mac: Option<[u8; 6]>,
+        universes: Option<Vec<u16>>,
+        sequence: Option<u8>,
+    ) {
+        let id = format!("artnet-{}", ip);
+        let thresholds = self.get_thresholds();
+        let mut sources = self.sources.write();
+
+        let entry = sources.entry(id.clone()).or_insert_with(|| {
+            SourceEntry::new(NetworkSource::from_artnet(ip, short_name, long_name, mac))
+        });
+
+        // Names can change, e.g. an ArtPollReply after DMX was first seen
+        entry.update_artnet_names(short_name, long_name);
+        entry.update_mac(mac);
+        entry.offline = false;
+        entry.last_packet = Instant::now();
+        entry.fps_counter.record_packet();
+
+        // Track per-universe fps and sequence number for packet loss
+        if let (Some(seq), Some(univs)) = (sequence, universes.as_deref()) {
+            entry.record_universe_packet(univs, seq, &self.sequence_gaps);
+        }
+
+        // Track jitter
+        entry.source.latency_jitter_ms = entry.latency_tracker.record_packet();
+
+        entry.source.packet_count += 1;
+        entry.source.fps = entry.fps_counter.fps();
+        entry.source.last_seen = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        entry.update_status(Instant::now(), &thresholds);
+
+        // Update universes if provided
+        if let Some(univs) = universes {
+            for u in univs {
+                entry.add_universe(u);
+            }
+        }
+    }
+
+    /// Update or add an sACN source, returning the change when a data packet
+    /// changes the priority of its universe
+    pub fn update_sacn_source(
+        &self,
+        ip: IpAddr,
+        source_name: &str,
+        cid: &[u8; 16],
+        priority: u8,
+        universe: u16,
+        sequence: Option<u8>,
+    ) -> Option<PriorityChange> {
+        let cid_string = crate::network::sacn::cid_to_string(cid);
+        let id = format!("sacn-{}", cid_string);
+        let thresholds = self.get_thresholds();
+        let mut sources = self.sources.write();
+
+        let entry = sources.entry(id.clone()).or_insert_with(|| {
+            SourceEntry::new(NetworkSource::from_sacn(ip, source_name, cid, priority))
+        });
+
+        // Names can change, e.g. a console renamed mid-session
+        entry.update_name(source_name);
+        entry.record_cid_ip(ip);
+        entry.offline = false;
+        entry.last_packet = Instant::now();
+        entry.fps_counter.record_packet();
+
+        // Track per-universe fps and sequence number for packet loss
+        if let Some(seq) = sequence {
+            entry.record_universe_packet(&[universe], seq, &self.sequence_gaps);
+        }
+
+        // Track jitter
+        entry.source.latency_jitter_ms = entry.latency_tracker.record_packet();
+
+        entry.source.packet_count += 1;
+        entry.source.fps = entry.fps_counter.fps();
+        entry.source.last_seen = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        entry.update_status(Instant::now(), &thresholds);
+        // Priority is only meaningful on data packets (discovery carries none)
+        let change = sequence.and_then(|_| entry.set_priority(universe, priority));
+
+        // Add universe
+        entry.add_universe(universe);
+        change
+    }
+
+    /// Update or add an Art-Net source with direction info (for sniffer mode)
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_artnet_source_with_direction(
+        &self,
+        ip: IpAddr,
+        short_name: &str,
+        long_name: &str,
+        mac: Option<[u8; 6]>,
+        universes: Option<Vec<u16>>,
+        direction: SourceDirection,
+        sequence: Option<u8>,
+    ) {
+        let id = format!("artnet-{}", ip);
+        let thresholds = self.get_thresholds();
+        let mut sources = self.sources.write();
+
+        let entry = sources.entry(id.clone()).or_insert_with(|| {
+            SourceEntry::new(NetworkSource::from_artnet(ip, short_name, long_name, mac))
+        });
+
+        // Names can change, e.g. an ArtPollReply after DMX was first seen
+        entry.update_artnet_names(short_name, long_name);
+        entry.update_mac(mac);
+        entry.offline = false;
+        entry.last_packet = Instant::now();
+        entry.fps_counter.record_packet();
+
+        // Track per-universe fps and sequence number for packet loss
+        if let (Some(seq), Some(univs)) = (sequence, universes.as_deref()) {
+            entry.record_universe_packet(univs, seq, &self.sequence_gaps);
+        }
+
+        // Track jitter
+        entry.source.latency_jitter_ms = entry.latency_tracker.record_packet();
+
+        entry.source.packet_count += 1;
+        entry.source.fps = entry.fps_counter.fps();
+        entry.source.last_seen = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        entry.update_status(Instant::now(), &thresholds);
 
         // Update direction - upgrade Unknown to specific, or to Both if conflicting
         entry.source.direction = match (entry.source.direction, direction) {
@@ -537,15 +1660,14 @@ impl SourceManager {
         // Update universes if provided
         if let Some(univs) = universes {
             for u in univs {
-                if !entry.source.universes.contains(&u) {
-                    entry.source.universes.push(u);
-                    entry.source.universes.sort();
-                }
+                entry.add_universe(u);
             }
         }
     }
 
-    /// Update or add an sACN source with direction info (for sniffer mode)
+    /// Update or add an sACN source with direction info (for sniffer mode),
+    /// returning the change when a data packet changes the priority of its universe
+    #[allow(clippy::too_many_arguments)]
     pub fn update_sacn_source_with_direction(
         &self,
         ip: IpAddr,
@@ -555,7 +1677,7 @@ impl SourceManager {
         universe: u16,
         direction: SourceDirection,
         sequence: Option<u8>,
-    ) {
+    ) -> Option<PriorityChange> {
         // For receiving-only devices without a real CID, use IP-based ID
         let id = if cid == &[0u8; 16] {
             format!("sacn-recv-{}", ip)
@@ -563,22 +1685,25 @@ impl SourceManager {
             let cid_string = crate::network::sacn::cid_to_string(cid);
             format!("sacn-{}", cid_string)
         };
+        let thresholds = self.get_thresholds();
         let mut sources = self.sources.write();
 
-        let entry = sources.entry(id.clone()).or_insert_with(|| SourceEntry {
-            source: NetworkSource::from_sacn(ip, source_name, cid, priority),
-            last_packet: Instant::now(),
-            fps_counter: FpsCounter::new(),
-            sequence_tracker: SequenceTracker::new(),
-            latency_tracker: LatencyTracker::new(),
+        let entry = sources.entry(id.clone()).or_insert_with(|| {
+            SourceEntry::new(NetworkSource::from_sacn(ip, source_name, cid, priority))
         });
 
+        // Names can change, e.g. a console renamed mid-session
+        entry.update_name(source_name);
+        if direction == SourceDirection::Sending {
+            entry.record_cid_ip(ip);
+        }
+        entry.offline = false;
         entry.last_packet = Instant::now();
         entry.fps_counter.record_packet();
 
-        // Track sequence number for packet loss
+        // Track per-universe fps and sequence number for packet loss
         if let Some(seq) = sequence {
-            entry.source.packet_loss_percent = entry.sequence_tracker.record_packet(seq);
+            entry.record_universe_packet(&[universe], seq, &self.sequence_gaps);
         }
 
         // Track jitter
@@ -590,10 +1715,9 @@ impl SourceManager {
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
             .as_millis() as u64;
-        entry
-            .source
-            .update_status(Instant::now(), entry.last_packet);
-        entry.source.sacn_priority = Some(priority);
+        entry.update_status(Instant::now(), &thresholds);
+        // Priority is only meaningful on data packets (discovery carries none)
+        let change = sequence.and_then(|_| entry.set_priority(universe, priority));
 
         // Update direction
         entry.source.direction = match (entry.source.direction, direction) {
@@ -604,35 +1728,542 @@ impl SourceManager {
         };
 
         // Add universe
-        if !entry.source.universes.contains(&universe) {
-            entry.source.universes.push(universe);
-            entry.source.universes.sort();
+        entry.add_universe(universe);
+        change
+    }
+
+    /// Update or add a source received by a protocol decoder, one per decoder and IP
+    pub fn update_decoded_source(
+        &self,
+        ip: IpAddr,
+        decoder: &str,
+        source_name: &str,
+        universes: &[u16],
+        direction: SourceDirection,
+        sequence: Option<u8>,
+    ) {
+        let id = format!("{}-{}", decoder, ip);
+        let thresholds = self.get_thresholds();
+        let mut sources = self.sources.write();
+
+        let entry = sources.entry(id.clone()).or_insert_with(|| {
+            SourceEntry::new(NetworkSource::from_decoder(ip, decoder, source_name))
+        });
+
+        entry.update_name(source_name);
+        entry.offline = false;
+        entry.last_packet = Instant::now();
+        entry.fps_counter.record_packet();
+
+        // Track per-universe fps and sequence number for packet loss
+        if let Some(seq) = sequence {
+            entry.record_universe_packet(universes, seq, &self.sequence_gaps);
+        }
+
+        // Track jitter
+        entry.source.latency_jitter_ms = entry.latency_tracker.record_packet();
+
+        entry.source.packet_count += 1;
+        entry.source.fps = entry.fps_counter.fps();
+        entry.source.last_seen = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        entry.update_status(Instant::now(), &thresholds);
+
+        entry.source.direction = match (entry.source.direction, direction) {
+            (SourceDirection::Unknown, d) => d,
+            (SourceDirection::Sending, SourceDirection::Receiving) => SourceDirection::Both,
+            (SourceDirection::Receiving, SourceDirection::Sending) => SourceDirection::Both,
+            (current, _) => current,
+        };
+
+        for &universe in universes {
+            entry.add_universe(universe);
         }
     }
 
-    /// Get all sources as a vector
-    pub fn get_all_sources(&self) -> Vec<NetworkSource> {
+    /// Count a received packet (UDP payload size) towards its sender's bandwidth,
+    /// noting the local interface it arrived on when known
+    pub fn record_traffic(
+        &self,
+        ip: IpAddr,
+        protocol: Protocol,
+        bytes: usize,
+        interface: Option<Ipv4Addr>,
+    ) {
+        self.traffic
+            .write()
+            .entry((ip, protocol))
+            .or_insert_with(TrafficCounter::new)
+            .record(bytes, interface);
+    }
+
+    /// Get total bandwidth per protocol
+    pub fn get_protocol_bandwidth(&self) -> Vec<ProtocolBandwidth> {
+        let mut totals: Vec<ProtocolBandwidth> = [Protocol::ArtNet, Protocol::Sacn]
+            .into_iter()
+            .map(|protocol| ProtocolBandwidth {
+                protocol,
+                bytes_per_second: 0.0,
+                packets_per_second: 0.0,
+                total_bytes: 0,
+                total_packets: 0,
+            })
+            .collect();
+        for ((_, protocol), counter) in self.traffic.read().iter() {
+            if let Some(total) = totals.iter_mut().find(|t| t.protocol == *protocol) {
+                total.bytes_per_second += counter.bytes_per_second;
+                total.packets_per_second += counter.packets_per_second;
+                total.total_bytes += counter.total_bytes;
+                total.total_packets += counter.total_packets;
+            }
+        }
+        totals
+    }
+
+    /// IPv4 addresses of the sACN sources sending or advertising each universe,
+    /// including known sources that are currently offline
+    pub fn sacn_source_ips(&self) -> HashMap<u16, Vec<Ipv4Addr>> {
+        let mut ips: HashMap<u16, Vec<Ipv4Addr>> = HashMap::new();
+        for entry in self.sources.read().values() {
+            let source = &entry.source;
+            let Ok(ip) = source.ip.parse::<Ipv4Addr>() else {
+                continue;
+            };
+            if source.protocol != Protocol::Sacn {
+                continue;
+            }
+            for universe in &source.universes {
+                let universe_ips = ips.entry(*universe).or_default();
+                if !universe_ips.contains(&ip) {
+                    universe_ips.push(ip);
+                }
+            }
+        }
+        ips
+    }
+
+    /// Compare the IP an ArtPollReply reports with the IP it was sent from
+    pub fn check_poll_reply_ip(&self, reply_ip: IpAddr, sender: IpAddr) {
+        let mut sources = self.sources.write();
+        let Some(entry) = sources.get_mut(&format!("artnet-{}", reply_ip)) else {
+            return;
+        };
+        let mismatch = (sender != reply_ip).then(|| sender.to_string());
+        if mismatch.is_some() && entry.source.reply_ip_mismatch != mismatch {
+            entry.push_event(
+                SourceEventKind::IpMismatch,
+                format!(
+                    "ArtPollReply reports {} but was sent from {}",
+                    reply_ip, sender
+                ),
+            );
+        }
+        entry.source.reply_ip_mismatch = mismatch;
+    }
+
+    /// Get FPS and packet loss per universe for a source
+    pub fn get_universe_stats(&self, id: &str) -> Result<Vec<SourceUniverseStats>, String> {
         let sources = self.sources.read();
-        sources.values().map(|e| e.source.clone()).collect()
+        let entry = sources
+            .get(id)
+            .ok_or_else(|| format!("Source {} not found", id))?;
+        let now = Instant::now();
+        let mut stats: Vec<SourceUniverseStats> = entry
+            .universe_trackers
+            .iter()
+            .map(|(universe, tracker)| SourceUniverseStats {
+                universe: *universe,
+                fps: tracker.fps_counter.fps(),
+                packet_loss_percent: tracker.packet_loss_percent,
+                packet_count: tracker.packet_count,
+                last_seen: tracker.last_seen,
+                stability: tracker.frame_rate.stability(now),
+                priority: tracker.priority,
+                lost_packets: tracker.sequence_tracker.lost_packets(),
+                out_of_order_packets: tracker.sequence_tracker.out_of_order_packets(),
+                duplicate_packets: tracker.sequence_tracker.duplicate_packets(),
+            })
+            .collect();
+        stats.sort_by_key(|s| s.universe);
+        Ok(stats)
+    }
+
+    /// Universes with more than one sender, each with all its senders
+    pub fn get_universe_conflicts(&self) -> Vec<UniverseConflict> {
+        let sources = self.sources.read();
+        let mut senders: HashMap<u16, Vec<ConflictSource>> = HashMap::new();
+        for entry in sources.values() {
+            let source = &entry.source;
+            // Universes of nodes only known from ArtPollReply are their outputs
+            let sending = matches!(
+                source.direction,
+                SourceDirection::Sending | SourceDirection::Both
+            );
+            if entry.offline || !sending {
+                continue;
+            }
+            for universe in &source.universes {
+                senders.entry(*universe).or_default().push(ConflictSource {
+                    source_id: source.id.clone(),
+                    name: source.alias.clone().unwrap_or_else(|| source.name.clone()),
+                    ip: source.ip.clone(),
+                    protocol: source.protocol,
+                    priority: entry.sending_priority(),
+                    status: source.status,
+                    fps: source.fps,
+                });
+            }
+        }
+
+        let mut conflicts: Vec<UniverseConflict> = senders
+            .into_iter()
+            .filter(|(_, sources)| sources.len() > 1)
+            .map(|(universe, mut sources)| {
+                sources.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.name.cmp(&b.name)));
+                let sacn = sources
+                    .iter()
+                    .filter(|s| s.protocol == Protocol::Sacn)
+                    .count();
+                let top = sources[0].priority;
+                let tied = sources.iter().filter(|s| s.priority == top).count();
+                let (kind, winner) = if sacn == 0 {
+                    (UniverseConflictKind::Merge, None)
+                } else if sacn < sources.len() {
+                    (UniverseConflictKind::MixedProtocols, None)
+                } else if tied > 1 {
+                    (UniverseConflictKind::PriorityConflict, None)
+                } else {
+                    (
+                        UniverseConflictKind::Backup,
+                        Some(sources[0].source_id.clone()),
+                    )
+                };
+                UniverseConflict {
+                    universe,
+                    kind,
+                    winner,
+                    sources,
+                }
+            })
+            .collect();
+        conflicts.sort_by_key(|c| c.universe);
+        conflicts
+    }
+
+    /// Every source with its event history
+    pub fn get_histories(&self) -> Vec<SourceHistory> {
+        self.sources
+            .read()
+            .values()
+            .map(|entry| SourceHistory {
+                source: entry.source.clone(),
+                events: entry.events.iter().cloned().collect(),
+            })
+            .collect()
+    }
+
+    /// Bring back sources and their history from before a restart. Sources not
+    /// heard yet this session come back offline with their packet count; events
+    /// recorded this session follow the restored ones.
+    pub fn restore_histories(&self, histories: Vec<SourceHistory>) {
+        let mut sources = self.sources.write();
+        for SourceHistory { source, events } in histories {
+            let (first_seen, packet_count) = (source.first_seen, source.packet_count);
+            let entry = sources
+                .entry(source.id.clone())
+                .or_insert_with(|| SourceEntry::offline(source));
+
+            let mut restored: VecDeque<SourceEvent> = events.into();
+            restored.append(&mut entry.events);
+            while restored.len() > MAX_SOURCE_EVENTS {
+                restored.pop_front();
+            }
+            entry.events = restored;
+            entry.source.first_seen = entry.source.first_seen.min(first_seen);
+            if entry.offline {
+                entry.source.packet_count = packet_count;
+            }
+        }
+    }
+
+    /// Get the event history of a source, oldest first
+    pub fn get_events(&self, id: &str) -> Result<Vec<SourceEvent>, String> {
+        self.sources
+            .read()
+            .get(id)
+            .map(|entry| entry.events.iter().cloned().collect())
+            .ok_or_else(|| format!("Source {} not found", id))
+    }
+
+    /// IP address of a source
+    pub fn get_source_ip(&self, id: &str) -> Result<IpAddr, String> {
+        let sources = self.sources.read();
+        let entry = sources
+            .get(id)
+            .ok_or_else(|| format!("Source {} not found", id))?;
+        entry
+            .source
+            .ip
+            .parse()
+            .map_err(|_| format!("Source {} has no valid IP address", id))
+    }
+
+    /// The newest `count` sequence gaps matching the filter, oldest first
+    pub fn get_sequence_gaps(
+        &self,
+        filter: &SequenceGapFilter,
+        count: Option<usize>,
+    ) -> Vec<SequenceGap> {
+        self.sequence_gaps.query(filter, count)
+    }
+
+    pub fn clear_sequence_gaps(&self) {
+        self.sequence_gaps.clear();
+    }
+
+    /// Record the Ethernet source MAC seen for an IP (sniffer mode)
+    #[cfg(feature = "sniffer")]
+    pub fn record_link_mac(&self, ip: IpAddr, mac: &[u8; 6]) {
+        let ip = ip.to_string();
+        let mac = mac_to_string(mac);
+        let mut sources = self.sources.write();
+        for entry in sources.values_mut() {
+            if entry.source.ip == ip && entry.source.link_mac.as_deref() != Some(mac.as_str()) {
+                entry.source.link_mac = Some(mac.clone());
+            }
+        }
+    }
+
+    /// Record the sender of a captured ARP packet (sniffer mode). Sources
+    /// without a MAC address, like sACN-only consoles that never send an
+    /// ArtPollReply, take it from here.
+    #[cfg(feature = "sniffer")]
+    pub fn record_arp(&self, ip: IpAddr, mac: &[u8; 6]) {
+        let mac = mac_to_string(mac);
+        self.arp_table
+            .write()
+            .insert(ip, (mac.clone(), Instant::now()));
+
+        let ip = ip.to_string();
+        for entry in self.sources.write().values_mut() {
+            if entry.source.ip == ip && entry.source.mac_address.is_none() {
+                entry.source.mac_address = Some(mac.clone());
+            }
+        }
+    }
+
+    /// IPs and MAC addresses learned from ARP, by IP
+    pub fn get_arp_table(&self) -> Vec<ArpMapping> {
+        let mut mappings: Vec<ArpMapping> = self
+            .arp_table
+            .read()
+            .iter()
+            .map(|(ip, (mac, last_seen))| ArpMapping {
+                ip: ip.to_string(),
+                mac: mac.clone(),
+                mac_vendor: self.oui.lookup(mac),
+                last_seen_secs: last_seen.elapsed().as_secs_f32(),
+            })
+            .collect();
+        mappings.sort_by_key(|m| m.ip.parse::<IpAddr>().ok());
+        mappings
+    }
+
+    /// Record the VLAN tags of frames captured from an IP (sniffer mode), None
+    /// for untagged frames
+    #[cfg(feature = "sniffer")]
+    pub fn record_vlan(&self, ip: IpAddr, vlan: Option<u16>, outer_vlan: Option<u16>) {
+        let ip = ip.to_string();
+        let mut sources = self.sources.write();
+        for entry in sources.values_mut() {
+            if entry.source.ip == ip {
+                entry.source.vlan = vlan;
+                entry.source.outer_vlan = outer_vlan;
+            }
+        }
+    }
+
+    /// Record the switch and port frames from an IP were captured behind
+    /// (sniffer mode)
+    #[cfg(feature = "sniffer")]
+    pub fn record_switch_port(&self, ip: IpAddr, (switch_name, switch_port): (String, String)) {
+        let ip = ip.to_string();
+        let mut sources = self.sources.write();
+        for entry in sources.values_mut() {
+            if entry.source.ip == ip {
+                entry.source.switch_name = Some(switch_name.clone());
+                entry.source.switch_port = Some(switch_port.clone());
+            }
+        }
+    }
+
+    /// Record the TTL of frames captured from an IP (sniffer mode), noting a
+    /// source event when its traffic starts arriving through routers
+    #[cfg(feature = "sniffer")]
+    pub fn record_ttl(&self, ip: IpAddr, ttl: u8) {
+        let ip = ip.to_string();
+        let hops = estimate_router_hops(ttl);
+        let mut sources = self.sources.write();
+        for entry in sources.values_mut() {
+            if entry.source.ip != ip || entry.source.ttl == Some(ttl) {
+                continue;
+            }
+            let was_routed = entry.source.router_hops.is_some_and(|h| h > 0);
+            if let Some(hops) = hops.filter(|&h| h > 0 && !was_routed) {
+                entry.push_event(
+                    SourceEventKind::Routed,
+                    format!(
+                        "Traffic arrives with TTL {}, about {} router hop{} away",
+                        ttl,
+                        hops,
+                        if hops == 1 { "" } else { "s" }
+                    ),
+                );
+            }
+            entry.source.ttl = Some(ttl);
+            entry.source.router_hops = hops;
+        }
+    }
+
+    /// Get all sources as a vector, leaving out hidden ones unless requested
+    pub fn contains_source(&self, id: &str) -> bool {
+        self.sources.read().contains_key(id)
+    }
+
+    pub fn get_all_sources(&self, include_hidden: bool) -> Vec<NetworkSource> {
+        let sources = self.sources.read();
+        sources
+            .values()
+            .filter(|e| include_hidden || !e.source.hidden)
+            .map(|e| e.source.clone())
+            .collect()
+    }
+
+    /// Set or clear (None / empty string) the user alias of a source
+    pub fn set_alias(&self, id: &str, alias: Option<String>) -> Result<(), String> {
+        self.annotate(id, |source| source.alias = non_empty(alias))
+    }
+
+    /// Set or clear (None / empty string) the user note of a source
+    pub fn set_note(&self, id: &str, note: Option<String>) -> Result<(), String> {
+        self.annotate(id, |source| source.note = non_empty(note))
+    }
+
+    /// Pin or unpin a source. Pinning a source also unhides it.
+    pub fn set_pinned(&self, id: &str, pinned: bool) -> Result<(), String> {
+        self.annotate(id, |source| {
+            source.pinned = pinned;
+            if pinned {
+                source.hidden = false;
+            }
+        })
+    }
+
+    /// Hide or unhide a source. Hiding a source also unpins it.
+    pub fn set_hidden(&self, id: &str, hidden: bool) -> Result<(), String> {
+        self.annotate(id, |source| {
+            source.hidden = hidden;
+            if hidden {
+                source.pinned = false;
+            }
+        })
+    }
+
+    /// Apply a user annotation and persist it straight away
+    fn annotate(&self, id: &str, apply: impl FnOnce(&mut NetworkSource)) -> Result<(), String> {
+        match self.sources.write().get_mut(id) {
+            Some(entry) => apply(&mut entry.source),
+            None => return Err(format!("Source {} not found", id)),
+        }
+        self.save_known_sources()
+    }
+
+    /// Remove a source from the list and the device database
+    pub fn remove_source(&self, id: &str) -> Result<(), String> {
+        if self.sources.write().remove(id).is_none() {
+            return Err(format!("Source {} not found", id));
+        }
+        self.save_known_sources()
+    }
+
+    /// Remove every source except pinned ones. Sources still transmitting
+    /// reappear as soon as their next packet arrives.
+    pub fn clear_sources(&self) -> Result<(), String> {
+        self.sources.write().retain(|_, entry| entry.source.pinned);
+        self.save_known_sources()
     }
 
     /// Update all source statuses, FPS warnings, and duplicate detection
     pub fn update_statuses(&self) {
         let now = Instant::now();
+        let thresholds = self.get_thresholds();
+        let mut traffic = self.traffic.write();
+        for counter in traffic.values_mut() {
+            counter.roll(now);
+        }
+        let arp_table = self.arp_table.read();
         let mut sources = self.sources.write();
+        merge_renumbered_sources(&mut sources);
 
         // Build universe -> source mapping for duplicate detection
         let mut universe_map: HashMap<u16, Vec<String>> = HashMap::new();
+        // Priorities of the sACN senders on each universe
+        let mut priority_map: HashMap<u16, Vec<u8>> = HashMap::new();
 
         for (id, entry) in sources.iter_mut() {
-            entry.source.update_status(now, entry.last_packet);
+            if entry.offline {
+                continue;
+            }
+            entry.update_status(now, &thresholds);
             entry.source.fps = entry.fps_counter.fps();
+            entry.update_duplicate_cid(now, Duration::from_millis(thresholds.stale_after_ms));
+            let mut wandering: Vec<u16> = entry
+                .universe_trackers
+                .iter()
+                .filter(|(_, tracker)| tracker.frame_rate.is_wandering(now))
+                .map(|(universe, _)| *universe)
+                .collect();
+            wandering.sort();
+            entry.source.wandering_universes = wandering;
+
+            // Sources sharing an IP and protocol (several sACN CIDs) share the figures
+            let counter = entry
+                .source
+                .ip
+                .parse::<IpAddr>()
+                .ok()
+                .and_then(|ip| traffic.get(&(ip, entry.source.protocol)));
+            entry.source.bytes_per_second = counter.map_or(0.0, |c| c.bytes_per_second);
+            entry.source.packets_per_second = counter.map_or(0.0, |c| c.packets_per_second);
+            if let Some(counter) = counter.filter(|c| !c.interfaces.is_empty()) {
+                entry.source.interfaces =
+                    counter.interfaces.iter().map(|i| i.to_string()).collect();
+            }
+
+            // Sources first seen after their ARP traffic
+            if entry.source.mac_address.is_none() {
+                entry.source.mac_address = entry
+                    .source
+                    .ip
+                    .parse::<IpAddr>()
+                    .ok()
+                    .and_then(|ip| arp_table.get(&ip))
+                    .map(|(mac, _)| mac.clone());
+            }
+
+            if entry.source.mac_vendor.is_none() {
+                if let Some(mac) = &entry.source.mac_address {
+                    entry.source.mac_vendor = self.oui.lookup(mac);
+                }
+            }
 
             // FPS warnings
             let fps = entry.source.fps;
-            entry.source.fps_warning = if fps > 0.0 && fps < self.fps_low_threshold {
+            entry.source.fps_warning = if fps > 0.0 && fps < thresholds.fps_low {
                 Some("low".to_string())
-            } else if fps > self.fps_high_threshold {
+            } else if fps > thresholds.fps_high {
                 Some("high".to_string())
             } else {
                 None
@@ -642,29 +2273,90 @@ impl SourceManager {
             for universe in &entry.source.universes {
                 universe_map.entry(*universe).or_default().push(id.clone());
             }
+            if let Some(priority) = entry.sending_priority() {
+                for universe in &entry.source.universes {
+                    priority_map.entry(*universe).or_default().push(priority);
+                }
+            }
         }
 
         // Store universe mapping
         *self.universe_sources.write() = universe_map.clone();
 
         // Update duplicate warnings on sources
-        for entry in sources.values_mut() {
+        for entry in sources.values_mut().filter(|e| !e.offline) {
             entry.source.duplicate_universes.clear();
+            entry.source.priority_conflicts.clear();
+            entry.source.backup_universes.clear();
+            let own_priority = entry.sending_priority();
             for universe in &entry.source.universes {
                 if let Some(source_ids) = universe_map.get(universe) {
                     if source_ids.len() > 1 {
                         entry.source.duplicate_universes.push(*universe);
                     }
                 }
+
+                // Equal priorities make receivers merge (HTP) or flicker between
+                // sources; a lower priority is an intentional backup
+                if let (Some(own), Some(priorities)) = (own_priority, priority_map.get(universe)) {
+                    if priorities.iter().filter(|p| **p == own).count() > 1 {
+                        entry.source.priority_conflicts.push(*universe);
+                    } else if priorities.iter().any(|p| *p > own) {
+                        entry.source.backup_universes.push(*universe);
+                    }
+                }
             }
         }
     }
 
-    /// Remove stale sources (inactive for more than 60 seconds)
+    /// Take stale sources (inactive past the offline threshold, 60 seconds by default)
+    /// offline. They stay listed as known devices and come back online when heard from,
+    /// until they have been offline past the forget threshold (7 days by default) or
+    /// the device database is over its size limit. Pinned sources are never forgotten.
     pub fn cleanup_stale_sources(&self) {
         let now = Instant::now();
+        let thresholds = self.get_thresholds();
+        let offline_after = Duration::from_millis(thresholds.offline_after_ms);
         let mut sources = self.sources.write();
-        sources.retain(|_, entry| now.duration_since(entry.last_packet) < Duration::from_secs(60));
+        for entry in sources.values_mut() {
+            if !entry.offline && now.duration_since(entry.last_packet) >= offline_after {
+                entry.take_offline();
+            }
+        }
+
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let forgettable = |entry: &SourceEntry| entry.offline && !entry.source.pinned;
+        let before = sources.len();
+        sources.retain(|_, entry| {
+            !forgettable(entry)
+                || now_ms.saturating_sub(entry.source.last_seen) < thresholds.forget_after_ms
+        });
+        if sources.len() > thresholds.max_known_sources {
+            let mut oldest: Vec<(u64, String)> = sources
+                .iter()
+                .filter(|(_, entry)| forgettable(entry))
+                .map(|(id, entry)| (entry.source.last_seen, id.clone()))
+                .collect();
+            oldest.sort();
+            for (_, id) in oldest
+                .into_iter()
+                .take(sources.len() - thresholds.max_known_sources)
+            {
+                sources.remove(&id);
+            }
+        }
+        let forgotten = before - sources.len();
+        drop(sources);
+        if forgotten > 0 {
+            info!("[Sources] Forgot {} long offline sources", forgotten);
+        }
+
+        self.traffic
+            .write()
+            .retain(|_, counter| now.duration_since(counter.last_packet) < offline_after);
     }
 }
 
@@ -674,6 +2366,85 @@ impl Default for SourceManager {
     }
 }
 
+/// Routers a packet crossed, from how far its TTL is below the nearest common
+/// initial TTL (64 for Linux, macOS and most embedded stacks, 128 for Windows,
+/// 255 for network gear). Low TTLs are usually chosen by the sender, e.g. the
+/// multicast default of 1, and tell nothing.
+#[cfg(feature = "sniffer")]
+fn estimate_router_hops(ttl: u8) -> Option<u8> {
+    const INITIAL_TTLS: [u8; 4] = [32, 64, 128, 255];
+    const MIN_INFORMATIVE_TTL: u8 = 17;
+
+    if ttl < MIN_INFORMATIVE_TTL {
+        return None;
+    }
+    INITIAL_TTLS
+        .iter()
+        .find(|&&initial| initial >= ttl)
+        .map(|initial| initial - ttl)
+}
+
+/// ID prefixes of sources keyed by IP. sACN senders are keyed by CID and
+/// keep their ID when their IP changes.
+const IP_KEYED_PREFIXES: [&str; 2] = ["artnet-", "sacn-recv-"];
+
+/// Fold sources that reappeared on a new IP (DHCP renumbering) into their old
+/// entry, matched by MAC address, instead of listing the device twice. The old
+/// entry must have gone quiet before the new one was first heard, so a device
+/// with several IPs on one MAC stays listed once per IP.
+fn merge_renumbered_sources(sources: &mut HashMap<String, SourceEntry>) {
+    let prefix = |id: &str| IP_KEYED_PREFIXES.into_iter().find(|p| id.starts_with(p));
+    let mut moves: Vec<(String, String)> = Vec::new();
+    for (new_id, new) in sources.iter() {
+        let (Some(mac), Some(new_prefix)) = (new.identity_mac(), prefix(new_id)) else {
+            continue;
+        };
+        if new.offline {
+            continue;
+        }
+        let old = sources
+            .iter()
+            .filter(|(id, old)| {
+                prefix(id) == Some(new_prefix)
+                    && old.source.ip != new.source.ip
+                    && old.identity_mac() == Some(mac)
+                    && old.source.last_seen <= new.source.first_seen
+            })
+            .min_by_key(|(_, old)| old.source.first_seen);
+        if let Some((old_id, _)) = old {
+            moves.push((old_id.clone(), new_id.clone()));
+        }
+    }
+
+    for (old_id, new_id) in moves {
+        // An entry may already have been merged into another
+        if !sources.contains_key(&old_id) || !sources.contains_key(&new_id) {
+            continue;
+        }
+        if let Some(old) = sources.remove(&old_id) {
+            if let Some(new) = sources.get_mut(&new_id) {
+                info!("[Sources] {} moved to {}", old_id, new_id);
+                new.inherit(old);
+            }
+        }
+    }
+}
+
+/// MAC address as upper-case colon separated hex
+pub fn mac_to_string(mac: &[u8; 6]) -> String {
+    format!(
+        "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
+        mac[0], mac[1], mac[2], mac[3], mac[4], mac[5]
+    )
+}
+
+/// Trim a user supplied string, treating empty as unset
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
 /// Thread-safe source manager handle
 pub type SourceManagerHandle = Arc<SourceManager>;
 
@@ -0,0 +1,264 @@
+// Broadcast storm detection - Aggregate broadcast and multicast rates in sniffer mode
+//
+// A controller broadcasting dozens of Art-Net universes onto a flat network
+// makes every device process every packet. The sniffer counts the broadcast
+// and multicast frames it captures per second and raises an alert when either
+// rate passes its limit, naming the busiest sender, and again once the rate
+// has settled below the limit.
+
+use crate::network::bus::EventBusHandle;
+use crate::network::listener::ListenerEvent;
+use crate::network::sniffer::SnifferStateHandle;
+use crate::persistence::{load_json, save_json};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// How often the rates are measured
+const STORM_WINDOW: Duration = Duration::from_secs(1);
+
+/// A storm ends when the rate falls below this share of the limit, so a rate
+/// hovering around the limit doesn't alert on every window
+const STORM_CLEAR_RATIO: f32 = 0.8;
+
+/// Broadcast storm limits in packets per second
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StormConfig {
+    pub broadcast_pps: u32,
+    pub multicast_pps: u32, // sACN is multicast, leave room for its universes
+}
+
+impl Default for StormConfig {
+    fn default() -> Self {
+        Self {
+            broadcast_pps: 2000,
+            multicast_pps: 20000,
+        }
+    }
+}
+
+/// Which class of traffic a storm is made of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StormTraffic {
+    Broadcast,
+    Multicast,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StormAlertKind {
+    Started,
+    Ended,
+}
+
+/// Alert raised when a broadcast or multicast rate crosses its limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StormAlert {
+    pub traffic: StormTraffic,
+    pub kind: StormAlertKind,
+    pub packets_per_second: f32,
+    pub limit: u32,
+    pub top_sender: Option<String>, // IP or MAC sending the most of this traffic
+    pub message: String,
+    pub timestamp: u64,
+}
+
+/// Current broadcast and multicast rates with the limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StormStatus {
+    pub config: StormConfig,
+    pub broadcast_pps: f32,
+    pub multicast_pps: f32,
+    pub broadcast_storm: bool,
+    pub multicast_storm: bool,
+    pub top_broadcast_sender: Option<String>,
+    pub top_multicast_sender: Option<String>,
+}
+
+/// Frame counts of one traffic class
+#[derive(Default)]
+struct TrafficWindow {
+    packets: u64,
+    senders: HashMap<String, u64>,
+    rate: f32,
+    top_sender: Option<String>,
+    storm: bool,
+}
+
+impl TrafficWindow {
+    /// Turn the counts into a rate and report a storm starting or ending
+    fn roll(&mut self, traffic: StormTraffic, secs: f32, limit: u32) -> Option<StormAlert> {
+        self.rate = self.packets as f32 / secs;
+        self.top_sender = self
+            .senders
+            .drain()
+            .max_by_key(|(_, packets)| *packets)
+            .map(|(sender, _)| sender);
+        self.packets = 0;
+
+        let kind = if !self.storm && self.rate > limit as f32 {
+            StormAlertKind::Started
+        } else if self.storm && self.rate < limit as f32 * STORM_CLEAR_RATIO {
+            StormAlertKind::Ended
+        } else {
+            return None;
+        };
+        self.storm = kind == StormAlertKind::Started;
+
+        let name = match traffic {
+            StormTraffic::Broadcast => "Broadcast",
+            StormTraffic::Multicast => "Multicast",
+        };
+        let message = match kind {
+            StormAlertKind::Started => format!(
+                "{} storm: {:.0} packets/s (limit {}), mostly from {}",
+                name,
+                self.rate,
+                limit,
+                self.top_sender.as_deref().unwrap_or("unknown senders")
+            ),
+            StormAlertKind::Ended => {
+                format!("{} traffic back to {:.0} packets/s", name, self.rate)
+            }
+        };
+        Some(StormAlert {
+            traffic,
+            kind,
+            packets_per_second: self.rate,
+            limit,
+            top_sender: self.top_sender.clone(),
+            message,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        })
+    }
+}
+
+struct StormWindows {
+    start: Instant,
+    broadcast: TrafficWindow,
+    multicast: TrafficWindow,
+}
+
+/// Broadcast and multicast rate tracking with persistent limits
+pub struct StormDetector {
+    config: RwLock<StormConfig>,
+    config_path: RwLock<Option<PathBuf>>,
+    windows: Mutex<StormWindows>,
+}
+
+impl StormDetector {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(StormConfig::default()),
+            config_path: RwLock::new(None),
+            windows: Mutex::new(StormWindows {
+                start: Instant::now(),
+                broadcast: TrafficWindow::default(),
+                multicast: TrafficWindow::default(),
+            }),
+        }
+    }
+
+    /// Load the limits from disk and persist future changes to the same file
+    pub fn load_config(&self, path: PathBuf) {
+        *self.config.write() = load_json(&path);
+        *self.config_path.write() = Some(path);
+    }
+
+    pub fn set_config(&self, config: StormConfig) -> Result<(), String> {
+        if config.broadcast_pps == 0 || config.multicast_pps == 0 {
+            return Err("Storm limits must be at least 1 packet per second".to_string());
+        }
+        *self.config.write() = config;
+        match self.config_path.read().as_ref() {
+            Some(path) => save_json(path, &*self.config.read()),
+            None => Ok(()),
+        }
+    }
+
+    /// Count a captured broadcast or multicast frame
+    #[cfg(feature = "sniffer")]
+    pub fn record(&self, traffic: StormTraffic, sender: Option<String>) {
+        let mut windows = self.windows.lock();
+        let window = match traffic {
+            StormTraffic::Broadcast => &mut windows.broadcast,
+            StormTraffic::Multicast => &mut windows.multicast,
+        };
+        window.packets += 1;
+        if let Some(sender) = sender {
+            *window.senders.entry(sender).or_insert(0) += 1;
+        }
+    }
+
+    /// Measure the rates once a window has passed, returning any alerts
+    pub fn roll_if_due(&self) -> Vec<StormAlert> {
+        let mut windows = self.windows.lock();
+        let elapsed = windows.start.elapsed();
+        if elapsed < STORM_WINDOW {
+            return Vec::new();
+        }
+        let secs = elapsed.as_secs_f32();
+        let config = self.config.read().clone();
+        windows.start = Instant::now();
+        [
+            windows
+                .broadcast
+                .roll(StormTraffic::Broadcast, secs, config.broadcast_pps),
+            windows
+                .multicast
+                .roll(StormTraffic::Multicast, secs, config.multicast_pps),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Forget the counts and storms of a previous capture
+    pub fn reset(&self) {
+        let mut windows = self.windows.lock();
+        windows.start = Instant::now();
+        windows.broadcast = TrafficWindow::default();
+        windows.multicast = TrafficWindow::default();
+    }
+
+    pub fn get_status(&self) -> StormStatus {
+        let windows = self.windows.lock();
+        StormStatus {
+            config: self.config.read().clone(),
+            broadcast_pps: windows.broadcast.rate,
+            multicast_pps: windows.multicast.rate,
+            broadcast_storm: windows.broadcast.storm,
+            multicast_storm: windows.multicast.storm,
+            top_broadcast_sender: windows.broadcast.top_sender.clone(),
+            top_multicast_sender: windows.multicast.top_sender.clone(),
+        }
+    }
+}
+
+impl Default for StormDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Measure the broadcast and multicast rates every window and send the storm
+/// alerts. Rates stay at zero while the sniffer isn't capturing.
+pub async fn start_storm_monitor(sniffer_state: SnifferStateHandle, event_tx: EventBusHandle) {
+    let mut interval = tokio::time::interval(STORM_WINDOW);
+    loop {
+        interval.tick().await;
+        for alert in sniffer_state.storm.roll_if_due() {
+            warn!("[Storm] {}", alert.message);
+            let _ = event_tx.send(ListenerEvent::StormAlert(alert));
+        }
+    }
+}
@@ -0,0 +1,596 @@
+// Timecode comparison - Offset and drift between the timecode feeds on the network
+//
+// Consoles and media servers following the same show send Art-Net ArtTimeCode,
+// and MIDI timecode arrives through protocol decoders. Every frame is stamped
+// with its arrival, so while two feeds run, each new frame of one can be
+// compared with where the other is at that moment: the offset is how far one
+// runs ahead. How the offset changes over the last minute is the drift, e.g. a
+// console on its own clock slowly walking away from the show. A pair of feeds
+// is flagged when its offset or drift passes its limit, and again once both
+// are back within. A jump in the offset is a feed relocating (a cue jumping),
+// not drift, and restarts the drift measurement.
+
+use crate::persistence::{load_json, save_json};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Feeds not heard from for this long are forgotten
+const FEED_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A running feed sends every frame, one quiet for longer has stopped
+const FEED_STALE: Duration = Duration::from_millis(500);
+
+/// Offset samples a pair keeps for its drift
+const DRIFT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Minimum time between two offset samples of a pair
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Latest samples averaged into the offset shown, smoothing out the frame
+/// steps and arrival jitter
+const OFFSET_SAMPLES: usize = 5;
+
+/// Time the samples have to span before a drift is reported
+const MIN_DRIFT_SPAN: Duration = Duration::from_secs(10);
+
+/// Offset change between two samples taken as a relocation
+const RELOCATE_MS: f64 = 1000.0;
+
+/// A flagged pair clears once below this share of both limits, so an offset
+/// hovering around a limit doesn't alert on every sample
+const CLEAR_RATIO: f64 = 0.8;
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// Frame rate of a timecode, numbered as in ArtTimeCode and MTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimecodeRate {
+    Film,      // 24 fps
+    Ebu,       // 25 fps
+    DropFrame, // 29.97 fps
+    Smpte,     // 30 fps
+}
+
+impl TimecodeRate {
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(TimecodeRate::Film),
+            1 => Some(TimecodeRate::Ebu),
+            2 => Some(TimecodeRate::DropFrame),
+            3 => Some(TimecodeRate::Smpte),
+            _ => None,
+        }
+    }
+
+    /// Frame numbers per second, 30 for drop frame
+    fn nominal_fps(self) -> u64 {
+        match self {
+            TimecodeRate::Film => 24,
+            TimecodeRate::Ebu => 25,
+            TimecodeRate::DropFrame | TimecodeRate::Smpte => 30,
+        }
+    }
+
+    pub fn fps(self) -> f64 {
+        match self {
+            TimecodeRate::DropFrame => 30000.0 / 1001.0,
+            rate => rate.nominal_fps() as f64,
+        }
+    }
+}
+
+/// A timecode value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub rate: TimecodeRate,
+}
+
+impl Timecode {
+    /// None when a field is out of range for the rate
+    pub fn new(
+        hours: u8,
+        minutes: u8,
+        seconds: u8,
+        frames: u8,
+        rate: TimecodeRate,
+    ) -> Option<Self> {
+        (hours < 24 && minutes < 60 && seconds < 60 && (frames as u64) < rate.nominal_fps())
+            .then_some(Self {
+                hours,
+                minutes,
+                seconds,
+                frames,
+                rate,
+            })
+    }
+
+    /// Timecode of an MTC full frame message, F0 7F <device> 01 01 hh mm ss ff F7.
+    /// The rate is in bits 5-6 of the hours.
+    pub fn from_mtc_full_frame(sysex: &[u8]) -> Option<Self> {
+        let [0xF0, 0x7F, _, 0x01, 0x01, hours, minutes, seconds, frames, 0xF7, ..] = *sysex else {
+            return None;
+        };
+        let rate = TimecodeRate::from_code((hours >> 5) & 0x03)?;
+        Self::new(hours & 0x1F, minutes, seconds, frames, rate)
+    }
+
+    /// Frames since midnight, leaving out the numbers drop frame skips: 0 and
+    /// 1 of every minute but each tenth
+    fn frame_number(&self) -> u64 {
+        let minutes = self.hours as u64 * 60 + self.minutes as u64;
+        let number =
+            (minutes * 60 + self.seconds as u64) * self.rate.nominal_fps() + self.frames as u64;
+        match self.rate {
+            TimecodeRate::DropFrame => number - 2 * (minutes - minutes / 10),
+            _ => number,
+        }
+    }
+
+    fn from_frame_number(number: u64, rate: TimecodeRate) -> Self {
+        let fps = rate.nominal_fps();
+        let mut number = number;
+        if rate == TimecodeRate::DropFrame {
+            // 17982 frames every ten minutes, 1798 in each minute after the first
+            let (tens, rest) = (number / 17982, number % 17982);
+            number += 18 * tens + if rest > 1 { 2 * ((rest - 2) / 1798) } else { 0 };
+        }
+        let number = number % (86400 * fps);
+        let seconds = number / fps;
+        Self {
+            hours: (seconds / 3600) as u8,
+            minutes: (seconds / 60 % 60) as u8,
+            seconds: (seconds % 60) as u8,
+            frames: (number % fps) as u8,
+            rate,
+        }
+    }
+
+    /// Seconds since midnight
+    pub fn position(&self) -> f64 {
+        self.frame_number() as f64 / self.rate.fps()
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.rate == TimecodeRate::DropFrame {
+            ';'
+        } else {
+            ':'
+        };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours, self.minutes, self.seconds, separator, self.frames
+        )
+    }
+}
+
+/// Assembles the eight MTC quarter frame messages (F1 0nnn dddd) of one sender
+#[derive(Debug, Clone, Default)]
+pub struct MtcQuarterFrames {
+    pieces: [u8; 8],
+    received: u8, // Bit per piece since the last piece 0
+}
+
+impl MtcQuarterFrames {
+    /// Add the data byte of a quarter frame message, returning the timecode
+    /// once piece 7 completes a set. A set takes two frames to send and
+    /// carries the frame it started on, so the timecode is two frames on.
+    pub fn push(&mut self, data: u8) -> Option<Timecode> {
+        let piece = (data >> 4) & 0x07;
+        if piece == 0 {
+            self.received = 0;
+        }
+        self.pieces[piece as usize] = data & 0x0F;
+        self.received |= 1 << piece;
+        if piece != 7 || self.received != 0xFF {
+            return None;
+        }
+        let p = &self.pieces;
+        let rate = TimecodeRate::from_code((p[7] >> 1) & 0x03)?;
+        let timecode = Timecode::new(
+            p[6] | ((p[7] & 0x01) << 4),
+            p[4] | ((p[5] & 0x03) << 4),
+            p[2] | ((p[3] & 0x03) << 4),
+            p[0] | ((p[1] & 0x01) << 4),
+            rate,
+        )?;
+        Some(Timecode::from_frame_number(
+            timecode.frame_number() + 2,
+            rate,
+        ))
+    }
+}
+
+/// Limits above which two feeds are flagged as diverged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimecodeConfig {
+    pub max_offset_ms: f64,
+    pub max_drift_ms_per_minute: f64,
+}
+
+impl Default for TimecodeConfig {
+    fn default() -> Self {
+        Self {
+            max_offset_ms: 80.0, // Two frames at 25 fps
+            max_drift_ms_per_minute: 20.0,
+        }
+    }
+}
+
+/// Latest frame of one timecode feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimecodeFeed {
+    pub id: String,        // Source id, with the stream when not the master stream
+    pub source_id: String, // e.g. "artnet-10.0.0.5"
+    pub stream: u8,
+    pub timecode: Timecode,
+    pub display: String, // HH:MM:SS:FF
+    pub running: bool,
+    pub last_seen: u64, // Unix timestamp ms
+}
+
+/// Offset and drift of one pair of feeds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimecodeComparison {
+    pub feed_a: String,
+    pub feed_b: String,
+    pub offset_ms: Option<f64>,           // How far a runs ahead of b
+    pub drift_ms_per_minute: Option<f64>, // Change of the offset, None until measured
+    pub samples: usize,
+    pub comparing: bool, // Both feeds running
+    pub diverged: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimecodeDriftKind {
+    Diverged,
+    Realigned,
+}
+
+/// Alert raised when two feeds pass the offset or drift limit, or are back within
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimecodeDriftAlert {
+    pub feed_a: String,
+    pub feed_b: String,
+    pub kind: TimecodeDriftKind,
+    pub offset_ms: f64,
+    pub drift_ms_per_minute: Option<f64>,
+    pub message: String,
+    pub timestamp: u64,
+}
+
+/// Feeds and their comparisons with the limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimecodeStatus {
+    pub config: TimecodeConfig,
+    pub feeds: Vec<TimecodeFeed>,
+    pub comparisons: Vec<TimecodeComparison>,
+}
+
+struct FeedState {
+    feed: TimecodeFeed,
+    position: f64,
+    received: Instant,
+}
+
+impl FeedState {
+    fn running_at(&self, now: Instant) -> bool {
+        self.feed.running && now.duration_since(self.received) < FEED_STALE
+    }
+
+    /// Position extrapolated to a moment after its last frame
+    fn position_at(&self, now: Instant) -> f64 {
+        self.position + now.duration_since(self.received).as_secs_f64()
+    }
+}
+
+#[derive(Default)]
+struct PairState {
+    samples: VecDeque<(Instant, f64)>, // Offsets in ms
+    diverged: bool,
+}
+
+impl PairState {
+    fn offset_ms(&self) -> Option<f64> {
+        let recent = self.samples.iter().rev().take(OFFSET_SAMPLES);
+        let count = recent.len();
+        (count > 0).then(|| recent.map(|(_, offset)| offset).sum::<f64>() / count as f64)
+    }
+
+    /// Least squares slope of the offsets in ms per minute
+    fn drift_ms_per_minute(&self) -> Option<f64> {
+        let (first, _) = *self.samples.front()?;
+        let (last, _) = *self.samples.back()?;
+        if last.duration_since(first) < MIN_DRIFT_SPAN {
+            return None;
+        }
+        let count = self.samples.len() as f64;
+        let points = self
+            .samples
+            .iter()
+            .map(|(at, offset)| (at.duration_since(first).as_secs_f64(), *offset));
+        let (sum_t, sum_offset) = points
+            .clone()
+            .fold((0.0, 0.0), |(t, o), (at, offset)| (t + at, o + offset));
+        let (mean_t, mean_offset) = (sum_t / count, sum_offset / count);
+        let (covariance, variance) = points.fold((0.0, 0.0), |(c, v), (at, offset)| {
+            (
+                c + (at - mean_t) * (offset - mean_offset),
+                v + (at - mean_t).powi(2),
+            )
+        });
+        (variance > 0.0).then(|| covariance / variance * 60.0)
+    }
+
+    /// Add an offset sample, returning whether the pair diverged or realigned
+    fn record(
+        &mut self,
+        now: Instant,
+        offset_ms: f64,
+        config: &TimecodeConfig,
+    ) -> Option<TimecodeDriftKind> {
+        if let Some((at, last)) = self.samples.back() {
+            if now.duration_since(*at) < SAMPLE_INTERVAL {
+                return None;
+            }
+            if (offset_ms - last).abs() > RELOCATE_MS {
+                self.samples.clear();
+            }
+        }
+        while self
+            .samples
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > DRIFT_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((now, offset_ms));
+        if self.samples.len() < OFFSET_SAMPLES {
+            return None;
+        }
+
+        let offset = self.offset_ms()?.abs();
+        let drift = self.drift_ms_per_minute().map(f64::abs);
+        let kind = if !self.diverged
+            && (offset > config.max_offset_ms
+                || drift.is_some_and(|d| d > config.max_drift_ms_per_minute))
+        {
+            TimecodeDriftKind::Diverged
+        } else if self.diverged
+            && offset < config.max_offset_ms * CLEAR_RATIO
+            && drift.is_none_or(|d| d < config.max_drift_ms_per_minute * CLEAR_RATIO)
+        {
+            TimecodeDriftKind::Realigned
+        } else {
+            return None;
+        };
+        self.diverged = kind == TimecodeDriftKind::Diverged;
+        Some(kind)
+    }
+}
+
+#[derive(Default)]
+struct TrackerState {
+    feeds: HashMap<String, FeedState>,
+    pairs: HashMap<(String, String), PairState>, // Feed ids in order
+}
+
+/// Timecode feeds and the offsets between them, with persistent limits
+pub struct TimecodeTracker {
+    config: RwLock<TimecodeConfig>,
+    config_path: RwLock<Option<PathBuf>>,
+    state: Mutex<TrackerState>,
+}
+
+impl TimecodeTracker {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(TimecodeConfig::default()),
+            config_path: RwLock::new(None),
+            state: Mutex::new(TrackerState::default()),
+        }
+    }
+
+    /// Load the limits from disk and persist future changes to the same file
+    pub fn load_config(&self, path: PathBuf) {
+        *self.config.write() = load_json(&path);
+        *self.config_path.write() = Some(path);
+    }
+
+    pub fn set_config(&self, config: TimecodeConfig) -> Result<(), String> {
+        if config.max_offset_ms <= 0.0 || config.max_drift_ms_per_minute <= 0.0 {
+            return Err("Timecode limits must be above 0 ms".to_string());
+        }
+        *self.config.write() = config;
+        match self.config_path.read().as_ref() {
+            Some(path) => save_json(path, &*self.config.read()),
+            None => Ok(()),
+        }
+    }
+
+    /// Note a frame of a feed and compare it with the other running feeds,
+    /// returning the pairs that diverged or realigned
+    pub fn record(
+        &self,
+        source_id: &str,
+        stream: u8,
+        timecode: Timecode,
+    ) -> Vec<TimecodeDriftAlert> {
+        let now = Instant::now();
+        let config = self.config.read().clone();
+        let id = match stream {
+            0 => source_id.to_string(),
+            stream => format!("{} stream {}", source_id, stream),
+        };
+        let position = timecode.position();
+        let last_seen = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        let mut state = self.state.lock();
+        let TrackerState { feeds, pairs } = &mut *state;
+        feeds.retain(|_, feed| now.duration_since(feed.received) < FEED_TIMEOUT);
+        pairs.retain(|(a, b), _| feeds.contains_key(a) && feeds.contains_key(b));
+
+        let running = feeds.get(&id).is_some_and(|feed| feed.position != position);
+        feeds.insert(
+            id.clone(),
+            FeedState {
+                feed: TimecodeFeed {
+                    id: id.clone(),
+                    source_id: source_id.to_string(),
+                    stream,
+                    timecode,
+                    display: timecode.to_string(),
+                    running,
+                    last_seen,
+                },
+                position,
+                received: now,
+            },
+        );
+        if !running {
+            return Vec::new();
+        }
+
+        let mut alerts = Vec::new();
+        for other in feeds
+            .values()
+            .filter(|f| f.feed.id != id && f.running_at(now))
+        {
+            let ahead_ms = wrap_day(position - other.position_at(now)) * 1000.0;
+            let (feed_a, feed_b, offset_ms) = if id < other.feed.id {
+                (id.clone(), other.feed.id.clone(), ahead_ms)
+            } else {
+                (other.feed.id.clone(), id.clone(), -ahead_ms)
+            };
+            let pair = pairs.entry((feed_a.clone(), feed_b.clone())).or_default();
+            if let Some(kind) = pair.record(now, offset_ms, &config) {
+                alerts.push(drift_alert(feed_a, feed_b, kind, pair, &config, last_seen));
+            }
+        }
+        alerts
+    }
+
+    pub fn get_status(&self) -> TimecodeStatus {
+        let now = Instant::now();
+        let state = self.state.lock();
+        let live = |id: &str| {
+            state
+                .feeds
+                .get(id)
+                .filter(|feed| now.duration_since(feed.received) < FEED_TIMEOUT)
+        };
+
+        let mut feeds: Vec<TimecodeFeed> = state
+            .feeds
+            .values()
+            .filter(|feed| now.duration_since(feed.received) < FEED_TIMEOUT)
+            .map(|feed| TimecodeFeed {
+                running: feed.running_at(now),
+                ..feed.feed.clone()
+            })
+            .collect();
+        feeds.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut comparisons: Vec<TimecodeComparison> = state
+            .pairs
+            .iter()
+            .filter_map(|((a, b), pair)| {
+                let (feed_a, feed_b) = (live(a)?, live(b)?);
+                Some(TimecodeComparison {
+                    feed_a: a.clone(),
+                    feed_b: b.clone(),
+                    offset_ms: pair.offset_ms(),
+                    drift_ms_per_minute: pair.drift_ms_per_minute(),
+                    samples: pair.samples.len(),
+                    comparing: feed_a.running_at(now) && feed_b.running_at(now),
+                    diverged: pair.diverged,
+                })
+            })
+            .collect();
+        comparisons.sort_by(|a, b| a.feed_a.cmp(&b.feed_a).then(a.feed_b.cmp(&b.feed_b)));
+
+        TimecodeStatus {
+            config: self.config.read().clone(),
+            feeds,
+            comparisons,
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        state.feeds.clear();
+        state.pairs.clear();
+    }
+}
+
+impl Default for TimecodeTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A difference of positions within ±12 hours, for feeds either side of midnight
+fn wrap_day(seconds: f64) -> f64 {
+    (seconds + SECONDS_PER_DAY / 2.0).rem_euclid(SECONDS_PER_DAY) - SECONDS_PER_DAY / 2.0
+}
+
+fn drift_alert(
+    feed_a: String,
+    feed_b: String,
+    kind: TimecodeDriftKind,
+    pair: &PairState,
+    config: &TimecodeConfig,
+    timestamp: u64,
+) -> TimecodeDriftAlert {
+    let offset_ms = pair.offset_ms().unwrap_or_default();
+    let drift_ms_per_minute = pair.drift_ms_per_minute();
+    let drift = drift_ms_per_minute
+        .map(|drift| format!(", drifting {:+.0} ms/min", drift))
+        .unwrap_or_default();
+    let message = match kind {
+        TimecodeDriftKind::Diverged => format!(
+            "Timecode {} is {:.0} ms {} {}{} (limits {:.0} ms, {:.0} ms/min)",
+            feed_a,
+            offset_ms.abs(),
+            if offset_ms >= 0.0 {
+                "ahead of"
+            } else {
+                "behind"
+            },
+            feed_b,
+            drift,
+            config.max_offset_ms,
+            config.max_drift_ms_per_minute
+        ),
+        TimecodeDriftKind::Realigned => format!(
+            "Timecode {} and {} back within limits, {:+.0} ms apart{}",
+            feed_a, feed_b, offset_ms, drift
+        ),
+    };
+    TimecodeDriftAlert {
+        feed_a,
+        feed_b,
+        kind,
+        offset_ms,
+        drift_ms_per_minute,
+        message,
+        timestamp,
+    }
+}
@@ -0,0 +1,161 @@
+// Topology module - Groups discovered sources by IP subnet and link-layer MAC
+//
+// Subnets are derived from a fixed prefix length since the interface netmask
+// is not available to us. Link MACs are only known in sniffer mode, where the
+// Ethernet header of each captured frame is inspected.
+
+use crate::network::source::{NetworkSource, Protocol, SourceStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr};
+
+/// Prefix length used when the frontend does not ask for one
+pub const DEFAULT_TOPOLOGY_PREFIX_LEN: u8 = 24;
+
+/// A source as placed on the network map
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyNode {
+    pub id: String,
+    pub name: String,
+    pub ip: String,
+    pub protocol: Protocol,
+    pub status: SourceStatus,
+    pub mac_address: Option<String>,
+    pub link_mac: Option<String>,
+}
+
+/// All sources sharing one subnet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologySubnet {
+    pub subnet: String, // e.g. "10.0.0.0/24"
+    /// Addresses of this machine on the subnet; empty means we are not on it
+    pub local_ips: Vec<String>,
+    pub nodes: Vec<TopologyNode>,
+}
+
+/// A link MAC seen sending for more than one IP, usually a router or a
+/// device with several addresses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyLinkGroup {
+    pub mac: String,
+    pub ips: Vec<String>,
+    pub subnets: Vec<String>,
+}
+
+/// Network map data for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkTopology {
+    pub prefix_len: u8,
+    pub subnets: Vec<TopologySubnet>,
+    pub link_groups: Vec<TopologyLinkGroup>,
+}
+
+/// Build the network map from a list of sources
+pub fn build_topology(
+    sources: &[NetworkSource],
+    prefix_len: u8,
+) -> Result<NetworkTopology, String> {
+    if !(8..=32).contains(&prefix_len) {
+        return Err("Prefix length must be between 8 and 32".to_string());
+    }
+
+    let mut subnets: BTreeMap<String, TopologySubnet> = BTreeMap::new();
+
+    // Our own interfaces, so the frontend can tell which subnets we are on
+    if let Ok(ifaces) = local_ip_address::list_afinet_netifas() {
+        for (_, ip) in ifaces {
+            if let IpAddr::V4(ipv4) = ip {
+                if ipv4.is_loopback() {
+                    continue;
+                }
+                let subnet = subnets
+                    .entry(subnet_key(ip, prefix_len))
+                    .or_insert_with_key(|key| empty_subnet(key));
+                let ip = ipv4.to_string();
+                if !subnet.local_ips.contains(&ip) {
+                    subnet.local_ips.push(ip);
+                }
+            }
+        }
+    }
+
+    let mut link_macs: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for source in sources {
+        let key = source
+            .ip
+            .parse::<IpAddr>()
+            .map(|ip| subnet_key(ip, prefix_len))
+            .unwrap_or_else(|_| source.ip.clone());
+
+        if let Some(mac) = &source.link_mac {
+            let ips = link_macs.entry(mac.clone()).or_default();
+            if !ips.iter().any(|(ip, _)| *ip == source.ip) {
+                ips.push((source.ip.clone(), key.clone()));
+            }
+        }
+
+        subnets
+            .entry(key)
+            .or_insert_with_key(|key| empty_subnet(key))
+            .nodes
+            .push(TopologyNode {
+                id: source.id.clone(),
+                name: source.alias.clone().unwrap_or_else(|| source.name.clone()),
+                ip: source.ip.clone(),
+                protocol: source.protocol,
+                status: source.status,
+                mac_address: source.mac_address.clone(),
+                link_mac: source.link_mac.clone(),
+            });
+    }
+
+    let mut link_groups: Vec<TopologyLinkGroup> = link_macs
+        .into_iter()
+        .filter(|(_, ips)| ips.len() > 1)
+        .map(|(mac, ips)| {
+            let mut subnets: Vec<String> = ips.iter().map(|(_, s)| s.clone()).collect();
+            subnets.sort();
+            subnets.dedup();
+            TopologyLinkGroup {
+                mac,
+                ips: ips.into_iter().map(|(ip, _)| ip).collect(),
+                subnets,
+            }
+        })
+        .collect();
+    link_groups.sort_by(|a, b| a.mac.cmp(&b.mac));
+
+    let mut subnets: Vec<TopologySubnet> = subnets.into_values().collect();
+    for subnet in &mut subnets {
+        subnet
+            .nodes
+            .sort_by(|a, b| a.ip.cmp(&b.ip).then(a.id.cmp(&b.id)));
+    }
+
+    Ok(NetworkTopology {
+        prefix_len,
+        subnets,
+        link_groups,
+    })
+}
+
+/// Subnet of an address as "network/prefix"
+fn subnet_key(ip: IpAddr, prefix_len: u8) -> String {
+    match ip {
+        IpAddr::V4(ipv4) => {
+            let mask = u32::MAX << (32 - prefix_len as u32);
+            let network = Ipv4Addr::from(u32::from(ipv4) & mask);
+            format!("{}/{}", network, prefix_len)
+        }
+        // Lighting networks are IPv4; keep any IPv6 source on its own
+        IpAddr::V6(ipv6) => format!("{}/128", ipv6),
+    }
+}
+
+fn empty_subnet(key: &str) -> TopologySubnet {
+    TopologySubnet {
+        subnet: key.to_string(),
+        local_ips: Vec::new(),
+        nodes: Vec::new(),
+    }
+}
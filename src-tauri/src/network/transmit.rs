@@ -0,0 +1,973 @@
+// Transmit - DMX output generation for verifying wiring and node output
+//
+// Outputs run as independent tasks driven by an OutputState that the
+// TransmitManager tracks, so they can be listed and stopped at runtime.
+
+use crate::network::artnet::{create_artdmx_packet, ARTNET_PORT};
+use crate::network::listener::{DmxStoreHandle, DMX_UNIVERSE_SIZE};
+use crate::network::sacn::{
+    cid_to_string, create_sacn_data_packet, create_sacn_sync_packet, parse_cid,
+    sacn_multicast_address, SacnSource, SACN_PORT,
+};
+use crate::network::source::{Protocol, RECEIVE_ONLY_PROTOCOL};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+use tracing::{info, warn};
+
+/// Maximum output refresh rate (DMX512 tops out around 44 fps)
+pub const MAX_OUTPUT_RATE_HZ: f32 = 44.0;
+
+/// Test pattern to transmit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TestPattern {
+    /// All channels at full
+    Full,
+    /// All channels fading 0-255 together, one step per frame
+    Ramp,
+    /// A single channel at full stepping through the universe, one channel per frame
+    Chase,
+    /// One channel at a fixed level, all others at zero
+    Single { channel: u16, level: u8 },
+    /// Scripted sequence of steps played from all channels at zero
+    Script {
+        steps: Vec<ScriptStep>,
+        #[serde(default)]
+        looping: bool,
+    },
+}
+
+impl TestPattern {
+    /// Render the pattern for a given frame number at the output's refresh rate
+    pub fn render(&self, frame: u64, rate_hz: f32) -> Vec<u8> {
+        let mut data = vec![0u8; DMX_UNIVERSE_SIZE];
+        match self {
+            TestPattern::Full => data.fill(255),
+            TestPattern::Ramp => data.fill((frame % 256) as u8),
+            TestPattern::Chase => data[(frame % DMX_UNIVERSE_SIZE as u64) as usize] = 255,
+            TestPattern::Single { channel, level } => {
+                if (1..=DMX_UNIVERSE_SIZE as u16).contains(channel) {
+                    data[*channel as usize - 1] = *level;
+                }
+            }
+            TestPattern::Script { steps, looping } => {
+                // Time is derived from the frame count so playback is deterministic
+                let mut time_ms = (frame as f64 * 1000.0 / rate_hz as f64) as u64;
+                let total_ms: u64 = steps.iter().map(|s| s.duration_ms() as u64).sum();
+                if *looping && total_ms > 0 {
+                    time_ms %= total_ms;
+                }
+
+                for step in steps {
+                    let duration_ms = step.duration_ms() as u64;
+                    if time_ms < duration_ms {
+                        step.apply(&mut data, time_ms as f32 / duration_ms as f32);
+                        break;
+                    }
+                    step.apply(&mut data, 1.0);
+                    time_ms -= duration_ms;
+                }
+            }
+        }
+        data
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            TestPattern::Single { channel, .. } => validate_channel_range(*channel, None),
+            TestPattern::Script { steps, looping } => {
+                if steps.is_empty() {
+                    return Err("Script must have at least one step".to_string());
+                }
+                for step in steps {
+                    let (channel, channel_end) = step.channels();
+                    validate_channel_range(channel, channel_end)?;
+                }
+                if *looping && steps.iter().all(|s| s.duration_ms() == 0) {
+                    return Err("A looping script needs at least one timed step".to_string());
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// One step of a scripted sequence. Steps that take a channel range
+/// (`channel` to `channel_end`) act on a single channel when no end is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ScriptStep {
+    /// Snap channels to a level
+    Set {
+        channel: u16,
+        #[serde(default)]
+        channel_end: Option<u16>,
+        level: u8,
+    },
+    /// Fade channels between two levels
+    Fade {
+        channel: u16,
+        #[serde(default)]
+        channel_end: Option<u16>,
+        from: u8,
+        to: u8,
+        duration_ms: u32,
+    },
+    /// Hold the current levels
+    Wait { duration_ms: u32 },
+}
+
+impl ScriptStep {
+    fn duration_ms(&self) -> u32 {
+        match self {
+            ScriptStep::Set { .. } => 0,
+            ScriptStep::Fade { duration_ms, .. } | ScriptStep::Wait { duration_ms } => *duration_ms,
+        }
+    }
+
+    fn channels(&self) -> (u16, Option<u16>) {
+        match self {
+            ScriptStep::Set {
+                channel,
+                channel_end,
+                ..
+            }
+            | ScriptStep::Fade {
+                channel,
+                channel_end,
+                ..
+            } => (*channel, *channel_end),
+            ScriptStep::Wait { .. } => (1, None),
+        }
+    }
+
+    /// Apply the step to the levels at a progress between 0.0 and 1.0
+    fn apply(&self, data: &mut [u8], progress: f32) {
+        let level = match self {
+            ScriptStep::Set { level, .. } => *level,
+            ScriptStep::Fade { from, to, .. } => {
+                (*from as f32 + (*to as f32 - *from as f32) * progress).round() as u8
+            }
+            ScriptStep::Wait { .. } => return,
+        };
+        let (channel, channel_end) = self.channels();
+        let end = channel_end.unwrap_or(channel);
+        for ch in channel..=end {
+            if let Some(slot) = data.get_mut(ch as usize - 1) {
+                *slot = level;
+            }
+        }
+    }
+}
+
+fn validate_channel_range(channel: u16, channel_end: Option<u16>) -> Result<(), String> {
+    let end = channel_end.unwrap_or(channel);
+    if !(1..=DMX_UNIVERSE_SIZE as u16).contains(&channel)
+        || !(channel..=DMX_UNIVERSE_SIZE as u16).contains(&end)
+    {
+        return Err(match channel_end {
+            Some(_) => format!("Channels {}-{} out of range (1-512)", channel, end),
+            None => format!("Channel {} out of range (1-512)", channel),
+        });
+    }
+    Ok(())
+}
+
+/// Default sACN priority for outputs
+pub const DEFAULT_SACN_PRIORITY: u8 = 100;
+
+fn default_output_protocol() -> Protocol {
+    Protocol::ArtNet
+}
+
+fn default_sacn_priority() -> u8 {
+    DEFAULT_SACN_PRIORITY
+}
+
+/// Configuration for a single output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputConfig {
+    #[serde(default = "default_output_protocol")]
+    pub protocol: Protocol,
+    pub universe: u16,
+    pub rate_hz: f32,
+    pub pattern: TestPattern,
+    /// Destination IP (Art-Net broadcast / sACN multicast if not set)
+    #[serde(default)]
+    pub destination: Option<String>,
+
+    // sACN specific
+    #[serde(default = "default_sacn_priority")]
+    pub priority: u8,
+    /// Component identifier as a UUID string (generated if not set)
+    #[serde(default)]
+    pub cid: Option<String>,
+    #[serde(default)]
+    pub source_name: Option<String>,
+    /// Synchronization universe; when set, each frame is followed by a sync packet
+    #[serde(default)]
+    pub sync_address: Option<u16>,
+}
+
+impl OutputConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match self.protocol {
+            Protocol::ArtNet => {
+                if self.universe > 0x7FFF {
+                    return Err(format!(
+                        "Art-Net universe {} out of range (0-32767)",
+                        self.universe
+                    ));
+                }
+            }
+            Protocol::Other => return Err(RECEIVE_ONLY_PROTOCOL.to_string()),
+            Protocol::Sacn => {
+                if !(1..=63999).contains(&self.universe) {
+                    return Err(format!(
+                        "sACN universe {} out of range (1-63999)",
+                        self.universe
+                    ));
+                }
+                if self.priority > 200 {
+                    return Err(format!(
+                        "sACN priority {} out of range (0-200)",
+                        self.priority
+                    ));
+                }
+                if let Some(cid) = &self.cid {
+                    if parse_cid(cid).is_none() {
+                        return Err(format!("Invalid CID: {}", cid));
+                    }
+                }
+                if let Some(sync_address) = self.sync_address {
+                    if !(1..=63999).contains(&sync_address) {
+                        return Err(format!(
+                            "sACN sync address {} out of range (1-63999)",
+                            sync_address
+                        ));
+                    }
+                }
+            }
+        }
+        if !(self.rate_hz > 0.0 && self.rate_hz <= MAX_OUTPUT_RATE_HZ) {
+            return Err(format!(
+                "Refresh rate must be between 0 and {} Hz",
+                MAX_OUTPUT_RATE_HZ
+            ));
+        }
+        self.pattern.validate()?;
+        self.destination_addr()?;
+        Ok(())
+    }
+
+    /// Resolve the destination socket address
+    pub fn destination_addr(&self) -> Result<SocketAddr, String> {
+        let unicast = match &self.destination {
+            Some(dest) if !dest.trim().is_empty() => Some(
+                dest.trim()
+                    .parse::<IpAddr>()
+                    .map_err(|_| format!("Invalid destination address: {}", dest))?,
+            ),
+            _ => None,
+        };
+
+        Ok(match self.protocol {
+            Protocol::ArtNet => SocketAddr::new(
+                unicast.unwrap_or(IpAddr::V4(Ipv4Addr::BROADCAST)),
+                ARTNET_PORT,
+            ),
+            Protocol::Sacn => SocketAddr::new(
+                unicast.unwrap_or(IpAddr::V4(sacn_multicast_address(self.universe))),
+                SACN_PORT,
+            ),
+            Protocol::Other => return Err(RECEIVE_ONLY_PROTOCOL.to_string()),
+        })
+    }
+
+    /// Resolve where sync packets go (unicast destination or the sync universe multicast)
+    pub fn sync_destination_addr(&self) -> Result<Option<SocketAddr>, String> {
+        let sync_address = match (self.protocol, self.sync_address) {
+            (Protocol::Sacn, Some(sync_address)) => sync_address,
+            _ => return Ok(None),
+        };
+        let mut addr = self.destination_addr()?;
+        if self
+            .destination
+            .as_deref()
+            .is_none_or(|d| d.trim().is_empty())
+        {
+            addr.set_ip(IpAddr::V4(sacn_multicast_address(sync_address)));
+        }
+        Ok(Some(addr))
+    }
+
+    /// Build the sACN source description for this output
+    fn sacn_source(&self) -> SacnSource {
+        SacnSource {
+            cid: self.cid.as_deref().and_then(parse_cid).unwrap_or_default(),
+            source_name: self
+                .source_name
+                .clone()
+                .unwrap_or_else(|| "LXMonitor".to_string()),
+            priority: self.priority,
+            sync_address: self.sync_address.unwrap_or(0),
+            sequence: 0,
+            options: 0,
+            universe: self.universe,
+        }
+    }
+}
+
+/// Output status for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputStatus {
+    pub id: u32,
+    pub config: OutputConfig,
+    pub running: bool,
+    pub frames_sent: u64,
+    pub sync_packets_sent: u64,
+    pub errors: u64,
+    pub last_error: Option<String>,
+}
+
+/// Shared state of a running output
+pub struct OutputState {
+    pub id: u32,
+    pub config: RwLock<OutputConfig>,
+    pub running: Mutex<bool>,
+    pub frames_sent: Mutex<u64>,
+    pub sync_packets_sent: Mutex<u64>,
+    pub errors: Mutex<u64>,
+    pub last_error: Mutex<Option<String>>,
+    pub stop_flag: Mutex<bool>,
+}
+
+impl OutputState {
+    fn new(id: u32, config: OutputConfig) -> Self {
+        Self {
+            id,
+            config: RwLock::new(config),
+            running: Mutex::new(true),
+            frames_sent: Mutex::new(0),
+            sync_packets_sent: Mutex::new(0),
+            errors: Mutex::new(0),
+            last_error: Mutex::new(None),
+            stop_flag: Mutex::new(false),
+        }
+    }
+
+    pub fn get_status(&self) -> OutputStatus {
+        OutputStatus {
+            id: self.id,
+            config: self.config.read().clone(),
+            running: *self.running.lock(),
+            frames_sent: *self.frames_sent.lock(),
+            sync_packets_sent: *self.sync_packets_sent.lock(),
+            errors: *self.errors.lock(),
+            last_error: self.last_error.lock().clone(),
+        }
+    }
+
+    pub fn record_error(&self, error: String) {
+        *self.errors.lock() += 1;
+        *self.last_error.lock() = Some(error);
+    }
+}
+
+pub type OutputStateHandle = Arc<OutputState>;
+
+/// Tracks all outputs created by the user
+pub struct TransmitManager {
+    outputs: RwLock<HashMap<u32, OutputStateHandle>>,
+    next_id: Mutex<u32>,
+}
+
+impl TransmitManager {
+    pub fn new() -> Self {
+        Self {
+            outputs: RwLock::new(HashMap::new()),
+            next_id: Mutex::new(1),
+        }
+    }
+
+    /// Register a new output; the caller is responsible for spawning its task
+    pub fn add_output(&self, mut config: OutputConfig) -> Result<OutputStateHandle, String> {
+        config.validate()?;
+
+        // sACN sources need a stable CID for the lifetime of the output
+        if config.protocol == Protocol::Sacn && config.cid.is_none() {
+            config.cid = Some(cid_to_string(uuid::Uuid::new_v4().as_bytes()));
+        }
+
+        let id = {
+            let mut next_id = self.next_id.lock();
+            let id = *next_id;
+            *next_id += 1;
+            id
+        };
+
+        let state = Arc::new(OutputState::new(id, config));
+        self.outputs.write().insert(id, state.clone());
+        Ok(state)
+    }
+
+    /// Signal an output to stop and forget it
+    pub fn stop_output(&self, id: u32) -> Result<(), String> {
+        match self.outputs.write().remove(&id) {
+            Some(state) => {
+                *state.stop_flag.lock() = true;
+                Ok(())
+            }
+            None => Err(format!("Output {} not found", id)),
+        }
+    }
+
+    /// Stop every output
+    pub fn stop_all(&self) {
+        for (_, state) in self.outputs.write().drain() {
+            *state.stop_flag.lock() = true;
+        }
+    }
+
+    pub fn get_outputs(&self) -> Vec<OutputStatus> {
+        let mut outputs: Vec<OutputStatus> = self
+            .outputs
+            .read()
+            .values()
+            .map(|o| o.get_status())
+            .collect();
+        outputs.sort_by_key(|o| o.id);
+        outputs
+    }
+}
+
+impl Default for TransmitManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type TransmitManagerHandle = Arc<TransmitManager>;
+
+/// Run an output until its stop flag is set
+pub async fn run_output(
+    output: OutputStateHandle,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let socket = UdpSocket::bind(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0)).await?;
+    socket.set_broadcast(true)?;
+
+    let (protocol, rate_hz, destination, sync_destination) = {
+        let config = output.config.read();
+        (
+            config.protocol,
+            config.rate_hz,
+            config.destination_addr()?,
+            config.sync_destination_addr()?,
+        )
+    };
+
+    info!(
+        "[Transmit] Output {} started - {:?} to {} at {} Hz",
+        output.id, protocol, destination, rate_hz
+    );
+
+    let mut interval = tokio::time::interval(Duration::from_secs_f32(1.0 / rate_hz));
+    let mut frame: u64 = 0;
+    let mut sequence: u8 = 0;
+    let mut sync_sequence: u8 = 0;
+
+    loop {
+        interval.tick().await;
+
+        if *output.stop_flag.lock() {
+            break;
+        }
+
+        let packet = {
+            let config = output.config.read();
+            let data = config.pattern.render(frame, config.rate_hz);
+            match config.protocol {
+                Protocol::ArtNet => {
+                    // Sequence runs 1-255, 0 is reserved for "sequencing disabled"
+                    sequence = if sequence == 255 { 1 } else { sequence + 1 };
+                    create_artdmx_packet(config.universe, sequence, &data)
+                }
+                Protocol::Sacn => {
+                    sequence = sequence.wrapping_add(1);
+                    let mut source = config.sacn_source();
+                    source.sequence = sequence;
+                    create_sacn_data_packet(&source, 0x00, &data)
+                }
+                // Rejected when the output is configured
+                Protocol::Other => break,
+            }
+        };
+
+        match socket.send_to(&packet, destination).await {
+            Ok(_) => *output.frames_sent.lock() += 1,
+            Err(e) => output.record_error(format!("Send failed: {}", e)),
+        }
+
+        // Sync packet tells synchronized receivers to act on the data just sent
+        if let Some(sync_destination) = sync_destination {
+            let packet = {
+                let config = output.config.read();
+                let source = config.sacn_source();
+                sync_sequence = sync_sequence.wrapping_add(1);
+                create_sacn_sync_packet(&source.cid, sync_sequence, source.sync_address)
+            };
+            match socket.send_to(&packet, sync_destination).await {
+                Ok(_) => *output.sync_packets_sent.lock() += 1,
+                Err(e) => output.record_error(format!("Sync send failed: {}", e)),
+            }
+        }
+
+        frame += 1;
+    }
+
+    *output.running.lock() = false;
+    info!("[Transmit] Output {} stopped", output.id);
+    Ok(())
+}
+
+// ============================================================================
+// Channel park/hold
+// ============================================================================
+
+/// sACN priority used for parked output so it wins the merge over live sources
+pub const PARK_PRIORITY: u8 = 200;
+
+/// Park output refresh rate
+const PARK_RATE_HZ: f32 = 40.0;
+
+/// E1.31 options bit signalling the source has stopped transmitting the universe
+const SACN_OPTION_STREAM_TERMINATED: u8 = 0x40;
+
+/// Start code for per-address priority (0xDD) frames
+const START_CODE_PER_ADDRESS_PRIORITY: u8 = 0xDD;
+
+/// A channel held at a fixed value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParkedChannel {
+    pub universe: u16,
+    pub channel: u16, // 1-based channel number
+    pub value: u8,
+}
+
+/// Parked channels, transmitted over sACN on top of live data
+pub struct ParkManager {
+    parked: RwLock<BTreeMap<u16, BTreeMap<u16, u8>>>,
+    cid: [u8; 16],
+}
+
+impl ParkManager {
+    pub fn new() -> Self {
+        Self {
+            parked: RwLock::new(BTreeMap::new()),
+            cid: *uuid::Uuid::new_v4().as_bytes(),
+        }
+    }
+
+    /// Park a channel at a fixed value
+    pub fn park(&self, universe: u16, channel: u16, value: u8) -> Result<(), String> {
+        if !(1..=63999).contains(&universe) {
+            return Err(format!("Universe {} out of range (1-63999)", universe));
+        }
+        if !(1..=DMX_UNIVERSE_SIZE as u16).contains(&channel) {
+            return Err(format!("Channel {} out of range (1-512)", channel));
+        }
+        self.parked
+            .write()
+            .entry(universe)
+            .or_default()
+            .insert(channel, value);
+        Ok(())
+    }
+
+    /// Release a parked channel
+    pub fn unpark(&self, universe: u16, channel: u16) {
+        let mut parked = self.parked.write();
+        if let Some(channels) = parked.get_mut(&universe) {
+            channels.remove(&channel);
+            if channels.is_empty() {
+                parked.remove(&universe);
+            }
+        }
+    }
+
+    /// Release every parked channel
+    pub fn unpark_all(&self) {
+        self.parked.write().clear();
+    }
+
+    pub fn get_parked(&self) -> Vec<ParkedChannel> {
+        self.parked
+            .read()
+            .iter()
+            .flat_map(|(&universe, channels)| {
+                channels
+                    .iter()
+                    .map(move |(&channel, &value)| ParkedChannel {
+                        universe,
+                        channel,
+                        value,
+                    })
+            })
+            .collect()
+    }
+
+    /// CID of the park output, for the listeners to skip it when it loops back
+    pub fn cid(&self) -> [u8; 16] {
+        self.cid
+    }
+
+    fn snapshot(&self) -> BTreeMap<u16, BTreeMap<u16, u8>> {
+        self.parked.read().clone()
+    }
+}
+
+impl Default for ParkManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type ParkManagerHandle = Arc<ParkManager>;
+
+/// Continuously transmit parked channels merged over live data
+///
+/// Each parked universe is sent as sACN at PARK_PRIORITY carrying the live levels
+/// with parked channels overridden, plus a per-address priority (0xDD) frame that
+/// claims only the parked slots, so receivers supporting 0xDD leave the rest of
+/// the universe to the live source and receivers ignoring it still see the live
+/// levels. The output can loop back to this machine, the listeners skip it by
+/// its CID (see `ListenerState::ignore_sacn_cid`) so the live levels never
+/// include the park itself.
+pub async fn start_park_output(
+    parks: ParkManagerHandle,
+    dmx_store: DmxStoreHandle,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let socket = UdpSocket::bind(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0)).await?;
+
+    let mut interval = tokio::time::interval(Duration::from_secs_f32(1.0 / PARK_RATE_HZ));
+    let mut sequences: HashMap<u16, u8> = HashMap::new();
+
+    loop {
+        interval.tick().await;
+
+        let parked = parks.snapshot();
+
+        // Universes that were parked last tick but no longer are get stream-terminated
+        let released: Vec<u16> = sequences
+            .keys()
+            .filter(|u| !parked.contains_key(u))
+            .copied()
+            .collect();
+        for universe in released {
+            let mut sequence = sequences.remove(&universe).unwrap_or(0);
+            let mut source = park_source(parks.cid, universe);
+            source.options = SACN_OPTION_STREAM_TERMINATED;
+            let dest = SocketAddr::new(IpAddr::V4(sacn_multicast_address(universe)), SACN_PORT);
+            // E1.31 requires three terminated packets
+            for _ in 0..3 {
+                sequence = sequence.wrapping_add(1);
+                source.sequence = sequence;
+                let packet = create_sacn_data_packet(&source, 0x00, &[]);
+                let _ = socket.send_to(&packet, dest).await;
+            }
+        }
+
+        for (universe, channels) in parked {
+            let mut levels = dmx_store
+                .get_padded(universe)
+                .unwrap_or_else(|| vec![0u8; DMX_UNIVERSE_SIZE]);
+            let mut priorities = vec![0u8; DMX_UNIVERSE_SIZE];
+            for (&channel, &value) in &channels {
+                levels[channel as usize - 1] = value;
+                priorities[channel as usize - 1] = PARK_PRIORITY;
+            }
+
+            let dest = SocketAddr::new(IpAddr::V4(sacn_multicast_address(universe)), SACN_PORT);
+            let mut source = park_source(parks.cid, universe);
+
+            for (start_code, data) in [
+                (0x00, &levels),
+                (START_CODE_PER_ADDRESS_PRIORITY, &priorities),
+            ] {
+                let sequence = sequences.entry(universe).or_insert(0);
+                *sequence = sequence.wrapping_add(1);
+                source.sequence = *sequence;
+                let packet = create_sacn_data_packet(&source, start_code, data);
+                if let Err(e) = socket.send_to(&packet, dest).await {
+                    warn!("[Park] Failed to send universe {}: {}", universe, e);
+                }
+            }
+        }
+    }
+}
+
+fn park_source(cid: [u8; 16], universe: u16) -> SacnSource {
+    SacnSource {
+        cid,
+        source_name: "LXMonitor Park".to_string(),
+        priority: PARK_PRIORITY,
+        sync_address: 0,
+        sequence: 0,
+        options: 0,
+        universe,
+    }
+}
+
+// ============================================================================
+// Stress test generator
+// ============================================================================
+
+/// Upper bound on stress test rate to keep the generator from saturating the host
+pub const MAX_STRESS_PPS: u32 = 200_000;
+
+/// Stress test configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressTestConfig {
+    pub protocol: Protocol,
+    pub universe_start: u16,
+    pub universe_count: u16,
+    /// Total packets per second across all universes
+    pub packets_per_second: u32,
+    /// Stop automatically after this many seconds (runs until stopped if not set)
+    #[serde(default)]
+    pub duration_secs: Option<u32>,
+    /// Unicast destination IP (Art-Net broadcast / sACN multicast if not set)
+    #[serde(default)]
+    pub destination: Option<String>,
+}
+
+impl StressTestConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_STRESS_PPS).contains(&self.packets_per_second) {
+            return Err(format!(
+                "Packets per second must be between 1 and {}",
+                MAX_STRESS_PPS
+            ));
+        }
+        if self.universe_count == 0 {
+            return Err("Universe count must be at least 1".to_string());
+        }
+        let last = self.universe_start as u32 + self.universe_count as u32 - 1;
+        let (min, max) = match self.protocol {
+            Protocol::ArtNet => (0, 0x7FFF),
+            Protocol::Sacn => (1, 63999),
+            Protocol::Other => return Err(RECEIVE_ONLY_PROTOCOL.to_string()),
+        };
+        if (self.universe_start as u32) < min || last > max {
+            return Err(format!(
+                "Universes {}-{} out of range ({}-{})",
+                self.universe_start, last, min, max
+            ));
+        }
+        self.destination_for(self.universe_start)?;
+        Ok(())
+    }
+
+    fn destination_for(&self, universe: u16) -> Result<SocketAddr, String> {
+        OutputConfig {
+            protocol: self.protocol,
+            universe,
+            rate_hz: 1.0,
+            pattern: TestPattern::Full,
+            destination: self.destination.clone(),
+            priority: DEFAULT_SACN_PRIORITY,
+            cid: None,
+            source_name: None,
+            sync_address: None,
+        }
+        .destination_addr()
+    }
+}
+
+/// Stress test status for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressTestStatus {
+    pub running: bool,
+    pub config: Option<StressTestConfig>,
+    pub packets_sent: u64,
+    pub errors: u64,
+    pub last_error: Option<String>,
+    /// Send rate achieved over the last second
+    pub achieved_pps: f64,
+    pub elapsed_ms: u64,
+}
+
+/// Stress test state (one test at a time)
+pub struct StressTestState {
+    pub running: Mutex<bool>,
+    pub config: Mutex<Option<StressTestConfig>>,
+    pub packets_sent: Mutex<u64>,
+    pub errors: Mutex<u64>,
+    pub last_error: Mutex<Option<String>>,
+    pub achieved_pps: Mutex<f64>,
+    pub elapsed_ms: Mutex<u64>,
+    pub stop_flag: Mutex<bool>,
+}
+
+impl StressTestState {
+    pub fn new() -> Self {
+        Self {
+            running: Mutex::new(false),
+            config: Mutex::new(None),
+            packets_sent: Mutex::new(0),
+            errors: Mutex::new(0),
+            last_error: Mutex::new(None),
+            achieved_pps: Mutex::new(0.0),
+            elapsed_ms: Mutex::new(0),
+            stop_flag: Mutex::new(false),
+        }
+    }
+
+    pub fn get_status(&self) -> StressTestStatus {
+        StressTestStatus {
+            running: *self.running.lock(),
+            config: self.config.lock().clone(),
+            packets_sent: *self.packets_sent.lock(),
+            errors: *self.errors.lock(),
+            last_error: self.last_error.lock().clone(),
+            achieved_pps: *self.achieved_pps.lock(),
+            elapsed_ms: *self.elapsed_ms.lock(),
+        }
+    }
+}
+
+impl Default for StressTestState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type StressTestStateHandle = Arc<StressTestState>;
+
+/// Run a stress test on the current thread until stopped or its duration elapses
+pub fn run_stress_test_blocking(config: StressTestConfig, state: StressTestStateHandle) {
+    let socket = match std::net::UdpSocket::bind("0.0.0.0:0") {
+        Ok(s) => s,
+        Err(e) => {
+            *state.last_error.lock() = Some(format!("Failed to create socket: {}", e));
+            *state.running.lock() = false;
+            return;
+        }
+    };
+    let _ = socket.set_broadcast(true);
+
+    // Pre-build one packet per universe; only the sequence byte changes per send
+    let cid = *uuid::Uuid::new_v4().as_bytes();
+    let data = vec![0x80u8; DMX_UNIVERSE_SIZE];
+    let mut streams: Vec<(Vec<u8>, SocketAddr, usize)> = Vec::new();
+    for i in 0..config.universe_count {
+        let universe = config.universe_start + i;
+        let dest = match config.destination_for(universe) {
+            Ok(d) => d,
+            Err(e) => {
+                *state.last_error.lock() = Some(e);
+                *state.running.lock() = false;
+                return;
+            }
+        };
+        let (packet, sequence_offset) = match config.protocol {
+            Protocol::ArtNet => (create_artdmx_packet(universe, 1, &data), 12),
+            Protocol::Sacn => {
+                let source = SacnSource {
+                    cid,
+                    source_name: "LXMonitor Stress Test".to_string(),
+                    priority: DEFAULT_SACN_PRIORITY,
+                    sync_address: 0,
+                    sequence: 0,
+                    options: 0,
+                    universe,
+                };
+                (create_sacn_data_packet(&source, 0x00, &data), 111)
+            }
+            Protocol::Other => {
+                *state.last_error.lock() = Some(RECEIVE_ONLY_PROTOCOL.to_string());
+                *state.running.lock() = false;
+                return;
+            }
+        };
+        streams.push((packet, dest, sequence_offset));
+    }
+
+    info!(
+        "[Stress] Started - {} universes at {} packets/s",
+        config.universe_count, config.packets_per_second
+    );
+
+    let start = Instant::now();
+    let duration = config.duration_secs.map(|s| Duration::from_secs(s as u64));
+    let pps = config.packets_per_second as f64;
+    let mut attempted: u64 = 0;
+    let mut next_stream = 0usize;
+    let mut window_start = Instant::now();
+    let mut window_sent: u64 = 0;
+
+    loop {
+        if *state.stop_flag.lock() {
+            break;
+        }
+
+        let elapsed = start.elapsed();
+        if duration.is_some_and(|d| elapsed >= d) {
+            break;
+        }
+
+        // Send whatever is due to keep pace with the target rate, in bounded batches
+        // so the stop flag is still checked regularly when the host can't keep up
+        let target = (elapsed.as_secs_f64() * pps) as u64;
+        let batch = target.saturating_sub(attempted).min(10_000);
+        let mut sent = 0u64;
+        let mut errors = 0u64;
+        let mut last_error = None;
+
+        for _ in 0..batch {
+            let (packet, dest, sequence_offset) = &mut streams[next_stream];
+            packet[*sequence_offset] = packet[*sequence_offset].wrapping_add(1).max(1);
+            match socket.send_to(packet, *dest) {
+                Ok(_) => sent += 1,
+                Err(e) => {
+                    errors += 1;
+                    last_error = Some(format!("Send to {} failed: {}", dest, e));
+                }
+            }
+            next_stream = (next_stream + 1) % streams.len();
+        }
+        attempted += batch;
+        window_sent += sent;
+
+        *state.packets_sent.lock() += sent;
+        if errors > 0 {
+            *state.errors.lock() += errors;
+            *state.last_error.lock() = last_error;
+        }
+        *state.elapsed_ms.lock() = elapsed.as_millis() as u64;
+
+        let window = window_start.elapsed();
+        if window >= Duration::from_secs(1) {
+            *state.achieved_pps.lock() = window_sent as f64 / window.as_secs_f64();
+            window_start = Instant::now();
+            window_sent = 0;
+        }
+
+        if batch < 10_000 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    *state.running.lock() = false;
+    info!(
+        "[Stress] Stopped after {} packets",
+        *state.packets_sent.lock()
+    );
+}
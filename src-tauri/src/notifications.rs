@@ -0,0 +1,178 @@
+// Notifications - OS notifications for critical alerts
+//
+// A minimized window is easy to miss during a show, so expected devices
+// dropping or never appearing and the network going silent also raise a
+// desktop notification. Silence is only reported once DMX has been seen, so
+// starting the monitor on an idle network stays quiet, and traffic returning
+// after a silence notification is reported too.
+
+use crate::network::{ExpectedDeviceAlert, ExpectedDeviceAlertKind};
+use crate::persistence::{load_json, save_json};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Accepted silence timeouts in seconds
+const MIN_SILENT_AFTER_SECS: u64 = 1;
+const MAX_SILENT_AFTER_SECS: u64 = 3600;
+
+/// Desktop notification configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    pub enabled: bool,
+    /// Expected devices dropping or never appearing
+    pub expected_devices: bool,
+    /// No DMX received from any source
+    pub network_silent: bool,
+    /// Seconds without DMX before the network counts as silent
+    pub silent_after_secs: u64,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            expected_devices: true,
+            network_silent: true,
+            silent_after_secs: 5,
+        }
+    }
+}
+
+/// A notification to show
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+}
+
+/// Persistent notification settings
+pub struct Notifications {
+    config: RwLock<NotificationConfig>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(NotificationConfig::default()),
+            path: RwLock::new(None),
+        }
+    }
+
+    /// Load the settings from disk and persist future changes to the same file
+    pub fn load(&self, path: PathBuf) {
+        *self.config.write() = load_json(&path);
+        *self.path.write() = Some(path);
+    }
+
+    pub fn get_config(&self) -> NotificationConfig {
+        self.config.read().clone()
+    }
+
+    pub fn set_config(&self, config: NotificationConfig) -> Result<(), String> {
+        if !(MIN_SILENT_AFTER_SECS..=MAX_SILENT_AFTER_SECS).contains(&config.silent_after_secs) {
+            return Err(format!(
+                "Silence timeout must be between {} and {} seconds",
+                MIN_SILENT_AFTER_SECS, MAX_SILENT_AFTER_SECS
+            ));
+        }
+        *self.config.write() = config;
+        self.save()
+    }
+
+    /// Notification for an expected device alert, if it is critical and enabled
+    pub fn for_expected_device(&self, alert: &ExpectedDeviceAlert) -> Option<Notification> {
+        let config = self.config.read();
+        if !config.enabled || !config.expected_devices {
+            return None;
+        }
+        let title = match alert.kind {
+            ExpectedDeviceAlertKind::Dropped => "Expected device offline",
+            ExpectedDeviceAlertKind::NeverSeen => "Expected device missing",
+            ExpectedDeviceAlertKind::Recovered => return None,
+        };
+        Some(Notification {
+            title: title.to_string(),
+            body: alert.message.clone(),
+        })
+    }
+
+    /// Notification for a change reported by a `SilenceDetector`, if enabled
+    pub fn for_silence(&self, silent: bool, after: Duration) -> Option<Notification> {
+        let config = self.config.read();
+        if !config.enabled || !config.network_silent {
+            return None;
+        }
+        Some(if silent {
+            Notification {
+                title: "Network silent".to_string(),
+                body: format!("No DMX received for {} seconds", after.as_secs()),
+            }
+        } else {
+            Notification {
+                title: "Network traffic resumed".to_string(),
+                body: "DMX is being received again".to_string(),
+            }
+        })
+    }
+
+    /// Time without DMX before the network counts as silent
+    pub fn silent_after(&self) -> Duration {
+        let secs = self.config.read().silent_after_secs;
+        Duration::from_secs(secs.clamp(MIN_SILENT_AFTER_SECS, MAX_SILENT_AFTER_SECS))
+    }
+
+    fn save(&self) -> Result<(), String> {
+        match self.path.read().as_ref() {
+            Some(path) => save_json(path, &*self.config.read()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type NotificationsHandle = Arc<Notifications>;
+
+/// Tracks whether DMX is still arriving
+#[derive(Default)]
+pub struct SilenceDetector {
+    last_dmx: Option<Instant>,
+    silent: bool,
+}
+
+impl SilenceDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record DMX from any source. Returns true if this ends a silence.
+    pub fn dmx_received(&mut self, now: Instant) -> bool {
+        self.last_dmx = Some(now);
+        std::mem::take(&mut self.silent)
+    }
+
+    pub fn is_silent(&self) -> bool {
+        self.silent
+    }
+
+    /// Returns true the first time no DMX has arrived for `after`
+    pub fn check(&mut self, now: Instant, after: Duration) -> bool {
+        match self.last_dmx {
+            Some(last) if !self.silent && now.duration_since(last) >= after => {
+                self.silent = true;
+                true
+            }
+            _ => false,
+        }
+    }
+}
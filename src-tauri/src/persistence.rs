@@ -0,0 +1,40 @@
+// Persistence - JSON files in the application data directory
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+use tracing::warn;
+
+/// Load a JSON file, falling back to the default value if it is missing or invalid
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => match serde_json::from_str(&contents) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("[Persistence] Failed to parse {}: {}", path.display(), e);
+                T::default()
+            }
+        },
+        Err(_) => T::default(),
+    }
+}
+
+/// Save a value as pretty-printed JSON, writing to a temporary file first so a
+/// crash mid-write never leaves a truncated file behind
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let contents = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, contents)
+        .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    std::fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+
+    Ok(())
+}
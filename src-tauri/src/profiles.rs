@@ -0,0 +1,141 @@
+// Venue profiles - Named sets of settings ("Main Hall", "Tour Rig")
+//
+// A profile holds the listening interfaces, joined sACN universe ranges,
+// expected devices, universe labels and alert limits of one venue or rig, so
+// moving between them is one load instead of setting everything up again.
+
+use crate::network::{ExpectedDevice, SourceThresholds, StormConfig, UniverseRange};
+use crate::persistence::{load_json, save_json};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Settings saved under a profile name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VenueProfile {
+    pub name: String,
+    pub saved_at: u64, // Unix timestamp ms
+    pub bind_addresses: Vec<Ipv4Addr>,
+    pub sacn_universe_ranges: Vec<UniverseRange>,
+    pub expected_devices: Vec<ExpectedDevice>,
+    pub universe_labels: BTreeMap<u16, String>,
+    pub source_thresholds: SourceThresholds,
+    pub storm_limits: StormConfig,
+}
+
+/// Profile listing entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileSummary {
+    pub name: String,
+    pub saved_at: u64,
+    pub active: bool, // Last loaded or saved
+    pub expected_devices: usize,
+    pub universe_labels: usize,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct ProfilesFile {
+    active: Option<String>,
+    profiles: Vec<VenueProfile>,
+}
+
+/// Persistent profile store
+pub struct ProfileStore {
+    profiles: RwLock<BTreeMap<String, VenueProfile>>,
+    active: RwLock<Option<String>>,
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl ProfileStore {
+    pub fn new() -> Self {
+        Self {
+            profiles: RwLock::new(BTreeMap::new()),
+            active: RwLock::new(None),
+            path: RwLock::new(None),
+        }
+    }
+
+    /// Load the profiles from disk and persist future changes to the same file
+    pub fn load(&self, path: PathBuf) {
+        let file: ProfilesFile = load_json(&path);
+        *self.profiles.write() = file
+            .profiles
+            .into_iter()
+            .map(|p| (p.name.clone(), p))
+            .collect();
+        *self.active.write() = file.active;
+        *self.path.write() = Some(path);
+    }
+
+    pub fn list(&self) -> Vec<ProfileSummary> {
+        let active = self.active.read();
+        self.profiles
+            .read()
+            .values()
+            .map(|p| ProfileSummary {
+                name: p.name.clone(),
+                saved_at: p.saved_at,
+                active: active.as_deref() == Some(p.name.as_str()),
+                expected_devices: p.expected_devices.len(),
+                universe_labels: p.universe_labels.len(),
+            })
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Result<VenueProfile, String> {
+        self.profiles
+            .read()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Profile \"{}\" not found", name))
+    }
+
+    /// Add or replace a profile and make it the active one
+    pub fn save_profile(&self, mut profile: VenueProfile) -> Result<(), String> {
+        profile.name = profile.name.trim().to_string();
+        if profile.name.is_empty() {
+            return Err("Profile name must not be empty".to_string());
+        }
+        *self.active.write() = Some(profile.name.clone());
+        self.profiles.write().insert(profile.name.clone(), profile);
+        self.save()
+    }
+
+    pub fn set_active(&self, name: &str) -> Result<(), String> {
+        *self.active.write() = Some(name.to_string());
+        self.save()
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        if self.profiles.write().remove(name).is_none() {
+            return Err(format!("Profile \"{}\" not found", name));
+        }
+        self.active.write().take_if(|active| active == name);
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        match self.path.read().as_ref() {
+            Some(path) => save_json(
+                path,
+                &ProfilesFile {
+                    active: self.active.read().clone(),
+                    profiles: self.profiles.read().values().cloned().collect(),
+                },
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for ProfileStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type ProfileStoreHandle = Arc<ProfileStore>;
@@ -0,0 +1,526 @@
+// Report - Network health report for production managers and incident reviews
+//
+// A self-contained HTML page: a summary, the sources with their frame rate,
+// loss and jitter, who sends each universe, expected devices, the alerts and
+// source events of the chosen time window, and the monitor's configuration.
+// Statistics are current values; alerts and events go back as far as they are
+// kept. Print the page from a browser to get a PDF.
+
+use crate::app::AppState;
+use crate::export::enum_name;
+use crate::network::{
+    EventReceiver, ExpectedDeviceState, ListenerEvent, NetworkSource, Protocol, RdmStatusType,
+    SourceStatus,
+};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Alerts kept for reports
+const MAX_LOGGED_ALERTS: usize = 1000;
+
+const REPORT_STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2em;color:#222}\
+h1{margin-bottom:0}h2{margin-top:2em;border-bottom:1px solid #ccc}\
+table{border-collapse:collapse;width:100%;font-size:0.9em}\
+th,td{text-align:left;padding:4px 8px;border-bottom:1px solid #eee}\
+th{background:#f4f4f4}.meta{color:#666}";
+
+/// An alert raised while the monitor was running
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedAlert {
+    pub timestamp: u64, // Unix timestamp ms
+    pub kind: String,   // "expected_device", "storm", "universe", "rdm_status" or "timecode"
+    pub message: String,
+}
+
+/// Recent expected device, storm, universe, RDM status and timecode alerts
+pub struct AlertLog {
+    alerts: Mutex<VecDeque<LoggedAlert>>,
+}
+
+impl AlertLog {
+    pub fn new() -> Self {
+        Self {
+            alerts: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn record(&self, event: &ListenerEvent) {
+        let alert = match event {
+            ListenerEvent::ExpectedDeviceAlert(alert) => LoggedAlert {
+                timestamp: alert.timestamp,
+                kind: "expected_device".to_string(),
+                message: alert.message.clone(),
+            },
+            ListenerEvent::StormAlert(alert) => LoggedAlert {
+                timestamp: alert.timestamp,
+                kind: "storm".to_string(),
+                message: alert.message.clone(),
+            },
+            ListenerEvent::UniverseAnomaly(alert) => LoggedAlert {
+                timestamp: alert.timestamp,
+                kind: "universe".to_string(),
+                message: alert.message.clone(),
+            },
+            ListenerEvent::RdmStatus(status)
+                if matches!(
+                    status.status_type,
+                    RdmStatusType::Warning | RdmStatusType::Error
+                ) =>
+            {
+                LoggedAlert {
+                    timestamp: status.timestamp,
+                    kind: "rdm_status".to_string(),
+                    message: format!(
+                        "RDM device {} on universe {}: {}",
+                        status.uid, status.universe, status.message
+                    ),
+                }
+            }
+            ListenerEvent::TimecodeDrift(alert) => LoggedAlert {
+                timestamp: alert.timestamp,
+                kind: "timecode".to_string(),
+                message: alert.message.clone(),
+            },
+            _ => return,
+        };
+        let mut alerts = self.alerts.lock();
+        if alerts.len() == MAX_LOGGED_ALERTS {
+            alerts.pop_front();
+        }
+        alerts.push_back(alert);
+    }
+
+    /// Put back alerts from before a restart, ahead of the ones raised since
+    pub fn restore(&self, restored: Vec<LoggedAlert>) {
+        let mut alerts = self.alerts.lock();
+        let mut combined: VecDeque<LoggedAlert> = restored.into();
+        combined.append(&mut alerts);
+        while combined.len() > MAX_LOGGED_ALERTS {
+            combined.pop_front();
+        }
+        *alerts = combined;
+    }
+
+    /// Alerts raised at or after `since` (Unix ms), oldest first
+    pub fn since(&self, since: u64) -> Vec<LoggedAlert> {
+        self.alerts
+            .lock()
+            .iter()
+            .filter(|a| a.timestamp >= since)
+            .cloned()
+            .collect()
+    }
+}
+
+impl Default for AlertLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type AlertLogHandle = Arc<AlertLog>;
+
+/// Record the alerts sent on the event bus until it closes
+pub async fn run_alert_log(log: AlertLogHandle, mut events: EventReceiver) {
+    loop {
+        match events.recv().await {
+            Ok(event) => log.record(&event),
+            Err(RecvError::Lagged(_)) => {}
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Write the report of everything since `since` (Unix ms, everything kept when
+/// None) to an HTML file
+pub fn write_report(state: &AppState, path: &Path, since: Option<u64>) -> Result<(), String> {
+    let html = render_report(state, since.unwrap_or(0), now_ms());
+    std::fs::write(path, html).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn render_report(state: &AppState, since: u64, now: u64) -> String {
+    let mut sources: Vec<NetworkSource> = state
+        .source_manager
+        .get_all_sources(true)
+        .into_iter()
+        .filter(|s| s.last_seen >= since)
+        .collect();
+    sources.sort_by(|a, b| a.ip.cmp(&b.ip).then(a.id.cmp(&b.id)));
+    let labels = state.universe_labels.get_all();
+    let expected = state.source_manager.get_expected_devices();
+    let alerts = state.alert_log.since(since);
+
+    // Senders of each universe with their per-universe statistics
+    let mut universe_rows = Vec::new();
+    for source in &sources {
+        for stats in state
+            .source_manager
+            .get_universe_stats(&source.id)
+            .unwrap_or_default()
+        {
+            universe_rows.push((stats.universe, source_name(source), stats));
+        }
+    }
+    universe_rows.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+    let universe_count = {
+        let mut universes: Vec<u16> = universe_rows.iter().map(|row| row.0).collect();
+        universes.dedup();
+        universes.len()
+    };
+
+    // Alerts and source events in one timeline
+    let mut timeline: Vec<(u64, String, String, String)> = alerts
+        .iter()
+        .map(|a| {
+            (
+                a.timestamp,
+                "Alert".to_string(),
+                a.kind.clone(),
+                a.message.clone(),
+            )
+        })
+        .collect();
+    for source in &sources {
+        for event in state
+            .source_manager
+            .get_events(&source.id)
+            .unwrap_or_default()
+        {
+            if event.timestamp >= since {
+                timeline.push((
+                    event.timestamp,
+                    source_name(source),
+                    enum_name(&event.kind),
+                    event.message,
+                ));
+            }
+        }
+    }
+    timeline.sort_by_key(|entry| entry.0);
+
+    let active = sources
+        .iter()
+        .filter(|s| s.status == SourceStatus::Active)
+        .count();
+    let missing = expected
+        .iter()
+        .filter(|d| d.state == ExpectedDeviceState::Missing)
+        .count();
+    let window = if since == 0 {
+        "Everything since the monitor started".to_string()
+    } else {
+        format!("{} to {}", format_time(since), format_time(now))
+    };
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">");
+    html.push_str("<title>LXMonitor network health report</title>");
+    html.push_str(&format!("<style>{}</style></head><body>\n", REPORT_STYLE));
+    html.push_str("<h1>Network health report</h1>\n");
+    html.push_str(&format!(
+        "<p class=\"meta\">Generated {} by LXMonitor {}<br>{}</p>\n",
+        format_time(now),
+        env!("CARGO_PKG_VERSION"),
+        escape(&window)
+    ));
+
+    html.push_str("<h2>Summary</h2>\n");
+    html.push_str(&table(
+        &["", ""],
+        vec![
+            vec!["Sources".to_string(), sources.len().to_string()],
+            vec!["Active now".to_string(), active.to_string()],
+            vec!["Universes".to_string(), universe_count.to_string()],
+            vec![
+                "Expected devices missing".to_string(),
+                format!("{} of {}", missing, expected.len()),
+            ],
+            vec!["Alerts".to_string(), alerts.len().to_string()],
+        ],
+    ));
+
+    html.push_str("<h2>Sources</h2>\n");
+    html.push_str(&table(
+        &[
+            "Name",
+            "IP",
+            "Protocol",
+            "Status",
+            "Universes",
+            "FPS",
+            "Loss %",
+            "Jitter ms",
+            "Warnings",
+            "Last seen",
+        ],
+        sources
+            .iter()
+            .map(|s| {
+                vec![
+                    source_name(s),
+                    s.ip.clone(),
+                    s.decoder
+                        .clone()
+                        .unwrap_or_else(|| protocol_name(s.protocol).to_string()),
+                    enum_name(&s.status),
+                    join(&s.universes),
+                    format!("{:.1}", s.fps),
+                    format!("{:.2}", s.packet_loss_percent),
+                    format!("{:.2}", s.latency_jitter_ms),
+                    source_warnings(s),
+                    format_time(s.last_seen),
+                ]
+            })
+            .collect(),
+    ));
+
+    html.push_str("<h2>Universes</h2>\n");
+    html.push_str(&table(
+        &["Universe", "Label", "Sender", "FPS", "Loss %", "Packets"],
+        universe_rows
+            .iter()
+            .map(|(universe, sender, stats)| {
+                vec![
+                    universe.to_string(),
+                    labels.get(universe).cloned().unwrap_or_default(),
+                    sender.clone(),
+                    format!("{:.1}", stats.fps),
+                    format!("{:.2}", stats.packet_loss_percent),
+                    stats.packet_count.to_string(),
+                ]
+            })
+            .collect(),
+    ));
+
+    html.push_str("<h2>Expected devices</h2>\n");
+    html.push_str(&table(
+        &["Device", "State", "Missing since"],
+        expected
+            .iter()
+            .map(|d| {
+                vec![
+                    d.device.label.clone(),
+                    enum_name(&d.state),
+                    d.missing_since.map(format_time).unwrap_or_default(),
+                ]
+            })
+            .collect(),
+    ));
+
+    html.push_str("<h2>Alerts and events</h2>\n");
+    html.push_str(&table(
+        &["Time", "Source", "Kind", "Message"],
+        timeline
+            .into_iter()
+            .map(|(timestamp, source, kind, message)| {
+                vec![format_time(timestamp), source, kind, message]
+            })
+            .collect(),
+    ));
+
+    html.push_str("<h2>Configuration</h2>\n");
+    html.push_str(&configuration_table(state));
+    html.push_str(&table(
+        &[
+            "Listener",
+            "Address",
+            "Packets",
+            "Parse failures",
+            "Queue drops",
+            "OS drops",
+        ],
+        state
+            .listener_state
+            .get_counters()
+            .into_iter()
+            .map(|c| {
+                vec![
+                    protocol_name(c.protocol).to_string(),
+                    format!("{}:{}", c.interface, c.port),
+                    c.packets_received.to_string(),
+                    c.parse_failures.to_string(),
+                    c.queue_drops.to_string(),
+                    c.os_drops.map(|d| d.to_string()).unwrap_or_default(),
+                ]
+            })
+            .collect(),
+    ));
+
+    html.push_str("</body></html>\n");
+    html
+}
+
+fn configuration_table(state: &AppState) -> String {
+    let listener = &state.listener_state;
+    let thresholds = state.source_manager.get_thresholds();
+    let storm = state.sniffer_state.storm.get_status().config;
+    let ips = |ips: Vec<std::net::Ipv4Addr>| {
+        ips.iter()
+            .map(|ip| ip.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let ranges = listener
+        .sacn_universe_ranges()
+        .iter()
+        .map(|r| format!("{}-{}", r.start, r.end))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let on_off = |enabled: bool| if enabled { "On" } else { "Off" }.to_string();
+
+    table(
+        &["Setting", "Value"],
+        vec![
+            vec![
+                "Art-Net".to_string(),
+                on_off(listener.protocol_enabled(Protocol::ArtNet)),
+            ],
+            vec![
+                "sACN".to_string(),
+                on_off(listener.protocol_enabled(Protocol::Sacn)),
+            ],
+            vec!["Interfaces".to_string(), ips(listener.bind_addrs())],
+            vec!["sACN universe ranges".to_string(), ranges],
+            vec![
+                "sACN source-specific multicast".to_string(),
+                on_off(listener.sacn_source_specific()),
+            ],
+            vec![
+                "Receive buffer".to_string(),
+                listener
+                    .receive_buffer_size()
+                    .map(|size| format!("{} bytes", size))
+                    .unwrap_or_else(|| "OS default".to_string()),
+            ],
+            vec!["ArtPoll".to_string(), on_off(listener.auto_poll_enabled())],
+            vec![
+                "Idle / stale / offline after".to_string(),
+                format!(
+                    "{} / {} / {} ms",
+                    thresholds.idle_after_ms,
+                    thresholds.stale_after_ms,
+                    thresholds.offline_after_ms
+                ),
+            ],
+            vec![
+                "FPS warning below / above".to_string(),
+                format!("{} / {}", thresholds.fps_low, thresholds.fps_high),
+            ],
+            vec![
+                "Storm limits (broadcast / multicast)".to_string(),
+                format!(
+                    "{} / {} packets/s",
+                    storm.broadcast_pps, storm.multicast_pps
+                ),
+            ],
+        ],
+    )
+}
+
+/// HTML table, or a note when there are no rows
+fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    if rows.is_empty() {
+        return "<p class=\"meta\">None</p>\n".to_string();
+    }
+    let mut html = String::from("<table>");
+    if headers.iter().any(|h| !h.is_empty()) {
+        html.push_str("<tr>");
+        for header in headers {
+            html.push_str(&format!("<th>{}</th>", escape(header)));
+        }
+        html.push_str("</tr>");
+    }
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str(&format!("<td>{}</td>", escape(&cell)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn source_name(source: &NetworkSource) -> String {
+    source.alias.clone().unwrap_or_else(|| source.name.clone())
+}
+
+fn protocol_name(protocol: Protocol) -> &'static str {
+    match protocol {
+        Protocol::ArtNet => "Art-Net",
+        Protocol::Sacn => "sACN",
+        Protocol::Other => "Decoder",
+    }
+}
+
+fn source_warnings(source: &NetworkSource) -> String {
+    let mut warnings = Vec::new();
+    if let Some(fps) = &source.fps_warning {
+        warnings.push(format!("FPS {}", fps));
+    }
+    if !source.duplicate_universes.is_empty() {
+        warnings.push(format!("Duplicate {}", join(&source.duplicate_universes)));
+    }
+    if !source.priority_conflicts.is_empty() {
+        warnings.push(format!(
+            "Priority conflict {}",
+            join(&source.priority_conflicts)
+        ));
+    }
+    if source.duplicate_cid {
+        warnings.push("Duplicate CID".to_string());
+    }
+    warnings.join(", ")
+}
+
+fn join(universes: &[u16]) -> String {
+    universes
+        .iter()
+        .map(|u| u.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Unix ms as "2024-05-01 21:04:05 UTC"
+fn format_time(ms: u64) -> String {
+    let secs = ms / 1000;
+    let (days, time) = ((secs / 86_400) as i64, secs % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
@@ -0,0 +1,577 @@
+// Scripting - Site-specific automation with Rhai scripts
+//
+// While scripting is enabled, every *.rhai file in the scripts folder of the
+// app data directory (or a chosen directory) is loaded. A script handles
+// events by defining any of:
+//
+//   fn on_source(source) { }        a source was seen for the first time
+//   fn on_dmx(universe, data) { }   a universe's levels changed, data is a blob
+//   fn on_alert(alert) { }          an expected device, broadcast storm or universe
+//                                   blackout alert, alert.type is "expected_device",
+//                                   "storm" or "universe"
+//
+// and can call:
+//
+//   log(message)                              write to the LXMonitor log
+//   send_udp("host:port", data)               send a blob or an array of bytes
+//   send_osc("host:port", "/address", args)   args: ints, floats, strings, bools
+//   set_label(universe, label)                name a universe, "" clears it
+//
+// Scripts have no file or process access. They run on their own thread, one
+// event at a time, and each call is limited in operations so a runaway loop
+// can't hang it. Events arriving while scripts are busy are dropped.
+//
+// Built with the "scripting" feature; without it scripting reports that it is
+// unavailable.
+
+use crate::labels::UniverseLabelsHandle;
+use crate::network::{EventBusHandle, SourceManagerHandle};
+use crate::persistence::{load_json, save_json};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+#[cfg(feature = "scripting")]
+use crate::network::{
+    ExpectedDeviceAlert, ListenerEvent, NetworkSource, StormAlert, UniverseAnomaly,
+};
+#[cfg(feature = "scripting")]
+use bytes::Bytes;
+#[cfg(feature = "scripting")]
+use rhai::{Array, Blob, CallFnOptions, Dynamic, Engine, EvalAltResult, Scope, AST, INT};
+#[cfg(feature = "scripting")]
+use std::collections::HashSet;
+#[cfg(feature = "scripting")]
+use std::net::{ToSocketAddrs, UdpSocket};
+#[cfg(feature = "scripting")]
+use std::path::Path;
+#[cfg(feature = "scripting")]
+use std::sync::atomic::AtomicBool;
+#[cfg(feature = "scripting")]
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+#[cfg(feature = "scripting")]
+use tokio::sync::broadcast::error::RecvError;
+#[cfg(feature = "scripting")]
+use tracing::{info, warn};
+
+/// Events waiting for the script thread before new ones are dropped
+#[cfg(feature = "scripting")]
+const SCRIPT_EVENT_QUEUE: usize = 256;
+
+/// Operations one hook call may run before it is stopped
+#[cfg(feature = "scripting")]
+const MAX_SCRIPT_OPERATIONS: u64 = 1_000_000;
+
+#[cfg(feature = "scripting")]
+const SCRIPT_HOOKS: [&str; 3] = ["on_source", "on_dmx", "on_alert"];
+
+/// Scripting settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScriptingConfig {
+    pub enabled: bool,
+    /// Where scripts are loaded from, the app data directory's scripts folder
+    /// when None
+    pub directory: Option<PathBuf>,
+}
+
+/// A loaded script
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptInfo {
+    pub name: String,          // File name
+    pub hooks: Vec<String>,    // Event handlers it defines
+    pub error: Option<String>, // Compile error or last runtime error
+}
+
+/// Scripting status for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptingStatus {
+    pub available: bool, // Built with the "scripting" feature
+    pub running: bool,
+    pub config: ScriptingConfig,
+    pub directory: Option<String>,
+    pub scripts: Vec<ScriptInfo>,
+    pub dropped: u64, // Events lost because the scripts were busy
+    pub error: Option<String>,
+}
+
+/// Handles scripts read from and act on
+#[derive(Clone)]
+pub struct ScriptingContext {
+    pub source_manager: SourceManagerHandle,
+    pub event_tx: EventBusHandle,
+    pub universe_labels: UniverseLabelsHandle,
+}
+
+/// Starts and stops the script thread
+pub struct ScriptHost {
+    config: RwLock<ScriptingConfig>,
+    config_path: RwLock<Option<PathBuf>>,
+    default_dir: RwLock<Option<PathBuf>>,
+    #[cfg(feature = "scripting")]
+    forwarder: Mutex<Option<tokio::task::AbortHandle>>,
+    scripts: Arc<Mutex<Vec<ScriptInfo>>>,
+    dropped: Arc<AtomicU64>,
+    error: Mutex<Option<String>>,
+}
+
+impl ScriptHost {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(ScriptingConfig::default()),
+            config_path: RwLock::new(None),
+            default_dir: RwLock::new(None),
+            #[cfg(feature = "scripting")]
+            forwarder: Mutex::new(None),
+            scripts: Arc::new(Mutex::new(Vec::new())),
+            dropped: Arc::new(AtomicU64::new(0)),
+            error: Mutex::new(None),
+        }
+    }
+
+    /// Load the saved settings, scripts coming from `default_dir` unless another
+    /// directory is configured; the caller starts scripting when enabled
+    pub fn load_config(&self, path: PathBuf, default_dir: PathBuf) {
+        *self.config.write() = load_json(&path);
+        *self.config_path.write() = Some(path);
+        *self.default_dir.write() = Some(default_dir);
+    }
+
+    pub fn get_config(&self) -> ScriptingConfig {
+        self.config.read().clone()
+    }
+
+    /// Save new settings and start, restart or stop scripting to match
+    pub fn set_config(
+        &self,
+        context: ScriptingContext,
+        config: ScriptingConfig,
+    ) -> Result<(), String> {
+        self.stop();
+        *self.config.write() = config.clone();
+        if let Some(path) = self.config_path.read().as_ref() {
+            save_json(path, &config)?;
+        }
+        if config.enabled {
+            self.start(context)?;
+        }
+        Ok(())
+    }
+
+    pub fn get_status(&self) -> ScriptingStatus {
+        #[cfg(feature = "scripting")]
+        let running = self.forwarder.lock().is_some();
+        #[cfg(not(feature = "scripting"))]
+        let running = false;
+        ScriptingStatus {
+            available: cfg!(feature = "scripting"),
+            running,
+            config: self.get_config(),
+            directory: self.directory().map(|dir| dir.display().to_string()),
+            scripts: self.scripts.lock().clone(),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            error: self.error.lock().clone(),
+        }
+    }
+
+    fn directory(&self) -> Option<PathBuf> {
+        self.config
+            .read()
+            .directory
+            .clone()
+            .or_else(|| self.default_dir.read().clone())
+    }
+
+    /// Load the scripts and pass them events until stopped. Needs to run inside
+    /// the tokio runtime.
+    #[cfg(feature = "scripting")]
+    pub fn start(&self, context: ScriptingContext) -> Result<(), String> {
+        self.stop();
+        let dir = self.directory().ok_or("No scripts directory configured")?;
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            let message = format!("Failed to create {}: {}", dir.display(), e);
+            *self.error.lock() = Some(message.clone());
+            return Err(message);
+        }
+        *self.error.lock() = None;
+
+        let (tx, rx) = sync_channel(SCRIPT_EVENT_QUEUE);
+        let wants_dmx = Arc::new(AtomicBool::new(false));
+        let scripts = self.scripts.clone();
+        let labels = context.universe_labels.clone();
+        let thread_wants_dmx = wants_dmx.clone();
+        std::thread::Builder::new()
+            .name("scripts".to_string())
+            .spawn(move || run_scripts(&dir, labels, rx, scripts, thread_wants_dmx))
+            .map_err(|e| format!("Failed to start the script thread: {}", e))?;
+
+        let forwarder = tokio::spawn(forward_events(context, tx, wants_dmx, self.dropped.clone()));
+        *self.forwarder.lock() = Some(forwarder.abort_handle());
+        Ok(())
+    }
+
+    /// Load the scripts again after they were edited
+    pub fn reload(&self, context: ScriptingContext) -> Result<(), String> {
+        if !self.get_config().enabled {
+            return Err("Scripting is disabled".to_string());
+        }
+        self.start(context)
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    pub fn start(&self, _context: ScriptingContext) -> Result<(), String> {
+        let message =
+            "Built without scripting support (enable the \"scripting\" feature)".to_string();
+        *self.error.lock() = Some(message.clone());
+        Err(message)
+    }
+
+    /// Stop passing events; the script thread exits once its queue is closed
+    pub fn stop(&self) {
+        #[cfg(feature = "scripting")]
+        if let Some(forwarder) = self.forwarder.lock().take() {
+            forwarder.abort();
+            self.scripts.lock().clear();
+            info!("[Scripts] Stopped");
+        }
+    }
+}
+
+impl Default for ScriptHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type ScriptHostHandle = Arc<ScriptHost>;
+
+/// Event handed to the scripts
+#[cfg(feature = "scripting")]
+enum ScriptEvent {
+    Source(Box<NetworkSource>),
+    Dmx { universe: u16, data: Bytes },
+    Alert(ScriptAlert),
+}
+
+#[cfg(feature = "scripting")]
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ScriptAlert {
+    ExpectedDevice(ExpectedDeviceAlert),
+    Storm(StormAlert),
+    Universe(UniverseAnomaly),
+}
+
+/// Turn bus events into script events. Dropping the sender when aborted ends
+/// the script thread.
+#[cfg(feature = "scripting")]
+async fn forward_events(
+    context: ScriptingContext,
+    tx: SyncSender<ScriptEvent>,
+    wants_dmx: Arc<AtomicBool>,
+    dropped: Arc<AtomicU64>,
+) {
+    let mut events = context.event_tx.subscribe();
+    // Sources already known when scripting starts are not new
+    let mut known: HashSet<String> = context
+        .source_manager
+        .get_all_sources(true)
+        .into_iter()
+        .map(|s| s.id)
+        .collect();
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+        let script_events = match event {
+            ListenerEvent::SourcesUpdated => context
+                .source_manager
+                .get_all_sources(true)
+                .into_iter()
+                .filter(|s| known.insert(s.id.clone()))
+                .map(|s| ScriptEvent::Source(Box::new(s)))
+                .collect(),
+            ListenerEvent::DmxData(data) if data.changed && wants_dmx.load(Ordering::Relaxed) => {
+                vec![ScriptEvent::Dmx {
+                    universe: data.universe,
+                    data: data.data,
+                }]
+            }
+            ListenerEvent::ExpectedDeviceAlert(alert) => {
+                vec![ScriptEvent::Alert(ScriptAlert::ExpectedDevice(alert))]
+            }
+            ListenerEvent::StormAlert(alert) => {
+                vec![ScriptEvent::Alert(ScriptAlert::Storm(alert))]
+            }
+            ListenerEvent::UniverseAnomaly(alert) => {
+                vec![ScriptEvent::Alert(ScriptAlert::Universe(*alert))]
+            }
+            _ => continue,
+        };
+        for event in script_events {
+            match tx.try_send(event) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(TrySendError::Disconnected(_)) => return,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "scripting")]
+struct LoadedScript {
+    name: String,
+    ast: AST,
+    scope: Scope<'static>,
+    hooks: Vec<&'static str>,
+}
+
+/// Script thread: load the scripts, then call their hooks for each event
+#[cfg(feature = "scripting")]
+fn run_scripts(
+    dir: &Path,
+    labels: UniverseLabelsHandle,
+    events: Receiver<ScriptEvent>,
+    scripts: Arc<Mutex<Vec<ScriptInfo>>>,
+    wants_dmx: Arc<AtomicBool>,
+) {
+    let engine = build_engine(labels);
+    let (mut loaded, infos) = load_scripts(&engine, dir);
+    wants_dmx.store(
+        loaded.iter().any(|s| s.hooks.contains(&"on_dmx")),
+        Ordering::Relaxed,
+    );
+    info!(
+        "[Scripts] Loaded {} of {} scripts from {}",
+        loaded.len(),
+        infos.len(),
+        dir.display()
+    );
+    *scripts.lock() = infos;
+
+    while let Ok(event) = events.recv() {
+        let (hook, args) = match &event {
+            ScriptEvent::Source(source) => ("on_source", rhai::serde::to_dynamic(source)),
+            ScriptEvent::Alert(alert) => ("on_alert", rhai::serde::to_dynamic(alert)),
+            ScriptEvent::Dmx { .. } => ("on_dmx", Ok(Dynamic::UNIT)),
+        };
+        let args = match args {
+            Ok(args) => args,
+            Err(e) => {
+                warn!("[Scripts] Failed to convert {} event: {}", hook, e);
+                continue;
+            }
+        };
+
+        for script in loaded.iter_mut().filter(|s| s.hooks.contains(&hook)) {
+            let options = CallFnOptions::new().eval_ast(false);
+            let result = match &event {
+                ScriptEvent::Dmx { universe, data } => engine.call_fn_with_options::<Dynamic>(
+                    options,
+                    &mut script.scope,
+                    &script.ast,
+                    hook,
+                    (INT::from(*universe), data.to_vec()),
+                ),
+                _ => engine.call_fn_with_options::<Dynamic>(
+                    options,
+                    &mut script.scope,
+                    &script.ast,
+                    hook,
+                    (args.clone(),),
+                ),
+            };
+            if let Err(e) = result {
+                let message = format!("{}: {}", hook, e);
+                warn!("[Scripts] {} {}", script.name, message);
+                if let Some(info) = scripts.lock().iter_mut().find(|i| i.name == script.name) {
+                    info.error = Some(message);
+                }
+            }
+        }
+    }
+}
+
+/// Compile and run the top level of every script in a directory, by file name
+#[cfg(feature = "scripting")]
+fn load_scripts(engine: &Engine, dir: &Path) -> (Vec<LoadedScript>, Vec<ScriptInfo>) {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+
+    let mut loaded = Vec::new();
+    let mut infos = Vec::new();
+    for path in paths {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let compiled = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|source| engine.compile(source).map_err(|e| e.to_string()));
+        let script = compiled.and_then(|ast| {
+            let mut scope = Scope::new();
+            engine
+                .run_ast_with_scope(&mut scope, &ast)
+                .map_err(|e| e.to_string())?;
+            let hooks = SCRIPT_HOOKS
+                .into_iter()
+                .filter(|hook| ast.iter_functions().any(|f| f.name == *hook))
+                .collect();
+            Ok(LoadedScript {
+                name: name.clone(),
+                ast,
+                scope,
+                hooks,
+            })
+        });
+        match script {
+            Ok(script) => {
+                infos.push(ScriptInfo {
+                    name,
+                    hooks: script.hooks.iter().map(|h| h.to_string()).collect(),
+                    error: None,
+                });
+                loaded.push(script);
+            }
+            Err(e) => {
+                warn!("[Scripts] Failed to load {}: {}", name, e);
+                infos.push(ScriptInfo {
+                    name,
+                    hooks: Vec::new(),
+                    error: Some(e),
+                });
+            }
+        }
+    }
+    (loaded, infos)
+}
+
+/// Engine with the script API and limits
+#[cfg(feature = "scripting")]
+fn build_engine(labels: UniverseLabelsHandle) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_SCRIPT_OPERATIONS);
+    engine.set_max_call_levels(32);
+    engine.disable_symbol("eval");
+    engine.on_print(|text: &str| info!("[Scripts] {}", text));
+
+    engine.register_fn("log", |message: &str| info!("[Scripts] {}", message));
+
+    let socket = UdpSocket::bind("0.0.0.0:0").ok().map(Arc::new);
+    let udp = socket.clone();
+    engine.register_fn(
+        "send_udp",
+        move |target: &str, data: Blob| -> Result<(), Box<EvalAltResult>> {
+            send_to(udp.as_deref(), target, &data)
+        },
+    );
+    let udp = socket.clone();
+    engine.register_fn(
+        "send_udp",
+        move |target: &str, data: Array| -> Result<(), Box<EvalAltResult>> {
+            let bytes = data
+                .iter()
+                .map(|byte| {
+                    byte.as_int()
+                        .ok()
+                        .and_then(|byte| u8::try_from(byte).ok())
+                        .ok_or("send_udp data must be bytes (0-255)")
+                })
+                .collect::<Result<Vec<u8>, _>>()?;
+            send_to(udp.as_deref(), target, &bytes)
+        },
+    );
+    let udp = socket;
+    engine.register_fn(
+        "send_osc",
+        move |target: &str, address: &str, args: Array| -> Result<(), Box<EvalAltResult>> {
+            let packet = encode_osc(address, &args)?;
+            send_to(udp.as_deref(), target, &packet)
+        },
+    );
+
+    engine.register_fn(
+        "set_label",
+        move |universe: INT, label: &str| -> Result<(), Box<EvalAltResult>> {
+            let universe =
+                u16::try_from(universe).map_err(|_| format!("Invalid universe: {}", universe))?;
+            Ok(labels.set(universe, Some(label.to_string()))?)
+        },
+    );
+    engine
+}
+
+#[cfg(feature = "scripting")]
+fn send_to(
+    socket: Option<&UdpSocket>,
+    target: &str,
+    data: &[u8],
+) -> Result<(), Box<EvalAltResult>> {
+    let socket = socket.ok_or("No UDP socket available")?;
+    let addr = target
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.find(|a| a.is_ipv4()))
+        .ok_or_else(|| format!("Invalid target \"{}\", expected host:port", target))?;
+    socket
+        .send_to(data, addr)
+        .map_err(|e| format!("Failed to send to {}: {}", target, e))?;
+    Ok(())
+}
+
+/// OSC 1.0 message with int32, float32, string and true/false arguments
+#[cfg(feature = "scripting")]
+fn encode_osc(address: &str, args: &[Dynamic]) -> Result<Vec<u8>, String> {
+    if !address.starts_with('/') {
+        return Err(format!("OSC address must start with '/': {}", address));
+    }
+    let mut tags = String::from(",");
+    let mut payload = Vec::new();
+    for arg in args {
+        if let Ok(value) = arg.as_int() {
+            tags.push('i');
+            payload.extend_from_slice(&(value as i32).to_be_bytes());
+        } else if let Ok(value) = arg.as_float() {
+            tags.push('f');
+            payload.extend_from_slice(&(value as f32).to_be_bytes());
+        } else if let Ok(value) = arg.as_bool() {
+            tags.push(if value { 'T' } else { 'F' });
+        } else if arg.is_string() {
+            tags.push('s');
+            payload.extend(osc_string(&arg.clone().into_string().unwrap_or_default()));
+        } else {
+            return Err(format!("Unsupported OSC argument: {}", arg.type_name()));
+        }
+    }
+    let mut packet = osc_string(address);
+    packet.extend(osc_string(&tags));
+    packet.extend(payload);
+    Ok(packet)
+}
+
+/// NUL-terminated string padded to a multiple of four bytes
+#[cfg(feature = "scripting")]
+fn osc_string(text: &str) -> Vec<u8> {
+    let mut bytes = text.as_bytes().to_vec();
+    bytes.push(0);
+    while !bytes.len().is_multiple_of(4) {
+        bytes.push(0);
+    }
+    bytes
+}
@@ -0,0 +1,127 @@
+// Session comparison - Diff the current sources against a saved source table
+//
+// Any JSON source list works as a session file: a JSON export_sources report
+// or a copy of known_sources.json from an earlier day.
+
+use crate::network::{NetworkSource, SourceStatus};
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Enough of a source to recognise it in a report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSourceSummary {
+    pub id: String,
+    pub name: String,
+    pub ip: String,
+    pub universes: Vec<u16>,
+}
+
+impl From<&NetworkSource> for SessionSourceSummary {
+    fn from(source: &NetworkSource) -> Self {
+        Self {
+            id: source.id.clone(),
+            name: source.alias.clone().unwrap_or_else(|| source.name.clone()),
+            ip: source.ip.clone(),
+            universes: source.universes.clone(),
+        }
+    }
+}
+
+/// A source present in both sessions whose details differ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSourceChange {
+    pub source: SessionSourceSummary,
+    pub previous_name: Option<String>, // Set when the name changed
+    pub previous_ip: Option<String>,   // Set when the IP changed
+    pub universes_added: Vec<u16>,
+    pub universes_removed: Vec<u16>,
+}
+
+/// Result of comparing the current sources with a saved session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionComparison {
+    pub disappeared: Vec<SessionSourceSummary>,
+    pub appeared: Vec<SessionSourceSummary>,
+    pub changed: Vec<SessionSourceChange>,
+    pub unchanged: usize,
+}
+
+/// Compare the sources heard this session (offline ones count as gone) with
+/// the sources in a saved session file
+pub fn compare_session(
+    current: &[NetworkSource],
+    path: &Path,
+) -> Result<SessionComparison, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let previous: Vec<NetworkSource> = serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+    let current: HashMap<&str, &NetworkSource> = current
+        .iter()
+        .filter(|s| s.status != SourceStatus::Offline)
+        .map(|s| (s.id.as_str(), s))
+        .collect();
+    let previous: HashMap<&str, &NetworkSource> =
+        previous.iter().map(|s| (s.id.as_str(), s)).collect();
+
+    let mut comparison = SessionComparison {
+        disappeared: Vec::new(),
+        appeared: Vec::new(),
+        changed: Vec::new(),
+        unchanged: 0,
+    };
+
+    for (id, old) in &previous {
+        let Some(new) = current.get(id) else {
+            comparison.disappeared.push((*old).into());
+            continue;
+        };
+
+        let universes_added: Vec<u16> = new
+            .universes
+            .iter()
+            .filter(|u| !old.universes.contains(u))
+            .copied()
+            .collect();
+        let universes_removed: Vec<u16> = old
+            .universes
+            .iter()
+            .filter(|u| !new.universes.contains(u))
+            .copied()
+            .collect();
+        let previous_name = Some(old.name.clone()).filter(|name| *name != new.name);
+        let previous_ip = Some(old.ip.clone()).filter(|ip| *ip != new.ip);
+
+        if previous_name.is_none()
+            && previous_ip.is_none()
+            && universes_added.is_empty()
+            && universes_removed.is_empty()
+        {
+            comparison.unchanged += 1;
+        } else {
+            comparison.changed.push(SessionSourceChange {
+                source: (*new).into(),
+                previous_name,
+                previous_ip,
+                universes_added,
+                universes_removed,
+            });
+        }
+    }
+
+    comparison.appeared = current
+        .iter()
+        .filter(|(id, _)| !previous.contains_key(*id))
+        .map(|(_, s)| (*s).into())
+        .collect();
+
+    comparison.disappeared.sort_by(|a, b| a.ip.cmp(&b.ip));
+    comparison.appeared.sort_by(|a, b| a.ip.cmp(&b.ip));
+    comparison
+        .changed
+        .sort_by(|a, b| a.source.ip.cmp(&b.source.ip));
+    Ok(comparison)
+}
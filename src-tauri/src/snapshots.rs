@@ -0,0 +1,309 @@
+// Snapshots - Periodic copies of the source table and universe statistics
+//
+// When enabled, a JSON snapshot is written every `interval_minutes` to the
+// snapshots folder of the app data directory (or a chosen directory) as
+// snapshot-<unix ms>.json, so a post-show review can see when things started
+// to degrade. Beyond `keep` files the oldest are deleted, as are snapshots
+// older than `max_age_hours` when it is set.
+
+use crate::labels::UniverseLabelsHandle;
+use crate::network::{NetworkSource, SourceManagerHandle};
+use crate::persistence::{load_json, save_json};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+/// Accepted snapshot intervals in minutes
+const MIN_SNAPSHOT_INTERVAL_MINUTES: u32 = 1;
+const MAX_SNAPSHOT_INTERVAL_MINUTES: u32 = 24 * 60;
+
+const SNAPSHOT_PREFIX: &str = "snapshot-";
+const SNAPSHOT_EXTENSION: &str = ".json";
+
+/// Snapshot schedule and retention
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotConfig {
+    pub enabled: bool,
+    pub interval_minutes: u32,
+    /// Snapshots kept on disk, the oldest are deleted first
+    pub keep: usize,
+    /// Delete snapshots older than this
+    pub max_age_hours: Option<u64>,
+    /// Where snapshots are written, the app data directory's snapshots folder
+    /// when None
+    pub directory: Option<PathBuf>,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 15,
+            keep: 96, // A day at the default interval
+            max_age_hours: None,
+            directory: None,
+        }
+    }
+}
+
+/// Snapshot settings with the outcome of the latest snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotStatus {
+    pub config: SnapshotConfig,
+    pub directory: Option<String>,
+    pub snapshots: usize,           // Files in the directory
+    pub last_snapshot: Option<u64>, // Unix timestamp ms
+    pub last_error: Option<String>,
+}
+
+/// Contents of a snapshot file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub timestamp: u64, // Unix timestamp ms
+    pub sources: Vec<NetworkSource>,
+    pub universes: Vec<UniverseSnapshot>,
+}
+
+/// Senders of one universe at the time of a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniverseSnapshot {
+    pub universe: u16,
+    pub label: Option<String>,
+    pub senders: Vec<UniverseSender>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniverseSender {
+    pub source_id: String,
+    pub name: String,
+    pub fps: f32,
+    pub packet_loss_percent: f32,
+    pub packet_count: u64,
+}
+
+pub struct SnapshotScheduler {
+    config: RwLock<SnapshotConfig>,
+    config_path: RwLock<Option<PathBuf>>,
+    default_dir: RwLock<Option<PathBuf>>,
+    last_snapshot: Mutex<Option<u64>>,
+    last_error: Mutex<Option<String>>,
+    changed: Notify,
+}
+
+impl SnapshotScheduler {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(SnapshotConfig::default()),
+            config_path: RwLock::new(None),
+            default_dir: RwLock::new(None),
+            last_snapshot: Mutex::new(None),
+            last_error: Mutex::new(None),
+            changed: Notify::new(),
+        }
+    }
+
+    /// Load the settings from disk, persist future changes to the same file and
+    /// write snapshots to `default_dir` unless another directory is configured
+    pub fn load_config(&self, path: PathBuf, default_dir: PathBuf) {
+        *self.config.write() = load_json(&path);
+        *self.config_path.write() = Some(path);
+        *self.default_dir.write() = Some(default_dir);
+        self.changed.notify_one();
+    }
+
+    pub fn get_config(&self) -> SnapshotConfig {
+        self.config.read().clone()
+    }
+
+    /// Save new settings, the schedule restarts from now
+    pub fn set_config(&self, config: SnapshotConfig) -> Result<(), String> {
+        if !(MIN_SNAPSHOT_INTERVAL_MINUTES..=MAX_SNAPSHOT_INTERVAL_MINUTES)
+            .contains(&config.interval_minutes)
+        {
+            return Err(format!(
+                "Snapshot interval must be between {} and {} minutes",
+                MIN_SNAPSHOT_INTERVAL_MINUTES, MAX_SNAPSHOT_INTERVAL_MINUTES
+            ));
+        }
+        if config.keep == 0 {
+            return Err("At least one snapshot must be kept".to_string());
+        }
+        *self.config.write() = config.clone();
+        if let Some(path) = self.config_path.read().as_ref() {
+            save_json(path, &config)?;
+        }
+        self.changed.notify_one();
+        Ok(())
+    }
+
+    pub fn get_status(&self) -> SnapshotStatus {
+        let directory = self.directory();
+        SnapshotStatus {
+            config: self.get_config(),
+            snapshots: directory
+                .as_deref()
+                .map(|dir| list_snapshots(dir).len())
+                .unwrap_or(0),
+            directory: directory.map(|dir| dir.display().to_string()),
+            last_snapshot: *self.last_snapshot.lock(),
+            last_error: self.last_error.lock().clone(),
+        }
+    }
+
+    fn directory(&self) -> Option<PathBuf> {
+        self.config
+            .read()
+            .directory
+            .clone()
+            .or_else(|| self.default_dir.read().clone())
+    }
+
+    /// Write a snapshot now and apply the retention limits, returning its path
+    pub fn take_snapshot(
+        &self,
+        source_manager: &SourceManagerHandle,
+        universe_labels: &UniverseLabelsHandle,
+    ) -> Result<PathBuf, String> {
+        let result = self.write_snapshot(source_manager, universe_labels);
+        *self.last_error.lock() = result.as_ref().err().cloned();
+        result
+    }
+
+    fn write_snapshot(
+        &self,
+        source_manager: &SourceManagerHandle,
+        universe_labels: &UniverseLabelsHandle,
+    ) -> Result<PathBuf, String> {
+        let dir = self.directory().ok_or("No snapshot directory configured")?;
+        let snapshot = collect_snapshot(source_manager, universe_labels);
+        let path = dir.join(format!(
+            "{}{}{}",
+            SNAPSHOT_PREFIX, snapshot.timestamp, SNAPSHOT_EXTENSION
+        ));
+        save_json(&path, &snapshot)?;
+        *self.last_snapshot.lock() = Some(snapshot.timestamp);
+
+        let config = self.get_config();
+        prune_snapshots(&dir, config.keep, config.max_age_hours, snapshot.timestamp);
+        Ok(path)
+    }
+}
+
+impl Default for SnapshotScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type SnapshotSchedulerHandle = Arc<SnapshotScheduler>;
+
+/// Take snapshots on schedule while enabled. Runs for the life of the app.
+pub async fn run_snapshots(
+    scheduler: SnapshotSchedulerHandle,
+    source_manager: SourceManagerHandle,
+    universe_labels: UniverseLabelsHandle,
+) {
+    loop {
+        let config = scheduler.get_config();
+        if !config.enabled {
+            scheduler.changed.notified().await;
+            continue;
+        }
+        let interval = Duration::from_secs(u64::from(config.interval_minutes) * 60);
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {
+                match scheduler.take_snapshot(&source_manager, &universe_labels) {
+                    Ok(path) => info!("[Snapshots] Wrote {}", path.display()),
+                    Err(e) => warn!("[Snapshots] {}", e),
+                }
+            }
+            _ = scheduler.changed.notified() => {}
+        }
+    }
+}
+
+fn collect_snapshot(
+    source_manager: &SourceManagerHandle,
+    universe_labels: &UniverseLabelsHandle,
+) -> Snapshot {
+    let mut sources = source_manager.get_all_sources(true);
+    sources.sort_by(|a, b| a.ip.cmp(&b.ip).then(a.id.cmp(&b.id)));
+
+    let mut universes: BTreeMap<u16, Vec<UniverseSender>> = BTreeMap::new();
+    for source in &sources {
+        for stats in source_manager
+            .get_universe_stats(&source.id)
+            .unwrap_or_default()
+        {
+            universes
+                .entry(stats.universe)
+                .or_default()
+                .push(UniverseSender {
+                    source_id: source.id.clone(),
+                    name: source.alias.clone().unwrap_or_else(|| source.name.clone()),
+                    fps: stats.fps,
+                    packet_loss_percent: stats.packet_loss_percent,
+                    packet_count: stats.packet_count,
+                });
+        }
+    }
+
+    Snapshot {
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+        sources,
+        universes: universes
+            .into_iter()
+            .map(|(universe, senders)| UniverseSnapshot {
+                universe,
+                label: universe_labels.get(universe),
+                senders,
+            })
+            .collect(),
+    }
+}
+
+/// Snapshot files in a directory with their timestamps, oldest first
+fn list_snapshots(dir: &Path) -> Vec<(u64, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<(u64, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let timestamp = name
+                .strip_prefix(SNAPSHOT_PREFIX)?
+                .strip_suffix(SNAPSHOT_EXTENSION)?
+                .parse()
+                .ok()?;
+            Some((timestamp, entry.path()))
+        })
+        .collect();
+    snapshots.sort();
+    snapshots
+}
+
+/// Delete the oldest snapshots beyond `keep` and those older than `max_age_hours`
+fn prune_snapshots(dir: &Path, keep: usize, max_age_hours: Option<u64>, now: u64) {
+    let snapshots = list_snapshots(dir);
+    let excess = snapshots.len().saturating_sub(keep);
+    let oldest_kept = max_age_hours.map(|hours| now.saturating_sub(hours * 3_600_000));
+    for (index, (timestamp, path)) in snapshots.iter().enumerate() {
+        let expired = oldest_kept.is_some_and(|oldest| *timestamp < oldest);
+        if index < excess || expired {
+            if let Err(e) = std::fs::remove_file(path) {
+                warn!("[Snapshots] Failed to delete {}: {}", path.display(), e);
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+// WebSocket stream - Live sources, alerts and DMX for remote clients
+//
+// Served at /ws by the remote API. Every message from the server is JSON text
+// with a "type" field, except DMX frames in binary format:
+//
+//   {"type": "sources", "sources": [...]}             source table, on connect and on change
+//   {"type": "dmx", "universe": 1, ..., "data": [...]} newest frame of a subscribed universe
+//   {"type": "expected_device_alert", "alert": {...}}
+//   {"type": "storm_alert", "alert": {...}}
+//   {"type": "universe_alert", "alert": {...}}
+//   {"type": "error", "message": "..."}               a client message was not understood
+//
+// Clients choose universes with {"type": "subscribe", "universes": [1, 2]} and
+// {"type": "unsubscribe", "universes": [2]}. DMX is coalesced per universe and
+// sent at most `rate` times a second (?rate=, default 30 Hz). With
+// ?format=binary each frame is a binary message instead: the universe as two
+// big-endian bytes followed by the channel levels.
+
+use crate::api::ApiContext;
+use crate::events::{DmxCoalescer, DmxUpdate};
+use crate::network::{
+    ExpectedDeviceAlert, ListenerEvent, NetworkSource, StormAlert, UniverseAnomaly,
+};
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+
+/// Accepted DMX rates in Hz
+const MIN_STREAM_HZ: f32 = 1.0;
+const MAX_STREAM_HZ: f32 = 120.0;
+const DEFAULT_STREAM_HZ: f32 = 30.0;
+
+/// How DMX frames are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamFormat {
+    #[default]
+    Json,
+    Binary,
+}
+
+#[derive(Deserialize)]
+pub struct StreamQuery {
+    #[serde(default)]
+    format: StreamFormat,
+    rate: Option<f32>,
+}
+
+/// Server to client message
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamMessage<'a> {
+    Sources {
+        sources: Vec<NetworkSource>,
+    },
+    Dmx {
+        #[serde(flatten)]
+        update: DmxUpdate,
+        data: &'a [u8],
+    },
+    ExpectedDeviceAlert {
+        alert: ExpectedDeviceAlert,
+    },
+    StormAlert {
+        alert: StormAlert,
+    },
+    UniverseAlert {
+        alert: UniverseAnomaly,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Client to server message
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe { universes: Vec<u16> },
+    Unsubscribe { universes: Vec<u16> },
+}
+
+/// Upgrade a /ws request to a stream
+pub async fn websocket(
+    upgrade: WebSocketUpgrade,
+    State(ctx): State<ApiContext>,
+    Query(query): Query<StreamQuery>,
+) -> Response {
+    let rate = query.rate.unwrap_or(DEFAULT_STREAM_HZ);
+    if !(MIN_STREAM_HZ..=MAX_STREAM_HZ).contains(&rate) {
+        let message = format!(
+            "Stream rate must be between {} and {} Hz",
+            MIN_STREAM_HZ, MAX_STREAM_HZ
+        );
+        let body = axum::Json(serde_json::json!({ "error": message }));
+        return (StatusCode::BAD_REQUEST, body).into_response();
+    }
+    let interval = Duration::from_secs_f32(1.0 / rate);
+    upgrade.on_upgrade(move |socket| stream(socket, ctx, query.format, interval))
+}
+
+/// Forward events to one client until it disconnects
+async fn stream(mut socket: WebSocket, ctx: ApiContext, format: StreamFormat, interval: Duration) {
+    let mut events = ctx.event_tx.subscribe();
+    let mut universes: BTreeSet<u16> = BTreeSet::new();
+    let mut dmx = DmxCoalescer::new();
+    let mut sources_changed = true;
+    let mut flush = tokio::time::interval(interval);
+    flush.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        let sent = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str(text.as_str()) {
+                    Ok(ClientMessage::Subscribe { universes: added }) => {
+                        universes.extend(added);
+                        Ok(())
+                    }
+                    Ok(ClientMessage::Unsubscribe { universes: removed }) => {
+                        universes.retain(|u| !removed.contains(u));
+                        Ok(())
+                    }
+                    Err(e) => {
+                        let message = format!("Invalid message: {}", e);
+                        send(&mut socket, &StreamMessage::Error { message }).await
+                    }
+                },
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => Ok(()), // Pings are answered by the socket itself
+            },
+            event = events.recv() => match event {
+                Ok(ListenerEvent::SourcesUpdated) => {
+                    sources_changed = true;
+                    Ok(())
+                }
+                Ok(ListenerEvent::DmxData(data)) => {
+                    if universes.contains(&data.universe) {
+                        dmx.push(data);
+                    }
+                    Ok(())
+                }
+                Ok(ListenerEvent::ExpectedDeviceAlert(alert)) => {
+                    send(&mut socket, &StreamMessage::ExpectedDeviceAlert { alert }).await
+                }
+                Ok(ListenerEvent::StormAlert(alert)) => {
+                    send(&mut socket, &StreamMessage::StormAlert { alert }).await
+                }
+                Ok(ListenerEvent::UniverseAnomaly(alert)) => {
+                    send(&mut socket, &StreamMessage::UniverseAlert { alert: *alert }).await
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => Ok(()),
+                Err(RecvError::Closed) => break,
+            },
+            _ = flush.tick() => {
+                flush_updates(&mut socket, &ctx, &mut dmx, &mut sources_changed, format).await
+            }
+        };
+        if sent.is_err() {
+            break;
+        }
+    }
+}
+
+/// Send the source table if it changed and the newest frame of each universe
+async fn flush_updates(
+    socket: &mut WebSocket,
+    ctx: &ApiContext,
+    dmx: &mut DmxCoalescer,
+    sources_changed: &mut bool,
+    format: StreamFormat,
+) -> Result<(), axum::Error> {
+    if std::mem::take(sources_changed) {
+        let sources = ctx.source_manager.get_all_sources(false);
+        send(socket, &StreamMessage::Sources { sources }).await?;
+    }
+    for (mut update, data) in dmx.take() {
+        // Every frame is pushed here, so each update carries its data
+        let Some(data) = data else {
+            continue;
+        };
+        match format {
+            StreamFormat::Json => {
+                update.label = ctx.universe_labels.get(update.universe);
+                send(
+                    socket,
+                    &StreamMessage::Dmx {
+                        update,
+                        data: &data,
+                    },
+                )
+                .await?;
+            }
+            StreamFormat::Binary => {
+                let mut frame = Vec::with_capacity(2 + data.len());
+                frame.extend_from_slice(&update.universe.to_be_bytes());
+                frame.extend_from_slice(&data);
+                socket.send(Message::Binary(frame.into())).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn send(socket: &mut WebSocket, message: &StreamMessage<'_>) -> Result<(), axum::Error> {
+    match serde_json::to_string(message) {
+        Ok(json) => socket.send(Message::Text(json.into())).await,
+        Err(e) => {
+            warn!("[API] Failed to serialize stream message: {}", e);
+            Ok(())
+        }
+    }
+}
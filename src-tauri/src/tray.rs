@@ -0,0 +1,251 @@
+// Tray - System tray icon showing overall network health
+//
+// The icon is green while everything is fine, amber on warnings (broadcast
+// storms, frame rate warnings, duplicate or conflicting universes) and red when
+// something is critical: an expected device is missing or no DMX has arrived
+// for the silence timeout of the notification settings. The tooltip says why.
+// Its menu shows the window, starts and stops listening and toggles sniffer mode.
+
+use crate::app::AppState;
+use crate::network::{
+    is_capture_available, EventReceiver, ExpectedDeviceState, ListenerEvent, Protocol, SourceStatus,
+};
+use crate::notifications::SilenceDetector;
+
+use std::time::{Duration, Instant};
+use tauri::image::Image;
+use tauri::menu::{Menu, MenuEvent, MenuItem};
+use tauri::tray::{TrayIcon, TrayIconBuilder};
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+
+const TRAY_ID: &str = "health";
+const ICON_SIZE: u32 = 32;
+
+/// How often health and the menu are refreshed
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Overall network health
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Health {
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl Health {
+    fn label(self) -> &'static str {
+        match self {
+            Health::Ok => "OK",
+            Health::Warning => "Warning",
+            Health::Critical => "Critical",
+        }
+    }
+
+    fn color(self) -> [u8; 3] {
+        match self {
+            Health::Ok => [0x2e, 0xcc, 0x71],
+            Health::Warning => [0xf3, 0x9c, 0x12],
+            Health::Critical => [0xe7, 0x4c, 0x3c],
+        }
+    }
+}
+
+/// Menu items whose text follows the app state
+struct TrayMenu {
+    listening: MenuItem<tauri::Wry>,
+    sniffer: MenuItem<tauri::Wry>,
+}
+
+/// Create the tray icon and keep it up to date with the events of `events`
+pub fn create_tray(app: &AppHandle, events: EventReceiver) -> tauri::Result<()> {
+    let show = MenuItem::with_id(app, "show", "Show LXMonitor", true, None::<&str>)?;
+    let listening = MenuItem::with_id(app, "listening", "Stop listening", true, None::<&str>)?;
+    let sniffer = MenuItem::with_id(
+        app,
+        "sniffer",
+        "Start sniffer",
+        is_capture_available(),
+        None::<&str>,
+    )?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&show, &listening, &sniffer, &quit])?;
+
+    let tray = TrayIconBuilder::with_id(TRAY_ID)
+        .icon(health_icon(Health::Ok))
+        .tooltip("LXMonitor")
+        .menu(&menu)
+        .on_menu_event(on_menu_event)
+        .build(app)?;
+
+    let menu = TrayMenu { listening, sniffer };
+    tauri::async_runtime::spawn(watch_health(app.clone(), tray, menu, events));
+    Ok(())
+}
+
+/// Track DMX for silence detection and refresh the icon, tooltip and menu
+async fn watch_health(app: AppHandle, tray: TrayIcon, menu: TrayMenu, mut events: EventReceiver) {
+    let mut silence = SilenceDetector::new();
+    let mut refresh = tokio::time::interval(REFRESH_INTERVAL);
+    refresh.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut shown_health = None;
+    let mut shown_tooltip = String::new();
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(ListenerEvent::DmxData(_)) => {
+                    silence.dmx_received(Instant::now());
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            },
+            _ = refresh.tick() => {
+                let state = app.state::<AppState>();
+                silence.check(Instant::now(), state.notifications.silent_after());
+
+                let (health, reason) = assess_health(&state, silence.is_silent());
+                if shown_health != Some(health) {
+                    shown_health = Some(health);
+                    if let Err(e) = tray.set_icon(Some(health_icon(health))) {
+                        warn!("[Tray] Failed to update icon: {}", e);
+                    }
+                }
+                let tooltip = format!("LXMonitor - {}: {}", health.label(), reason);
+                if tooltip != shown_tooltip {
+                    let _ = tray.set_tooltip(Some(&tooltip));
+                    shown_tooltip = tooltip;
+                }
+
+                let _ = menu.listening.set_text(if is_listening(&state) {
+                    "Stop listening"
+                } else {
+                    "Start listening"
+                });
+                let _ = menu.sniffer.set_text(if state.sniffer_state.is_running() {
+                    "Stop sniffer"
+                } else {
+                    "Start sniffer"
+                });
+            }
+        }
+    }
+}
+
+/// Health and the reason for it, the most severe problem winning
+fn assess_health(state: &AppState, silent: bool) -> (Health, String) {
+    let missing = state
+        .source_manager
+        .get_expected_devices()
+        .iter()
+        .filter(|d| d.state == ExpectedDeviceState::Missing)
+        .count();
+    if missing > 0 {
+        return (
+            Health::Critical,
+            format!("{} expected device(s) missing", missing),
+        );
+    }
+    if !is_listening(state) {
+        return (Health::Warning, "Not listening".to_string());
+    }
+    if silent {
+        return (Health::Critical, "No DMX received".to_string());
+    }
+
+    let storm = state.sniffer_state.storm.get_status();
+    if storm.broadcast_storm || storm.multicast_storm {
+        return (Health::Warning, "Broadcast storm".to_string());
+    }
+
+    let sources = state.source_manager.get_all_sources(false);
+    let active: Vec<_> = sources
+        .iter()
+        .filter(|s| s.status == SourceStatus::Active)
+        .collect();
+    let warnings = active
+        .iter()
+        .filter(|s| {
+            s.fps_warning.is_some()
+                || !s.duplicate_universes.is_empty()
+                || !s.priority_conflicts.is_empty()
+                || s.duplicate_cid
+        })
+        .count();
+    if warnings > 0 {
+        return (
+            Health::Warning,
+            format!("{} source(s) with warnings", warnings),
+        );
+    }
+    (Health::Ok, format!("{} active source(s)", active.len()))
+}
+
+/// Listening while either protocol is enabled
+fn is_listening(state: &AppState) -> bool {
+    state.listener_state.protocol_enabled(Protocol::ArtNet)
+        || state.listener_state.protocol_enabled(Protocol::Sacn)
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        "show" => crate::app::show_main_window(app),
+        "listening" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = toggle_listening(&app).await {
+                    warn!("[Tray] {}", e);
+                }
+            });
+        }
+        "sniffer" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = toggle_sniffer(&app).await {
+                    warn!("[Tray] {}", e);
+                }
+            });
+        }
+        "quit" => {
+            // State is saved on exit
+            app.exit(0);
+        }
+        _ => {}
+    }
+}
+
+/// Stop both listeners, or start both again. The choice is remembered.
+async fn toggle_listening(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let enabled = !is_listening(&state);
+    for protocol in [Protocol::ArtNet, Protocol::Sacn] {
+        crate::app::set_listener_enabled(state.clone(), protocol, enabled).await?;
+    }
+    *state.is_listening.lock() = enabled;
+    Ok(())
+}
+
+/// Start the sniffer on the interface carrying lighting traffic, or stop it
+async fn toggle_sniffer(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let enabled = !state.sniffer_state.is_running();
+    crate::app::set_sniffer_mode(state, enabled, None, None, None, None, None).await
+}
+
+/// Filled circle in the color of the health
+fn health_icon(health: Health) -> Image<'static> {
+    let [r, g, b] = health.color();
+    let center = (ICON_SIZE as f32 - 1.0) / 2.0;
+    let radius = ICON_SIZE as f32 / 2.0 - 2.0;
+    let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let distance = (x as f32 - center).hypot(y as f32 - center);
+            // Soften the edge over one pixel
+            let alpha = (radius + 0.5 - distance).clamp(0.0, 1.0);
+            rgba.extend_from_slice(&[r, g, b, (alpha * 255.0) as u8]);
+        }
+    }
+    Image::new_owned(rgba, ICON_SIZE, ICON_SIZE)
+}
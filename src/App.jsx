@@ -350,7 +350,7 @@ function SettingsModal({
   // Sniffer props
   snifferEnabled,
   onSnifferToggle,
-  npcapAvailable,
+  captureSupport,
   captureInterfaces,
   selectedCaptureInterface,
   onCaptureInterfaceChange,
@@ -567,12 +567,12 @@ function SettingsModal({
           <div className="settings-section">
             <h3>Sniffer Mode (Advanced)</h3>
             <div className="sniffer-settings">
-              {!npcapAvailable ? (
+              {!captureSupport.available ? (
                 <div className="sniffer-warning">
                   <span className="warning-icon">⚠️</span>
                   <div>
-                    <p><strong>Npcap Not Installed</strong></p>
-                    <p className="description">Sniffer mode requires Npcap to be installed. <button onClick={() => openUrl('https://npcap.com/')} style={{ background: 'none', border: 'none', color: 'var(--accent-primary)', cursor: 'pointer', padding: 0, font: 'inherit' }}>Download Npcap</button></p>
+                    <p><strong>Packet Capture Unavailable</strong></p>
+                    <p className="description">Sniffer mode requires {captureSupport.library}. {captureSupport.message} {captureSupport.download_url && <button onClick={() => openUrl(captureSupport.download_url)} style={{ background: 'none', border: 'none', color: 'var(--accent-primary)', cursor: 'pointer', padding: 0, font: 'inherit' }}>Download {captureSupport.library}</button>}</p>
                   </div>
                 </div>
               ) : (
@@ -750,7 +750,7 @@ function SettingsModal({
                   <li>Network diagnostics (FPS, jitter, packet loss)</li>
                   <li>Duplicate universe detection</li>
                   <li>Channel history graphing</li>
-                  <li>Sniffer mode (requires Npcap)</li>
+                  <li>Sniffer mode (requires Npcap on Windows, libpcap on Linux and macOS)</li>
                 </ul>
               </div>
 
@@ -1231,6 +1231,72 @@ function UniverseViewer({
   );
 }
 
+// Shown after a crash when the autosave held back routes and parked channels.
+// They transmit on the network, so they only restart once the user confirms.
+function RestorePrompt({ pending, savedAt, onConfirm, onDiscard }) {
+  const [busy, setBusy] = useState(false);
+
+  const choose = async (action) => {
+    setBusy(true);
+    try {
+      await action();
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div className="settings-overlay">
+      <div className="settings-modal">
+        <div className="settings-header">
+          <h2>Restore Previous Session?</h2>
+        </div>
+        <div className="settings-content">
+          <p className="restore-intro">
+            LXMonitor did not shut down cleanly
+            {savedAt ? ` (last saved ${new Date(savedAt).toLocaleString()})` : ''}.
+            These were running and would transmit again:
+          </p>
+          {pending.routes.length > 0 && (
+            <div className="settings-section">
+              <h3>Routes</h3>
+              <ul className="restore-list">
+                {pending.routes.map((route, i) => (
+                  <li key={i}>
+                    {route.input_protocol} {route.universe_start}-{route.universe_end} → {route.output_protocol}
+                    {route.universe_offset ? ` (offset ${route.universe_offset})` : ''}
+                    {route.destination ? ` to ${route.destination}` : ''}
+                  </li>
+                ))}
+              </ul>
+            </div>
+          )}
+          {pending.parked.length > 0 && (
+            <div className="settings-section">
+              <h3>Parked Channels</h3>
+              <ul className="restore-list">
+                {pending.parked.map((park) => (
+                  <li key={`${park.universe}-${park.channel}`}>
+                    Universe {park.universe} channel {park.channel} at {park.value}
+                  </li>
+                ))}
+              </ul>
+            </div>
+          )}
+          <div className="export-options">
+            <button className="export-btn" disabled={busy} onClick={() => choose(onConfirm)}>
+              Restore
+            </button>
+            <button className="export-btn" disabled={busy} onClick={() => choose(onDiscard)}>
+              Discard
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
+
 // Empty State Component
 function EmptyState({ isListening }) {
   return (
@@ -1248,6 +1314,138 @@ function EmptyState({ isListening }) {
   );
 }
 
+// Channel history for the 'lastUsed' color mode: when each channel was last above zero
+function updateChannelHistory(prev, values, now) {
+  const updated = { ...prev };
+  values.forEach((value, index) => {
+    if (value > 0) {
+      updated[index + 1] = { lastActive: now, lastValue: value };
+    }
+  });
+  return updated;
+}
+
+// Heatmap activity: decays every frame, boosted for channels whose value changed
+function updateChannelActivity(prev, prevValues, values) {
+  const updated = { ...prev };
+  values.forEach((value, index) => {
+    const channelNum = index + 1;
+    const decayedActivity = (updated[channelNum] || 0) * 0.95;
+    if (value !== (prevValues[index] || 0)) {
+      updated[channelNum] = Math.min(1, decayedActivity + 0.15);
+    } else {
+      updated[channelNum] = decayedActivity;
+    }
+  });
+  return updated;
+}
+
+// Single universe window, opened with ?universe=N by open_universe_window.
+// The backend subscribes the window to dmx-N, which carries the universe's
+// levels whenever they change.
+export function UniverseWindow({ universe }) {
+  const [data, setData] = useState(null);
+  const [sources, setSources] = useState([]);
+  const [frames, setFrames] = useState(0);
+  const [colorMode, setColorMode] = useState(localStorage.getItem('lxmonitor-colormode') || 'level');
+  const [viewMode, setViewMode] = useState(localStorage.getItem('lxmonitor-viewmode') || 'grid');
+  const [trackedChannels, setTrackedChannels] = useState(
+    JSON.parse(localStorage.getItem('lxmonitor-trackedchannels') || '[]')
+  );
+  const [channelHistory, setChannelHistory] = useState({});
+  const [channelActivity, setChannelActivity] = useState({});
+  const prevDataRef = useRef([]);
+  const theme = localStorage.getItem('lxmonitor-theme') || 'dark';
+
+  // Same appearance as the main window
+  useEffect(() => {
+    if (theme !== 'dark') {
+      document.documentElement.classList.add(theme);
+    }
+    const accessibility = JSON.parse(localStorage.getItem('lxmonitor-accessibility') || '{}');
+    Object.entries(accessibility).forEach(([key, value]) => {
+      if (value) {
+        document.documentElement.classList.add(key);
+      }
+    });
+  }, [theme]);
+
+  useEffect(() => {
+    const applyLevels = (values) => {
+      setData(values);
+      setChannelHistory(prev => updateChannelHistory(prev, values, Date.now()));
+      setChannelActivity(prev => updateChannelActivity(prev, prevDataRef.current, values));
+      prevDataRef.current = values;
+    };
+
+    invoke('get_dmx_data', { universe })
+      .then(result => result && applyLevels(result))
+      .catch(err => console.error('Failed to fetch DMX data:', err));
+    invoke('get_sources')
+      .then(setSources)
+      .catch(err => console.error('Failed to fetch sources:', err));
+
+    const unlistenDmx = listen(`dmx-${universe}`, (event) => {
+      applyLevels(event.payload);
+      setFrames(prev => prev + 1);
+    });
+    const unlistenSources = listen('sources-updated', (event) => {
+      setSources(event.payload);
+    });
+
+    return () => {
+      unlistenDmx.then(fn => fn());
+      unlistenSources.then(fn => fn());
+    };
+  }, [universe]);
+
+  const handleColorModeChange = (mode) => {
+    setColorMode(mode);
+    localStorage.setItem('lxmonitor-colormode', mode);
+  };
+
+  const handleViewModeChange = (mode) => {
+    setViewMode(mode);
+    localStorage.setItem('lxmonitor-viewmode', mode);
+  };
+
+  const handleToggleChannel = (channelNum) => {
+    setTrackedChannels(prev => {
+      const newChannels = prev.includes(channelNum)
+        ? prev.filter(c => c !== channelNum)
+        : [...prev, channelNum].slice(-10); // Max 10 channels
+      localStorage.setItem('lxmonitor-trackedchannels', JSON.stringify(newChannels));
+      return newChannels;
+    });
+  };
+
+  const source = sources.find(s => s.universes.includes(universe));
+
+  return (
+    <div className="universe-window" onContextMenu={(e) => e.preventDefault()}>
+      <main className="content">
+        <UniverseViewer
+          universe={universe}
+          data={data}
+          stats={{ fps: source?.fps ? Math.round(source.fps) : 0, packets: frames }}
+          allUniverses={[universe]}
+          onUniverseChange={() => {}}
+          colorMode={colorMode}
+          onColorModeChange={handleColorModeChange}
+          viewMode={viewMode}
+          onViewModeChange={handleViewModeChange}
+          sources={sources}
+          channelHistory={channelHistory}
+          channelActivity={channelActivity}
+          trackedChannels={trackedChannels}
+          onToggleChannel={handleToggleChannel}
+          theme={theme}
+        />
+      </main>
+    </div>
+  );
+}
+
 // Main App Component
 function App() {
   const [theme, setTheme] = useState('dark');
@@ -1274,7 +1472,7 @@ function App() {
   const [snifferStatus, setSnifferStatus] = useState(null);
   const [captureInterfaces, setCaptureInterfaces] = useState([]);
   const [selectedCaptureInterface, setSelectedCaptureInterface] = useState('');
-  const [npcapAvailable, setNpcapAvailable] = useState(false);
+  const [captureSupport, setCaptureSupport] = useState({ available: false, library: 'pcap', message: null, download_url: null });
 
   // Device tab state (all, sending, receiving)
   const [deviceTab, setDeviceTab] = useState('all');
@@ -1282,6 +1480,9 @@ function App() {
   // Dashboard view state
   const [showDashboard, setShowDashboard] = useState(false);
 
+  // Routes and parked channels held back by the autosave after a crash
+  const [autosaveStatus, setAutosaveStatus] = useState(null);
+
   // Get all universes from all sources
   const allUniverses = [...new Set(sources.flatMap(s => s.universes))].sort((a, b) => a - b);
 
@@ -1323,10 +1524,10 @@ function App() {
     // Fetch sniffer info
     const fetchSnifferInfo = async () => {
       try {
-        const available = await invoke('check_npcap_available');
-        setNpcapAvailable(available);
+        const support = await invoke('get_capture_support');
+        setCaptureSupport(support);
 
-        if (available) {
+        if (support.available) {
           const interfaces = await invoke('get_capture_interfaces');
           setCaptureInterfaces(interfaces);
           if (interfaces.length > 0) {
@@ -1342,8 +1543,22 @@ function App() {
       }
     };
     fetchSnifferInfo();
+
+    invoke('get_autosave_status')
+      .then(setAutosaveStatus)
+      .catch(err => console.error('Failed to fetch autosave status:', err));
   }, []);
 
+  const handleRestoreChoice = async (command) => {
+    try {
+      await invoke(command);
+    } catch (err) {
+      console.error(`Failed to ${command}:`, err);
+      alert('Failed to restore the previous session: ' + err);
+    }
+    setAutosaveStatus(await invoke('get_autosave_status'));
+  };
+
   // Save color mode
   const handleColorModeChange = (mode) => {
     setColorMode(mode);
@@ -1453,7 +1668,7 @@ function App() {
   }, []);
 
   // Fetch DMX data for selected universe (initial load only, no channel tracking
-  // since the event-driven dmx-updated handler handles ongoing updates)
+  // since the event-driven dmx-batch handler handles ongoing updates)
   const fetchDmxData = useCallback(async (universe) => {
     try {
       const result = await invoke('get_dmx_data', { universe });
@@ -1472,45 +1687,18 @@ function App() {
       setSources(event.payload);
     });
 
-    const unlistenDmx = listen('dmx-updated', async (event) => {
-      const { universe } = event.payload;
-      const result = await invoke('get_dmx_data', { universe });
+    // DMX updates arrive coalesced, one entry per universe that received frames.
+    // `received` counts every frame, `frames` only those with new data, so the
+    // levels are refetched only when something changed.
+    const handleDmxUpdate = async ({ universe, frames, received }) => {
+      const result = frames > 0 ? await invoke('get_dmx_data', { universe }) : null;
       if (result) {
         setDmxData(prev => ({ ...prev, [universe]: result }));
 
-        // Update channel history
-        const now = Date.now();
-        setChannelHistory(prev => {
-          const updated = { ...prev };
-          result.forEach((value, index) => {
-            if (value > 0) {
-              updated[index + 1] = { lastActive: now, lastValue: value };
-            }
-          });
-          return updated;
-        });
-
-        // Update channel activity for heatmap (track value changes)
+        // Update channel history and heatmap activity (track value changes)
+        setChannelHistory(prev => updateChannelHistory(prev, result, Date.now()));
         const prevData = prevDmxDataRef.current[universe] || [];
-        setChannelActivity(prev => {
-          const updated = { ...prev };
-          result.forEach((value, index) => {
-            const channelNum = index + 1;
-            const prevValue = prevData[index] || 0;
-            const currentActivity = updated[channelNum] || 0;
-
-            // Decay existing activity over time
-            const decayedActivity = currentActivity * 0.95;
-
-            // Add activity boost if value changed
-            if (value !== prevValue) {
-              updated[channelNum] = Math.min(1, decayedActivity + 0.15);
-            } else {
-              updated[channelNum] = decayedActivity;
-            }
-          });
-          return updated;
-        });
+        setChannelActivity(prev => updateChannelActivity(prev, prevData, result));
         prevDmxDataRef.current[universe] = result;
       }
 
@@ -1518,10 +1706,32 @@ function App() {
         ...prev,
         [universe]: {
           ...prev[universe],
-          packets: (prev[universe]?.packets || 0) + 1,
+          packets: (prev[universe]?.packets || 0) + received,
           lastUpdate: Date.now()
         }
       }));
+    };
+
+    const unlistenDmx = listen('dmx-batch', (event) => {
+      event.payload.forEach(handleDmxUpdate);
+    });
+
+    // lxmonitor://source/<id> links select the source (universe links open
+    // their own window in the backend)
+    const unlistenDeepLink = listen('deep-link', async (event) => {
+      if (event.payload.kind !== 'source') return;
+      try {
+        const result = await invoke('get_sources');
+        setSources(result);
+        const source = result.find(s => s.id === event.payload.id);
+        if (source) {
+          setDeviceTab('all');
+          setShowDashboard(false);
+          handleSourceSelect(source);
+        }
+      } catch (err) {
+        console.error('Failed to open deep link:', err);
+      }
     });
 
     fetchSources();
@@ -1529,6 +1739,7 @@ function App() {
     return () => {
       unlistenSources.then(fn => fn());
       unlistenDmx.then(fn => fn());
+      unlistenDeepLink.then(fn => fn());
     };
   }, [fetchSources]);
 
@@ -1692,7 +1903,7 @@ function App() {
         // Sniffer props
         snifferEnabled={snifferEnabled}
         onSnifferToggle={handleSnifferToggle}
-        npcapAvailable={npcapAvailable}
+        captureSupport={captureSupport}
         captureInterfaces={captureInterfaces}
         selectedCaptureInterface={selectedCaptureInterface}
         onCaptureInterfaceChange={handleCaptureInterfaceChange}
@@ -1703,6 +1914,15 @@ function App() {
         selectedUniverse={selectedUniverse}
         allUniverses={allUniverses}
       />
+
+      {autosaveStatus?.pending_restore && (
+        <RestorePrompt
+          pending={autosaveStatus.pending_restore}
+          savedAt={autosaveStatus.restored_from}
+          onConfirm={() => handleRestoreChoice('confirm_pending_restore')}
+          onDiscard={() => handleRestoreChoice('discard_pending_restore')}
+        />
+      )}
     </div>
   );
 }
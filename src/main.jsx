@@ -1,9 +1,12 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
-import App from "./App";
+import App, { UniverseWindow } from "./App";
+
+// Universe windows load the frontend with ?universe=N
+const universe = new URLSearchParams(window.location.search).get("universe");
 
 ReactDOM.createRoot(document.getElementById("root")).render(
   <React.StrictMode>
-    <App />
+    {universe !== null ? <UniverseWindow universe={Number(universe)} /> : <App />}
   </React.StrictMode>,
 );